@@ -0,0 +1,304 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! On-disk archive of the collations built by the collator.
+//!
+//! The collations are appended as SCALE encoded [`ArchivedCollation`]s to segment files named
+//! `collations-<index>.scale`. A new segment is started when the current one exceeds
+//! [`CollationArchiveConfig::max_segment_size`] and the oldest segments are removed to keep at
+//! most [`CollationArchiveConfig::max_segments`] of them.
+
+use codec::{Decode, Encode};
+use cumulus_primitives_core::{relay_chain::Hash as PHash, CollationInfo};
+use parking_lot::Mutex;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto};
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, Write},
+	ops::RangeInclusive,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::LOG_TARGET;
+
+const SEGMENT_PREFIX: &str = "collations-";
+const SEGMENT_EXTENSION: &str = "scale";
+
+/// A collation as written to the archive.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct ArchivedCollation<Block: BlockT> {
+	/// Header of the block.
+	pub header: Block::Header,
+	/// Relay parent of the collation.
+	pub relay_parent: PHash,
+	/// Hash of the compressed PoV.
+	pub pov_hash: PHash,
+	/// Size of the compressed PoV.
+	pub pov_size: u32,
+	/// The collation info returned by the runtime, including the `vote_result`.
+	pub collation_info: CollationInfo,
+	/// Unix time in milliseconds at which the collation was archived.
+	pub archived_at: u64,
+}
+
+impl<Block: BlockT> ArchivedCollation<Block> {
+	/// Number of the block as `u64`.
+	pub fn block_number(&self) -> u64 {
+		(*self.header.number()).unique_saturated_into()
+	}
+}
+
+/// Configuration of the [`CollationArchive`].
+#[derive(Debug, Clone)]
+pub struct CollationArchiveConfig {
+	/// Directory of the segment files.
+	pub path: PathBuf,
+	/// Size in bytes after which a new segment is started.
+	pub max_segment_size: u64,
+	/// Maximum number of segments kept, the oldest are removed first.
+	pub max_segments: usize,
+}
+
+struct Writer {
+	config: CollationArchiveConfig,
+	/// Indices of the existing segments, oldest first.
+	segments: Vec<u64>,
+	current: File,
+	current_size: u64,
+}
+
+impl Writer {
+	fn open(config: CollationArchiveConfig) -> io::Result<Self> {
+		fs::create_dir_all(&config.path)?;
+
+		// Always start a new segment, the last one may end with a truncated record.
+		let mut segments = segment_indices(&config.path)?;
+		let index = segments.last().map_or(0, |last| last + 1);
+		segments.push(index);
+		let current = open_segment(&config.path, index)?;
+
+		let mut writer = Self { config, segments, current, current_size: 0 };
+		writer.remove_old_segments()?;
+		Ok(writer)
+	}
+
+	fn append(&mut self, record: &[u8]) -> io::Result<()> {
+		if self.current_size > 0 &&
+			self.current_size + record.len() as u64 > self.config.max_segment_size
+		{
+			let index = self.segments.last().map_or(0, |last| last + 1);
+			self.current = open_segment(&self.config.path, index)?;
+			self.current_size = 0;
+			self.segments.push(index);
+			self.remove_old_segments()?;
+		}
+
+		self.current.write_all(record)?;
+		self.current.flush()?;
+		self.current_size += record.len() as u64;
+		Ok(())
+	}
+
+	fn remove_old_segments(&mut self) -> io::Result<()> {
+		let max_segments = self.config.max_segments.max(1);
+		while self.segments.len() > max_segments {
+			let index = self.segments.remove(0);
+			fs::remove_file(segment_path(&self.config.path, index))?;
+		}
+		Ok(())
+	}
+}
+
+fn segment_path(path: &Path, index: u64) -> PathBuf {
+	path.join(format!("{}{:020}.{}", SEGMENT_PREFIX, index, SEGMENT_EXTENSION))
+}
+
+fn open_segment(path: &Path, index: u64) -> io::Result<File> {
+	OpenOptions::new().create(true).append(true).open(segment_path(path, index))
+}
+
+/// Returns the indices of the segments in `path`, oldest first.
+fn segment_indices(path: &Path) -> io::Result<Vec<u64>> {
+	let mut indices = Vec::new();
+	for entry in fs::read_dir(path)? {
+		let file_name = entry?.file_name();
+		let index = file_name
+			.to_str()
+			.and_then(|name| name.strip_prefix(SEGMENT_PREFIX))
+			.and_then(|name| name.strip_suffix(SEGMENT_EXTENSION))
+			.and_then(|name| name.strip_suffix('.'))
+			.and_then(|index| index.parse().ok());
+		indices.extend(index);
+	}
+	indices.sort_unstable();
+	Ok(indices)
+}
+
+/// Rotating on-disk archive of the collations built by the collator.
+pub struct CollationArchive<Block: BlockT> {
+	writer: Arc<Mutex<Writer>>,
+	_phantom: std::marker::PhantomData<Block>,
+}
+
+impl<Block: BlockT> Clone for CollationArchive<Block> {
+	fn clone(&self) -> Self {
+		Self { writer: self.writer.clone(), _phantom: Default::default() }
+	}
+}
+
+impl<Block: BlockT> CollationArchive<Block> {
+	/// Open the archive, creating its directory if required.
+	pub fn open(config: CollationArchiveConfig) -> io::Result<Self> {
+		Ok(Self {
+			writer: Arc::new(Mutex::new(Writer::open(config)?)),
+			_phantom: Default::default(),
+		})
+	}
+
+	/// Append a collation to the archive.
+	///
+	/// Failures are logged, a collation is never dropped because it couldn't be archived.
+	pub(crate) fn archive(
+		&self,
+		header: Block::Header,
+		relay_parent: PHash,
+		pov_hash: PHash,
+		pov_size: u32,
+		collation_info: CollationInfo,
+	) {
+		let archived_at = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_or(0, |time| time.as_millis() as u64);
+		let block_hash = header.hash();
+		let record = ArchivedCollation::<Block> {
+			header,
+			relay_parent,
+			pov_hash,
+			pov_size,
+			collation_info,
+			archived_at,
+		};
+
+		if let Err(e) = self.writer.lock().append(&record.encode()) {
+			tracing::warn!(
+				target: LOG_TARGET,
+				?block_hash,
+				error = ?e,
+				"Failed to archive collation.",
+			);
+		}
+	}
+
+	/// Read the archived collations of the blocks in `range` from the archive at `path`.
+	///
+	/// The collations are returned in the order they were archived. A truncated record at the end
+	/// of a segment, e.g. after a crash, is ignored.
+	pub fn read(
+		path: &Path,
+		range: RangeInclusive<u64>,
+	) -> io::Result<Vec<ArchivedCollation<Block>>> {
+		let mut collations = Vec::new();
+
+		for index in segment_indices(path)? {
+			let segment = fs::read(segment_path(path, index))?;
+			let mut input = &segment[..];
+
+			while !input.is_empty() {
+				match ArchivedCollation::<Block>::decode(&mut input) {
+					Ok(collation) =>
+						if range.contains(&collation.block_number()) {
+							collations.push(collation);
+						},
+					Err(e) => {
+						tracing::warn!(
+							target: LOG_TARGET,
+							segment = index,
+							error = ?e,
+							"Ignoring the invalid end of an archive segment.",
+						);
+						break
+					},
+				}
+			}
+		}
+
+		Ok(collations)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cumulus_test_runtime::{Block, Header};
+
+	fn archive_collation(archive: &CollationArchive<Block>, number: u32) {
+		let header = Header::new(
+			number,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		let collation_info = CollationInfo {
+			upward_messages: Vec::new(),
+			horizontal_messages: Vec::new(),
+			new_validation_code: None,
+			processed_downward_messages: 0,
+			hrmp_watermark: number,
+			head_data: header.encode().into(),
+			vote_result: None,
+		};
+		archive.archive(header, PHash::repeat_byte(1), PHash::repeat_byte(2), 100, collation_info);
+	}
+
+	#[test]
+	fn archived_collations_are_read_back_and_segments_rotate() {
+		let dir = tempfile::tempdir().unwrap();
+		let config = CollationArchiveConfig {
+			path: dir.path().to_path_buf(),
+			max_segment_size: 1,
+			max_segments: 3,
+		};
+
+		let archive = CollationArchive::<Block>::open(config.clone()).unwrap();
+		for number in 1..=5 {
+			archive_collation(&archive, number);
+		}
+
+		// Every collation is written to its own segment, only the last 3 are kept.
+		assert_eq!(vec![2, 3, 4], segment_indices(dir.path()).unwrap());
+		let numbers = |range| {
+			CollationArchive::<Block>::read(dir.path(), range)
+				.unwrap()
+				.iter()
+				.map(|c| c.block_number())
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(vec![3, 4, 5], numbers(0..=u64::MAX));
+		assert_eq!(vec![4], numbers(4..=4));
+
+		// The truncated record is skipped and reopening starts a new segment.
+		let last_segment = segment_path(dir.path(), 4);
+		let content = fs::read(&last_segment).unwrap();
+		fs::write(&last_segment, &content[..content.len() - 1]).unwrap();
+
+		let archive = CollationArchive::<Block>::open(config).unwrap();
+		archive_collation(&archive, 6);
+		assert_eq!(vec![4, 6], numbers(0..=u64::MAX));
+	}
+}
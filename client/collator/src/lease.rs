@@ -0,0 +1,328 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Leader lease of hot-standby collators.
+//!
+//! Collators running with the same Aura key equivocate when they author at the same time. With a
+//! leader lease, only the holder of the lease authors while the other collators keep syncing and
+//! take over authoring once the lease expires, e.g. because the holder was stopped or lost access
+//! to the lease.
+//!
+//! The lease is a file on a file system shared by the collators, holding the name of its holder and
+//! its expiry time. The holder renews the lease every third of its duration and stops authoring a
+//! third of the duration before the expiry of the last renewal. A collator only starts authoring
+//! after it observed itself as the holder twice in a row, so that two collators acquiring an
+//! expired lease at the same time don't both author. The clocks of the collators need to be
+//! synchronized, with a skew well below the lease duration.
+
+use codec::{Decode, Encode};
+use futures_timer::Delay;
+use std::{
+	fs, io,
+	path::PathBuf,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::LOG_TARGET;
+
+/// Minimum duration of the lease.
+///
+/// The lease is renewed every third of its duration, shorter leases would be renewed in a busy
+/// loop.
+pub const MIN_LEADER_LEASE_DURATION: Duration = Duration::from_secs(3);
+
+/// Configuration of the [`LeaderLease`].
+#[derive(Debug, Clone)]
+pub struct LeaderLeaseConfig {
+	/// Path of the lease file, shared by the collators.
+	pub path: PathBuf,
+	/// Name of this collator, unique among the collators sharing the lease.
+	pub holder: String,
+	/// Duration of the lease, at least [`MIN_LEADER_LEASE_DURATION`].
+	pub duration: Duration,
+}
+
+/// Content of the lease file.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+struct LeaseRecord {
+	holder: String,
+	/// Unix time in milliseconds at which the lease expires.
+	expires_at: u64,
+}
+
+fn now_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |time| time.as_millis() as u64)
+}
+
+/// Leader lease deciding whether this collator may author.
+///
+/// The lease is acquired and renewed by the future returned by [`Self::run`], which needs to be
+/// spawned. Until then, this collator never authors.
+#[derive(Clone)]
+pub struct LeaderLease {
+	config: LeaderLeaseConfig,
+	/// Unix time in milliseconds until which this collator may author.
+	authoring_until: Arc<AtomicU64>,
+}
+
+impl LeaderLease {
+	/// Create a new instance.
+	///
+	/// Fails if the duration of the lease is shorter than [`MIN_LEADER_LEASE_DURATION`].
+	pub fn new(config: LeaderLeaseConfig) -> Result<Self, String> {
+		if config.duration < MIN_LEADER_LEASE_DURATION {
+			return Err(format!(
+				"The leader lease duration must be at least {:?}, got {:?}",
+				MIN_LEADER_LEASE_DURATION, config.duration,
+			))
+		}
+
+		Ok(Self { config, authoring_until: Default::default() })
+	}
+
+	/// Returns `true` if this collator holds the lease and may author.
+	pub fn is_leader(&self) -> bool {
+		now_millis() < self.authoring_until.load(Ordering::Relaxed)
+	}
+
+	/// Returns the future acquiring and renewing the lease.
+	///
+	/// The lease is released when the future is dropped, letting a standby collator take over
+	/// without waiting for the expiry of the lease.
+	pub fn run(&self) -> impl std::future::Future<Output = ()> {
+		let lease_file = LeaseFile {
+			config: self.config.clone(),
+			authoring_until: self.authoring_until.clone(),
+		};
+		let interval = self.config.duration / 3;
+
+		async move {
+			loop {
+				lease_file.update();
+				Delay::new(interval).await;
+			}
+		}
+	}
+}
+
+/// Access to the lease file, releasing the lease when dropped.
+struct LeaseFile {
+	config: LeaderLeaseConfig,
+	authoring_until: Arc<AtomicU64>,
+}
+
+impl LeaseFile {
+	fn read(&self) -> io::Result<Option<LeaseRecord>> {
+		match fs::read(&self.config.path) {
+			Ok(content) => LeaseRecord::decode(&mut &content[..])
+				.map(Some)
+				.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Atomically replace the lease file with `record`.
+	fn write(&self, record: &LeaseRecord) -> io::Result<()> {
+		let file_name = self.config.path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+		let holder = self.config.holder.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+		let tmp_path = self.config.path.with_file_name(format!(".{}.{}.tmp", file_name, holder));
+
+		fs::write(&tmp_path, record.encode())?;
+		fs::rename(&tmp_path, &self.config.path)
+	}
+
+	/// Acquire or renew the lease if possible and update until when this collator may author.
+	fn update(&self) {
+		self.update_at(now_millis())
+	}
+
+	fn update_at(&self, now: u64) {
+		let was_leader = self.authoring_until.load(Ordering::Relaxed) > now;
+
+		// On errors, the collator keeps authoring until the end of the last renewal.
+		let current = match self.read() {
+			Ok(current) => current,
+			Err(e) => {
+				tracing::warn!(target: LOG_TARGET, error = ?e, "Failed to read the leader lease.");
+				return
+			},
+		};
+
+		let ours = current.as_ref().map_or(false, |record| record.holder == self.config.holder);
+		if let Some(record) = current.as_ref().filter(|record| !ours && record.expires_at > now) {
+			self.authoring_until.store(0, Ordering::Relaxed);
+			if was_leader {
+				tracing::warn!(
+					target: LOG_TARGET,
+					holder = %record.holder,
+					"Lost the leader lease, authoring is disabled.",
+				);
+			}
+			return
+		}
+
+		let duration = self.config.duration.as_millis() as u64;
+		let expires_at = now + duration;
+		let record = LeaseRecord { holder: self.config.holder.clone(), expires_at };
+		if let Err(e) = self.write(&record) {
+			tracing::warn!(target: LOG_TARGET, error = ?e, "Failed to write the leader lease.");
+			return
+		}
+
+		// A lease acquired from another holder is only used once it is still ours on the next
+		// update, another collator may have acquired it at the same time.
+		if ours {
+			self.authoring_until.store(expires_at - duration / 3, Ordering::Relaxed);
+			if !was_leader {
+				tracing::info!(target: LOG_TARGET, "Acquired the leader lease, authoring is enabled.");
+			}
+		}
+	}
+}
+
+impl Drop for LeaseFile {
+	fn drop(&mut self) {
+		self.authoring_until.store(0, Ordering::Relaxed);
+
+		if matches!(self.read(), Ok(Some(record)) if record.holder == self.config.holder) {
+			match fs::remove_file(&self.config.path) {
+				Ok(()) => tracing::info!(target: LOG_TARGET, "Released the leader lease."),
+				Err(e) => tracing::warn!(
+					target: LOG_TARGET,
+					error = ?e,
+					"Failed to release the leader lease.",
+				),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lease_file(path: &std::path::Path, holder: &str, duration: Duration) -> LeaseFile {
+		LeaseFile {
+			config: LeaderLeaseConfig {
+				path: path.join("leader.lease"),
+				holder: holder.into(),
+				duration,
+			},
+			authoring_until: Default::default(),
+		}
+	}
+
+	fn is_leader(lease_file: &LeaseFile) -> bool {
+		LeaderLease {
+			config: lease_file.config.clone(),
+			authoring_until: lease_file.authoring_until.clone(),
+		}
+		.is_leader()
+	}
+
+	fn is_leader_at(lease_file: &LeaseFile, now: u64) -> bool {
+		now < lease_file.authoring_until.load(Ordering::Relaxed)
+	}
+
+	#[test]
+	fn only_one_collator_holds_the_lease() {
+		let dir = tempfile::tempdir().unwrap();
+		let primary = lease_file(dir.path(), "primary", Duration::from_secs(60));
+		let standby = lease_file(dir.path(), "standby", Duration::from_secs(60));
+
+		// Acquiring the lease needs a second update to confirm it.
+		primary.update();
+		assert!(!is_leader(&primary));
+		standby.update();
+		primary.update();
+		assert!(is_leader(&primary));
+
+		standby.update();
+		standby.update();
+		assert!(!is_leader(&standby));
+
+		// Dropping the primary releases the lease to the standby.
+		drop(primary);
+		standby.update();
+		assert!(!is_leader(&standby));
+		standby.update();
+		assert!(is_leader(&standby));
+	}
+
+	#[test]
+	fn concurrent_acquisition_is_resolved() {
+		let dir = tempfile::tempdir().unwrap();
+		let first = lease_file(dir.path(), "first", Duration::from_secs(60));
+		let second = lease_file(dir.path(), "second", Duration::from_secs(60));
+
+		// Both see the lease as free and write it, the last write wins.
+		first.update();
+		second
+			.write(&LeaseRecord { holder: "second".into(), expires_at: now_millis() + 60_000 })
+			.unwrap();
+		first.update();
+		second.update();
+
+		assert!(!is_leader(&first));
+		assert!(is_leader(&second));
+	}
+
+	#[test]
+	fn short_lease_is_rejected() {
+		let config = LeaderLeaseConfig {
+			path: "leader.lease".into(),
+			holder: "primary".into(),
+			duration: Duration::ZERO,
+		};
+		assert!(LeaderLease::new(config.clone()).is_err());
+		assert!(LeaderLease::new(LeaderLeaseConfig {
+			duration: MIN_LEADER_LEASE_DURATION,
+			..config
+		})
+		.is_ok());
+	}
+
+	#[test]
+	fn expired_lease_is_taken_over() {
+		let dir = tempfile::tempdir().unwrap();
+		let primary = lease_file(dir.path(), "primary", Duration::from_secs(60));
+		let standby = lease_file(dir.path(), "standby", Duration::from_secs(60));
+
+		let now = now_millis();
+		primary.update_at(now);
+		primary.update_at(now);
+		assert!(is_leader_at(&primary, now));
+
+		// The primary stopped renewing the lease, which expired.
+		let expired = now + 60_001;
+		assert!(!is_leader_at(&primary, expired));
+		standby.update_at(expired);
+		standby.update_at(expired);
+		assert!(is_leader_at(&standby, expired));
+
+		// The lease is not released by the former holder.
+		drop(primary);
+		standby.update_at(expired);
+		assert!(is_leader_at(&standby, expired));
+	}
+}
@@ -45,6 +45,14 @@ use parking_lot::Mutex;
 use std::sync::Arc;
 use tracing::Instrument;
 
+mod archive;
+mod lease;
+mod stats;
+
+pub use archive::{ArchivedCollation, CollationArchive, CollationArchiveConfig};
+pub use lease::{LeaderLease, LeaderLeaseConfig, MIN_LEADER_LEASE_DURATION};
+pub use stats::{CollationStats, CollationStatsRecorder, MAX_RECORDED_COLLATIONS};
+
 /// The logging target.
 const LOG_TARGET: &str = "cumulus-collator";
 
@@ -54,6 +62,9 @@ pub struct Collator<Block: BlockT, BS, RA> {
 	parachain_consensus: Box<dyn ParachainConsensus<Block>>,
 	wait_to_announce: Arc<Mutex<WaitToAnnounce<Block>>>,
 	runtime_api: Arc<RA>,
+	collation_stats: Option<CollationStatsRecorder<Block>>,
+	collation_archive: Option<CollationArchive<Block>>,
+	leader_lease: Option<LeaderLease>,
 }
 
 impl<Block: BlockT, BS, RA> Clone for Collator<Block, BS, RA> {
@@ -63,6 +74,9 @@ impl<Block: BlockT, BS, RA> Clone for Collator<Block, BS, RA> {
 			wait_to_announce: self.wait_to_announce.clone(),
 			parachain_consensus: self.parachain_consensus.clone(),
 			runtime_api: self.runtime_api.clone(),
+			collation_stats: self.collation_stats.clone(),
+			collation_archive: self.collation_archive.clone(),
+			leader_lease: self.leader_lease.clone(),
 		}
 	}
 }
@@ -81,10 +95,21 @@ where
 		announce_block: Arc<dyn Fn(Block::Hash, Option<Vec<u8>>) + Send + Sync>,
 		runtime_api: Arc<RA>,
 		parachain_consensus: Box<dyn ParachainConsensus<Block>>,
+		collation_stats: Option<CollationStatsRecorder<Block>>,
+		collation_archive: Option<CollationArchive<Block>>,
+		leader_lease: Option<LeaderLease>,
 	) -> Self {
 		let wait_to_announce = Arc::new(Mutex::new(WaitToAnnounce::new(spawner, announce_block)));
 
-		Self { block_status, wait_to_announce, runtime_api, parachain_consensus }
+		Self {
+			block_status,
+			wait_to_announce,
+			runtime_api,
+			parachain_consensus,
+			collation_stats,
+			collation_archive,
+			leader_lease,
+		}
 	}
 
 	/// Checks the status of the given block hash in the Parachain.
@@ -183,6 +208,7 @@ where
 		&self,
 		block: ParachainBlockData<Block>,
 		block_hash: Block::Hash,
+		relay_parent: PHash,
 		pov: PoV,
 	) -> Option<Collation> {
 		let collation_info = self
@@ -196,6 +222,8 @@ where
 			})
 			.ok()
 			.flatten()?;
+		let archived_collation_info =
+			self.collation_archive.as_ref().map(|_| collation_info.clone());
 
 		let upward_messages = collation_info
 			.upward_messages
@@ -220,6 +248,18 @@ where
 			})
 			.ok()?;
 
+		if let (Some(archive), Some(collation_info)) =
+			(&self.collation_archive, archived_collation_info)
+		{
+			archive.archive(
+				block.header().clone(),
+				relay_parent,
+				pov.hash(),
+				pov.block_data.0.len() as u32,
+				collation_info,
+			);
+		}
+
 		Some(Collation {
 			upward_messages,
 			new_validation_code: collation_info.new_validation_code,
@@ -243,6 +283,15 @@ where
 			"Producing candidate",
 		);
 
+		if self.leader_lease.as_ref().map_or(false, |lease| !lease.is_leader()) {
+			tracing::debug!(
+				target: LOG_TARGET,
+				relay_parent = ?relay_parent,
+				"Skipping candidate production, because this collator doesn't hold the leader lease.",
+			);
+			return None
+		}
+
 		let last_head = match Block::Header::decode(&mut &validation_data.parent_head.0[..]) {
 			Ok(x) => x,
 			Err(e) => {
@@ -288,26 +337,51 @@ where
 		// Create the parachain block data for the validators.
 		let b = ParachainBlockData::<Block>::new(header, extrinsics, compact_proof);
 
+		let header_size = b.header().encoded_size();
+		let extrinsics_size = b.extrinsics().encoded_size();
+		let storage_proof_size = b.storage_proof().encoded_size();
+		let extrinsic_count = b.extrinsics().len();
+
 		tracing::info!(
 			target: LOG_TARGET,
 			"PoV size {{ header: {}kb, extrinsics: {}kb, storage_proof: {}kb }}",
-			b.header().encode().len() as f64 / 1024f64,
-			b.extrinsics().encode().len() as f64 / 1024f64,
-			b.storage_proof().encode().len() as f64 / 1024f64,
+			header_size as f64 / 1024f64,
+			extrinsics_size as f64 / 1024f64,
+			storage_proof_size as f64 / 1024f64,
 		);
 
+		let block_data = b.encode();
+		let uncompressed_pov_size = block_data.len();
 		let pov = infrablockspace_node_primitives::maybe_compress_pov(PoV {
-			block_data: BlockData(b.encode()),
+			block_data: BlockData(block_data),
 		});
+		let pov_size = pov.block_data.0.len();
 
 		tracing::info!(
 			target: LOG_TARGET,
 			"Compressed PoV size: {}kb",
-			pov.block_data.0.len() as f64 / 1024f64,
+			pov_size as f64 / 1024f64,
 		);
 
 		let block_hash = b.header().hash();
-		let collation = self.build_collation(b, block_hash, pov)?;
+		let block_number = *b.header().number();
+		let collation = self.build_collation(b, block_hash, relay_parent, pov)?;
+
+		if let Some(collation_stats) = &self.collation_stats {
+			collation_stats.record(CollationStats {
+				block_hash,
+				block_number,
+				relay_parent,
+				max_pov_size: validation_data.max_pov_size,
+				pov_size,
+				uncompressed_pov_size,
+				header_size,
+				extrinsics_size,
+				storage_proof_size,
+				extrinsic_count,
+				vote_result_size: collation.vote_result.as_ref().map_or(0, |v| v.encoded_size()),
+			});
+		}
 
 		let (result_sender, signed_stmt_recv) = oneshot::channel();
 
@@ -329,6 +403,14 @@ pub struct StartCollatorParams<Block: BlockT, RA, BS, Spawner> {
 	pub spawner: Spawner,
 	pub key: CollatorPair,
 	pub parachain_consensus: Box<dyn ParachainConsensus<Block>>,
+	/// Records the statistics of the built collations, if given.
+	pub collation_stats: Option<CollationStatsRecorder<Block>>,
+	/// Archives the built collations, if given.
+	pub collation_archive: Option<CollationArchive<Block>>,
+	/// Only produce candidates while holding this lease, if given.
+	///
+	/// The future of [`LeaderLease::run`] needs to be spawned.
+	pub leader_lease: Option<LeaderLease>,
 }
 
 /// Start the collator.
@@ -342,6 +424,9 @@ pub async fn start_collator<Block, RA, BS, Spawner>(
 		key,
 		parachain_consensus,
 		runtime_api,
+		collation_stats,
+		collation_archive,
+		leader_lease,
 	}: StartCollatorParams<Block, RA, BS, Spawner>,
 ) where
 	Block: BlockT,
@@ -356,6 +441,9 @@ pub async fn start_collator<Block, RA, BS, Spawner>(
 		announce_block,
 		runtime_api,
 		parachain_consensus,
+		collation_stats,
+		collation_archive,
+		leader_lease,
 	);
 
 	let span = tracing::Span::current();
@@ -458,6 +546,8 @@ mod tests {
 
 		spawner.spawn("overseer", None, overseer.run().then(|_| async { () }).boxed());
 
+		let collation_stats = CollationStatsRecorder::new(None);
+
 		let collator_start = start_collator(StartCollatorParams {
 			runtime_api: client.clone(),
 			block_status: client.clone(),
@@ -467,6 +557,9 @@ mod tests {
 			para_id,
 			key: CollatorPair::generate().0,
 			parachain_consensus: Box::new(DummyParachainConsensus { client: client.clone() }),
+			collation_stats: Some(collation_stats.clone()),
+			collation_archive: None,
+			leader_lease: None,
 		});
 		block_on(collator_start);
 
@@ -496,6 +589,14 @@ mod tests {
 
 		assert_eq!(1, *block.header().number());
 
+		let stats = collation_stats.last_collations(MAX_RECORDED_COLLATIONS);
+		assert_eq!(1, stats.len());
+		assert_eq!(block.header().hash(), stats[0].block_hash);
+		assert_eq!(1, stats[0].block_number);
+		assert_eq!(pov.block_data.0.len(), stats[0].pov_size);
+		assert_eq!(decompressed.len(), stats[0].uncompressed_pov_size);
+		assert_eq!(block.extrinsics().len(), stats[0].extrinsic_count);
+
 		// Ensure that we did not include `:code` in the proof.
 		let proof = block.storage_proof();
 		let db = proof
@@ -0,0 +1,189 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Statistics of the collations built by the collator.
+
+use cumulus_primitives_core::relay_chain::Hash as PHash;
+use parking_lot::Mutex;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+use std::{collections::VecDeque, sync::Arc};
+use substrate_prometheus_endpoint::{
+	exponential_buckets, linear_buckets, register, Histogram, HistogramOpts, PrometheusError,
+	Registry,
+};
+
+use crate::LOG_TARGET;
+
+/// Number of collations for which the statistics are kept.
+pub const MAX_RECORDED_COLLATIONS: usize = 256;
+
+/// Statistics of a collation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollationStats<Block: BlockT> {
+	/// Hash of the block.
+	pub block_hash: Block::Hash,
+	/// Number of the block.
+	pub block_number: NumberFor<Block>,
+	/// Relay parent of the collation.
+	pub relay_parent: PHash,
+	/// `max_pov_size` given by the relay chain for the collation.
+	pub max_pov_size: u32,
+	/// Size of the compressed PoV.
+	pub pov_size: usize,
+	/// Size of the PoV before compression.
+	pub uncompressed_pov_size: usize,
+	/// Size of the encoded header.
+	pub header_size: usize,
+	/// Size of the encoded extrinsics.
+	pub extrinsics_size: usize,
+	/// Size of the encoded storage proof.
+	pub storage_proof_size: usize,
+	/// Number of extrinsics in the block.
+	pub extrinsic_count: usize,
+	/// Size of the encoded vote result, `0` if there is none.
+	pub vote_result_size: usize,
+}
+
+struct Metrics {
+	pov_size: Histogram,
+	uncompressed_pov_size: Histogram,
+	storage_proof_size: Histogram,
+	extrinsic_count: Histogram,
+	vote_result_size: Histogram,
+	pov_size_ratio: Histogram,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		let size_histogram = |name: &str, help: &str| -> Result<Histogram, PrometheusError> {
+			register(
+				Histogram::with_opts(
+					HistogramOpts::new(name, help).buckets(exponential_buckets(1024.0, 2.0, 14)?),
+				)?,
+				registry,
+			)
+		};
+
+		Ok(Self {
+			pov_size: size_histogram(
+				"cumulus_collator_pov_size_bytes",
+				"Size of the compressed PoV of the built collations",
+			)?,
+			uncompressed_pov_size: size_histogram(
+				"cumulus_collator_uncompressed_pov_size_bytes",
+				"Size of the PoV of the built collations before compression",
+			)?,
+			storage_proof_size: size_histogram(
+				"cumulus_collator_storage_proof_size_bytes",
+				"Size of the storage proof of the built collations",
+			)?,
+			extrinsic_count: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_collator_extrinsics",
+						"Number of extrinsics of the built collations",
+					)
+					.buckets(exponential_buckets(1.0, 2.0, 14)?),
+				)?,
+				registry,
+			)?,
+			vote_result_size: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_collator_vote_result_size_bytes",
+						"Size of the vote result of the built collations",
+					)
+					.buckets(exponential_buckets(64.0, 2.0, 14)?),
+				)?,
+				registry,
+			)?,
+			pov_size_ratio: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"cumulus_collator_pov_size_ratio",
+						"Size of the compressed PoV relative to the `max_pov_size` of the built collations",
+					)
+					.buckets(linear_buckets(0.05, 0.05, 20)?),
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	fn observe<Block: BlockT>(&self, stats: &CollationStats<Block>) {
+		self.pov_size.observe(stats.pov_size as f64);
+		self.uncompressed_pov_size.observe(stats.uncompressed_pov_size as f64);
+		self.storage_proof_size.observe(stats.storage_proof_size as f64);
+		self.extrinsic_count.observe(stats.extrinsic_count as f64);
+		self.vote_result_size.observe(stats.vote_result_size as f64);
+		if stats.max_pov_size > 0 {
+			self.pov_size_ratio.observe(stats.pov_size as f64 / stats.max_pov_size as f64);
+		}
+	}
+}
+
+/// Records the statistics of the collations built by the collator.
+///
+/// The statistics of the last [`MAX_RECORDED_COLLATIONS`] collations are kept and also exported
+/// as Prometheus histograms if a registry is given.
+pub struct CollationStatsRecorder<Block: BlockT> {
+	collations: Arc<Mutex<VecDeque<CollationStats<Block>>>>,
+	metrics: Option<Arc<Metrics>>,
+}
+
+impl<Block: BlockT> Clone for CollationStatsRecorder<Block> {
+	fn clone(&self) -> Self {
+		Self { collations: self.collations.clone(), metrics: self.metrics.clone() }
+	}
+}
+
+impl<Block: BlockT> CollationStatsRecorder<Block> {
+	/// Create a new instance.
+	pub fn new(registry: Option<&Registry>) -> Self {
+		let metrics = registry.and_then(|registry| {
+			Metrics::register(registry)
+				.map_err(|err| {
+					tracing::warn!(
+						target: LOG_TARGET,
+						error = ?err,
+						"Failed to register collation metrics",
+					)
+				})
+				.ok()
+				.map(Arc::new)
+		});
+
+		Self { collations: Default::default(), metrics }
+	}
+
+	/// Returns the statistics of the last `count` collations, the most recent first.
+	pub fn last_collations(&self, count: usize) -> Vec<CollationStats<Block>> {
+		self.collations.lock().iter().rev().take(count).cloned().collect()
+	}
+
+	/// Record the statistics of a new collation.
+	pub(crate) fn record(&self, stats: CollationStats<Block>) {
+		if let Some(metrics) = &self.metrics {
+			metrics.observe(&stats);
+		}
+
+		let mut collations = self.collations.lock();
+		if collations.len() >= MAX_RECORDED_COLLATIONS {
+			collations.pop_front();
+		}
+		collations.push_back(stats);
+	}
+}
@@ -0,0 +1,198 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of the AuRa slot author of announced blocks and report of their equivocations.
+
+use codec::{Codec, Encode};
+use cumulus_client_network::{
+	AuthorVerification, BlockAuthor, BlockAuthorVerifier, Equivocation, EquivocationReporter,
+};
+use cumulus_primitives_core::CollatorEquivocationApi;
+use futures::FutureExt;
+use sc_transaction_pool_api::{TransactionPool, TransactionSource};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::{BlockStatus, HeaderBackend};
+use sp_consensus_aura::{digests::CompatibleDigestItem, AuraApi};
+use sp_core::{crypto::Pair, traits::SpawnNamed};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Verifies that announced blocks are sealed by the AuRa author of their slot.
+///
+/// The authorities are fetched from the state of the parent block, thus the author of blocks
+/// whose parent is not known yet can not be verified.
+pub struct AuraAuthorVerifier<P, Client> {
+	client: Arc<Client>,
+	_phantom: PhantomData<P>,
+}
+
+impl<P, Client> AuraAuthorVerifier<P, Client> {
+	/// Create a new instance.
+	pub fn new(client: Arc<Client>) -> Self {
+		Self { client, _phantom: PhantomData }
+	}
+}
+
+impl<Block, P, Client> BlockAuthorVerifier<Block> for AuraAuthorVerifier<P, Client>
+where
+	Block: BlockT,
+	P: Pair + Send + Sync,
+	P::Public: Codec,
+	P::Signature: Codec,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+	Client::Api: AuraApi<Block, P::Public>,
+{
+	fn verify_author(&self, header: &Block::Header) -> AuthorVerification {
+		let parent_hash = *header.parent_hash();
+		if !matches!(self.client.status(parent_hash), Ok(BlockStatus::InChain)) {
+			return AuthorVerification::Unknown
+		}
+
+		let authorities = match self.client.runtime_api().authorities(parent_hash) {
+			Ok(authorities) => authorities,
+			Err(err) => {
+				tracing::debug!(
+					target: crate::LOG_TARGET,
+					error = ?err,
+					?parent_hash,
+					"Unable to fetch the authorities to verify an announced block.",
+				);
+				return AuthorVerification::Unknown
+			},
+		};
+
+		let mut header = header.clone();
+		let signature = match header
+			.digest_mut()
+			.pop()
+			.and_then(|seal| CompatibleDigestItem::<P::Signature>::as_aura_seal(&seal))
+		{
+			Some(signature) => signature,
+			None => return AuthorVerification::Invalid("Header is unsealed".into()),
+		};
+
+		let slot = match sc_consensus_aura::find_pre_digest::<Block, P::Signature>(&header) {
+			Ok(slot) => slot,
+			Err(err) => return AuthorVerification::Invalid(err.to_string()),
+		};
+
+		if authorities.is_empty() {
+			return AuthorVerification::Invalid("No authorities".into())
+		}
+		let author = &authorities[*slot as usize % authorities.len()];
+
+		// The seal signs the hash of the header without the seal.
+		if P::verify(&signature, header.hash().as_ref(), author) {
+			AuthorVerification::Valid(BlockAuthor { slot: *slot, author: author.encode() })
+		} else {
+			AuthorVerification::Invalid(format!("Not sealed by the author of slot {}", *slot))
+		}
+	}
+}
+
+/// Reports equivocations to the runtime.
+///
+/// The unsigned report extrinsic is built by the [`CollatorEquivocationApi`] at the best block
+/// and submitted to the local transaction pool.
+pub struct RuntimeEquivocationReporter<Client, Pool> {
+	client: Arc<Client>,
+	transaction_pool: Arc<Pool>,
+	spawner: Box<dyn SpawnNamed>,
+}
+
+impl<Client, Pool> RuntimeEquivocationReporter<Client, Pool> {
+	/// Create a new instance.
+	pub fn new(
+		client: Arc<Client>,
+		transaction_pool: Arc<Pool>,
+		spawner: impl SpawnNamed + 'static,
+	) -> Self {
+		Self { client, transaction_pool, spawner: Box::new(spawner) }
+	}
+}
+
+impl<Block, Client, Pool> EquivocationReporter<Block> for RuntimeEquivocationReporter<Client, Pool>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+	Client::Api: CollatorEquivocationApi<Block>,
+	Pool: TransactionPool<Block = Block> + 'static,
+{
+	fn report_equivocation(&self, equivocation: Equivocation<Block>) {
+		let best_hash = self.client.info().best_hash;
+		let runtime_api = self.client.runtime_api();
+
+		if !runtime_api
+			.has_api::<dyn CollatorEquivocationApi<Block>>(best_hash)
+			.unwrap_or(false)
+		{
+			tracing::debug!(
+				target: crate::LOG_TARGET,
+				"The runtime doesn't support equivocation reports.",
+			);
+			return
+		}
+
+		let extrinsic = match runtime_api.equivocation_report_extrinsic(
+			best_hash,
+			equivocation.first_header,
+			equivocation.second_header,
+		) {
+			Ok(Some(extrinsic)) => extrinsic,
+			Ok(None) => {
+				tracing::debug!(
+					target: crate::LOG_TARGET,
+					author = ?equivocation.author.author,
+					slot = %equivocation.author.slot,
+					"Equivocation not reportable to the runtime.",
+				);
+				return
+			},
+			Err(err) => {
+				tracing::warn!(
+					target: crate::LOG_TARGET,
+					error = ?err,
+					"Unable to build the equivocation report.",
+				);
+				return
+			},
+		};
+
+		let transaction_pool = self.transaction_pool.clone();
+		self.spawner.spawn(
+			"equivocation-report",
+			Some("parachain"),
+			async move {
+				let submission = transaction_pool.submit_one(
+					&BlockId::Hash(best_hash),
+					TransactionSource::Local,
+					extrinsic,
+				);
+				if let Err(err) = submission.await {
+					tracing::warn!(
+						target: crate::LOG_TARGET,
+						error = ?err,
+						"Failed to submit the equivocation report.",
+					);
+				}
+			}
+			.boxed(),
+		);
+	}
+}
@@ -44,9 +44,13 @@ use sp_keystore::SyncCryptoStorePtr;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Member, NumberFor};
 use std::{convert::TryFrom, hash::Hash, marker::PhantomData, sync::Arc};
 
+mod author_verifier;
 mod import_queue;
+mod relay_parent_staleness;
 
+pub use author_verifier::{AuraAuthorVerifier, RuntimeEquivocationReporter};
 pub use import_queue::{build_verifier, import_queue, BuildVerifierParams, ImportQueueParams};
+pub use relay_parent_staleness::{RelayParentStaleness, RelayParentStalenessConfig};
 pub use sc_consensus_aura::{slot_duration, AuraVerifier, BuildAuraWorkerParams, SlotProportion};
 pub use sc_consensus_slots::InherentDataProviderExt;
 
@@ -57,6 +61,7 @@ pub struct AuraConsensus<B, CIDP, W> {
 	create_inherent_data_providers: Arc<CIDP>,
 	aura_worker: Arc<Mutex<W>>,
 	slot_duration: SlotDuration,
+	relay_parent_staleness: Option<RelayParentStaleness>,
 	_phantom: PhantomData<B>,
 }
 
@@ -66,6 +71,7 @@ impl<B, CIDP, W> Clone for AuraConsensus<B, CIDP, W> {
 			create_inherent_data_providers: self.create_inherent_data_providers.clone(),
 			aura_worker: self.aura_worker.clone(),
 			slot_duration: self.slot_duration,
+			relay_parent_staleness: self.relay_parent_staleness.clone(),
 			_phantom: PhantomData,
 		}
 	}
@@ -85,6 +91,8 @@ pub struct BuildAuraConsensusParams<PF, BI, CIDP, Client, BS, SO> {
 	pub telemetry: Option<TelemetryHandle>,
 	pub block_proposal_slot_portion: SlotProportion,
 	pub max_block_proposal_slot_portion: Option<SlotProportion>,
+	/// Skip authoring on relay parents lagging too far behind the best relay chain block.
+	pub relay_parent_staleness: Option<RelayParentStaleness>,
 }
 
 impl<B, CIDP> AuraConsensus<B, CIDP, ()>
@@ -108,6 +116,7 @@ where
 			telemetry,
 			block_proposal_slot_portion,
 			max_block_proposal_slot_portion,
+			relay_parent_staleness,
 		}: BuildAuraConsensusParams<PF, BI, CIDP, Client, BS, SO>,
 	) -> Box<dyn ParachainConsensus<B>>
 	where
@@ -155,6 +164,7 @@ where
 			create_inherent_data_providers: Arc::new(create_inherent_data_providers),
 			aura_worker: Arc::new(Mutex::new(worker)),
 			slot_duration,
+			relay_parent_staleness,
 			_phantom: PhantomData,
 		})
 	}
@@ -204,6 +214,12 @@ where
 		relay_parent: PHash,
 		validation_data: &PersistedValidationData,
 	) -> Option<ParachainCandidate<B>> {
+		if let Some(relay_parent_staleness) = &self.relay_parent_staleness {
+			if !relay_parent_staleness.should_author(validation_data.relay_parent_number) {
+				return None
+			}
+		}
+
 		let inherent_data_providers =
 			self.inherent_data(parent.hash(), validation_data, relay_parent).await?;
 
@@ -0,0 +1,244 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Check of the age of the relay parent before authoring.
+//!
+//! A collator following the relay chain over a slow connection may be asked to author on a relay
+//! parent that lags far behind the best relay chain block. The resulting candidates will never be
+//! backed, so authoring on such a relay parent is skipped and, optionally, authoring is backed off
+//! for a number of collation requests after consecutive stale relay parents.
+
+use cumulus_primitives_core::relay_chain::BlockNumber as RelayBlockNumber;
+use cumulus_relay_chain_interface::RelayChainInterface;
+use futures::StreamExt;
+use std::sync::{
+	atomic::{AtomicU32, Ordering},
+	Arc, Mutex,
+};
+use substrate_prometheus_endpoint::{
+	register, CounterVec, Gauge, Opts, PrometheusError, Registry, U64,
+};
+
+use crate::LOG_TARGET;
+
+/// Configuration of the relay parent staleness check.
+#[derive(Debug, Clone, Copy)]
+pub struct RelayParentStalenessConfig {
+	/// Maximum number of relay chain blocks the relay parent may lag behind the best relay chain
+	/// block.
+	pub max_lag: RelayBlockNumber,
+	/// Maximum number of collation requests skipped after consecutive stale relay parents.
+	///
+	/// The number of skipped requests doubles with each consecutive stale relay parent, up to
+	/// this maximum. With `0`, only the requests on a stale relay parent are skipped.
+	pub max_backoff: u32,
+}
+
+#[derive(Default)]
+struct State {
+	/// Number of consecutive stale relay parents.
+	consecutive_stale: u32,
+	/// Number of collation requests still to be skipped.
+	remaining_backoff: u32,
+}
+
+struct Metrics {
+	skipped: CounterVec<U64>,
+	relay_parent_lag: Gauge<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			skipped: register(
+				CounterVec::new(
+					Opts::new(
+						"cumulus_aura_skipped_collations_total",
+						"Number of collation requests for which authoring was skipped",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
+			relay_parent_lag: register(
+				Gauge::new(
+					"cumulus_aura_relay_parent_lag",
+					"Number of relay chain blocks the last relay parent lagged behind the best relay chain block",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Decides whether to author on a relay parent, based on its age.
+///
+/// The best relay chain block number is tracked by the future returned by
+/// [`Self::track_best_number`], which needs to be spawned. Until the first best block is
+/// known, authoring is never skipped.
+#[derive(Clone)]
+pub struct RelayParentStaleness {
+	config: RelayParentStalenessConfig,
+	best_number: Arc<AtomicU32>,
+	state: Arc<Mutex<State>>,
+	metrics: Option<Arc<Metrics>>,
+}
+
+impl RelayParentStaleness {
+	/// Create a new instance.
+	pub fn new(config: RelayParentStalenessConfig, registry: Option<&Registry>) -> Self {
+		let metrics = registry.and_then(|registry| {
+			Metrics::register(registry)
+				.map_err(|err| {
+					tracing::warn!(
+						target: LOG_TARGET,
+						error = ?err,
+						"Failed to register relay parent staleness metrics",
+					)
+				})
+				.ok()
+				.map(Arc::new)
+		});
+
+		Self { config, best_number: Default::default(), state: Default::default(), metrics }
+	}
+
+	/// Returns the future tracking the number of the best relay chain block.
+	pub fn track_best_number<R>(&self, relay_chain: R) -> impl std::future::Future<Output = ()>
+	where
+		R: RelayChainInterface + Clone,
+	{
+		let best_number = self.best_number.clone();
+
+		async move {
+			let mut new_best_heads = match relay_chain.new_best_notification_stream().await {
+				Ok(new_best_heads) => new_best_heads,
+				Err(err) => {
+					tracing::error!(
+						target: LOG_TARGET,
+						error = ?err,
+						"Unable to retrieve the relay chain best heads stream.",
+					);
+					return
+				},
+			};
+
+			while let Some(header) = new_best_heads.next().await {
+				best_number.store(header.number, Ordering::Relaxed);
+			}
+
+			tracing::debug!(target: LOG_TARGET, "Stopping tracking the best relay chain block.");
+		}
+	}
+
+	/// Returns `true` if authoring on a relay parent with the given number should proceed.
+	pub(crate) fn should_author(&self, relay_parent_number: RelayBlockNumber) -> bool {
+		let best_number = self.best_number.load(Ordering::Relaxed);
+		let lag = best_number.saturating_sub(relay_parent_number);
+		if let Some(metrics) = &self.metrics {
+			metrics.relay_parent_lag.set(lag.into());
+		}
+
+		let mut state = self.state.lock().expect("Lock is never poisoned; qed");
+
+		if lag > self.config.max_lag {
+			state.consecutive_stale = state.consecutive_stale.saturating_add(1);
+			state.remaining_backoff =
+				2u32.saturating_pow(state.consecutive_stale - 1).min(self.config.max_backoff);
+
+			tracing::info!(
+				target: LOG_TARGET,
+				relay_parent_number,
+				best_number,
+				backoff = state.remaining_backoff,
+				"Skipping authoring, the relay parent is stale.",
+			);
+			self.skipped("stale_relay_parent");
+			return false
+		}
+
+		state.consecutive_stale = 0;
+		if state.remaining_backoff > 0 {
+			state.remaining_backoff -= 1;
+
+			tracing::debug!(
+				target: LOG_TARGET,
+				relay_parent_number,
+				remaining = state.remaining_backoff,
+				"Skipping authoring, backing off after stale relay parents.",
+			);
+			self.skipped("backoff");
+			return false
+		}
+
+		true
+	}
+
+	fn skipped(&self, reason: &str) {
+		if let Some(metrics) = &self.metrics {
+			metrics.skipped.with_label_values(&[reason]).inc();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn staleness(max_lag: u32, max_backoff: u32, best_number: u32) -> RelayParentStaleness {
+		let staleness =
+			RelayParentStaleness::new(RelayParentStalenessConfig { max_lag, max_backoff }, None);
+		staleness.best_number.store(best_number, Ordering::Relaxed);
+		staleness
+	}
+
+	#[test]
+	fn skips_stale_relay_parents_only() {
+		let staleness = staleness(2, 0, 10);
+
+		assert!(staleness.should_author(10));
+		assert!(staleness.should_author(8));
+		assert!(!staleness.should_author(7));
+		assert!(staleness.should_author(9));
+	}
+
+	#[test]
+	fn never_skips_without_best_number() {
+		let staleness = staleness(0, 4, 0);
+
+		assert!(staleness.should_author(100));
+	}
+
+	#[test]
+	fn backs_off_after_consecutive_stale_relay_parents() {
+		let staleness = staleness(1, 3, 10);
+
+		// First stale relay parent: back off one request.
+		assert!(!staleness.should_author(5));
+		assert!(!staleness.should_author(10));
+		assert!(staleness.should_author(10));
+
+		// Consecutive stale relay parents: the backoff doubles up to the maximum.
+		assert!(!staleness.should_author(5));
+		assert!(!staleness.should_author(5));
+		assert!(!staleness.should_author(5));
+		assert_eq!(3, staleness.state.lock().unwrap().remaining_backoff);
+		assert!(!staleness.should_author(10));
+		assert!(!staleness.should_author(10));
+		assert!(!staleness.should_author(10));
+		assert!(staleness.should_author(10));
+	}
+}
@@ -15,12 +15,13 @@
 // along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
 
 use sc_client_api::{blockchain::Backend as _, Backend, HeaderBackend as _};
-use sp_blockchain::{HashAndNumber, TreeRoute};
+use sp_blockchain::{HashAndNumber, HeaderMetadata as _, TreeRoute};
 use sp_runtime::traits::{Block as BlockT, NumberFor, One, Saturating, UniqueSaturatedInto, Zero};
 use std::{
-	collections::{HashMap, HashSet},
+	collections::{BTreeSet, HashMap, HashSet},
 	sync::Arc,
 };
+use substrate_prometheus_endpoint::{register, Counter, Gauge, PrometheusError, Registry, U64};
 
 /// Value good enough to be used with parachains using the current backend implementation
 /// that ships with Substrate. This value may change in the future.
@@ -37,8 +38,10 @@ const CLEANUP_THRESHOLD: u32 = 32;
 /// In environments where blocks confirmations from the relay chain may be "slow", then
 /// setting an upper bound helps keeping the chain health by dropping old (presumably) stale
 /// leaves and prevents discarding new blocks because we've reached the backend max value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum LevelLimit {
 	/// Limit set to [`MAX_LEAVES_PER_LEVEL_SENSIBLE_DEFAULT`].
+	#[default]
 	Default,
 	/// No explicit limit, however a limit may be implicitly imposed by the backend implementation.
 	None,
@@ -46,20 +49,98 @@ pub enum LevelLimit {
 	Some(usize),
 }
 
+/// Strategy used to choose the blocks to remove from the blockchain.
+///
+/// Independently from the policy, the blocks in the route of the current best block are never
+/// removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PruningPolicy {
+	/// On level overflow remove the least fresh blocks.
+	#[default]
+	LeastFresh,
+	/// Like [`PruningPolicy::LeastFresh`], but forks containing blocks authored by this node
+	/// are removed only if there is no other candidate.
+	KeepOwn,
+	/// Like [`PruningPolicy::LeastFresh`], but forks containing blocks that were backed by
+	/// the relay chain are removed only if there is no other candidate.
+	///
+	/// Backed blocks are reported through
+	/// [`ParachainBlockImport::block_backed`](crate::ParachainBlockImport::block_backed),
+	/// see [`follow_backed_heads`](crate::follow_backed_heads).
+	KeepBacked,
+	/// Like [`PruningPolicy::LeastFresh`], but additionally forks with no block imported within
+	/// the given number of block imports are removed, even if the level limit is not reached.
+	MaxAge(u32),
+}
+
+/// Fork pruning configuration of the [`ParachainBlockImport`](crate::ParachainBlockImport).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ForkPruning {
+	/// Upper bound to the number of leaves allowed for each level.
+	pub level_limit: LevelLimit,
+	/// Strategy used to choose the blocks to remove.
+	pub policy: PruningPolicy,
+}
+
+/// Prometheus metrics of the [`LevelMonitor`].
+pub(crate) struct Metrics {
+	level_size: Gauge<U64>,
+	max_level_size: Gauge<U64>,
+	pruned_blocks: Counter<U64>,
+}
+
+impl Metrics {
+	/// Register the metrics at the given registry.
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			level_size: register(
+				Gauge::new(
+					"parachain_level_monitor_level_size",
+					"Number of blocks at the height of the last imported block",
+				)?,
+				registry,
+			)?,
+			max_level_size: register(
+				Gauge::new(
+					"parachain_level_monitor_max_level_size",
+					"Number of blocks of the largest non finalized level",
+				)?,
+				registry,
+			)?,
+			pruned_blocks: register(
+				Counter::new(
+					"parachain_level_monitor_pruned_blocks_total",
+					"Number of blocks removed by the level monitor",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
 /// Support structure to constrain the number of leaves at each level.
 pub struct LevelMonitor<Block: BlockT, BE> {
 	// Max number of leaves for each level.
 	level_limit: usize,
+	// Strategy used to choose the blocks to remove.
+	policy: PruningPolicy,
+	// Blocks that should be removed only if there is no other candidate.
+	// Ancestors of a protected block are protected as well.
+	pub(crate) protected: HashSet<Block::Hash>,
 	// Monotonic counter used to keep track of block freshness.
 	pub(crate) import_counter: NumberFor<Block>,
 	// Map between blocks hashes and freshness.
 	pub(crate) freshness: HashMap<Block::Hash, NumberFor<Block>>,
+	// Leaves known by the monitor, ordered by freshness (less fresh first).
+	pub(crate) fresh_leaves: BTreeSet<(NumberFor<Block>, Block::Hash)>,
 	// Blockchain levels cache.
 	pub(crate) levels: HashMap<NumberFor<Block>, HashSet<Block::Hash>>,
 	// Lower level number stored by the levels map.
 	lowest_level: NumberFor<Block>,
 	// Backend reference to remove blocks on level saturation.
 	backend: Arc<BE>,
+	// Prometheus metrics.
+	metrics: Option<Metrics>,
 }
 
 /// Contains information about the target scheduled for removal.
@@ -76,14 +157,23 @@ where
 	BE: Backend<Block>,
 {
 	/// Instance a new monitor structure.
-	pub fn new(level_limit: usize, backend: Arc<BE>) -> Self {
+	pub(crate) fn new(
+		level_limit: usize,
+		policy: PruningPolicy,
+		backend: Arc<BE>,
+		metrics: Option<Metrics>,
+	) -> Self {
 		let mut monitor = LevelMonitor {
 			level_limit,
+			policy,
+			protected: HashSet::new(),
 			import_counter: Zero::zero(),
 			freshness: HashMap::new(),
+			fresh_leaves: BTreeSet::new(),
 			levels: HashMap::new(),
 			lowest_level: Zero::zero(),
 			backend,
+			metrics,
 		};
 		monitor.restore();
 		monitor
@@ -144,8 +234,13 @@ where
 	/// A block freshness is determined by the most recent leaf freshness descending from the block
 	/// itself. In other words its freshness is equal to its more "fresh" descendant.
 	///
-	/// The least "fresh" blocks are eventually removed.
+	/// The least "fresh" blocks are eventually removed, taking into account the blocks
+	/// protected by the [`PruningPolicy`].
 	pub fn enforce_limit(&mut self, number: NumberFor<Block>) {
+		if let PruningPolicy::MaxAge(max_age) = self.policy {
+			self.remove_stale(max_age);
+		}
+
 		let level_len = self.levels.get(&number).map(|l| l.len()).unwrap_or_default();
 		if level_len < self.level_limit {
 			return
@@ -153,8 +248,7 @@ where
 
 		// Sort leaves by freshness only once (less fresh first) and keep track of
 		// leaves that were invalidated on removal.
-		let mut leaves = self.backend.blockchain().leaves().unwrap_or_default();
-		leaves.sort_unstable_by(|a, b| self.freshness.get(a).cmp(&self.freshness.get(b)));
+		let leaves = self.sorted_leaves();
 		let mut invalidated_leaves = HashSet::new();
 
 		// This may not be the most efficient way to remove **multiple** entries, but is the easy
@@ -170,13 +264,78 @@ where
 		);
 
 		(0..remove_count).all(|_| {
-			self.find_target(number, &leaves, &invalidated_leaves).map_or(false, |target| {
+			let target = self
+				.find_target(number, &leaves, &invalidated_leaves, true)
+				.or_else(|| self.find_target(number, &leaves, &invalidated_leaves, false));
+			target.map_or(false, |target| {
 				self.remove_target(target, number, &leaves, &mut invalidated_leaves);
 				true
 			})
 		});
 	}
 
+	// Remove the forks whose freshest leaf was imported more than `max_age` imports ago.
+	//
+	// Only the stale leaves are visited, thanks to the leaves freshness index. Each of them is
+	// removed together with its ancestors, up to the first one having other descendants.
+	fn remove_stale(&mut self, max_age: u32) {
+		let max_age = NumberFor::<Block>::from(max_age);
+		if self.import_counter <= max_age {
+			return
+		}
+		let threshold = self.import_counter - max_age;
+
+		let info = self.backend.blockchain().info();
+		let stale_leaves = self
+			.fresh_leaves
+			.iter()
+			.take_while(|(freshness, _)| *freshness < threshold)
+			.map(|(_, hash)| *hash)
+			.filter(|hash| *hash != info.best_hash)
+			.collect::<Vec<_>>();
+
+		for leaf in stale_leaves {
+			log::debug!(target: "parachain", "Removing stale fork with leaf {:?}", leaf);
+			let mut hash = leaf;
+			loop {
+				let meta = match self.backend.blockchain().header_metadata(hash) {
+					Ok(meta) if meta.number > info.finalized_number => meta,
+					_ => break,
+				};
+				if !self.remove_block(meta.number, hash) {
+					break
+				}
+				// The parent is never fresher than its child, thus it is stale as well.
+				self.index_if_leaf(meta.parent);
+				match self.freshness.get(&meta.parent) {
+					Some(freshness) if self.fresh_leaves.contains(&(*freshness, meta.parent)) =>
+						hash = meta.parent,
+					_ => break,
+				}
+			}
+		}
+	}
+
+	// Returns the leaves sorted by freshness (less fresh first).
+	fn sorted_leaves(&self) -> Vec<Block::Hash> {
+		let mut leaves = self.backend.blockchain().leaves().unwrap_or_default();
+		leaves.sort_unstable_by(|a, b| self.freshness.get(a).cmp(&self.freshness.get(b)));
+		leaves
+	}
+
+	// Protect the given block together with its non finalized ancestors.
+	fn protect(&mut self, hash: Block::Hash) {
+		let blockchain = self.backend.blockchain();
+		let finalized_number = blockchain.info().finalized_number;
+		let mut hash = hash;
+		while self.protected.insert(hash) {
+			match blockchain.header_metadata(hash) {
+				Ok(meta) if meta.number > finalized_number + One::one() => hash = meta.parent,
+				_ => break,
+			}
+		}
+	}
+
 	// Helper function to find the best candidate to be removed.
 	//
 	// Given a set of blocks with height equal to `number` (potential candidates)
@@ -184,6 +343,8 @@ where
 	// 2. Set the candidate freshness equal to the fresher of its descending leaves.
 	// 3. The target is set as the candidate that is less fresh.
 	//
+	// If `skip_protected` is set, then candidates protected by the pruning policy are ignored.
+	//
 	// Input `leaves` are assumed to be already ordered by "freshness" (less fresh first).
 	//
 	// Returns the index of the target fresher leaf within `leaves` and the route from target to
@@ -193,7 +354,12 @@ where
 		number: NumberFor<Block>,
 		leaves: &[Block::Hash],
 		invalidated_leaves: &HashSet<usize>,
+		skip_protected: bool,
 	) -> Option<TargetInfo<Block>> {
+		if skip_protected && self.protected.is_empty() {
+			return None
+		}
+
 		let mut target_info: Option<TargetInfo<Block>> = None;
 		let blockchain = self.backend.blockchain();
 		let best_hash = blockchain.info().best_hash;
@@ -204,7 +370,12 @@ where
 
 		let level = self.levels.get(&number)?;
 
-		for blk_hash in level.iter().filter(|hash| **hash != best_hash) {
+		let candidates = level
+			.iter()
+			.filter(|hash| **hash != best_hash)
+			.filter(|hash| !skip_protected || !self.protected.contains(*hash));
+
+		for blk_hash in candidates {
 			// Search for the fresher leaf information for this block
 			let candidate_info = leaves
 				.iter()
@@ -297,16 +468,10 @@ where
 		leaves: &[Block::Hash],
 		invalidated_leaves: &mut HashSet<usize>,
 	) {
-		let mut remove_leaf = |number, hash| {
-			log::debug!(target: "parachain", "Removing block (@{}) {:?}", number, hash);
-			if let Err(err) = self.backend.remove_leaf_block(hash) {
-				log::debug!(target: "parachain", "Remove not possible for {}: {}", hash, err);
-				return false
-			}
-			self.levels.get_mut(&number).map(|level| level.remove(&hash));
-			self.freshness.remove(&hash);
-			true
-		};
+		let backend = self.backend.clone();
+		let target_hash = target.freshest_route.common_block().hash;
+		let target_parent =
+			backend.blockchain().header_metadata(target_hash).map(|meta| meta.parent);
 
 		invalidated_leaves.insert(target.freshest_leaf_idx);
 
@@ -315,10 +480,13 @@ where
 		// and it will be removed while removing another route from the same block but to a
 		// different leaf.
 		let mut remove_route = |route: TreeRoute<Block>| {
-			route.enacted().iter().rev().all(|elem| remove_leaf(elem.number, elem.hash));
+			route
+				.enacted()
+				.iter()
+				.rev()
+				.all(|elem| self.remove_block(elem.number, elem.hash));
 		};
 
-		let target_hash = target.freshest_route.common_block().hash;
 		debug_assert_eq!(
 			target.freshest_route.common_block().number,
 			number,
@@ -334,7 +502,7 @@ where
 			if invalidated_leaves.contains(&leaf_idx) {
 				return
 			}
-			match sp_blockchain::tree_route(self.backend.blockchain(), target_hash, *leaf_hash) {
+			match sp_blockchain::tree_route(backend.blockchain(), target_hash, *leaf_hash) {
 				Ok(route) if route.retracted().is_empty() => {
 					invalidated_leaves.insert(leaf_idx);
 					remove_route(route);
@@ -350,12 +518,55 @@ where
 			};
 		});
 
-		remove_leaf(number, target_hash);
+		if self.remove_block(number, target_hash) {
+			if let Ok(parent) = target_parent {
+				self.index_if_leaf(parent);
+			}
+		}
+	}
+
+	// Remove the given leaf block from the backend and from the monitor.
+	fn remove_block(&mut self, number: NumberFor<Block>, hash: Block::Hash) -> bool {
+		log::debug!(target: "parachain", "Removing block (@{}) {:?}", number, hash);
+		if let Err(err) = self.backend.remove_leaf_block(hash) {
+			log::debug!(target: "parachain", "Remove not possible for {}: {}", hash, err);
+			return false
+		}
+		self.levels.get_mut(&number).map(|level| level.remove(&hash));
+		if let Some(freshness) = self.freshness.remove(&hash) {
+			self.fresh_leaves.remove(&(freshness, hash));
+		}
+		self.protected.remove(&hash);
+		if let Some(metrics) = &self.metrics {
+			metrics.pruned_blocks.inc();
+		}
+		true
+	}
+
+	// Add the given block to the leaves freshness index if it has no children.
+	fn index_if_leaf(&mut self, hash: Block::Hash) {
+		let is_leaf = self
+			.backend
+			.blockchain()
+			.children(hash)
+			.map_or(false, |children| children.is_empty());
+		if let (true, Some(freshness)) = (is_leaf, self.freshness.get(&hash)) {
+			self.fresh_leaves.insert((*freshness, hash));
+		}
 	}
 
 	/// Add a new imported block information to the monitor.
 	pub fn block_imported(&mut self, number: NumberFor<Block>, hash: Block::Hash) {
-		self.freshness.insert(hash, self.import_counter);
+		if let Some(freshness) = self.freshness.insert(hash, self.import_counter) {
+			self.fresh_leaves.remove(&(freshness, hash));
+		}
+		self.fresh_leaves.insert((self.import_counter, hash));
+		// The parent is not a leaf anymore.
+		if let Ok(meta) = self.backend.blockchain().header_metadata(hash) {
+			if let Some(freshness) = self.freshness.get(&meta.parent) {
+				self.fresh_leaves.remove(&(*freshness, meta.parent));
+			}
+		}
 		self.levels.entry(number).or_default().insert(hash);
 		self.import_counter += One::one();
 
@@ -367,12 +578,48 @@ where
 				let number = self.lowest_level + i.unique_saturated_into();
 				self.levels.remove(&number).map(|level| {
 					level.iter().for_each(|hash| {
-						self.freshness.remove(hash);
+						if let Some(freshness) = self.freshness.remove(hash) {
+							self.fresh_leaves.remove(&(freshness, *hash));
+						}
+						self.protected.remove(hash);
 					})
 				});
 			}
 
 			self.lowest_level = finalized_num;
 		}
+
+		if let Some(metrics) = &self.metrics {
+			let level_size = self.levels.get(&number).map(|l| l.len()).unwrap_or_default();
+			let max_level_size = self
+				.levels
+				.iter()
+				.filter(|(level, _)| **level > finalized_num)
+				.map(|(_, l)| l.len())
+				.max()
+				.unwrap_or_default();
+			metrics.level_size.set(level_size as u64);
+			metrics.max_level_size.set(max_level_size as u64);
+		}
+	}
+
+	/// Note that the block with the given hash was authored by this node.
+	///
+	/// With the [`PruningPolicy::KeepOwn`] policy the block is protected from removal.
+	pub fn own_block_imported(&mut self, hash: Block::Hash) {
+		if self.policy == PruningPolicy::KeepOwn {
+			self.protect(hash);
+		}
+	}
+
+	/// Note that the block with the given hash was backed by the relay chain.
+	///
+	/// With the [`PruningPolicy::KeepBacked`] policy the block is protected from removal.
+	/// Blocks not imported yet are ignored, the relay chain reports them again while they are
+	/// pending availability.
+	pub fn block_backed(&mut self, hash: Block::Hash) {
+		if self.policy == PruningPolicy::KeepBacked && self.freshness.contains_key(&hash) {
+			self.protect(hash);
+		}
 	}
 }
@@ -19,6 +19,7 @@ use infrablockspace_primitives::{Hash as PHash, PersistedValidationData};
 use sc_client_api::Backend;
 use sc_consensus::{shared_data::SharedData, BlockImport, ImportResult};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use substrate_prometheus_endpoint::Registry;
 
 use std::sync::Arc;
 
@@ -27,10 +28,16 @@ mod parachain_consensus;
 #[cfg(test)]
 mod tests;
 
-pub use parachain_consensus::run_parachain_consensus;
+pub use parachain_consensus::{
+	follow_backed_heads, follow_included_depth, run_parachain_consensus,
+	ProvisionalFinalityNotification, ProvisionalFinalitySender, ProvisionalFinalityStream,
+	MAX_INCLUDED_DEPTH,
+};
 
 use level_monitor::LevelMonitor;
-pub use level_monitor::{LevelLimit, MAX_LEAVES_PER_LEVEL_SENSIBLE_DEFAULT};
+pub use level_monitor::{
+	ForkPruning, LevelLimit, PruningPolicy, MAX_LEAVES_PER_LEVEL_SENSIBLE_DEFAULT,
+};
 
 /// The result of [`ParachainConsensus::produce_candidate`].
 pub struct ParachainCandidate<B> {
@@ -101,17 +108,53 @@ impl<Block: BlockT, BI, BE: Backend<Block>> ParachainBlockImport<Block, BI, BE>
 	/// This function alone doesn't enforce the limit on levels for old imported blocks,
 	/// the limit is eventually enforced only when new blocks are imported.
 	pub fn new_with_limit(inner: BI, backend: Arc<BE>, level_leaves_max: LevelLimit) -> Self {
-		let level_limit = match level_leaves_max {
+		Self::new_with_pruning(
+			inner,
+			backend,
+			ForkPruning { level_limit: level_leaves_max, ..Default::default() },
+			None,
+		)
+	}
+
+	/// Create a new instance with an explicit fork pruning configuration.
+	///
+	/// If a `registry` is given, the level monitor metrics are registered there.
+	pub fn new_with_pruning(
+		inner: BI,
+		backend: Arc<BE>,
+		pruning: ForkPruning,
+		registry: Option<&Registry>,
+	) -> Self {
+		let level_limit = match pruning.level_limit {
 			LevelLimit::None => None,
 			LevelLimit::Some(limit) => Some(limit),
 			LevelLimit::Default => Some(MAX_LEAVES_PER_LEVEL_SENSIBLE_DEFAULT),
 		};
 
-		let monitor =
-			level_limit.map(|level_limit| SharedData::new(LevelMonitor::new(level_limit, backend)));
+		let metrics = registry.and_then(|registry| {
+			level_monitor::Metrics::register(registry)
+				.map_err(
+					|err| log::warn!(target: "parachain", "Failed to register level monitor metrics: {err}"),
+				)
+				.ok()
+		});
+
+		let monitor = level_limit.map(|level_limit| {
+			SharedData::new(LevelMonitor::new(level_limit, pruning.policy, backend, metrics))
+		});
 
 		Self { inner, monitor }
 	}
+
+	/// Note that the block with the given hash was backed by the relay chain.
+	///
+	/// With the [`PruningPolicy::KeepBacked`] policy, the fork containing the block is removed
+	/// only if there is no other candidate.
+	pub fn block_backed(&self, hash: Block::Hash) {
+		if let Some(monitor) = &self.monitor {
+			monitor.shared_data().block_backed(hash);
+		}
+	}
 }
 
 impl<Block: BlockT, I: Clone, BE> Clone for ParachainBlockImport<Block, I, BE> {
@@ -145,6 +188,7 @@ where
 		// Blocks are stored within the backend by using POST hash.
 		let hash = params.post_hash();
 		let number = *params.header.number();
+		let is_own = params.origin == sp_consensus::BlockOrigin::Own;
 
 		// Best block is determined by the relay chain, or if we are doing the initial sync
 		// we import all blocks as new best.
@@ -163,6 +207,9 @@ where
 		if let (Some(mut monitor_lock), ImportResult::Imported(_)) = (maybe_lock, &res) {
 			let mut monitor = monitor_lock.upgrade();
 			monitor.block_imported(number, hash);
+			if is_own {
+				monitor.own_block_imported(hash);
+			}
 		}
 
 		Ok(res)
@@ -18,10 +18,11 @@ use sc_client_api::{
 	Backend, BlockBackend, BlockImportNotification, BlockchainEvents, Finalizer, UsageProvider,
 };
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy};
+use sc_utils::notification::{NotificationSender, NotificationStream, TracingKeyStr};
 use schnellru::{ByLength, LruMap};
 use sp_blockchain::Error as ClientError;
 use sp_consensus::{BlockOrigin, BlockStatus};
-use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
 
 use cumulus_client_pov_recovery::{RecoveryKind, RecoveryRequest};
 use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult};
@@ -35,6 +36,45 @@ use std::sync::Arc;
 
 const LOG_TARGET: &str = "cumulus-consensus";
 const FINALIZATION_CACHE_SIZE: u32 = 40;
+// Number of relay chain headers kept around to find the ancestors of the relay chain best block.
+const RELAY_HEADERS_CACHE_SIZE: u32 = 256;
+
+/// Maximum depth supported by [`follow_included_depth`].
+///
+/// Deeper ancestors of the relay chain best block are not kept around.
+pub const MAX_INCLUDED_DEPTH: u32 = RELAY_HEADERS_CACHE_SIZE;
+
+/// A parachain block that is included in the relay chain at least `depth` blocks deep.
+///
+/// This is a provisional finality: the block is not finalized and it may still be reverted
+/// by a relay chain reorganization.
+#[derive(Debug, Clone)]
+pub struct ProvisionalFinalityNotification<Block: BlockT> {
+	/// Hash of the parachain block.
+	pub hash: Block::Hash,
+	/// Number of the parachain block.
+	pub number: NumberFor<Block>,
+	/// Relay chain block at which the parachain block was included.
+	pub relay_parent: PHash,
+	/// Number of relay chain blocks built on top of `relay_parent`.
+	pub depth: u32,
+}
+
+/// Provides tracing key for the provisional finality notification stream.
+#[derive(Clone)]
+pub struct ProvisionalFinalityTracingKey;
+
+impl TracingKeyStr for ProvisionalFinalityTracingKey {
+	const TRACING_KEY: &'static str = "mpsc_provisional_finality_notification_stream";
+}
+
+/// Sender side of the provisional finality notification stream.
+pub type ProvisionalFinalitySender<Block> =
+	NotificationSender<ProvisionalFinalityNotification<Block>>;
+
+/// Receiver side of the provisional finality notification stream.
+pub type ProvisionalFinalityStream<Block> =
+	NotificationStream<ProvisionalFinalityNotification<Block>, ProvisionalFinalityTracingKey>;
 
 fn handle_new_finalized_head<P, Block, B>(
 	parachain: &Arc<P>,
@@ -168,6 +208,210 @@ where
 	}
 }
 
+/// Follow the relay chain best block and notify the parachain blocks included `depth` blocks deep.
+///
+/// For every new best block of the relay chain, the parachain head included at its ancestor
+/// `depth` blocks back is sent to `sender`, if it is higher than the last one sent. A notification
+/// implies that all the ancestors of the notified block are included as deep as well.
+///
+/// Unlike [`follow_finalized_head`], this doesn't depend on the relay chain finality and doesn't
+/// finalize anything. It is meant for services that can tolerate the (small) risk of a relay chain
+/// reorganization deeper than `depth`, when relay chain finality stalls.
+///
+/// Nothing is notified if `depth` is greater than [`MAX_INCLUDED_DEPTH`].
+pub async fn follow_included_depth<Block, R>(
+	para_id: ParaId,
+	relay_chain: R,
+	depth: u32,
+	sender: ProvisionalFinalitySender<Block>,
+) where
+	Block: BlockT,
+	R: RelayChainInterface + Clone,
+{
+	if depth > MAX_INCLUDED_DEPTH {
+		tracing::error!(
+			target: LOG_TARGET,
+			depth,
+			max_depth = MAX_INCLUDED_DEPTH,
+			"Included depth too high, not following included depth.",
+		);
+		return
+	}
+
+	let (imported_heads, new_best_heads) = match (
+		relay_chain.import_notification_stream().await,
+		relay_chain.new_best_notification_stream().await,
+	) {
+		(Ok(imported_heads), Ok(new_best_heads)) => (imported_heads.fuse(), new_best_heads.fuse()),
+		(Err(err), _) | (_, Err(err)) => {
+			tracing::error!(target: LOG_TARGET, error = ?err, "Unable to retrieve relay chain heads streams.");
+			return
+		},
+	};
+
+	pin_mut!(imported_heads);
+	pin_mut!(new_best_heads);
+
+	// Map between relay chain block hashes and their parent hash.
+	let mut parents = LruMap::new(ByLength::new(RELAY_HEADERS_CACHE_SIZE));
+	let mut last_number = None;
+
+	loop {
+		select! {
+			h = imported_heads.next() => {
+				match h {
+					Some(h) => {
+						parents.insert(h.hash(), *h.parent_hash());
+					},
+					None => {
+						tracing::debug!(target: LOG_TARGET, "Stopping following included depth.");
+						return
+					}
+				}
+			},
+			h = new_best_heads.next() => {
+				match h {
+					Some(h) => {
+						parents.insert(h.hash(), *h.parent_hash());
+						let mut relay_parent = h.hash();
+						let found = (0..depth).all(|_| {
+							parents.get(&relay_parent).map(|parent| relay_parent = *parent).is_some()
+						});
+						if !found {
+							tracing::trace!(
+								target: LOG_TARGET,
+								relay_block = ?h.hash(),
+								"Unknown relay chain ancestor, skipping provisional finality.",
+							);
+							continue
+						}
+
+						handle_included_at_depth::<Block>(
+							&relay_chain,
+							para_id,
+							relay_parent,
+							depth,
+							&mut last_number,
+							&sender,
+						).await;
+					},
+					None => {
+						tracing::debug!(target: LOG_TARGET, "Stopping following included depth.");
+						return
+					}
+				}
+			},
+		}
+	}
+}
+
+/// Notify the parachain head included at `relay_parent`, if it is higher than `last_number`.
+async fn handle_included_at_depth<Block: BlockT>(
+	relay_chain: &impl RelayChainInterface,
+	para_id: ParaId,
+	relay_parent: PHash,
+	depth: u32,
+	last_number: &mut Option<NumberFor<Block>>,
+	sender: &ProvisionalFinalitySender<Block>,
+) {
+	let head = match parachain_head_at(relay_chain, relay_parent, para_id).await {
+		Ok(Some(head)) => head,
+		Ok(None) => return,
+		Err(err) => {
+			tracing::debug!(
+				target: LOG_TARGET,
+				error = ?err,
+				?relay_parent,
+				"Could not fetch the parachain head included at relay chain block.",
+			);
+			return
+		},
+	};
+
+	let header = match Block::Header::decode(&mut &head[..]) {
+		Ok(header) => header,
+		Err(err) => {
+			tracing::debug!(
+				target: LOG_TARGET,
+				error = ?err,
+				"Could not decode parachain header while following included depth.",
+			);
+			return
+		},
+	};
+
+	let number = *header.number();
+	if last_number.map_or(false, |last_number| number <= last_number) {
+		return
+	}
+	*last_number = Some(number);
+
+	let notification =
+		ProvisionalFinalityNotification { hash: header.hash(), number, relay_parent, depth };
+	tracing::debug!(
+		target: LOG_TARGET,
+		block_hash = ?notification.hash,
+		block_number = %number,
+		?relay_parent,
+		"Parachain block included {depth} relay chain blocks deep.",
+	);
+	let _ = sender.notify(|| Ok::<_, ()>(notification));
+}
+
+/// Follow the parachain candidates backed on the relay chain.
+///
+/// For every imported relay chain block, the parachain block pending availability at it (backed
+/// but not yet included) is passed to `on_backed`. This is meant to feed
+/// [`ParachainBlockImport::block_backed`](crate::ParachainBlockImport::block_backed).
+pub async fn follow_backed_heads<Block, R>(
+	para_id: ParaId,
+	relay_chain: R,
+	on_backed: impl Fn(Block::Hash) + Send,
+) where
+	Block: BlockT,
+	R: RelayChainInterface,
+{
+	let mut imported_blocks = match relay_chain.import_notification_stream().await {
+		Ok(imported_blocks) => imported_blocks,
+		Err(err) => {
+			tracing::error!(
+				target: LOG_TARGET,
+				error = ?err,
+				"Unable to retrieve relay chain import stream.",
+			);
+			return
+		},
+	};
+
+	while let Some(relay_header) = imported_blocks.next().await {
+		let relay_parent = relay_header.hash();
+		let head = match relay_chain.candidate_pending_availability(relay_parent, para_id).await {
+			Ok(Some(candidate)) => candidate.commitments.head_data.0,
+			Ok(None) => continue,
+			Err(err) => {
+				tracing::debug!(
+					target: LOG_TARGET,
+					error = ?err,
+					?relay_parent,
+					"Could not fetch the parachain candidate pending availability.",
+				);
+				continue
+			},
+		};
+
+		match Block::Header::decode(&mut &head[..]) {
+			Ok(header) => on_backed(header.hash()),
+			Err(err) => tracing::debug!(
+				target: LOG_TARGET,
+				error = ?err,
+				"Could not decode backed parachain header.",
+			),
+		}
+	}
+
+	tracing::debug!(target: LOG_TARGET, "Stopping following backed heads.");
+}
+
 /// Run the parachain consensus.
 ///
 /// This will follow the given `relay_chain` to act as consensus for the parachain that corresponds
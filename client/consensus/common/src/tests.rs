@@ -110,17 +110,9 @@ impl RelayChainInterface for Relaychain {
 		_: ParaId,
 		_: OccupiedCoreAssumption,
 	) -> RelayChainResult<Option<PersistedValidationData>> {
-		Ok(Some(PersistedValidationData {
-			parent_head: self
-				.inner
-				.lock()
-				.unwrap()
-				.relay_chain_hash_to_header
-				.get(&hash)
-				.unwrap()
-				.encode()
-				.into(),
-			..Default::default()
+		// Relay chain blocks that were never notified (like the genesis) have no parachain head.
+		Ok(self.inner.lock().unwrap().relay_chain_hash_to_header.get(&hash).map(|header| {
+			PersistedValidationData { parent_head: header.encode().into(), ..Default::default() }
 		}))
 	}
 
@@ -139,7 +131,8 @@ impl RelayChainInterface for Relaychain {
 	async fn import_notification_stream(
 		&self,
 	) -> RelayChainResult<Pin<Box<dyn Stream<Item = PHeader> + Send>>> {
-		unimplemented!("Not needed for test")
+		// Imported blocks are not needed for test, the new best blocks are enough.
+		Ok(futures::stream::pending().boxed())
 	}
 
 	async fn finality_notification_stream(
@@ -794,3 +787,294 @@ fn restore_limit_monitor() {
 	}));
 	assert_eq!(*monitor.freshness.get(&block13.header.hash()).unwrap(), monitor.import_counter - 1);
 }
+
+#[test]
+fn keep_own_blocks_on_level_overflow() {
+	// Here we are using the timestamp value to generate blocks with different hashes.
+	const LEVEL_LIMIT: usize = 3;
+	const TIMESTAMP_MULTIPLIER: u64 = 60000;
+
+	let backend = Arc::new(Backend::new_test(1000, 3));
+	let client = Arc::new(TestClientBuilder::with_backend(backend.clone()).build());
+	let registry = substrate_prometheus_endpoint::Registry::new();
+	let mut para_import = ParachainBlockImport::new_with_pruning(
+		client.clone(),
+		backend.clone(),
+		ForkPruning { level_limit: LevelLimit::Some(LEVEL_LIMIT), policy: PruningPolicy::KeepOwn },
+		Some(&registry),
+	);
+
+	let block0 = build_and_import_block_ext(
+		&*client,
+		BlockOrigin::NetworkInitialSync,
+		true,
+		&mut para_import,
+		None,
+		None,
+	);
+	let id0 = block0.header.hash();
+
+	let blocks1 = (0..LEVEL_LIMIT)
+		.into_iter()
+		.map(|i| {
+			build_and_import_block_ext(
+				&*client,
+				if i == 0 { BlockOrigin::Own } else { BlockOrigin::NetworkBroadcast },
+				false,
+				&mut para_import,
+				Some(id0),
+				Some(i as u64 * TIMESTAMP_MULTIPLIER),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	// Initial scenario (B10 authored by us)
+	//
+	//   B0 --+-- B10
+	//        +-- B11
+	//        +-- B12
+
+	let block13 = build_and_import_block_ext(
+		&*client,
+		BlockOrigin::NetworkBroadcast,
+		false,
+		&mut para_import,
+		Some(id0),
+		Some(LEVEL_LIMIT as u64 * TIMESTAMP_MULTIPLIER),
+	);
+
+	// Expected scenario
+	//
+	//   B0 --+-- B10
+	//        +-- B12
+	//        +--(B13)              <-- B11 has been replaced, B10 is kept
+
+	let leaves = backend.blockchain().leaves().unwrap();
+	let expected = vec![blocks1[0].header.hash(), blocks1[2].header.hash(), block13.header.hash()];
+	assert_eq!(leaves, expected);
+
+	let pruned = registry
+		.gather()
+		.into_iter()
+		.find(|family| family.get_name() == "parachain_level_monitor_pruned_blocks_total")
+		.map(|family| family.get_metric()[0].get_counter().get_value())
+		.unwrap();
+	assert_eq!(pruned, 1.0);
+}
+
+#[test]
+fn keep_backed_blocks_on_level_overflow() {
+	// Here we are using the timestamp value to generate blocks with different hashes.
+	const LEVEL_LIMIT: usize = 3;
+	const TIMESTAMP_MULTIPLIER: u64 = 60000;
+
+	let backend = Arc::new(Backend::new_test(1000, 3));
+	let client = Arc::new(TestClientBuilder::with_backend(backend.clone()).build());
+	let mut para_import = ParachainBlockImport::new_with_pruning(
+		client.clone(),
+		backend.clone(),
+		ForkPruning {
+			level_limit: LevelLimit::Some(LEVEL_LIMIT),
+			policy: PruningPolicy::KeepBacked,
+		},
+		None,
+	);
+
+	let block0 = build_and_import_block_ext(
+		&*client,
+		BlockOrigin::NetworkInitialSync,
+		true,
+		&mut para_import,
+		None,
+		None,
+	);
+	let id0 = block0.header.hash();
+
+	// B11 is imported as best, B10 and B12 are not.
+	let blocks1 = (0..LEVEL_LIMIT)
+		.into_iter()
+		.map(|i| {
+			build_and_import_block_ext(
+				&*client,
+				if i == 1 { BlockOrigin::NetworkInitialSync } else { BlockOrigin::Own },
+				i == 1,
+				&mut para_import,
+				Some(id0),
+				Some(i as u64 * TIMESTAMP_MULTIPLIER),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	// B10 is backed by the relay chain.
+	para_import.block_backed(blocks1[0].header.hash());
+
+	// Initial scenario (with B11 as best and B10 backed)
+	//
+	//   B0 --+-- B10
+	//        +-- B11
+	//        +-- B12
+
+	let best = client.usage_info().chain.best_hash;
+	assert_eq!(best, blocks1[1].header.hash());
+
+	let block13 = build_and_import_block_ext(
+		&*client,
+		BlockOrigin::Own,
+		false,
+		&mut para_import,
+		Some(id0),
+		Some(LEVEL_LIMIT as u64 * TIMESTAMP_MULTIPLIER),
+	);
+
+	// Expected scenario
+	//
+	//   B0 --+-- B10
+	//        +-- B11
+	//        +--(B13)              <-- B12 has been replaced, B10 is kept
+
+	let leaves = backend.blockchain().leaves().unwrap();
+	let expected = vec![blocks1[0].header.hash(), blocks1[1].header.hash(), block13.header.hash()];
+	assert_eq!(leaves, expected);
+
+	let monitor = para_import.monitor.unwrap();
+	let monitor = monitor.shared_data();
+	assert!(monitor.protected.contains(&blocks1[0].header.hash()));
+	assert!(monitor.protected.contains(&id0));
+}
+
+#[test]
+fn prune_stale_forks() {
+	const MAX_AGE: u32 = 3;
+
+	let backend = Arc::new(Backend::new_test(1000, 3));
+	let client = Arc::new(TestClientBuilder::with_backend(backend.clone()).build());
+	let mut para_import = ParachainBlockImport::new_with_pruning(
+		client.clone(),
+		backend.clone(),
+		ForkPruning {
+			level_limit: LevelLimit::Some(usize::MAX),
+			policy: PruningPolicy::MaxAge(MAX_AGE),
+		},
+		None,
+	);
+
+	let block0 = build_and_import_block_ext(
+		&*client,
+		BlockOrigin::NetworkInitialSync,
+		true,
+		&mut para_import,
+		None,
+		None,
+	);
+	let id0 = block0.header.hash();
+
+	let block10 = build_and_import_block_ext(
+		&*client,
+		BlockOrigin::Own,
+		false,
+		&mut para_import,
+		Some(id0),
+		Some(60000),
+	);
+
+	// Extend the best chain, B10 stays behind.
+	let mut best = id0;
+	let best_chain = (0..3)
+		.into_iter()
+		.map(|_| {
+			let block = build_and_import_block_ext(
+				&*client,
+				BlockOrigin::NetworkInitialSync,
+				true,
+				&mut para_import,
+				Some(best),
+				None,
+			);
+			best = block.header.hash();
+			block
+		})
+		.collect::<Vec<_>>();
+
+	// Scenario before B10 gets too old
+	// Import order (freshness): B0, B10, B11, B20, B30
+	//
+	//   B0 --+-- B10
+	//        +-- B11 --- B20 --- B30
+
+	let leaves = backend.blockchain().leaves().unwrap();
+	let expected = vec![best_chain[2].header.hash(), block10.header.hash()];
+	assert_eq!(leaves, expected);
+
+	let block40 = build_and_import_block_ext(
+		&*client,
+		BlockOrigin::NetworkInitialSync,
+		true,
+		&mut para_import,
+		Some(best),
+		None,
+	);
+
+	// Expected scenario
+	//
+	//   B0 --- B11 --- B20 --- B30 --- B40     <-- B10 has been removed
+
+	let leaves = backend.blockchain().leaves().unwrap();
+	assert_eq!(leaves, vec![block40.header.hash()]);
+
+	let monitor = para_import.monitor.unwrap();
+	let monitor = monitor.shared_data();
+	let fresh_leaves = monitor.fresh_leaves.iter().map(|(_, hash)| *hash).collect::<Vec<_>>();
+	assert_eq!(fresh_leaves, vec![block40.header.hash()]);
+}
+
+#[test]
+fn follow_included_depth_works() {
+	sp_tracing::try_init_simple();
+
+	const DEPTH: u32 = 1;
+
+	let client = Arc::new(TestClientBuilder::default().build());
+
+	let blocks = (0..3).map(|_| build_and_import_block(client.clone(), true)).collect::<Vec<_>>();
+	let relay_chain = Relaychain::new();
+	let new_best_heads_sender = relay_chain.inner.lock().unwrap().new_best_heads_sender.clone();
+
+	let (sender, stream) = ProvisionalFinalityStream::<Block>::channel();
+	let mut notifications = stream.subscribe(100);
+
+	let follow = follow_included_depth(100.into(), relay_chain, DEPTH, sender);
+
+	let work = async move {
+		// The parachain blocks are directly used as relay chain blocks including themselves.
+		for block in &blocks {
+			new_best_heads_sender.unbounded_send(block.header().clone()).unwrap();
+		}
+
+		// The relay parent of the first block is the genesis, which includes no parachain head,
+		// thus nothing is notified for it.
+		for block in &blocks[..2] {
+			let notification = notifications.next().await.unwrap();
+			assert_eq!(notification.hash, block.hash());
+			assert_eq!(notification.number, *block.header().number());
+			assert_eq!(notification.depth, DEPTH);
+		}
+	};
+
+	block_on(async move {
+		futures::pin_mut!(follow);
+		futures::pin_mut!(work);
+
+		select! {
+			r = follow.fuse() => panic!("Following should not end: {:?}", r),
+			_ = work.fuse() => {},
+		}
+	});
+}
+
+#[test]
+fn follow_included_depth_rejects_too_deep() {
+	let (sender, _stream) = ProvisionalFinalityStream::<Block>::channel();
+
+	// Returns right away instead of following the relay chain forever.
+	block_on(follow_included_depth(100.into(), Relaychain::new(), MAX_INCLUDED_DEPTH + 1, sender));
+}
@@ -0,0 +1,261 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of the author of announced blocks and equivocation detection.
+//!
+//! Parachains with a known set of block authors (e.g. AuRa based ones) can provide a
+//! [`BlockAuthorVerifier`] to the [`BlockAnnounceValidator`](crate::BlockAnnounceValidator).
+//! Announced blocks that are not sealed by the expected author are rejected, while authors
+//! announcing different blocks for the same slot are reported as equivocating.
+
+use parking_lot::Mutex;
+use schnellru::{ByLength, LruMap};
+use sp_consensus::block_validation::Validation;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use substrate_prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+
+use std::sync::Arc;
+
+use crate::LOG_TARGET;
+
+// Number of `(author, slot)` pairs for which the first announced header is remembered.
+const SEEN_SLOTS_CACHE_SIZE: u32 = 1024;
+// Number of equivocating authors that are remembered.
+const OFFENDERS_CACHE_SIZE: u32 = 256;
+/// Number of equivocations after which the conflicting blocks of the same author are rejected.
+pub const REPEAT_OFFENDER_THRESHOLD: u32 = 2;
+
+/// The author of a block.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockAuthor {
+	/// Slot at which the block was authored.
+	pub slot: u64,
+	/// Encoded public key of the author.
+	pub author: Vec<u8>,
+}
+
+/// Result of [`BlockAuthorVerifier::verify_author`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorVerification {
+	/// The block is sealed by the expected author.
+	Valid(BlockAuthor),
+	/// The block is not sealed by the expected author.
+	Invalid(String),
+	/// The author can not be verified, e.g. because the parent block is not known yet.
+	Unknown,
+}
+
+/// Verifies the author of announced blocks.
+pub trait BlockAuthorVerifier<Block: BlockT>: Send + Sync {
+	/// Verify that the block with the given `header` is sealed by the expected author.
+	fn verify_author(&self, header: &Block::Header) -> AuthorVerification;
+}
+
+/// Two different blocks announced by the same author for the same slot.
+#[derive(Debug, Clone)]
+pub struct Equivocation<Block: BlockT> {
+	/// The equivocating author.
+	pub author: BlockAuthor,
+	/// The first announced header.
+	pub first_header: Block::Header,
+	/// The second announced header.
+	pub second_header: Block::Header,
+	/// Number of equivocations of the author seen so far, including this one.
+	pub offences: u32,
+}
+
+/// Reports equivocations, e.g. by submitting a report to the runtime.
+pub trait EquivocationReporter<Block: BlockT>: Send + Sync {
+	/// Report the given equivocation.
+	fn report_equivocation(&self, equivocation: Equivocation<Block>);
+}
+
+/// Configuration of the block author verification.
+pub struct AuthorVerificationConfig<Block: BlockT> {
+	/// Verifier of the author of announced blocks.
+	pub verifier: Arc<dyn BlockAuthorVerifier<Block>>,
+	/// Reporter of the detected equivocations.
+	pub reporter: Option<Arc<dyn EquivocationReporter<Block>>>,
+}
+
+struct Metrics {
+	invalid_authors: Counter<U64>,
+	equivocations: Counter<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			invalid_authors: register(
+				Counter::new(
+					"parachain_block_announce_invalid_author_total",
+					"Number of announced blocks not sealed by the expected author",
+				)?,
+				registry,
+			)?,
+			equivocations: register(
+				Counter::new(
+					"parachain_block_announce_equivocations_total",
+					"Number of equivocations detected in block announcements",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+struct Tracker<Block: BlockT> {
+	// First announced header for each author and slot.
+	seen: LruMap<BlockAuthor, Block::Header>,
+	// Headers already reported as equivocations, as the same block is announced by many peers.
+	reported: LruMap<Block::Hash, ()>,
+	// Number of equivocations for each author.
+	offences: LruMap<Vec<u8>, u32>,
+}
+
+/// Verifies the author of announced blocks and keeps track of equivocations.
+pub(crate) struct AuthorCheck<Block: BlockT> {
+	config: AuthorVerificationConfig<Block>,
+	tracker: Mutex<Tracker<Block>>,
+	metrics: Option<Metrics>,
+}
+
+impl<Block: BlockT> AuthorCheck<Block> {
+	/// Create a new instance.
+	pub(crate) fn new(
+		config: AuthorVerificationConfig<Block>,
+		registry: Option<&Registry>,
+	) -> Self {
+		let metrics = registry.and_then(|registry| {
+			Metrics::register(registry)
+				.map_err(|err| {
+					tracing::warn!(
+						target: LOG_TARGET,
+						error = ?err,
+						"Failed to register block announce metrics",
+					)
+				})
+				.ok()
+		});
+
+		Self {
+			config,
+			tracker: Mutex::new(Tracker {
+				seen: LruMap::new(ByLength::new(SEEN_SLOTS_CACHE_SIZE)),
+				reported: LruMap::new(ByLength::new(SEEN_SLOTS_CACHE_SIZE)),
+				offences: LruMap::new(ByLength::new(OFFENDERS_CACHE_SIZE)),
+			}),
+			metrics,
+		}
+	}
+
+	/// Check the author of the announced block.
+	///
+	/// Returns `Some(_)` if the announcement should be rejected: the block is not sealed by the
+	/// expected author, or conflicts with the first block of its slot while its author is a
+	/// repeat offender. The first block of each slot is never rejected, as honest peers relay it.
+	pub(crate) fn check(&self, header: &Block::Header) -> Option<Validation> {
+		let author = match self.config.verifier.verify_author(header) {
+			AuthorVerification::Valid(author) => author,
+			AuthorVerification::Unknown => return None,
+			AuthorVerification::Invalid(reason) => {
+				tracing::debug!(
+					target: LOG_TARGET,
+					block_hash = ?header.hash(),
+					%reason,
+					"Announced block is not sealed by the expected author.",
+				);
+				if let Some(metrics) = &self.metrics {
+					metrics.invalid_authors.inc();
+				}
+				return Some(Validation::Failure { disconnect: true })
+			},
+		};
+
+		let mut guard = self.tracker.lock();
+		let tracker = &mut *guard;
+		let hash = header.hash();
+		let first_header = match tracker.seen.get(&author) {
+			Some(first_header) if first_header.hash() == hash => return None,
+			Some(_) if tracker.reported.peek(&hash).is_some() => None,
+			Some(first_header) => {
+				let first_header = first_header.clone();
+				tracker.reported.insert(hash, ());
+				Some(first_header)
+			},
+			None => {
+				tracker.seen.insert(author.clone(), header.clone());
+				return None
+			},
+		};
+
+		// The block conflicts with the first block of its slot.
+		let offences = match first_header {
+			Some(first_header) => {
+				let offences = tracker
+					.offences
+					.get_or_insert(author.author.clone(), || 0)
+					.map(|offences| {
+						*offences += 1;
+						*offences
+					})
+					.unwrap_or(1);
+				drop(guard);
+				self.report(Equivocation {
+					author: author.clone(),
+					first_header,
+					second_header: header.clone(),
+					offences,
+				});
+				offences
+			},
+			None => tracker.offences.peek(&author.author).copied().unwrap_or_default(),
+		};
+
+		if offences >= REPEAT_OFFENDER_THRESHOLD {
+			tracing::debug!(
+				target: LOG_TARGET,
+				block_hash = ?hash,
+				author = ?author.author,
+				%offences,
+				"Rejecting conflicting block announced by repeatedly equivocating author.",
+			);
+			return Some(Validation::Failure { disconnect: false })
+		}
+
+		None
+	}
+
+	fn report(&self, equivocation: Equivocation<Block>) {
+		tracing::warn!(
+			target: LOG_TARGET,
+			author = ?equivocation.author.author,
+			slot = %equivocation.author.slot,
+			first = ?equivocation.first_header.hash(),
+			second = ?equivocation.second_header.hash(),
+			offences = %equivocation.offences,
+			"Detected equivocation in block announcements.",
+		);
+
+		if let Some(metrics) = &self.metrics {
+			metrics.equivocations.inc();
+		}
+
+		if let Some(reporter) = &self.config.reporter {
+			reporter.report_equivocation(equivocation);
+		}
+	}
+}
@@ -19,12 +19,15 @@
 //! Provides a custom block announcement implementation for parachains
 //! that use the relay chain provided consensus. See [`BlockAnnounceValidator`]
 //! and [`WaitToAnnounce`] for more information about this implementation.
+//!
+//! The [`author`] module provides the verification of the author of announced blocks.
 
 use sp_consensus::block_validation::{
 	BlockAnnounceValidator as BlockAnnounceValidatorT, Validation,
 };
 use sp_core::traits::SpawnNamed;
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use substrate_prometheus_endpoint::Registry;
 
 use cumulus_relay_chain_interface::RelayChainInterface;
 use infrablockspace_node_primitives::{CollationSecondedSignal, Statement};
@@ -38,9 +41,16 @@ use codec::{Decode, DecodeAll, Encode};
 use futures::{channel::oneshot, future::FutureExt, Future};
 use std::{convert::TryFrom, fmt, marker::PhantomData, pin::Pin, sync::Arc};
 
+pub mod author;
 #[cfg(test)]
 mod tests;
 
+use author::AuthorCheck;
+pub use author::{
+	AuthorVerification, AuthorVerificationConfig, BlockAuthor, BlockAuthorVerifier, Equivocation,
+	EquivocationReporter,
+};
+
 const LOG_TARGET: &str = "sync::cumulus";
 
 type BoxedError = Box<dyn std::error::Error + Send>;
@@ -212,20 +222,53 @@ impl TryFrom<&'_ CollationSecondedSignal> for BlockAnnounceData {
 /// chain. If it is at the tip, it is required to provide a justification or otherwise we reject
 /// it. However, if the announcement is for a block below the tip the announcement is accepted
 /// as it probably comes from a node that is currently syncing the chain.
-#[derive(Clone)]
-pub struct BlockAnnounceValidator<Block, RCInterface> {
+///
+/// If created with [`BlockAnnounceValidator::new_with_author_verification`], the author of every
+/// announced block is verified first. Announcements of blocks not sealed by the expected author
+/// are rejected, equivocations are reported and announcements of blocks of authors that
+/// equivocated repeatedly are rejected, which lowers the reputation of the announcing peer.
+pub struct BlockAnnounceValidator<Block: BlockT, RCInterface> {
 	phantom: PhantomData<Block>,
 	relay_chain_interface: RCInterface,
 	para_id: ParaId,
+	author_check: Option<Arc<AuthorCheck<Block>>>,
+}
+
+impl<Block: BlockT, RCInterface: Clone> Clone for BlockAnnounceValidator<Block, RCInterface> {
+	fn clone(&self) -> Self {
+		Self {
+			phantom: PhantomData,
+			relay_chain_interface: self.relay_chain_interface.clone(),
+			para_id: self.para_id,
+			author_check: self.author_check.clone(),
+		}
+	}
 }
 
-impl<Block, RCInterface> BlockAnnounceValidator<Block, RCInterface>
+impl<Block: BlockT, RCInterface> BlockAnnounceValidator<Block, RCInterface>
 where
 	RCInterface: Clone,
 {
 	/// Create a new [`BlockAnnounceValidator`].
 	pub fn new(relay_chain_interface: RCInterface, para_id: ParaId) -> Self {
-		Self { phantom: Default::default(), relay_chain_interface, para_id }
+		Self { phantom: Default::default(), relay_chain_interface, para_id, author_check: None }
+	}
+
+	/// Create a new [`BlockAnnounceValidator`] that also verifies the author of announced blocks.
+	///
+	/// If a `registry` is given, the author verification metrics are registered there.
+	pub fn new_with_author_verification(
+		relay_chain_interface: RCInterface,
+		para_id: ParaId,
+		config: AuthorVerificationConfig<Block>,
+		registry: Option<&Registry>,
+	) -> Self {
+		Self {
+			phantom: Default::default(),
+			relay_chain_interface,
+			para_id,
+			author_check: Some(Arc::new(AuthorCheck::new(config, registry))),
+		}
 	}
 }
 
@@ -342,6 +385,12 @@ where
 				return Ok(Validation::Success { is_new_best: false })
 			}
 
+			if let Some(validation) =
+				block_announce_validator.author_check.as_ref().and_then(|c| c.check(&header))
+			{
+				return Ok(validation)
+			}
+
 			if data.is_empty() {
 				return block_announce_validator.handle_empty_block_announce_data(header).await
 			}
@@ -568,3 +568,105 @@ struct ApiData {
 	validators: Vec<ValidatorId>,
 	has_pending_availability: bool,
 }
+
+/// Verifier attributing all blocks to the same author, at a slot equal to the block number.
+///
+/// Blocks with an `extrinsics_root` equal to [`INVALID_AUTHOR`] are not sealed by the author.
+struct DummyAuthorVerifier;
+
+const INVALID_AUTHOR: H256 = H256::repeat_byte(0xff);
+
+impl BlockAuthorVerifier<Block> for DummyAuthorVerifier {
+	fn verify_author(&self, header: &Header) -> AuthorVerification {
+		if header.extrinsics_root == INVALID_AUTHOR {
+			AuthorVerification::Invalid("Invalid seal".into())
+		} else {
+			AuthorVerification::Valid(BlockAuthor { slot: header.number as u64, author: vec![1] })
+		}
+	}
+}
+
+#[derive(Default)]
+struct DummyEquivocationReporter {
+	equivocations: Mutex<Vec<Equivocation<Block>>>,
+}
+
+impl EquivocationReporter<Block> for DummyEquivocationReporter {
+	fn report_equivocation(&self, equivocation: Equivocation<Block>) {
+		self.equivocations.lock().push(equivocation);
+	}
+}
+
+fn make_validator_with_author_verification(
+) -> (BlockAnnounceValidator<Block, Arc<DummyRelayChainInterface>>, Arc<DummyEquivocationReporter>)
+{
+	let relay_chain_interface = Arc::new(DummyRelayChainInterface::new());
+	let reporter = Arc::new(DummyEquivocationReporter::default());
+	let config = AuthorVerificationConfig {
+		verifier: Arc::new(DummyAuthorVerifier),
+		reporter: Some(reporter.clone()),
+	};
+
+	(
+		BlockAnnounceValidator::new_with_author_verification(
+			relay_chain_interface,
+			ParaId::from(56),
+			config,
+			None,
+		),
+		reporter,
+	)
+}
+
+#[test]
+fn invalid_if_not_sealed_by_expected_author() {
+	let mut validator = make_validator_with_author_verification().0;
+	let header = Header { number: 0, extrinsics_root: INVALID_AUTHOR, ..default_header() };
+	let res = block_on(validator.validate(&header, &[]));
+
+	assert_eq!(
+		res.unwrap(),
+		Validation::Failure { disconnect: true },
+		"validation fails if the block is not sealed by the expected author",
+	);
+}
+
+#[test]
+fn equivocations_are_reported_and_conflicting_blocks_of_repeat_offenders_rejected() {
+	let (mut validator, reporter) = make_validator_with_author_verification();
+	let headers = (0..3)
+		.map(|_| Header { number: 0, state_root: Hash::random(), ..default_header() })
+		.collect::<Vec<_>>();
+
+	let res = block_on(validator.validate(&headers[0], &[]));
+	assert_eq!(res.unwrap(), Validation::Success { is_new_best: false });
+	assert!(reporter.equivocations.lock().is_empty());
+
+	// Same slot, different block: the first equivocation is only reported.
+	let res = block_on(validator.validate(&headers[1], &[]));
+	assert_eq!(res.unwrap(), Validation::Success { is_new_best: false });
+	// Announcing the same block again is not a new equivocation.
+	let res = block_on(validator.validate(&headers[1], &[]));
+	assert_eq!(res.unwrap(), Validation::Success { is_new_best: false });
+	{
+		let equivocations = reporter.equivocations.lock();
+		assert_eq!(equivocations.len(), 1);
+		assert_eq!(equivocations[0].first_header, headers[0]);
+		assert_eq!(equivocations[0].second_header, headers[1]);
+		assert_eq!(equivocations[0].offences, 1);
+	}
+
+	// The conflicting blocks of a repeat offender are rejected.
+	let res = block_on(validator.validate(&headers[2], &[]));
+	assert_eq!(res.unwrap(), Validation::Failure { disconnect: false });
+	assert_eq!(reporter.equivocations.lock().len(), 2);
+	let res = block_on(validator.validate(&headers[1], &[]));
+	assert_eq!(res.unwrap(), Validation::Failure { disconnect: false });
+
+	// Its first block of the slot and its blocks of other slots are not.
+	let res = block_on(validator.validate(&headers[0], &[]));
+	assert_eq!(res.unwrap(), Validation::Success { is_new_best: false });
+	let next = Header { number: 1, state_root: Hash::random(), ..default_header() };
+	let res = block_on(validator.validate(&next, &[]));
+	assert_eq!(res.unwrap(), Validation::Success { is_new_best: false });
+}
@@ -35,7 +35,10 @@
 //! 2. If between starting and firing the timer the block is imported, we skip the recovery of the
 //!    PoV.
 //!
-//! 3. If the timer fired we recover the PoV using the relay chain PoV recovery protocol.
+//! 3. If the timer fired we recover the PoV. When a [`BlockDataRequester`] is configured, we first
+//!    ask parachain peers for the block (see [`peer_recovery`]) and verify the response against the
+//!    para head of the candidate receipt. If that fails, we fall back to the relay chain PoV
+//!    recovery protocol.
 //!
 //! 4a. After it is recovered, we restore the block and import it.
 //!
@@ -54,13 +57,13 @@ use infrablockspace_node_primitives::{AvailableData, POV_BOMB_LIMIT};
 use infrablockspace_node_subsystem::messages::AvailabilityRecoveryMessage;
 use infrablockspace_overseer::Handle as OverseerHandle;
 use infrablockspace_primitives::{
-	CandidateReceipt, CommittedCandidateReceipt, Id as ParaId, SessionIndex,
+	CandidateReceipt, CommittedCandidateReceipt, HeadData, Id as ParaId, SessionIndex,
 };
 
 use cumulus_primitives_core::ParachainBlockData;
 use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult};
 
-use codec::Decode;
+use codec::{Decode, Encode};
 use futures::{
 	channel::mpsc::Receiver, select, stream::FuturesUnordered, Future, FutureExt, Stream, StreamExt,
 };
@@ -77,6 +80,14 @@ use std::{
 mod active_candidate_recovery;
 use active_candidate_recovery::ActiveCandidateRecovery;
 
+pub mod peer_recovery;
+use peer_recovery::ActivePeerRecovery;
+pub use peer_recovery::{BlockDataRequester, NetworkBlockDataRequester};
+
+pub mod status;
+pub use status::{RecoveryControl, RecoveryController, RecoveryStatus, RecoveryStatusHandle};
+use status::{RecoveryResult, RecoveryStage};
+
 const LOG_TARGET: &str = "cumulus-pov-recovery";
 
 /// Test-friendly wrapper trait for the overseer handle.
@@ -216,6 +227,8 @@ pub struct PoVRecovery<Block: BlockT, PC, RC> {
 	/// available anymore in this map, it means that it was already imported.
 	candidate_recovery_queue: RecoveryQueue<Block>,
 	active_candidate_recovery: ActiveCandidateRecovery<Block>,
+	/// Recoveries from parachain peers, tried before `active_candidate_recovery`.
+	active_peer_recovery: Option<ActivePeerRecovery<Block>>,
 	/// Blocks that wait that the parent is imported.
 	///
 	/// Uses parent -> blocks mapping.
@@ -228,6 +241,8 @@ pub struct PoVRecovery<Block: BlockT, PC, RC> {
 	recovery_chan_rx: Receiver<RecoveryRequest<Block>>,
 	/// Blocks that we are retrying currently
 	candidates_in_retry: HashSet<Block::Hash>,
+	/// Recovery state shared with the [`RecoveryController`].
+	status: RecoveryStatusHandle<Block>,
 }
 
 impl<Block: BlockT, PC, RCInterface> PoVRecovery<Block, PC, RCInterface>
@@ -244,11 +259,14 @@ where
 		relay_chain_interface: RCInterface,
 		para_id: ParaId,
 		recovery_chan_rx: Receiver<RecoveryRequest<Block>>,
+		block_data_requester: Option<Arc<dyn BlockDataRequester<Block>>>,
+		status: RecoveryStatusHandle<Block>,
 	) -> Self {
 		Self {
 			candidates: HashMap::new(),
 			candidate_recovery_queue: RecoveryQueue::new(recovery_delay_range),
 			active_candidate_recovery: ActiveCandidateRecovery::new(recovery_handle),
+			active_peer_recovery: block_data_requester.map(ActivePeerRecovery::new),
 			waiting_for_parent: HashMap::new(),
 			parachain_client,
 			parachain_import_queue,
@@ -256,6 +274,7 @@ where
 			para_id,
 			candidates_in_retry: HashSet::new(),
 			recovery_chan_rx,
+			status,
 		}
 	}
 
@@ -312,22 +331,105 @@ where
 		});
 	}
 
+	/// Update the recovery stage of the candidate with `block_hash` in the shared status.
+	fn set_stage(&self, block_hash: Block::Hash, stage: RecoveryStage) {
+		if let Some(candidate) = self.candidates.get(&block_hash) {
+			self.status.set_stage(block_hash, candidate.block_number, stage);
+		}
+	}
+
 	/// Handle a finalized block with the given `block_number`.
 	fn handle_block_finalized(&mut self, block_number: NumberFor<Block>) {
 		self.candidates.retain(|_, pc| pc.block_number > block_number);
+		self.status.finalized(block_number);
 	}
 
 	/// Recover the candidate for the given `block_hash`.
+	///
+	/// Parachain peers are asked first, if peer recovery is enabled.
 	async fn recover_candidate(&mut self, block_hash: Block::Hash) {
+		match self.candidates.get(&block_hash) {
+			Some(candidate) if candidate.waiting_recovery => match self.active_peer_recovery {
+				Some(ref mut peer_recovery) => {
+					tracing::debug!(target: LOG_TARGET, ?block_hash, "Issuing peer recovery request");
+					peer_recovery.recover_block(block_hash, candidate.block_number);
+					self.status.set_stage(
+						block_hash,
+						candidate.block_number,
+						RecoveryStage::FromPeers,
+					);
+				},
+				None => {
+					tracing::debug!(target: LOG_TARGET, ?block_hash, "Issuing recovery request");
+					self.active_candidate_recovery.recover_candidate(block_hash, candidate).await;
+					self.status.set_stage(
+						block_hash,
+						candidate.block_number,
+						RecoveryStage::FromRelayChain,
+					);
+				},
+			},
+			_ => (),
+		}
+	}
+
+	/// Recover the candidate for the given `block_hash` from the relay chain validators.
+	async fn recover_candidate_from_relay_chain(&mut self, block_hash: Block::Hash) {
 		match self.candidates.get(&block_hash) {
 			Some(candidate) if candidate.waiting_recovery => {
 				tracing::debug!(target: LOG_TARGET, ?block_hash, "Issuing recovery request");
 				self.active_candidate_recovery.recover_candidate(block_hash, candidate).await;
+				self.status.set_stage(
+					block_hash,
+					candidate.block_number,
+					RecoveryStage::FromRelayChain,
+				);
 			},
 			_ => (),
 		}
 	}
 
+	/// Handle block data recovered from parachain peers.
+	///
+	/// The block is only accepted if it matches the para head of the candidate receipt. Otherwise
+	/// we fall back to the relay chain recovery.
+	async fn handle_peer_block_data(
+		&mut self,
+		block_hash: Block::Hash,
+		block_data: Option<ParachainBlockData<Block>>,
+	) {
+		let expected_para_head = match self.candidates.get(&block_hash) {
+			Some(candidate) if candidate.waiting_recovery => candidate.receipt.descriptor.para_head,
+			// Block got imported or finalized in the meantime.
+			_ => return,
+		};
+
+		match block_data {
+			Some(block_data)
+				if HeadData(block_data.header().encode()).hash() == expected_para_head =>
+			{
+				tracing::debug!(target: LOG_TARGET, ?block_hash, "Recovered block from peers");
+				self.import_recovered_block(block_hash, block_data.into_block()).await;
+			},
+			Some(_) => {
+				tracing::warn!(
+					target: LOG_TARGET,
+					?block_hash,
+					"Block data recovered from peers does not match the candidate para head",
+				);
+				self.recover_candidate_from_relay_chain(block_hash).await;
+			},
+			None => {
+				tracing::debug!(
+					target: LOG_TARGET,
+					?block_hash,
+					"Unable to recover block from peers, falling back to availability recovery",
+				);
+				self.recover_candidate_from_relay_chain(block_hash).await;
+			},
+		}
+	}
+
 	/// Clear `waiting_for_parent` and `waiting_recovery` for the candidate with `hash`.
 	/// Also clears children blocks waiting for this parent.
 	fn reset_candidate(&mut self, hash: Block::Hash) {
@@ -335,12 +437,24 @@ where
 
 		while let Some(delete) = blocks_to_delete.pop() {
 			if let Some(childs) = self.waiting_for_parent.remove(&delete) {
-				blocks_to_delete.extend(childs.iter().map(BlockT::hash));
+				for child in childs.iter().map(BlockT::hash) {
+					self.status.finish(
+						child,
+						RecoveryResult::Failed("Recovery of the parent failed".into()),
+					);
+					blocks_to_delete.push(child);
+				}
 			}
 		}
 		self.clear_waiting_recovery(&hash);
 	}
 
+	/// Note that the recovery of the candidate with `hash` failed for `reason` and reset it.
+	fn recovery_failed(&mut self, hash: Block::Hash, reason: &str) {
+		self.status.finish(hash, RecoveryResult::Failed(reason.into()));
+		self.reset_candidate(hash);
+	}
+
 	/// Handle a recovered candidate.
 	async fn handle_candidate_recovered(
 		&mut self,
@@ -356,6 +470,7 @@ where
 				if self.candidates_in_retry.insert(block_hash) {
 					tracing::debug!(target: LOG_TARGET, ?block_hash, "Recovery failed, retrying.");
 					self.candidate_recovery_queue.push_recovery(block_hash);
+					self.set_stage(block_hash, RecoveryStage::Queued);
 					return
 				} else {
 					tracing::warn!(
@@ -364,7 +479,7 @@ where
 						"Unable to recover block after retry.",
 					);
 					self.candidates_in_retry.remove(&block_hash);
-					self.reset_candidate(block_hash);
+					self.recovery_failed(block_hash, "PoV is not available");
 					return
 				},
 		};
@@ -377,7 +492,7 @@ where
 			Err(error) => {
 				tracing::debug!(target: LOG_TARGET, ?error, "Failed to decompress PoV");
 
-				self.reset_candidate(block_hash);
+				self.recovery_failed(block_hash, "Failed to decompress PoV");
 				return
 			},
 		};
@@ -391,13 +506,16 @@ where
 					"Failed to decode parachain block data from recovered PoV",
 				);
 
-				self.reset_candidate(block_hash);
+				self.recovery_failed(block_hash, "Failed to decode parachain block data");
 				return
 			},
 		};
 
-		let block = block_data.into_block();
+		self.import_recovered_block(block_hash, block_data.into_block()).await;
+	}
 
+	/// Import a recovered `block`, or make it wait for its parent if the parent is being recovered.
+	async fn import_recovered_block(&mut self, block_hash: Block::Hash, block: Block) {
 		let parent = *block.header().parent_hash();
 
 		match self.parachain_client.block_status(parent) {
@@ -416,6 +534,7 @@ where
 					);
 
 					self.waiting_for_parent.entry(parent).or_default().push(block);
+					self.set_stage(block_hash, RecoveryStage::WaitingForParent);
 					return
 				} else {
 					tracing::debug!(
@@ -425,7 +544,7 @@ where
 						"Parent not found while trying to import recovered block.",
 					);
 
-					self.reset_candidate(block_hash);
+					self.recovery_failed(block_hash, "Parent not found");
 					return
 				}
 			},
@@ -437,7 +556,7 @@ where
 					"Error while checking block status",
 				);
 
-				self.reset_candidate(block_hash);
+				self.recovery_failed(block_hash, "Failed to get parent block status");
 				return
 			},
 			// Any other status is fine to "ignore/accept"
@@ -461,6 +580,7 @@ where
 		while let Some(block) = blocks.pop_front() {
 			let block_hash = block.hash();
 			let (header, body) = block.deconstruct();
+			self.status.finish(block_hash, RecoveryResult::Submitted);
 
 			incoming_blocks.push(IncomingBlock {
 				hash: block_hash,
@@ -498,6 +618,7 @@ where
 						block_hash = ?hash,
 						"Cound not recover. Block was never announced as candidate"
 					);
+					self.status.finish(hash, RecoveryResult::UnknownCandidate);
 					return
 				},
 			};
@@ -518,6 +639,8 @@ where
 					for hash in to_recover {
 						self.clear_waiting_recovery(&hash);
 					}
+					self.status
+						.finish(hash, RecoveryResult::Failed("Failed to get block status".into()));
 					return
 				},
 			}
@@ -531,6 +654,7 @@ where
 
 		for hash in to_recover.into_iter().rev() {
 			self.candidate_recovery_queue.push_recovery(hash);
+			self.set_stage(hash, RecoveryStage::Queued);
 		}
 	}
 
@@ -570,6 +694,7 @@ where
 				imported = imported_blocks.next() => {
 					if let Some(imported) = imported {
 						self.clear_waiting_recovery(&imported.hash);
+						self.status.imported(&imported.hash);
 					} else {
 						tracing::debug!(target: LOG_TARGET,	"Imported blocks stream ended");
 						return;
@@ -591,11 +716,26 @@ where
 				{
 					self.handle_candidate_recovered(block_hash, available_data).await;
 				},
+				(block_hash, block_data) =
+					wait_for_peer_recovery(&mut self.active_peer_recovery).fuse() =>
+				{
+					self.handle_peer_block_data(block_hash, block_data).await;
+				},
 			}
 		}
 	}
 }
 
+/// Waits for the next peer recovery, or forever if peer recovery is disabled.
+async fn wait_for_peer_recovery<Block: BlockT>(
+	peer_recovery: &mut Option<ActivePeerRecovery<Block>>,
+) -> (Block::Hash, Option<ParachainBlockData<Block>>) {
+	match peer_recovery {
+		Some(peer_recovery) => peer_recovery.wait_for_recovery().await,
+		None => futures::future::pending().await,
+	}
+}
+
 /// Returns a stream over pending candidates for the parachain corresponding to `para_id`.
 async fn pending_candidates(
 	relay_chain_client: impl RelayChainInterface + Clone,
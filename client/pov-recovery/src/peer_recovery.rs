@@ -0,0 +1,472 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parachain side request/response protocol for recovering blocks from parachain peers.
+//!
+//! Nodes that already imported a block can serve its [`ParachainBlockData`] by hash. The storage
+//! proof is regenerated by re-executing the block on top of its parent, so any full node with the
+//! parent state can answer. Recovering from peers is much cheaper than going through the relay
+//! chain availability-recovery, so [`PoVRecovery`](crate::PoVRecovery) tries it first.
+
+use sc_client_api::BlockBackend;
+use sc_network::{PeerId, ReputationChange};
+use sc_network_common::{
+	protocol::ProtocolName,
+	request_responses::{IfDisconnected, IncomingRequest, OutgoingResponse, ProtocolConfig},
+	service::NetworkRequest,
+};
+use sc_network_sync::SyncingService;
+use sp_api::{ApiExt, Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, HashFor, Header as HeaderT, NumberFor};
+
+use infrablockspace_node_primitives::POV_BOMB_LIMIT;
+
+use cumulus_primitives_core::ParachainBlockData;
+
+use codec::{Decode, Encode};
+use futures::{channel::mpsc, stream::FuturesUnordered, Future, FutureExt, StreamExt};
+
+use std::{
+	collections::HashMap,
+	pin::Pin,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use crate::LOG_TARGET;
+
+/// Maximum number of peers asked for a block before giving up.
+const MAX_PEERS_PER_REQUEST: usize = 3;
+
+/// Timeout for a single block data request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Number of incoming requests that can be queued before they are dropped.
+const INBOUND_QUEUE_SIZE: usize = 16;
+
+/// Maximum number of requests served to a single peer per [`RATE_LIMIT_WINDOW`].
+const MAX_REQUESTS_PER_WINDOW: u32 = 4;
+
+/// Window over which the requests of a peer are counted.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(6);
+
+/// Maximum number of peers tracked by the rate limiter before stale entries are dropped.
+const MAX_TRACKED_PEERS: usize = 1024;
+
+/// Reputation cost of a request that can not be decoded.
+const COST_INVALID_REQUEST: ReputationChange =
+	ReputationChange::new(-(1 << 10), "PoV recovery: invalid request");
+
+/// Reputation cost of a request above the rate limit.
+const COST_RATE_LIMITED: ReputationChange =
+	ReputationChange::new(-(1 << 8), "PoV recovery: too many requests");
+
+/// Request for the [`ParachainBlockData`] of the block with the given hash.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct BlockDataRequest<Hash> {
+	/// Hash of the requested block.
+	pub hash: Hash,
+}
+
+/// Returns the name of the block data request protocol.
+pub fn protocol_name<Hash: AsRef<[u8]>>(genesis_hash: Hash, fork_id: Option<&str>) -> ProtocolName {
+	let genesis_hash = array_bytes::bytes2hex("", genesis_hash.as_ref());
+	match fork_id {
+		Some(fork_id) => format!("/{}/{}/pov-recovery/1", genesis_hash, fork_id),
+		None => format!("/{}/pov-recovery/1", genesis_hash),
+	}
+	.into()
+}
+
+/// Generate the block data request protocol configuration.
+///
+/// The returned receiver needs to be passed to [`BlockDataRequestHandler`].
+pub fn generate_protocol_config(
+	protocol_name: ProtocolName,
+) -> (ProtocolConfig, mpsc::Receiver<IncomingRequest>) {
+	let (tx, rx) = mpsc::channel(INBOUND_QUEUE_SIZE);
+
+	let config = ProtocolConfig {
+		name: protocol_name,
+		fallback_names: Vec::new(),
+		max_request_size: 1024,
+		max_response_size: POV_BOMB_LIMIT as u64,
+		request_timeout: REQUEST_TIMEOUT,
+		inbound_queue: Some(tx),
+	};
+
+	(config, rx)
+}
+
+/// Limits the number of requests served to each peer per window.
+struct PeerRateLimiter {
+	window: Duration,
+	max_requests: u32,
+	/// Start of the current window of each peer and the requests served in it.
+	peers: HashMap<PeerId, (Instant, u32)>,
+}
+
+impl PeerRateLimiter {
+	fn new(window: Duration, max_requests: u32) -> Self {
+		Self { window, max_requests, peers: HashMap::new() }
+	}
+
+	/// Record a request of `peer` at `now`, returns `false` if it is above the limit.
+	fn allow(&mut self, peer: PeerId, now: Instant) -> bool {
+		if self.peers.len() >= MAX_TRACKED_PEERS {
+			let window = self.window;
+			self.peers
+				.retain(|_, (start, _)| now.saturating_duration_since(*start) < window);
+		}
+
+		let (start, count) = self.peers.entry(peer).or_insert((now, 0));
+		if now.saturating_duration_since(*start) >= self.window {
+			*start = now;
+			*count = 0;
+		}
+
+		if *count >= self.max_requests {
+			return false
+		}
+		*count += 1;
+		true
+	}
+}
+
+/// Check an incoming request of `peer`, returning the reputation cost if it is not served.
+fn check_request<Hash: Decode>(
+	rate_limiter: &mut PeerRateLimiter,
+	peer: PeerId,
+	payload: &[u8],
+	now: Instant,
+) -> Result<BlockDataRequest<Hash>, ReputationChange> {
+	let request = BlockDataRequest::<Hash>::decode(&mut &payload[..]).map_err(|error| {
+		tracing::debug!(target: LOG_TARGET, ?peer, ?error, "Failed to decode block data request");
+		COST_INVALID_REQUEST
+	})?;
+
+	if !rate_limiter.allow(peer, now) {
+		tracing::debug!(target: LOG_TARGET, ?peer, "Block data request above the rate limit");
+		return Err(COST_RATE_LIMITED)
+	}
+
+	Ok(request)
+}
+
+/// Serves [`BlockDataRequest`]s from the local database.
+///
+/// Serving a request re-executes the block, so the handler must be spawned as a blocking task.
+/// Requests are served one at a time and each peer is limited to [`MAX_REQUESTS_PER_WINDOW`]
+/// requests per [`RATE_LIMIT_WINDOW`].
+pub struct BlockDataRequestHandler<Block, Client> {
+	client: Arc<Client>,
+	request_receiver: mpsc::Receiver<IncomingRequest>,
+	rate_limiter: PeerRateLimiter,
+	_phantom: std::marker::PhantomData<Block>,
+}
+
+impl<Block, Client> BlockDataRequestHandler<Block, Client>
+where
+	Block: BlockT,
+	Client: BlockBackend<Block> + HeaderBackend<Block> + ProvideRuntimeApi<Block> + Send + Sync,
+	Client::Api: Core<Block>,
+{
+	/// Create a new instance.
+	pub fn new(client: Arc<Client>, request_receiver: mpsc::Receiver<IncomingRequest>) -> Self {
+		Self {
+			client,
+			request_receiver,
+			rate_limiter: PeerRateLimiter::new(RATE_LIMIT_WINDOW, MAX_REQUESTS_PER_WINDOW),
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Run the request handler.
+	pub async fn run(mut self) {
+		while let Some(IncomingRequest { peer, payload, pending_response }) =
+			self.request_receiver.next().await
+		{
+			let mut reputation_changes = Vec::new();
+			let result = match check_request::<Block::Hash>(
+				&mut self.rate_limiter,
+				peer,
+				&payload,
+				Instant::now(),
+			) {
+				Ok(request) => {
+					let block_data = self.block_data(request.hash);
+					tracing::trace!(
+						target: LOG_TARGET,
+						?peer,
+						block_hash = ?request.hash,
+						found = block_data.is_some(),
+						"Handled block data request",
+					);
+
+					match sp_maybe_compressed_blob::compress(&block_data.encode(), POV_BOMB_LIMIT) {
+						Some(response) => Ok(response),
+						None => {
+							tracing::debug!(
+								target: LOG_TARGET,
+								block_hash = ?request.hash,
+								"Failed to compress block data response",
+							);
+							Err(())
+						},
+					}
+				},
+				Err(cost) => {
+					reputation_changes.push(cost);
+					Err(())
+				},
+			};
+
+			let _ = pending_response.send(OutgoingResponse {
+				result,
+				reputation_changes,
+				sent_feedback: None,
+			});
+		}
+	}
+
+	/// Build the [`ParachainBlockData`] for the block with the given `hash`.
+	///
+	/// The storage proof is recreated by re-executing the block on its parent state.
+	fn block_data(&self, hash: Block::Hash) -> Option<ParachainBlockData<Block>> {
+		let header = self.client.header(hash).ok()??;
+		let extrinsics = self.client.block_body(hash).ok()??;
+		let parent_hash = *header.parent_hash();
+		let parent_header = self.client.header(parent_hash).ok()??;
+
+		let mut runtime_api = self.client.runtime_api();
+		runtime_api.record_proof();
+		if let Err(error) =
+			runtime_api.execute_block(parent_hash, Block::new(header.clone(), extrinsics.clone()))
+		{
+			tracing::debug!(
+				target: LOG_TARGET,
+				block_hash = ?hash,
+				?error,
+				"Failed to re-execute block for block data request",
+			);
+			return None
+		}
+
+		let compact_proof = runtime_api
+			.extract_proof()?
+			.into_compact_proof::<HashFor<Block>>(*parent_header.state_root())
+			.map_err(|error| {
+				tracing::debug!(
+					target: LOG_TARGET,
+					block_hash = ?hash,
+					?error,
+					"Failed to compact proof for block data request",
+				)
+			})
+			.ok()?;
+
+		Some(ParachainBlockData::new(header, extrinsics, compact_proof))
+	}
+}
+
+/// Test-friendly interface for requesting [`ParachainBlockData`] from parachain peers.
+#[async_trait::async_trait]
+pub trait BlockDataRequester<Block: BlockT>: Send + Sync {
+	/// Request the block data of the block with `hash` and `number`.
+	///
+	/// The returned data is not verified.
+	async fn request_block_data(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+	) -> Option<ParachainBlockData<Block>>;
+}
+
+/// [`BlockDataRequester`] that sends requests through the parachain network.
+pub struct NetworkBlockDataRequester<Block: BlockT> {
+	network: Arc<dyn NetworkRequest + Send + Sync>,
+	sync_service: Arc<SyncingService<Block>>,
+	protocol_name: ProtocolName,
+}
+
+impl<Block: BlockT> NetworkBlockDataRequester<Block> {
+	/// Create a new instance.
+	pub fn new(
+		network: Arc<dyn NetworkRequest + Send + Sync>,
+		sync_service: Arc<SyncingService<Block>>,
+		protocol_name: ProtocolName,
+	) -> Self {
+		Self { network, sync_service, protocol_name }
+	}
+
+	/// Peers whose best block is at least at `number`.
+	async fn candidate_peers(&self, number: NumberFor<Block>) -> Vec<PeerId> {
+		match self.sync_service.peers_info().await {
+			Ok(peers) => peers
+				.into_iter()
+				.filter(|(_, info)| info.best_number >= number)
+				.map(|(peer, _)| peer)
+				.take(MAX_PEERS_PER_REQUEST)
+				.collect(),
+			Err(_) => Vec::new(),
+		}
+	}
+
+	/// Request the block data from `peer`.
+	async fn request_from_peer(
+		&self,
+		peer: PeerId,
+		hash: Block::Hash,
+	) -> Option<ParachainBlockData<Block>> {
+		let response = self
+			.network
+			.request(
+				peer,
+				self.protocol_name.clone(),
+				BlockDataRequest { hash }.encode(),
+				IfDisconnected::ImmediateError,
+			)
+			.await
+			.map_err(|error| {
+				tracing::debug!(
+					target: LOG_TARGET,
+					?peer,
+					block_hash = ?hash,
+					?error,
+					"Block data request failed",
+				)
+			})
+			.ok()?;
+
+		let raw = sp_maybe_compressed_blob::decompress(&response, POV_BOMB_LIMIT)
+			.map_err(
+				|error| tracing::debug!(target: LOG_TARGET, ?peer, ?error, "Failed to decompress block data"),
+			)
+			.ok()?;
+
+		Option::<ParachainBlockData<Block>>::decode(&mut &raw[..])
+			.map_err(
+				|error| tracing::debug!(target: LOG_TARGET, ?peer, ?error, "Failed to decode block data"),
+			)
+			.ok()?
+	}
+}
+
+#[async_trait::async_trait]
+impl<Block: BlockT> BlockDataRequester<Block> for NetworkBlockDataRequester<Block> {
+	async fn request_block_data(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+	) -> Option<ParachainBlockData<Block>> {
+		for peer in self.candidate_peers(number).await {
+			if let Some(block_data) = self.request_from_peer(peer, hash).await {
+				if block_data.header().hash() == hash {
+					return Some(block_data)
+				}
+
+				tracing::debug!(
+					target: LOG_TARGET,
+					?peer,
+					block_hash = ?hash,
+					"Peer responded with block data for a different block",
+				);
+			}
+		}
+
+		None
+	}
+}
+
+/// The active peer recoveries.
+pub(crate) struct ActivePeerRecovery<Block: BlockT> {
+	/// The recoveries that are currently being executed.
+	recoveries: FuturesUnordered<
+		Pin<Box<dyn Future<Output = (Block::Hash, Option<ParachainBlockData<Block>>)> + Send>>,
+	>,
+	requester: Arc<dyn BlockDataRequester<Block>>,
+}
+
+impl<Block: BlockT> ActivePeerRecovery<Block> {
+	pub fn new(requester: Arc<dyn BlockDataRequester<Block>>) -> Self {
+		Self { recoveries: Default::default(), requester }
+	}
+
+	/// Start recovering the block with `block_hash` and `block_number` from peers.
+	pub fn recover_block(&mut self, block_hash: Block::Hash, block_number: NumberFor<Block>) {
+		let requester = self.requester.clone();
+		self.recoveries.push(
+			async move { (block_hash, requester.request_block_data(block_hash, block_number).await) }
+				.boxed(),
+		);
+	}
+
+	/// Waits for the next recovery.
+	///
+	/// If the returned [`ParachainBlockData`] is `None`, it means that the recovery failed.
+	pub async fn wait_for_recovery(&mut self) -> (Block::Hash, Option<ParachainBlockData<Block>>) {
+		loop {
+			if let Some(res) = self.recoveries.next().await {
+				return res
+			} else {
+				futures::pending!()
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::testing::H256;
+
+	#[test]
+	fn requests_above_the_rate_limit_are_refused() {
+		let mut limiter = PeerRateLimiter::new(Duration::from_secs(6), 2);
+		let (alice, bob) = (PeerId::random(), PeerId::random());
+		let now = Instant::now();
+
+		assert!(limiter.allow(alice, now));
+		assert!(limiter.allow(alice, now));
+		assert!(!limiter.allow(alice, now + Duration::from_secs(5)));
+		// Peers are limited independently.
+		assert!(limiter.allow(bob, now));
+		// A new window starts once the previous one is over.
+		assert!(limiter.allow(alice, now + Duration::from_secs(6)));
+	}
+
+	#[test]
+	fn invalid_and_rate_limited_requests_cost_reputation() {
+		let mut limiter = PeerRateLimiter::new(Duration::from_secs(6), 1);
+		let peer = PeerId::random();
+		let now = Instant::now();
+		let request = BlockDataRequest { hash: H256::repeat_byte(1) }.encode();
+
+		assert_eq!(
+			check_request::<H256>(&mut limiter, peer, &[1, 2], now).unwrap_err(),
+			COST_INVALID_REQUEST,
+		);
+		assert_eq!(
+			check_request::<H256>(&mut limiter, peer, &request, now).unwrap().hash,
+			H256::repeat_byte(1),
+		);
+		assert_eq!(
+			check_request::<H256>(&mut limiter, peer, &request, now).unwrap_err(),
+			COST_RATE_LIMITED,
+		);
+	}
+}
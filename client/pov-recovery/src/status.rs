@@ -0,0 +1,333 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Operator facing control over a running [`PoVRecovery`](crate::PoVRecovery).
+//!
+//! [`RecoveryControl`] is handed to the service that spawns the recovery worker, while the
+//! [`RecoveryController`] obtained from it can be used by e.g. RPC to enqueue explicit recovery
+//! requests and to inspect the recovery queue and the outcome of recent recoveries.
+
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use parking_lot::Mutex;
+
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::Arc,
+};
+
+use crate::{RecoveryKind, RecoveryRequest};
+
+// Given the sporadic nature of the explicit recovery operation and the
+// possibility to retry infinite times this value is more than enough.
+// In practice here we expect no more than one queued messages.
+const RECOVERY_CHAN_SIZE: usize = 8;
+
+/// Number of recovery outcomes that are kept around.
+const MAX_OUTCOMES: usize = 64;
+
+/// Stage of a block that is being recovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryStage {
+	/// Waiting for the recovery delay to pass.
+	Queued,
+	/// Requested from parachain peers.
+	FromPeers,
+	/// Requested from the relay chain availability-recovery.
+	FromRelayChain,
+	/// Recovered, waiting for the parent to be recovered.
+	WaitingForParent,
+}
+
+/// Result of a finished recovery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryResult {
+	/// The recovered block was submitted to the import queue, its import may still fail.
+	Submitted,
+	/// The block was imported by other means before the recovery finished.
+	AlreadyImported,
+	/// The block is not a pending candidate of the parachain, so it can not be recovered.
+	UnknownCandidate,
+	/// The recovery failed.
+	Failed(String),
+}
+
+/// A block that is currently being recovered.
+#[derive(Debug, Clone)]
+pub struct PendingRecovery<Block: BlockT> {
+	/// Hash of the block.
+	pub hash: Block::Hash,
+	/// Number of the block.
+	pub number: NumberFor<Block>,
+	/// Current stage of the recovery.
+	pub stage: RecoveryStage,
+}
+
+/// Outcome of a finished recovery.
+#[derive(Debug, Clone)]
+pub struct RecoveryOutcome<Block: BlockT> {
+	/// Hash of the block.
+	pub hash: Block::Hash,
+	/// The result of the recovery.
+	pub result: RecoveryResult,
+}
+
+/// Snapshot of the recovery state.
+#[derive(Debug, Clone)]
+pub struct RecoveryStatus<Block: BlockT> {
+	/// Blocks that are currently being recovered, ordered by block number.
+	pub pending: Vec<PendingRecovery<Block>>,
+	/// Outcome of the most recent recoveries, oldest first.
+	pub outcomes: Vec<RecoveryOutcome<Block>>,
+}
+
+struct Inner<Block: BlockT> {
+	pending: HashMap<Block::Hash, PendingRecovery<Block>>,
+	outcomes: VecDeque<RecoveryOutcome<Block>>,
+}
+
+/// Shared recovery state, updated by [`PoVRecovery`](crate::PoVRecovery).
+pub struct RecoveryStatusHandle<Block: BlockT> {
+	inner: Arc<Mutex<Inner<Block>>>,
+}
+
+impl<Block: BlockT> Clone for RecoveryStatusHandle<Block> {
+	fn clone(&self) -> Self {
+		Self { inner: self.inner.clone() }
+	}
+}
+
+impl<Block: BlockT> Default for RecoveryStatusHandle<Block> {
+	fn default() -> Self {
+		Self {
+			inner: Arc::new(Mutex::new(Inner {
+				pending: Default::default(),
+				outcomes: Default::default(),
+			})),
+		}
+	}
+}
+
+impl<Block: BlockT> RecoveryStatusHandle<Block> {
+	/// Returns a snapshot of the current recovery state.
+	pub fn snapshot(&self) -> RecoveryStatus<Block> {
+		let inner = self.inner.lock();
+		let mut pending = inner.pending.values().cloned().collect::<Vec<_>>();
+		pending.sort_by_key(|p| p.number);
+
+		RecoveryStatus { pending, outcomes: inner.outcomes.iter().cloned().collect() }
+	}
+
+	/// Set the recovery stage of the block with `hash`.
+	pub(crate) fn set_stage(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		stage: RecoveryStage,
+	) {
+		self.inner.lock().pending.insert(hash, PendingRecovery { hash, number, stage });
+	}
+
+	/// Note that the recovery of the block with `hash` finished with `result`.
+	pub(crate) fn finish(&self, hash: Block::Hash, result: RecoveryResult) {
+		let mut inner = self.inner.lock();
+		inner.pending.remove(&hash);
+		if inner.outcomes.len() >= MAX_OUTCOMES {
+			inner.outcomes.pop_front();
+		}
+		inner.outcomes.push_back(RecoveryOutcome { hash, result });
+	}
+
+	/// Note that the block with `hash` got imported.
+	pub(crate) fn imported(&self, hash: &Block::Hash) {
+		if self.inner.lock().pending.contains_key(hash) {
+			self.finish(*hash, RecoveryResult::AlreadyImported);
+		}
+	}
+
+	/// Forget about pending recoveries of blocks that are finalized.
+	pub(crate) fn finalized(&self, number: NumberFor<Block>) {
+		self.inner.lock().pending.retain(|_, p| p.number > number);
+	}
+}
+
+/// Service side of the recovery control, consumed when starting [`PoVRecovery`](crate::PoVRecovery).
+pub struct RecoveryControl<Block: BlockT> {
+	/// Sender for explicit recovery requests.
+	pub sender: Sender<RecoveryRequest<Block>>,
+	/// Receiver for explicit recovery requests.
+	pub receiver: Receiver<RecoveryRequest<Block>>,
+	/// Shared recovery state.
+	pub status: RecoveryStatusHandle<Block>,
+}
+
+impl<Block: BlockT> RecoveryControl<Block> {
+	/// Create a new instance.
+	pub fn new() -> Self {
+		let (sender, receiver) = channel(RECOVERY_CHAN_SIZE);
+		Self { sender, receiver, status: Default::default() }
+	}
+
+	/// Returns a [`RecoveryController`] that can be used after [`PoVRecovery`] is started.
+	///
+	/// [`PoVRecovery`]: crate::PoVRecovery
+	pub fn controller(&self) -> RecoveryController<Block> {
+		RecoveryController {
+			sender: Arc::new(Mutex::new(self.sender.clone())),
+			status: self.status.clone(),
+		}
+	}
+}
+
+impl<Block: BlockT> Default for RecoveryControl<Block> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Error returned by [`RecoveryController::recover`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecoveryControllerError {
+	/// Too many recovery requests are queued.
+	QueueFull,
+	/// The recovery worker is not running anymore.
+	Stopped,
+}
+
+/// Handle to enqueue explicit recovery requests and to inspect the recovery state.
+pub struct RecoveryController<Block: BlockT> {
+	// Shared, as every clone of a `Sender` gets its own slot in the channel.
+	sender: Arc<Mutex<Sender<RecoveryRequest<Block>>>>,
+	status: RecoveryStatusHandle<Block>,
+}
+
+impl<Block: BlockT> Clone for RecoveryController<Block> {
+	fn clone(&self) -> Self {
+		Self { sender: self.sender.clone(), status: self.status.clone() }
+	}
+}
+
+impl<Block: BlockT> RecoveryController<Block> {
+	/// Enqueue the recovery of the block with `hash`.
+	///
+	/// With [`RecoveryKind::Full`] all unknown ancestors of the block are recovered as well.
+	pub fn recover(
+		&self,
+		hash: Block::Hash,
+		kind: RecoveryKind,
+	) -> Result<(), RecoveryControllerError> {
+		self.sender.lock().try_send(RecoveryRequest { hash, kind }).map_err(|e| {
+			if e.is_full() {
+				RecoveryControllerError::QueueFull
+			} else {
+				RecoveryControllerError::Stopped
+			}
+		})
+	}
+
+	/// Returns a snapshot of the current recovery state.
+	pub fn status(&self) -> RecoveryStatus<Block> {
+		self.status.snapshot()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, H256};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	fn hash(n: u8) -> H256 {
+		H256::repeat_byte(n)
+	}
+
+	#[test]
+	fn pending_recoveries_are_ordered_by_number() {
+		let status = RecoveryStatusHandle::<Block>::default();
+		status.set_stage(hash(2), 2, RecoveryStage::Queued);
+		status.set_stage(hash(1), 1, RecoveryStage::FromPeers);
+		status.set_stage(hash(2), 2, RecoveryStage::FromRelayChain);
+
+		let pending = status.snapshot().pending;
+		assert_eq!(
+			pending.iter().map(|p| (p.hash, p.stage)).collect::<Vec<_>>(),
+			vec![(hash(1), RecoveryStage::FromPeers), (hash(2), RecoveryStage::FromRelayChain)],
+		);
+	}
+
+	#[test]
+	fn finished_recoveries_are_kept_up_to_the_limit() {
+		let status = RecoveryStatusHandle::<Block>::default();
+		status.set_stage(hash(0), 1, RecoveryStage::Queued);
+		status.finish(hash(0), RecoveryResult::Failed("unavailable".into()));
+
+		let snapshot = status.snapshot();
+		assert!(snapshot.pending.is_empty());
+		assert_eq!(snapshot.outcomes[0].result, RecoveryResult::Failed("unavailable".into()));
+
+		for n in 1..=MAX_OUTCOMES as u8 {
+			status.finish(hash(n), RecoveryResult::Submitted);
+		}
+		let outcomes = status.snapshot().outcomes;
+		assert_eq!(outcomes.len(), MAX_OUTCOMES);
+		assert_eq!(outcomes[0].hash, hash(1));
+		assert_eq!(outcomes[MAX_OUTCOMES - 1].hash, hash(MAX_OUTCOMES as u8));
+	}
+
+	#[test]
+	fn imported_and_finalized_blocks_are_no_longer_pending() {
+		let status = RecoveryStatusHandle::<Block>::default();
+		status.set_stage(hash(1), 1, RecoveryStage::Queued);
+		status.set_stage(hash(2), 2, RecoveryStage::WaitingForParent);
+		status.set_stage(hash(3), 3, RecoveryStage::FromRelayChain);
+
+		// Only the blocks being recovered get an outcome.
+		status.imported(&hash(4));
+		status.imported(&hash(2));
+		let snapshot = status.snapshot();
+		assert_eq!(snapshot.outcomes.len(), 1);
+		assert_eq!(snapshot.outcomes[0].hash, hash(2));
+		assert_eq!(snapshot.outcomes[0].result, RecoveryResult::AlreadyImported);
+
+		status.finalized(1);
+		assert_eq!(
+			status.snapshot().pending.iter().map(|p| p.hash).collect::<Vec<_>>(),
+			vec![hash(3)],
+		);
+	}
+
+	#[test]
+	fn controller_reports_full_queue_and_stopped_worker() {
+		let control = RecoveryControl::<Block>::new();
+		let controller = control.controller();
+
+		for _ in 0..=RECOVERY_CHAN_SIZE {
+			assert_eq!(controller.recover(hash(1), RecoveryKind::Simple), Ok(()));
+		}
+		assert_eq!(
+			controller.recover(hash(1), RecoveryKind::Full),
+			Err(RecoveryControllerError::QueueFull),
+		);
+
+		drop(control);
+		assert_eq!(
+			controller.recover(hash(1), RecoveryKind::Simple),
+			Err(RecoveryControllerError::Stopped),
+		);
+	}
+}
@@ -19,17 +19,17 @@
 //! Provides functions for starting a collator node or a normal full node.
 
 use cumulus_client_cli::CollatorOptions;
+use cumulus_client_collator::{CollationArchive, CollationStatsRecorder, LeaderLease};
 use cumulus_client_consensus_common::ParachainConsensus;
-use cumulus_client_network::BlockAnnounceValidator;
-use cumulus_client_pov_recovery::{PoVRecovery, RecoveryDelayRange, RecoveryHandle};
+use cumulus_client_network::{AuthorVerificationConfig, BlockAnnounceValidator};
+use cumulus_client_pov_recovery::{
+	BlockDataRequester, PoVRecovery, RecoveryControl, RecoveryDelayRange, RecoveryHandle,
+};
 use cumulus_primitives_core::{CollectCollationInfo, ParaId};
 use cumulus_relay_chain_inprocess_interface::build_inprocess_relay_chain;
 use cumulus_relay_chain_interface::{RelayChainInterface, RelayChainResult};
 use cumulus_relay_chain_minimal_node::build_minimal_relay_chain_node;
-use futures::{
-	channel::{mpsc, oneshot},
-	FutureExt, StreamExt,
-};
+use futures::{channel::oneshot, FutureExt, StreamExt};
 use infrablockspace_primitives::{CollatorPair, OccupiedCoreAssumption};
 use sc_client_api::{
 	Backend as BackendT, BlockBackend, BlockchainEvents, Finalizer, ProofProvider, UsageProvider,
@@ -51,10 +51,6 @@ use sp_core::{traits::SpawnNamed, Decode};
 use sp_runtime::traits::{Block as BlockT, BlockIdTo};
 use std::{sync::Arc, time::Duration};
 
-// Given the sporadic nature of the explicit recovery operation and the
-// possibility to retry infinite times this value is more than enough.
-// In practice here we expect no more than one queued messages.
-const RECOVERY_CHAN_SIZE: usize = 8;
 const LOG_TARGET_SYNC: &str = "sync::cumulus";
 
 /// Parameters given to [`start_collator`].
@@ -71,6 +67,16 @@ pub struct StartCollatorParams<'a, Block: BlockT, BS, Client, RCInterface, Spawn
 	pub collator_key: CollatorPair,
 	pub relay_chain_slot_duration: Duration,
 	pub recovery_handle: Box<dyn RecoveryHandle>,
+	/// Used to recover blocks from parachain peers before asking the relay chain.
+	pub block_data_requester: Option<Arc<dyn BlockDataRequester<Block>>>,
+	/// Used to drive and inspect the pov-recovery from outside, e.g. through RPC.
+	pub recovery_control: Option<RecoveryControl<Block>>,
+	/// Records the statistics of the built collations, e.g. to expose them through RPC.
+	pub collation_stats: Option<CollationStatsRecorder<Block>>,
+	/// Archives the built collations on disk, if given.
+	pub collation_archive: Option<CollationArchive<Block>>,
+	/// Only author while holding this lease, if given, e.g. to run hot-standby collators.
+	pub leader_lease: Option<LeaderLease>,
 }
 
 /// Start a collator node for a parachain.
@@ -92,6 +98,11 @@ pub async fn start_collator<'a, Block, BS, Client, Backend, RCInterface, Spawner
 		collator_key,
 		relay_chain_slot_duration,
 		recovery_handle,
+		block_data_requester,
+		recovery_control,
+		collation_stats,
+		collation_archive,
+		leader_lease,
 	}: StartCollatorParams<'a, Block, BS, Client, RCInterface, Spawner>,
 ) -> sc_service::error::Result<()>
 where
@@ -112,7 +123,8 @@ where
 	RCInterface: RelayChainInterface + Clone + 'static,
 	Backend: BackendT<Block> + 'static,
 {
-	let (recovery_chan_tx, recovery_chan_rx) = mpsc::channel(RECOVERY_CHAN_SIZE);
+	let RecoveryControl { sender: recovery_chan_tx, receiver: recovery_chan_rx, status } =
+		recovery_control.unwrap_or_default();
 
 	let consensus = cumulus_client_consensus_common::run_parachain_consensus(
 		para_id,
@@ -137,12 +149,20 @@ where
 		relay_chain_interface.clone(),
 		para_id,
 		recovery_chan_rx,
+		block_data_requester,
+		status,
 	);
 
 	task_manager
 		.spawn_essential_handle()
 		.spawn("cumulus-pov-recovery", None, pov_recovery.run());
 
+	if let Some(leader_lease) = &leader_lease {
+		task_manager
+			.spawn_handle()
+			.spawn("cumulus-leader-lease", None, leader_lease.run());
+	}
+
 	let overseer_handle = relay_chain_interface
 		.overseer_handle()
 		.map_err(|e| sc_service::Error::Application(Box::new(e)))?;
@@ -155,6 +175,9 @@ where
 		para_id,
 		key: collator_key,
 		parachain_consensus,
+		collation_stats,
+		collation_archive,
+		leader_lease,
 	})
 	.await;
 
@@ -171,6 +194,10 @@ pub struct StartFullNodeParams<'a, Block: BlockT, Client, RCInterface> {
 	pub relay_chain_slot_duration: Duration,
 	pub import_queue: Box<dyn ImportQueueService<Block>>,
 	pub recovery_handle: Box<dyn RecoveryHandle>,
+	/// Used to recover blocks from parachain peers before asking the relay chain.
+	pub block_data_requester: Option<Arc<dyn BlockDataRequester<Block>>>,
+	/// Used to drive and inspect the pov-recovery from outside, e.g. through RPC.
+	pub recovery_control: Option<RecoveryControl<Block>>,
 }
 
 /// Start a full node for a parachain.
@@ -187,6 +214,8 @@ pub fn start_full_node<Block, Client, Backend, RCInterface>(
 		relay_chain_slot_duration,
 		import_queue,
 		recovery_handle,
+		block_data_requester,
+		recovery_control,
 	}: StartFullNodeParams<Block, Client, RCInterface>,
 ) -> sc_service::error::Result<()>
 where
@@ -202,7 +231,8 @@ where
 	Backend: BackendT<Block> + 'static,
 	RCInterface: RelayChainInterface + Clone + 'static,
 {
-	let (recovery_chan_tx, recovery_chan_rx) = mpsc::channel(RECOVERY_CHAN_SIZE);
+	let RecoveryControl { sender: recovery_chan_tx, receiver: recovery_chan_rx, status } =
+		recovery_control.unwrap_or_default();
 
 	let consensus = cumulus_client_consensus_common::run_parachain_consensus(
 		para_id,
@@ -232,6 +262,8 @@ where
 		relay_chain_interface,
 		para_id,
 		recovery_chan_rx,
+		block_data_requester,
+		status,
 	);
 
 	task_manager
@@ -302,6 +334,8 @@ pub struct BuildNetworkParams<
 	pub relay_chain_interface: RCInterface,
 	pub spawn_handle: SpawnTaskHandle,
 	pub import_queue: IQ,
+	/// Verification of the author of announced blocks, if supported by the consensus.
+	pub author_verification: Option<AuthorVerificationConfig<Block>>,
 }
 
 /// Build the network service, the network status sinks and an RPC sender.
@@ -314,6 +348,7 @@ pub async fn build_network<'a, Block, Client, RCInterface, IQ>(
 		spawn_handle,
 		relay_chain_interface,
 		import_queue,
+		author_verification,
 	}: BuildNetworkParams<'a, Block, Client, RCInterface, IQ>,
 ) -> sc_service::error::Result<(
 	Arc<NetworkService<Block, Block::Hash>>,
@@ -354,7 +389,15 @@ where
 		_ => None,
 	};
 
-	let block_announce_validator = BlockAnnounceValidator::new(relay_chain_interface, para_id);
+	let block_announce_validator = match author_verification {
+		Some(config) => BlockAnnounceValidator::new_with_author_verification(
+			relay_chain_interface,
+			para_id,
+			config,
+			parachain_config.prometheus_registry(),
+		),
+		None => BlockAnnounceValidator::new(relay_chain_interface, para_id),
+	};
 	let block_announce_validator_builder = move |_| Box::new(block_announce_validator) as Box<_>;
 
 	sc_service::build_network(sc_service::BuildNetworkParams {
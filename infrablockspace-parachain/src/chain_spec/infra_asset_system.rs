@@ -247,5 +247,6 @@ pub fn infra_asset_system_genesis(
 		infrablockspace_xcm: infra_asset_system_runtime::InfrablockspaceXcmConfig {
 			safe_xcm_version: Some(SAFE_XCM_VERSION),
 		},
+		xcm_barrier: Default::default(),
 	}
 }
@@ -0,0 +1,181 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::chain_spec::{
+	get_account_id_from_seed, get_collator_keys_from_seed, Extensions, SAFE_XCM_VERSION,
+};
+use cumulus_primitives_core::ParaId;
+use parachain_template_runtime::{AccountId, AuraId, EXISTENTIAL_DEPOSIT};
+use sc_service::ChainType;
+use sp_core::sr25519;
+
+/// Specialized `ChainSpec` for the parachain template runtime.
+pub type ParachainTemplateChainSpec =
+	sc_service::GenericChainSpec<parachain_template_runtime::GenesisConfig, Extensions>;
+
+/// Generate the session keys from individual elements.
+///
+/// The input must be a tuple of individual keys (a single arg for now since we have just one key).
+pub fn parachain_template_session_keys(keys: AuraId) -> parachain_template_runtime::SessionKeys {
+	parachain_template_runtime::SessionKeys { aura: keys }
+}
+
+fn parachain_template_properties() -> sc_chain_spec::Properties {
+	let mut properties = sc_chain_spec::Properties::new();
+	properties.insert("tokenSymbol".into(), "UNIT".into());
+	properties.insert("tokenDecimals".into(), 12.into());
+	properties.insert("ss58Format".into(), 42.into());
+	properties
+}
+
+fn testnet_accounts() -> Vec<AccountId> {
+	["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"]
+		.into_iter()
+		.flat_map(|seed| {
+			[
+				get_account_id_from_seed::<sr25519::Public>(seed),
+				get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", seed)),
+			]
+		})
+		.collect()
+}
+
+pub fn parachain_template_development_config() -> ParachainTemplateChainSpec {
+	ParachainTemplateChainSpec::from_genesis(
+		// Name
+		"Parachain Template Development",
+		// ID
+		"parachain_template_dev",
+		ChainType::Development,
+		move || {
+			parachain_template_genesis(
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// initial collators.
+				vec![(
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_collator_keys_from_seed::<AuraId>("Alice"),
+				)],
+				testnet_accounts(),
+				2000.into(),
+			)
+		},
+		Vec::new(),
+		None,
+		None,
+		None,
+		Some(parachain_template_properties()),
+		Extensions { relay_chain: "infrablockspace-local".into(), para_id: 2000 },
+	)
+}
+
+pub fn parachain_template_local_config() -> ParachainTemplateChainSpec {
+	ParachainTemplateChainSpec::from_genesis(
+		// Name
+		"Parachain Template Local",
+		// ID
+		"parachain_template_local",
+		ChainType::Local,
+		move || {
+			parachain_template_genesis(
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// initial collators.
+				vec![
+					(
+						get_account_id_from_seed::<sr25519::Public>("Alice"),
+						get_collator_keys_from_seed::<AuraId>("Alice"),
+					),
+					(
+						get_account_id_from_seed::<sr25519::Public>("Bob"),
+						get_collator_keys_from_seed::<AuraId>("Bob"),
+					),
+				],
+				testnet_accounts(),
+				2000.into(),
+			)
+		},
+		Vec::new(),
+		None,
+		Some("template-local"),
+		None,
+		Some(parachain_template_properties()),
+		Extensions { relay_chain: "infrablockspace-local".into(), para_id: 2000 },
+	)
+}
+
+pub fn parachain_template_genesis(
+	root_key: AccountId,
+	invulnerables: Vec<(AccountId, AuraId)>,
+	endowed_accounts: Vec<AccountId>,
+	id: ParaId,
+) -> parachain_template_runtime::GenesisConfig {
+	parachain_template_runtime::GenesisConfig {
+		system: parachain_template_runtime::SystemConfig {
+			code: parachain_template_runtime::WASM_BINARY
+				.expect("WASM binary was not build, please build it!")
+				.to_vec(),
+		},
+		balances: parachain_template_runtime::BalancesConfig {
+			balances: endowed_accounts
+				.iter()
+				.cloned()
+				.map(|k| (k, 1_000_000 * EXISTENTIAL_DEPOSIT))
+				.collect(),
+		},
+		parachain_info: parachain_template_runtime::ParachainInfoConfig { parachain_id: id },
+		collator_selection: parachain_template_runtime::CollatorSelectionConfig {
+			invulnerables: invulnerables.iter().cloned().map(|(acc, _)| acc).collect(),
+			candidacy_bond: EXISTENTIAL_DEPOSIT * 16,
+			..Default::default()
+		},
+		session: parachain_template_runtime::SessionConfig {
+			keys: invulnerables
+				.into_iter()
+				.map(|(acc, aura)| {
+					(
+						acc.clone(),                           // account id
+						acc,                                   // validator id
+						parachain_template_session_keys(aura), // session keys
+					)
+				})
+				.collect(),
+		},
+		sudo: parachain_template_runtime::SudoConfig { key: Some(root_key) },
+		assets: pallet_assets::GenesisConfig {
+			assets: vec![(
+				99,                                                 // asset_id
+				get_account_id_from_seed::<sr25519::Public>("Bob"), // owner
+				true,                                               // is_sufficient
+				1000,                                               // min_balance
+			)],
+			metadata: vec![(99, "iTEST".into(), "iTEST".into(), 12)],
+			accounts: vec![(
+				99,
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				1_000_000_000_000, // 1 iTEST
+			)],
+			..Default::default()
+		},
+		// no need to pass anything to aura, in fact it will panic if we do. Session will take care
+		// of this.
+		aura: Default::default(),
+		aura_ext: Default::default(),
+		parachain_system: Default::default(),
+		infrablockspace_xcm: parachain_template_runtime::InfrablockspaceXcmConfig {
+			safe_xcm_version: Some(SAFE_XCM_VERSION),
+		},
+		transaction_payment: Default::default(),
+	}
+}
@@ -0,0 +1,480 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Genesis presets of the asset system, used by the `build-genesis` sub-command.
+//!
+//! A preset is a TOML or JSON file describing the genesis of a chain. It is validated before
+//! the chain spec is built, so that a mistake fails loudly instead of producing a chain that
+//! can not produce blocks.
+
+use crate::chain_spec::{
+	infra_asset_system::{infra_asset_system_genesis, InfraAssetSystemChainSpec},
+	Extensions,
+};
+use codec::Encode;
+use parachains_common::{AccountId, AssetIdForTrustBackedAssets as AssetId, AuraId, Balance};
+use sc_chain_spec::ChainSpec;
+use sc_service::ChainType;
+use serde::Deserialize;
+use sp_core::{crypto::Ss58Codec, sr25519, Pair};
+use sp_runtime::BuildStorage;
+use std::{collections::BTreeSet, path::Path};
+use xcm::latest::prelude::*;
+
+const EXISTENTIAL_DEPOSIT: Balance =
+	infra_asset_system_runtime::constants::currency::EXISTENTIAL_DEPOSIT;
+/// Balance of the endowed accounts without an explicit balance.
+const DEFAULT_ENDOWMENT: Balance = EXISTENTIAL_DEPOSIT * 4096;
+/// Maximum length of the name and symbol of an asset, see `AssetsStringLimit` of the runtime.
+const ASSET_STRING_LIMIT: usize = 50;
+
+/// Genesis preset of the asset system.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisPreset {
+	/// Name of the chain.
+	pub name: String,
+	/// Id of the chain.
+	pub id: String,
+	/// Type of the chain.
+	#[serde(default)]
+	pub chain_type: PresetChainType,
+	/// Id of the relay chain.
+	pub relay_chain: String,
+	/// Id of the parachain.
+	pub para_id: u32,
+	/// Account of the sudo key, if any.
+	#[serde(default)]
+	pub sudo: Option<String>,
+	/// Invulnerable collators.
+	pub collators: Vec<CollatorPreset>,
+	/// Accounts endowed with the native token.
+	#[serde(default)]
+	pub endowed: Vec<EndowmentPreset>,
+	/// Assets created at genesis.
+	#[serde(default)]
+	pub assets: Vec<AssetPreset>,
+	/// Token properties of the chain.
+	#[serde(default)]
+	pub properties: Option<PropertiesPreset>,
+	/// Boot nodes of the chain.
+	#[serde(default)]
+	pub boot_nodes: Vec<String>,
+}
+
+/// Type of the chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetChainType {
+	/// A development chain.
+	Development,
+	/// A local chain.
+	#[default]
+	Local,
+	/// A live chain.
+	Live,
+}
+
+/// An invulnerable collator.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollatorPreset {
+	/// Account of the collator.
+	pub account: String,
+	/// Aura key of the collator, the key of `account` if not set.
+	#[serde(default)]
+	pub aura: Option<String>,
+}
+
+/// An account endowed with the native token.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndowmentPreset {
+	/// The endowed account.
+	pub account: String,
+	/// Balance of the account.
+	#[serde(default)]
+	pub balance: Option<Amount>,
+}
+
+/// An asset created at genesis.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssetPreset {
+	/// Id of the asset.
+	pub id: AssetId,
+	/// Owner of the asset.
+	pub owner: String,
+	/// Name of the asset.
+	pub name: String,
+	/// Symbol of the asset.
+	pub symbol: String,
+	/// Number of decimals of the asset.
+	pub decimals: u8,
+	/// Minimum balance of the asset.
+	pub min_balance: Amount,
+	/// Whether the asset is sufficient, i.e. whether it can be held by accounts without native
+	/// tokens.
+	#[serde(default = "default_sufficient")]
+	pub sufficient: bool,
+	/// Balances of the asset.
+	#[serde(default)]
+	pub balances: Vec<AssetBalancePreset>,
+	/// Location of the system token the asset is linked to, if any.
+	#[serde(default)]
+	pub link: Option<SystemTokenLink>,
+}
+
+fn default_sufficient() -> bool {
+	true
+}
+
+/// Balance of an asset.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssetBalancePreset {
+	/// The account holding the asset.
+	pub account: String,
+	/// Balance of the account.
+	pub amount: Amount,
+}
+
+/// Location of a system token, i.e.
+/// `../Parachain(para_id)/PalletInstance(pallet_instance)/GeneralIndex(general_index)`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SystemTokenLink {
+	/// Number of parents of the location.
+	#[serde(default = "default_parents")]
+	pub parents: u8,
+	/// Parachain holding the system token.
+	pub para_id: u32,
+	/// Index of the assets pallet holding the system token.
+	pub pallet_instance: u8,
+	/// Id of the system token in the assets pallet.
+	pub general_index: u128,
+}
+
+fn default_parents() -> u8 {
+	1
+}
+
+impl SystemTokenLink {
+	fn location(&self) -> MultiLocation {
+		MultiLocation::new(
+			self.parents,
+			X3(
+				Parachain(self.para_id),
+				PalletInstance(self.pallet_instance),
+				GeneralIndex(self.general_index),
+			),
+		)
+	}
+}
+
+/// Token properties of the chain.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PropertiesPreset {
+	/// Symbol of the native token.
+	pub token_symbol: String,
+	/// Number of decimals of the native token.
+	pub token_decimals: u8,
+	/// SS58 prefix of the addresses.
+	pub ss58_format: u16,
+}
+
+/// An amount of tokens.
+///
+/// Given as a string if it does not fit in a TOML integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "AmountRepr")]
+pub struct Amount(pub Balance);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AmountRepr {
+	Number(u64),
+	String(String),
+}
+
+impl TryFrom<AmountRepr> for Amount {
+	type Error = String;
+
+	fn try_from(repr: AmountRepr) -> Result<Self, Self::Error> {
+		match repr {
+			AmountRepr::Number(amount) => Ok(Amount(amount.into())),
+			AmountRepr::String(amount) => amount
+				.replace('_', "")
+				.parse()
+				.map(Amount)
+				.map_err(|e| format!("Invalid amount '{}': {}", amount, e)),
+		}
+	}
+}
+
+/// Parse an account given either as SS58 address or as secret URI (e.g. `//Alice`).
+fn parse_account(account: &str) -> Result<sr25519::Public, String> {
+	if account.starts_with("//") {
+		sr25519::Pair::from_string(account, None)
+			.map(|pair| pair.public())
+			.map_err(|e| format!("Invalid secret URI '{}': {:?}", account, e))
+	} else {
+		sr25519::Public::from_ss58check(account)
+			.map_err(|e| format!("Invalid account '{}': {:?}", account, e))
+	}
+}
+
+fn account_id(account: &str) -> Result<AccountId, String> {
+	parse_account(account).map(Into::into)
+}
+
+impl GenesisPreset {
+	/// Load a preset from a TOML (`.toml` extension) or JSON file.
+	pub fn load(path: &Path) -> Result<Self, String> {
+		let content = std::fs::read_to_string(path)
+			.map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+
+		if path.extension().map_or(false, |ext| ext == "toml") {
+			toml::from_str(&content).map_err(|e| format!("Invalid preset: {}", e))
+		} else {
+			serde_json::from_str(&content).map_err(|e| format!("Invalid preset: {}", e))
+		}
+	}
+
+	/// Check that the preset describes a chain that can produce blocks.
+	pub fn validate(&self) -> Result<(), String> {
+		if self.name.is_empty() || self.id.is_empty() {
+			return Err("The name and the id of the chain must not be empty".into())
+		}
+		if self.relay_chain.is_empty() {
+			return Err("The relay chain must not be empty".into())
+		}
+		if self.collators.is_empty() {
+			return Err("At least one collator is required to produce blocks".into())
+		}
+
+		let mut collators = BTreeSet::new();
+		for collator in &self.collators {
+			let account = account_id(&collator.account)?;
+			if let Some(aura) = &collator.aura {
+				parse_account(aura)?;
+			}
+			if !collators.insert(account) {
+				return Err(format!("Duplicate collator '{}'", collator.account))
+			}
+		}
+
+		if let Some(sudo) = &self.sudo {
+			account_id(sudo)?;
+		}
+
+		let mut endowed = BTreeSet::new();
+		for endowment in &self.endowed {
+			let account = account_id(&endowment.account)?;
+			if endowment.balance.map_or(DEFAULT_ENDOWMENT, |b| b.0) < EXISTENTIAL_DEPOSIT {
+				return Err(format!(
+					"Balance of '{}' is below the existential deposit of {}",
+					endowment.account, EXISTENTIAL_DEPOSIT,
+				))
+			}
+			if !endowed.insert(account) {
+				return Err(format!("Duplicate endowed account '{}'", endowment.account))
+			}
+		}
+
+		let mut asset_ids = BTreeSet::new();
+		let mut links = BTreeSet::new();
+		for asset in &self.assets {
+			if !asset_ids.insert(asset.id) {
+				return Err(format!("Duplicate asset {}", asset.id))
+			}
+			account_id(&asset.owner)?;
+			if asset.min_balance.0 == 0 {
+				return Err(format!("Minimum balance of asset {} must not be zero", asset.id))
+			}
+			if asset.name.len() > ASSET_STRING_LIMIT || asset.symbol.len() > ASSET_STRING_LIMIT {
+				return Err(format!(
+					"Name and symbol of asset {} must not be longer than {} bytes",
+					asset.id, ASSET_STRING_LIMIT,
+				))
+			}
+
+			let mut holders = BTreeSet::new();
+			for balance in &asset.balances {
+				let account = account_id(&balance.account)?;
+				if balance.amount.0 < asset.min_balance.0 {
+					return Err(format!(
+						"Balance of '{}' in asset {} is below the minimum balance",
+						balance.account, asset.id,
+					))
+				}
+				if !asset.sufficient && !endowed.contains(&account) {
+					return Err(format!(
+						"'{}' must be endowed to hold the insufficient asset {}",
+						balance.account, asset.id,
+					))
+				}
+				if !holders.insert(account) {
+					return Err(format!(
+						"Duplicate balance of '{}' in asset {}",
+						balance.account, asset.id,
+					))
+				}
+			}
+
+			if let Some(link) = &asset.link {
+				if !links.insert(link.location().encode()) {
+					return Err(format!(
+						"Asset {} is linked to an already linked location",
+						asset.id
+					))
+				}
+			}
+		}
+
+		for boot_node in &self.boot_nodes {
+			boot_node
+				.parse::<sc_service::config::MultiaddrWithPeerId>()
+				.map_err(|e| format!("Invalid boot node '{}': {}", boot_node, e))?;
+		}
+
+		Ok(())
+	}
+
+	/// Build the genesis config described by the preset.
+	fn genesis(&self) -> Result<infra_asset_system_runtime::GenesisConfig, String> {
+		let invulnerables = self
+			.collators
+			.iter()
+			.map(|collator| {
+				let account = parse_account(&collator.account)?;
+				let aura = match &collator.aura {
+					Some(aura) => parse_account(aura)?,
+					None => account,
+				};
+				Ok((account.into(), AuraId::from(aura)))
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+
+		let mut genesis =
+			infra_asset_system_genesis(invulnerables, Vec::new(), self.para_id.into());
+
+		genesis.balances.balances = self
+			.endowed
+			.iter()
+			.map(|endowment| {
+				Ok((
+					account_id(&endowment.account)?,
+					endowment.balance.map_or(DEFAULT_ENDOWMENT, |b| b.0),
+				))
+			})
+			.collect::<Result<_, String>>()?;
+		genesis.sudo.key = self.sudo.as_deref().map(account_id).transpose()?;
+
+		genesis.assets.assets = Vec::new();
+		genesis.assets.metadata = Vec::new();
+		genesis.assets.accounts = Vec::new();
+		for asset in &self.assets {
+			genesis.assets.assets.push((
+				asset.id,
+				account_id(&asset.owner)?,
+				asset.sufficient,
+				asset.min_balance.0,
+			));
+			genesis.assets.metadata.push((
+				asset.id,
+				asset.name.clone().into_bytes(),
+				asset.symbol.clone().into_bytes(),
+				asset.decimals,
+			));
+			for balance in &asset.balances {
+				genesis.assets.accounts.push((
+					asset.id,
+					account_id(&balance.account)?,
+					balance.amount.0,
+				));
+			}
+		}
+
+		Ok(genesis)
+	}
+
+	/// Build the raw chain spec described by the preset.
+	///
+	/// The preset is validated first.
+	pub fn build_raw_chain_spec(&self) -> Result<String, String> {
+		self.validate()?;
+
+		let genesis = self.genesis()?;
+		let properties = self.properties.as_ref().map(|properties| {
+			let mut props = sc_chain_spec::Properties::new();
+			props.insert("ss58Format".into(), properties.ss58_format.into());
+			props.insert("tokenSymbol".into(), properties.token_symbol.clone().into());
+			props.insert("tokenDecimals".into(), properties.token_decimals.into());
+			props
+		});
+		let boot_nodes = self
+			.boot_nodes
+			.iter()
+			.map(|boot_node| boot_node.parse().map_err(|e| format!("{}", e)))
+			.collect::<Result<_, String>>()?;
+		let chain_type = match self.chain_type {
+			PresetChainType::Development => ChainType::Development,
+			PresetChainType::Local => ChainType::Local,
+			PresetChainType::Live => ChainType::Live,
+		};
+
+		let mut chain_spec = InfraAssetSystemChainSpec::from_genesis(
+			&self.name,
+			&self.id,
+			chain_type,
+			move || genesis.clone(),
+			boot_nodes,
+			None,
+			None,
+			None,
+			properties,
+			Extensions { relay_chain: self.relay_chain.clone(), para_id: self.para_id },
+		);
+
+		let mut storage = chain_spec.build_storage()?;
+		for asset in &self.assets {
+			if let Some(link) = &asset.link {
+				link_system_token(&mut storage, asset.id, link.location());
+			}
+		}
+		chain_spec.set_storage(storage);
+
+		chain_spec.as_json(true)
+	}
+}
+
+/// Link `asset_id` to the system token at `location` in the storage of `pallet_asset_link`.
+///
+/// The pallet has no genesis config, thus its maps are written into the built storage.
+fn link_system_token(
+	storage: &mut sp_core::storage::Storage,
+	asset_id: AssetId,
+	location: MultiLocation,
+) {
+	use infra_asset_system_runtime::Runtime;
+
+	sp_state_machine::BasicExternalities::execute_with_storage(storage, || {
+		pallet_asset_link::AssetIdMultiLocation::<Runtime>::insert(asset_id, location);
+		pallet_asset_link::AssetMultiLocationId::<Runtime>::insert(location, asset_id);
+	});
+}
@@ -14,7 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::path::PathBuf;
+use crate::service::{AuthoringOptions, DevSeal};
+use cumulus_client_collator::{
+	CollationArchiveConfig, LeaderLeaseConfig, MIN_LEADER_LEASE_DURATION,
+};
+use cumulus_client_consensus_aura::RelayParentStalenessConfig;
+use cumulus_client_consensus_common::{ForkPruning, LevelLimit, PruningPolicy, MAX_INCLUDED_DEPTH};
+use cumulus_primitives_core::relay_chain::{BlockNumber as RelayBlockNumber, Hash as PHash};
+use std::{path::PathBuf, time::Duration};
 
 /// Sub-commands supported by the collator.
 #[derive(Debug, clap::Subcommand)]
@@ -26,12 +33,25 @@ pub enum Subcommand {
 	/// Build a chain specification.
 	BuildSpec(sc_cli::BuildSpecCmd),
 
+	/// Build a raw chain specification from a genesis preset.
+	BuildGenesis(BuildGenesisCmd),
+
+	/// List the runtimes supported by this node.
+	ListRuntimes,
+
+	/// Replay a PoV through the `validate_block` function of the runtime, like the relay chain
+	/// validators do.
+	ReplayPov(ReplayPovCmd),
+
 	/// Validate blocks.
 	CheckBlock(sc_cli::CheckBlockCmd),
 
 	/// Export blocks.
 	ExportBlocks(sc_cli::ExportBlocksCmd),
 
+	/// Export the collations archived with `--collation-archive` as JSON lines.
+	ExportCollations(ExportCollationsCmd),
+
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
@@ -77,6 +97,18 @@ pub struct Cli {
 	#[command(flatten)]
 	pub run: cumulus_client_cli::RunCmd,
 
+	#[command(flatten)]
+	pub fork_pruning: ForkPruningParams,
+
+	#[command(flatten)]
+	pub dev_seal: DevSealParams,
+
+	#[command(flatten)]
+	pub collation_archive: CollationArchiveParams,
+
+	#[command(flatten)]
+	pub authoring: AuthoringParams,
+
 	/// Disable automatic hardware benchmarks.
 	///
 	/// By default these benchmarks are automatically ran at startup and measure
@@ -87,11 +119,364 @@ pub struct Cli {
 	#[arg(long)]
 	pub no_hardware_benchmarks: bool,
 
+	/// Notify parachain blocks included this number of relay chain blocks deep.
+	///
+	/// The notifications are served by the `cumulus_subscribeProvisionalFinality` RPC
+	/// subscription. This is not finality: the notified blocks may still be reverted by a relay
+	/// chain reorganization deeper than the given number of blocks. At most
+	/// `MAX_INCLUDED_DEPTH` (256) blocks deep are supported.
+	#[arg(
+		long,
+		value_name = "DEPTH",
+		value_parser = clap::value_parser!(u32).range(..=MAX_INCLUDED_DEPTH as i64)
+	)]
+	pub provisional_finality_depth: Option<u32>,
+
+	/// Maximum share in percent of the ready transaction queue occupied by the transactions paying
+	/// their fee with the same asset.
+	///
+	/// Once exceeded, the transactions of lowest priority paying with that asset are dropped from
+	/// the pool, so that a flood of transactions paying with one system token can't crowd out the
+	/// others. Only supported by the asset system runtime.
+	#[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100))]
+	pub fee_asset_pool_share: Option<u8>,
+
 	/// Relay chain arguments
 	#[arg(raw = true)]
 	pub relaychain_args: Vec<String>,
 }
 
+/// The `build-genesis` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct BuildGenesisCmd {
+	/// Genesis preset of the asset system, in TOML (`.toml` extension) or JSON format.
+	///
+	/// See `infrablockspace-parachain/presets` for examples.
+	#[arg(value_name = "PRESET")]
+	pub preset: PathBuf,
+
+	/// Output file name or stdout if unspecified.
+	#[arg(long, short)]
+	pub output: Option<PathBuf>,
+
+	/// Only validate the preset, without building the chain specification.
+	#[arg(long)]
+	pub check: bool,
+}
+
+/// The `replay-pov` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ReplayPovCmd {
+	/// The PoV to replay: the SCALE encoded `ParachainBlockData`, optionally compressed.
+	///
+	/// The file contains either the raw bytes or a `0x` prefixed hex string.
+	#[arg(value_name = "POV")]
+	pub pov: PathBuf,
+
+	/// Head data of the parent block, as a `0x` prefixed hex string or a path to a file.
+	#[arg(long, value_name = "HEX|FILE")]
+	pub parent_head: String,
+
+	/// Number of the relay parent of the candidate.
+	#[arg(long, value_name = "NUMBER")]
+	pub relay_parent_number: RelayBlockNumber,
+
+	/// Storage root of the relay parent of the candidate.
+	#[arg(long, value_name = "HASH")]
+	pub relay_parent_storage_root: PHash,
+
+	/// Runtime code validating the PoV, e.g. the validation code registered on the relay chain.
+	///
+	/// Defaults to the code of `--runtime` compiled into this node.
+	#[arg(long, value_name = "FILE")]
+	pub wasm: Option<PathBuf>,
+
+	/// `spec_name` of the runtime of the parachain.
+	///
+	/// Only required without `--wasm`, the runtime is otherwise resolved from the given code.
+	/// See the `list-runtimes` sub-command.
+	#[arg(long, value_name = "SPEC_NAME")]
+	pub runtime: Option<String>,
+
+	/// Expected SCALE encoded `vote_result` of the candidate, as a `0x` prefixed hex string or a
+	/// path to a file.
+	#[arg(long, value_name = "HEX|FILE")]
+	pub expected_vote_result: Option<String>,
+
+	/// Number of 64KiB pages of the runtime heap.
+	#[arg(long, value_name = "COUNT", default_value_t = 2080)]
+	pub heap_pages: u64,
+
+	/// Method for executing the runtime code.
+	#[arg(
+		long,
+		value_enum,
+		value_name = "METHOD",
+		default_value_t = sc_cli::WasmExecutionMethod::Compiled
+	)]
+	pub wasm_execution: sc_cli::WasmExecutionMethod,
+}
+
+/// The `export-collations` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportCollationsCmd {
+	/// Number of the first block to export.
+	#[arg(long, value_name = "BLOCK", default_value_t = 0)]
+	pub from: u64,
+
+	/// Number of the last block to export, the last archived block if unspecified.
+	#[arg(long, value_name = "BLOCK")]
+	pub to: Option<u64>,
+
+	/// Output file name or stdout if unspecified.
+	#[arg(long, short)]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+}
+
+impl sc_cli::CliConfiguration for ExportCollationsCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+}
+
+/// Returns the directory of the collation archive of the node with the given `config`.
+pub fn collation_archive_path(config: &sc_service::Configuration) -> Option<PathBuf> {
+	config
+		.base_path
+		.as_ref()
+		.map(|base_path| base_path.config_dir(config.chain_spec.id()).join("collations"))
+}
+
+/// Parameters of the block authoring of a collator.
+#[derive(Debug, Clone, clap::Args)]
+pub struct AuthoringParams {
+	/// Skip authoring when the relay parent lags more than this number of blocks behind the best
+	/// relay chain block.
+	///
+	/// Candidates built on such stale relay parents, e.g. over a slow relay chain RPC connection,
+	/// are never backed.
+	#[arg(long, value_name = "BLOCKS")]
+	pub max_relay_parent_lag: Option<u32>,
+
+	/// Maximum number of collation requests skipped after consecutive stale relay parents.
+	///
+	/// The number of skipped requests doubles with each consecutive stale relay parent. With `0`,
+	/// only the requests on a stale relay parent are skipped. Only used with
+	/// `--max-relay-parent-lag`.
+	#[arg(long, value_name = "COUNT", default_value_t = 0)]
+	pub stale_relay_parent_backoff: u32,
+
+	/// Slow down authoring when the finalized block lags behind the best block.
+	#[arg(long)]
+	pub backoff_authoring_blocks: bool,
+
+	/// Only author while holding the leader lease stored in this file.
+	///
+	/// Collators running with the same keys and sharing the lease file author one at a time: the
+	/// standby collators keep syncing and take over authoring once the lease of the holder expires
+	/// or is released on shutdown. The file needs to be on a file system shared by the collators
+	/// and their clocks need to be synchronized.
+	#[arg(long, value_name = "PATH")]
+	pub leader_lease: Option<PathBuf>,
+
+	/// Duration of the leader lease in seconds.
+	///
+	/// A standby collator takes over authoring at most this long after the holder stopped renewing
+	/// the lease. At least 3 seconds, only used with `--leader-lease`.
+	#[arg(
+		long,
+		value_name = "SECONDS",
+		default_value_t = 30,
+		value_parser = clap::value_parser!(u64).range(MIN_LEADER_LEASE_DURATION.as_secs()..),
+	)]
+	pub leader_lease_duration: u64,
+
+	/// Name of this collator in the leader lease, unique among the collators sharing the lease.
+	///
+	/// Defaults to the peer id of the node. Only used with `--leader-lease`.
+	#[arg(long, value_name = "NAME")]
+	pub leader_lease_holder: Option<String>,
+}
+
+impl AuthoringParams {
+	/// Returns the authoring options of the collator.
+	pub fn authoring_options(
+		&self,
+		config: &sc_service::Configuration,
+	) -> sc_cli::Result<AuthoringOptions> {
+		let leader_lease = match &self.leader_lease {
+			Some(path) => {
+				let holder = match &self.leader_lease_holder {
+					Some(holder) => holder.clone(),
+					// Node names are not unique, peer ids are.
+					None => config
+						.network
+						.node_key
+						.clone()
+						.into_keypair()?
+						.public()
+						.to_peer_id()
+						.to_base58(),
+				};
+				Some(LeaderLeaseConfig {
+					path: path.clone(),
+					holder,
+					duration: Duration::from_secs(self.leader_lease_duration),
+				})
+			},
+			None => None,
+		};
+
+		Ok(AuthoringOptions {
+			relay_parent_staleness: self.max_relay_parent_lag.map(|max_lag| {
+				RelayParentStalenessConfig { max_lag, max_backoff: self.stale_relay_parent_backoff }
+			}),
+			backoff_authoring_blocks: self.backoff_authoring_blocks,
+			leader_lease,
+		})
+	}
+}
+
+/// Parameters of the on-disk archive of the built collations.
+#[derive(Debug, Clone, clap::Args)]
+pub struct CollationArchiveParams {
+	/// Archive the collations built by this collator on disk.
+	///
+	/// The header, PoV hash, relay parent and collation info (including the vote result) of each
+	/// collation are kept in `<base-path>/chains/<chain>/collations` and can be exported with the
+	/// `export-collations` sub-command.
+	#[arg(long)]
+	pub collation_archive: bool,
+
+	/// Size in MiB after which a new segment of the collation archive is started.
+	#[arg(long, value_name = "MIB", default_value_t = 64)]
+	pub collation_archive_segment_size: u64,
+
+	/// Maximum number of segments of the collation archive, the oldest are removed first.
+	#[arg(long, value_name = "COUNT", default_value_t = 16)]
+	pub collation_archive_max_segments: usize,
+}
+
+impl CollationArchiveParams {
+	/// Returns the configuration of the collation archive, if enabled.
+	pub fn config(&self, config: &sc_service::Configuration) -> Option<CollationArchiveConfig> {
+		if !self.collation_archive {
+			return None
+		}
+
+		collation_archive_path(config).map(|path| CollationArchiveConfig {
+			path,
+			max_segment_size: self.collation_archive_segment_size * 1024 * 1024,
+			max_segments: self.collation_archive_max_segments,
+		})
+	}
+}
+
+/// Strategy used to choose the forks to remove when a blockchain level is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ForkPruningPolicy {
+	/// Remove the least recently extended forks.
+	LeastFresh,
+	/// Keep the forks containing blocks authored by this node if possible.
+	KeepOwn,
+	/// Keep the forks containing blocks backed by the relay chain if possible.
+	KeepBacked,
+	/// Like `least-fresh`, but additionally remove forks not extended for `--fork-max-age` imports.
+	MaxAge,
+}
+
+/// Parameters to control the pruning of parachain forks.
+#[derive(Debug, Clone, clap::Args)]
+pub struct ForkPruningParams {
+	/// Maximum number of leaves for each level of the blockchain.
+	///
+	/// When the limit is reached, forks are removed according to `--fork-pruning`.
+	/// Use `0` to disable the limit (and the fork pruning).
+	#[arg(long, value_name = "COUNT")]
+	pub max_leaves_per_level: Option<usize>,
+
+	/// Strategy used to choose the forks to remove.
+	#[arg(
+		long,
+		value_enum,
+		value_name = "POLICY",
+		default_value_t = ForkPruningPolicy::LeastFresh
+	)]
+	pub fork_pruning: ForkPruningPolicy,
+
+	/// Number of block imports after which a fork that was not extended is removed.
+	///
+	/// Only used with `--fork-pruning max-age`.
+	#[arg(long, value_name = "IMPORTS", default_value_t = 256)]
+	pub fork_max_age: u32,
+}
+
+impl ForkPruningParams {
+	/// Returns the fork pruning configuration of the parachain block import.
+	pub fn fork_pruning(&self) -> ForkPruning {
+		let level_limit = match self.max_leaves_per_level {
+			None => LevelLimit::Default,
+			Some(0) => LevelLimit::None,
+			Some(limit) => LevelLimit::Some(limit),
+		};
+		let policy = match self.fork_pruning {
+			ForkPruningPolicy::LeastFresh => PruningPolicy::LeastFresh,
+			ForkPruningPolicy::KeepOwn => PruningPolicy::KeepOwn,
+			ForkPruningPolicy::KeepBacked => PruningPolicy::KeepBacked,
+			ForkPruningPolicy::MaxAge => PruningPolicy::MaxAge(self.fork_max_age),
+		};
+
+		ForkPruning { level_limit, policy }
+	}
+}
+
+/// Block production mode of a node running without a relay chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DevSealMode {
+	/// Seal a block as soon as a transaction is imported into the pool.
+	Instant,
+	/// Seal a block every `--dev-seal-interval` milliseconds.
+	Interval,
+}
+
+/// Parameters to run the parachain runtime without a relay chain.
+#[derive(Debug, Clone, clap::Args)]
+pub struct DevSealParams {
+	/// Run the parachain runtime standalone, without connecting to a relay chain.
+	///
+	/// Blocks are authored and finalized locally, using mocked relay chain validation data.
+	/// Blocks can also be created on demand with the `engine_createBlock` RPC.
+	/// The relay chain arguments are ignored.
+	#[arg(long, value_enum, value_name = "MODE")]
+	pub dev_seal: Option<DevSealMode>,
+
+	/// Interval in milliseconds between blocks.
+	///
+	/// At least 1 millisecond, only used with `--dev-seal interval`.
+	#[arg(
+		long,
+		value_name = "MILLISECONDS",
+		default_value_t = 6000,
+		value_parser = clap::value_parser!(u64).range(1..),
+	)]
+	pub dev_seal_interval: u64,
+}
+
+impl DevSealParams {
+	/// Returns the block production of the node, if it runs without a relay chain.
+	pub fn dev_seal(&self) -> Option<DevSeal> {
+		self.dev_seal.map(|mode| match mode {
+			DevSealMode::Instant => DevSeal::Instant,
+			DevSealMode::Interval =>
+				DevSeal::Interval(Duration::from_millis(self.dev_seal_interval)),
+		})
+	}
+}
+
 #[derive(Debug)]
 pub struct RelayChainCli {
 	/// The actual relay chain cli object.
@@ -16,11 +16,13 @@
 
 use crate::{
 	chain_spec,
-	cli::{Cli, RelayChainCli, Subcommand},
-	service::{new_partial, Block, InfraAssetSytemExecutor},
+	cli::{collation_archive_path, Cli, RelayChainCli, Subcommand},
+	fee_asset_reservation::{self, FeeAssetReservation},
+	service::{new_partial, Block, InfraAssetSytemExecutor, ParachainTemplateExecutor},
 };
 use codec::Encode;
 use cumulus_client_cli::generate_genesis_block;
+use cumulus_client_collator::CollationArchive;
 use cumulus_primitives_core::ParaId;
 use frame_benchmarking_cli::{BenchmarkCmd, SUBSTRATE_REFERENCE_HARDWARE};
 use log::{info, warn};
@@ -29,100 +31,228 @@ use sc_cli::{
 	ChainSpec, CliConfiguration, DefaultConfigurationValues, ImportParams, KeystoreParams,
 	NetworkParams, Result, RuntimeVersion, SharedParams, SubstrateCli,
 };
+use sc_executor_common::runtime_blob::RuntimeBlob;
 use sc_service::config::{BasePath, PrometheusConfig};
-use sp_core::hexdisplay::HexDisplay;
-use sp_runtime::traits::{AccountIdConversion, Block as BlockT};
-use std::{net::SocketAddr, path::PathBuf};
+use sp_core::{hexdisplay::HexDisplay, storage::well_known_keys};
+use sp_runtime::{
+	traits::{AccountIdConversion, Block as BlockT, Header as HeaderT},
+	Percent,
+};
+use std::{
+	collections::BTreeMap,
+	io::Write,
+	net::SocketAddr,
+	path::PathBuf,
+	sync::{Mutex, PoisonError},
+};
 
-/// Helper enum that is used for better distinction of different parachain/runtime configuration
-/// (it is based/calculated on ChainSpec's ID attribute)
-#[derive(Debug, PartialEq, Default)]
+/// The runtimes supported by this node.
+///
+/// The runtime of a chain spec is resolved from the `spec_name` of the runtime version embedded
+/// in its genesis `:code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Runtime {
-	/// This is the default runtime (actually based on rococo)
-	#[default]
-	Default,
 	InfraAssetSystem,
+	ParachainTemplate,
 }
 
-trait RuntimeResolver {
-	fn runtime(&self) -> Runtime;
-}
+impl Runtime {
+	/// All the supported runtimes.
+	const ALL: [Runtime; 2] = [Runtime::InfraAssetSystem, Runtime::ParachainTemplate];
 
-impl RuntimeResolver for dyn ChainSpec {
-	fn runtime(&self) -> Runtime {
-		runtime(self.id())
+	/// Version of the runtime compiled into this node.
+	fn native_version(&self) -> &'static RuntimeVersion {
+		match self {
+			Runtime::InfraAssetSystem => &infra_asset_system_runtime::VERSION,
+			Runtime::ParachainTemplate => &parachain_template_runtime::VERSION,
+		}
+	}
+
+	/// Short description of the runtime.
+	fn description(&self) -> &'static str {
+		match self {
+			Runtime::InfraAssetSystem => "Asset system parachain of InfraBlockspace",
+			Runtime::ParachainTemplate => "Template parachain with system token fee payment",
+		}
+	}
+
+	/// Find the runtime with the given `spec_name`.
+	fn from_spec_name(spec_name: &str) -> std::result::Result<Self, String> {
+		Self::ALL
+			.into_iter()
+			.find(|runtime| &*runtime.native_version().spec_name == spec_name)
+			.ok_or_else(|| {
+				format!(
+					"Unsupported runtime '{}', the supported runtimes are: {}. \
+					See the `list-runtimes` sub-command.",
+					spec_name,
+					Self::ALL
+						.iter()
+						.map(|runtime| format!("'{}'", runtime.native_version().spec_name))
+						.collect::<Vec<_>>()
+						.join(", "),
+				)
+			})
+	}
+
+	/// Resolve the runtime of the given JSON chain spec.
+	fn from_chain_spec_json(chain_spec: &serde_json::Value) -> std::result::Result<Self, String> {
+		let genesis = &chain_spec["genesis"];
+		let code = genesis["raw"]["top"][format!("0x{}", HexDisplay::from(&well_known_keys::CODE))]
+			.as_str()
+			.or_else(|| genesis["runtime"]["system"]["code"].as_str())
+			.ok_or("The chain spec has no genesis runtime code")?;
+		let code = sp_core::bytes::from_hex(code)
+			.map_err(|e| format!("Invalid genesis runtime code: {}", e))?;
+
+		Self::from_code(&code)
+	}
+
+	/// Resolve the runtime of the given runtime code.
+	fn from_code(code: &[u8]) -> std::result::Result<Self, String> {
+		let blob = RuntimeBlob::uncompress_if_needed(code)
+			.map_err(|e| format!("Invalid runtime code: {}", e))?;
+		let version = sc_executor::read_embedded_version(&blob)
+			.map_err(|e| format!("Failed to read the runtime version: {}", e))?
+			.ok_or("The runtime code has no embedded runtime version")?;
+
+		Self::from_spec_name(&version.spec_name)
+	}
+
+	/// Code of the runtime compiled into this node.
+	fn wasm_binary(&self) -> std::result::Result<&'static [u8], String> {
+		match self {
+			Runtime::InfraAssetSystem => infra_asset_system_runtime::WASM_BINARY,
+			Runtime::ParachainTemplate => parachain_template_runtime::WASM_BINARY,
+		}
+		.ok_or_else(|| {
+			format!(
+				"The '{}' runtime code is not available, the node was built without it",
+				self.native_version().spec_name,
+			)
+		})
 	}
 }
 
-/// Implementation, that can resolve [`Runtime`] from any json configuration file
-impl RuntimeResolver for PathBuf {
-	fn runtime(&self) -> Runtime {
-		#[derive(Debug, serde::Deserialize)]
-		struct EmptyChainSpecWithId {
-			id: String,
+/// Runtimes of the chain specs resolved so far, by chain spec id.
+///
+/// Resolving the runtime of a chain spec serializes it and reads the version embedded in its
+/// genesis code, thus it is done once per chain spec.
+static RESOLVED_RUNTIMES: Mutex<BTreeMap<String, Runtime>> = Mutex::new(BTreeMap::new());
+
+/// Remember that the chain spec `id` uses `runtime`.
+fn note_runtime(id: &str, runtime: Runtime) {
+	RESOLVED_RUNTIMES
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+		.insert(id.into(), runtime);
+}
+
+trait RuntimeResolver {
+	fn runtime(&self) -> std::result::Result<Runtime, String>;
+}
+
+impl RuntimeResolver for dyn ChainSpec {
+	fn runtime(&self) -> std::result::Result<Runtime, String> {
+		let resolved = RESOLVED_RUNTIMES
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.get(self.id())
+			.copied();
+		if let Some(runtime) = resolved {
+			return Ok(runtime)
 		}
 
-		let file = std::fs::File::open(self).expect("Failed to open file");
-		let reader = std::io::BufReader::new(file);
-		let chain_spec: EmptyChainSpecWithId = sp_serializer::from_reader(reader)
-			.expect("Failed to read 'json' file with ChainSpec configuration");
+		let chain_spec = serde_json::from_str(&self.as_json(false)?)
+			.map_err(|e| format!("Failed to read the chain spec: {}", e))?;
+		let runtime = Runtime::from_chain_spec_json(&chain_spec)?;
+		note_runtime(self.id(), runtime);
 
-		runtime(&chain_spec.id)
+		Ok(runtime)
 	}
 }
 
-fn runtime(id: &str) -> Runtime {
-	let id = id.replace("_", "-");
-	let (_, id, _) = extract_parachain_id(&id);
+/// Implementation, that can resolve [`Runtime`] from any json configuration file
+impl RuntimeResolver for PathBuf {
+	fn runtime(&self) -> std::result::Result<Runtime, String> {
+		let file = std::fs::File::open(self)
+			.map_err(|e| format!("Failed to open '{}': {}", self.display(), e))?;
+		let chain_spec = serde_json::from_reader(std::io::BufReader::new(file))
+			.map_err(|e| format!("Failed to read the chain spec '{}': {}", self.display(), e))?;
 
-	if id.starts_with("infra-asset-system") {
-		Runtime::InfraAssetSystem
-	} else {
-		log::warn!("No specific runtime was recognized for ChainSpec's id: '{}', so Runtime::default() will be used", id);
-		Runtime::default()
+		Runtime::from_chain_spec_json(&chain_spec)
 	}
 }
 
+/// Load the chain spec `id`, and remember its runtime.
+///
+/// Fails for chain specs of runtimes not supported by this node.
 fn load_spec(id: &str) -> std::result::Result<Box<dyn ChainSpec>, String> {
 	let (id, _, _) = extract_parachain_id(id);
-	Ok(match id {
+	let (chain_spec, runtime): (Box<dyn ChainSpec>, Runtime) = match id {
 		// -- Infra Asset System
-		"infra-asset-system-dev" =>
+		"infra-asset-system-dev" => (
 			Box::new(chain_spec::infra_asset_system::infra_asset_system_development_config()),
-		"infra-asset-system-local" =>
+			Runtime::InfraAssetSystem,
+		),
+		"infra-asset-system-local" => (
 			Box::new(chain_spec::infra_asset_system::infra_asset_system_local_config()),
+			Runtime::InfraAssetSystem,
+		),
 		// the chain spec as used for generating the upgrade genesis values
-		"infra-asset-system-genesis" =>
+		"infra-asset-system-genesis" => (
 			Box::new(chain_spec::infra_asset_system::infra_asset_system_config()),
-		// the shell-based chain spec as used for syncing
+			Runtime::InfraAssetSystem,
+		),
+		// the shell-based chain spec as used for syncing, whose genesis code is not resolvable
 		// ToDo: change to infra asset system
-		"infra-asset-system" =>
+		"infra-asset-system" => (
 			Box::new(chain_spec::infra_asset_system::InfraAssetSystemChainSpec::from_json_bytes(
 				&include_bytes!("../../parachains/chain-specs/statemine.json")[..],
 			)?),
+			Runtime::InfraAssetSystem,
+		),
+		// -- Parachain Template
+		"parachain-template-dev" => (
+			Box::new(chain_spec::parachain_template::parachain_template_development_config()),
+			Runtime::ParachainTemplate,
+		),
+		"parachain-template-local" => (
+			Box::new(chain_spec::parachain_template::parachain_template_local_config()),
+			Runtime::ParachainTemplate,
+		),
 		// -- Fallback (generic chainspec)
 		"" => {
 			log::warn!("No ChainSpec.id specified, so using default one, based on infra-asset-system runtime");
-			Box::new(chain_spec::infra_asset_system::infra_asset_system_local_config())
+			(
+				Box::new(chain_spec::infra_asset_system::infra_asset_system_local_config()),
+				Runtime::InfraAssetSystem,
+			)
 		},
 
 		// -- Loading a specific spec from disk
 		path => {
 			let path: PathBuf = path.into();
-			match path.runtime() {
+			let runtime = path.runtime()?;
+			let chain_spec: Box<dyn ChainSpec> = match runtime {
 				Runtime::InfraAssetSystem => Box::new(
 					chain_spec::infra_asset_system::InfraAssetSystemChainSpec::from_json_file(
 						path,
 					)?,
 				),
-				Runtime::Default => Box::new(
-					chain_spec::infra_asset_system::InfraAssetSystemChainSpec::from_json_file(
+				Runtime::ParachainTemplate => Box::new(
+					chain_spec::parachain_template::ParachainTemplateChainSpec::from_json_file(
 						path,
 					)?,
 				),
-			}
+			};
+			(chain_spec, runtime)
 		},
-	})
+	};
+
+	note_runtime(chain_spec.id(), runtime);
+
+	Ok(chain_spec)
 }
 
 /// Extracts the normalized chain id and parachain id from the input chain id.
@@ -184,8 +314,13 @@ impl SubstrateCli for Cli {
 
 	fn native_runtime_version(chain_spec: &Box<dyn ChainSpec>) -> &'static RuntimeVersion {
 		match chain_spec.runtime() {
-			Runtime::InfraAssetSystem => &infra_asset_system_runtime::VERSION,
-			Runtime::Default => &infra_asset_system_runtime::VERSION,
+			Ok(runtime) => runtime.native_version(),
+			// `load_spec` already failed for chain specs of unsupported runtimes, and this can not
+			// fail, thus only chain specs not loaded by `load_spec` end up here.
+			Err(error) => {
+				warn!("{}, reporting the version of the default runtime", error);
+				Runtime::InfraAssetSystem.native_version()
+			},
 		}
 	}
 }
@@ -233,15 +368,23 @@ impl SubstrateCli for RelayChainCli {
 /// Creates partial components for the runtimes that are supported by the benchmarks.
 macro_rules! construct_benchmark_partials {
 	($config:expr, |$partials:ident| $code:expr) => {
-		match $config.chain_spec.runtime() {
+		match $config.chain_spec.runtime()? {
 			Runtime::InfraAssetSystem => {
 				let $partials = new_partial::<infra_asset_system_runtime::RuntimeApi, _>(
 					&$config,
+					Default::default(),
+					crate::service::aura_build_import_queue::<_, AuraId>,
+				)?;
+				$code
+			},
+			Runtime::ParachainTemplate => {
+				let $partials = new_partial::<parachain_template_runtime::RuntimeApi, _>(
+					&$config,
+					Default::default(),
 					crate::service::aura_build_import_queue::<_, AuraId>,
 				)?;
 				$code
 			},
-			_ => Err("The chain is not supported".into()),
 		}
 	};
 }
@@ -249,21 +392,23 @@ macro_rules! construct_benchmark_partials {
 macro_rules! construct_async_run {
 	(|$components:ident, $cli:ident, $cmd:ident, $config:ident| $( $code:tt )* ) => {{
 		let runner = $cli.create_runner($cmd)?;
-		match runner.config().chain_spec.runtime() {
-			Runtime::Default => {
+		match runner.config().chain_spec.runtime()? {
+			Runtime::InfraAssetSystem => {
 				runner.async_run(|$config| {
 					let $components = new_partial::<infra_asset_system_runtime::RuntimeApi, _>(
 						&$config,
+						Default::default(),
 						crate::service::aura_build_import_queue::<_, AuraId>,
 					)?;
 					let task_manager = $components.task_manager;
 					{ $( $code )* }.map(|v| (v, task_manager))
 				})
 			},
-			Runtime::InfraAssetSystem => {
+			Runtime::ParachainTemplate => {
 				runner.async_run(|$config| {
-					let $components = new_partial::<infra_asset_system_runtime::RuntimeApi, _>(
+					let $components = new_partial::<parachain_template_runtime::RuntimeApi, _>(
 						&$config,
+						Default::default(),
 						crate::service::aura_build_import_queue::<_, AuraId>,
 					)?;
 					let task_manager = $components.task_manager;
@@ -283,6 +428,59 @@ pub fn run() -> Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
 		},
+		Some(Subcommand::ListRuntimes) => {
+			for runtime in Runtime::ALL {
+				let version = runtime.native_version();
+				println!(
+					"{} (spec version {}): {}",
+					version.spec_name,
+					version.spec_version,
+					runtime.description(),
+				);
+			}
+
+			Ok(())
+		},
+		Some(Subcommand::BuildGenesis(cmd)) => {
+			let preset = chain_spec::preset::GenesisPreset::load(&cmd.preset)?;
+			if cmd.check {
+				return preset.validate().map_err(Into::into)
+			}
+
+			let raw_chain_spec = preset.build_raw_chain_spec()?;
+			match &cmd.output {
+				Some(output) => std::fs::write(output, raw_chain_spec)?,
+				None => std::io::stdout().write_all(raw_chain_spec.as_bytes())?,
+			}
+
+			Ok(())
+		},
+		Some(Subcommand::ReplayPov(cmd)) => {
+			let runtime = cmd.runtime.as_deref().map(Runtime::from_spec_name).transpose()?;
+			let (runtime, code) = match (&cmd.wasm, runtime) {
+				(Some(path), runtime) => {
+					let code = std::fs::read(path)?;
+					let code_runtime = Runtime::from_code(&code)?;
+					if runtime.map_or(false, |runtime| runtime != code_runtime) {
+						return Err("The runtime of `--wasm` is not the one of `--runtime`".into())
+					}
+					(code_runtime, code)
+				},
+				(None, Some(runtime)) => (runtime, runtime.wasm_binary()?.to_vec()),
+				(None, None) => return Err("Either `--wasm` or `--runtime` is required".into()),
+			};
+
+			match runtime {
+				Runtime::InfraAssetSystem => cmd
+					.run::<infra_asset_system_runtime::UncheckedExtrinsic, infra_asset_system_runtime::Runtime>(
+						&code,
+					),
+				Runtime::ParachainTemplate => cmd
+					.run::<parachain_template_runtime::UncheckedExtrinsic, parachain_template_runtime::Runtime>(
+						&code,
+					),
+			}
+		},
 		Some(Subcommand::CheckBlock(cmd)) => {
 			construct_async_run!(|components, cli, cmd, config| {
 				Ok(cmd.run(components.client, components.import_queue))
@@ -293,6 +491,50 @@ pub fn run() -> Result<()> {
 				Ok(cmd.run(components.client, config.database))
 			})
 		},
+		Some(Subcommand::ExportCollations(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let path = collation_archive_path(&config)
+					.ok_or("The node has no base path, there is no collation archive")?;
+				if !path.is_dir() {
+					return Err(format!("No collation archive in '{}'", path.display()).into())
+				}
+				let collations =
+					CollationArchive::<Block>::read(&path, cmd.from..=cmd.to.unwrap_or(u64::MAX))?;
+
+				let hex = |bytes: &[u8]| format!("0x{}", HexDisplay::from(&bytes));
+				let mut output = String::new();
+				for collation in collations {
+					let info = &collation.collation_info;
+					let vote_result = info.vote_result.as_ref().map(|v| hex(&v.encode()));
+					let line = serde_json::json!({
+						"blockNumber": collation.block_number(),
+						"blockHash": format!("{:?}", collation.header.hash()),
+						"header": hex(&collation.header.encode()),
+						"relayParent": format!("{:?}", collation.relay_parent),
+						"povHash": format!("{:?}", collation.pov_hash),
+						"povSize": collation.pov_size,
+						"archivedAt": collation.archived_at,
+						"upwardMessages": info.upward_messages.len(),
+						"horizontalMessages": info.horizontal_messages.len(),
+						"newValidationCode": info.new_validation_code.is_some(),
+						"processedDownwardMessages": info.processed_downward_messages,
+						"hrmpWatermark": info.hrmp_watermark,
+						"voteResult": vote_result,
+						"collationInfo": hex(&info.encode()),
+					});
+					output.push_str(&line.to_string());
+					output.push('\n');
+				}
+
+				match &cmd.output {
+					Some(file) => std::fs::write(file, output)?,
+					None => std::io::stdout().write_all(output.as_bytes())?,
+				}
+
+				Ok(())
+			})
+		},
 		Some(Subcommand::ExportState(cmd)) => {
 			construct_async_run!(|components, cli, cmd, config| {
 				Ok(cmd.run(components.client, config.chain_spec))
@@ -347,14 +589,11 @@ pub fn run() -> Result<()> {
 			match cmd {
 				BenchmarkCmd::Pallet(cmd) =>
 					if cfg!(feature = "runtime-benchmarks") {
-						runner.sync_run(|config| match config.chain_spec.runtime() {
+						runner.sync_run(|config| match config.chain_spec.runtime()? {
 							Runtime::InfraAssetSystem =>
 								cmd.run::<Block, InfraAssetSytemExecutor>(config),
-							_ => Err(format!(
-								"Chain '{:?}' doesn't support benchmarking",
-								config.chain_spec.runtime()
-							)
-							.into()),
+							Runtime::ParachainTemplate =>
+								cmd.run::<Block, ParachainTemplateExecutor>(config),
 						})
 					} else {
 						Err("Benchmarking wasn't enabled when building the node. \
@@ -407,85 +646,23 @@ pub fn run() -> Result<()> {
 
 			let info_provider = timestamp_with_aura_info(6000);
 
-			match runner.config().chain_spec.runtime() {
-				Runtime::Statemine => runner.async_run(|_| {
-					Ok((
-						cmd.run::<Block, HostFunctionsOf<StatemineRuntimeExecutor>, _>(Some(
-							info_provider,
-						)),
-						task_manager,
-					))
-				}),
-				Runtime::Westmint => runner.async_run(|_| {
+			match runner.config().chain_spec.runtime()? {
+				Runtime::InfraAssetSystem => runner.async_run(|_| {
 					Ok((
-						cmd.run::<Block, HostFunctionsOf<WestmintRuntimeExecutor>, _>(Some(
+						cmd.run::<Block, HostFunctionsOf<InfraAssetSytemExecutor>, _>(Some(
 							info_provider,
 						)),
 						task_manager,
 					))
 				}),
-				Runtime::Statemint => runner.async_run(|_| {
+				Runtime::ParachainTemplate => runner.async_run(|_| {
 					Ok((
-						cmd.run::<Block, HostFunctionsOf<StatemintRuntimeExecutor>, _>(Some(
+						cmd.run::<Block, HostFunctionsOf<ParachainTemplateExecutor>, _>(Some(
 							info_provider,
 						)),
 						task_manager,
 					))
 				}),
-				Runtime::CollectivesPolkadot | Runtime::CollectivesWestend =>
-					runner.async_run(|_| {
-						Ok((
-							cmd.run::<Block, HostFunctionsOf<CollectivesPolkadotRuntimeExecutor>, _>(Some(info_provider)),
-							task_manager,
-						))
-					}),
-				Runtime::BridgeHub(bridge_hub_runtime_type) =>
-					match bridge_hub_runtime_type {
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::Polkadot |
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::PolkadotLocal |
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::PolkadotDevelopment =>
-							runner.async_run(|_| {
-								Ok((
-							cmd.run::<Block, HostFunctionsOf<BridgeHubPolkadotRuntimeExecutor>, _>(Some(info_provider)),
-							task_manager,
-						))
-							}),
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::Kusama |
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::KusamaLocal |
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::KusamaDevelopment => runner.async_run(|_| {
-							Ok((
-							cmd.run::<Block, HostFunctionsOf<BridgeHubKusamaRuntimeExecutor>, _>(Some(info_provider)),
-							task_manager,
-						))
-						}),
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::Rococo |
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::RococoLocal |
-						chain_spec::bridge_hubs::BridgeHubRuntimeType::RococoDevelopment => runner.async_run(|_| {
-							Ok((
-							cmd.run::<Block, HostFunctionsOf<BridgeHubRococoRuntimeExecutor>, _>(Some(info_provider)),
-							task_manager,
-						))
-						}),
-						_ => Err(format!(
-						"Chain '{:?}' doesn't support try-runtime for bridge_hub_runtime_type: {:?}",
-						runner.config().chain_spec.runtime(),
-						bridge_hub_runtime_type
-					)
-						.into()),
-					},
-				Runtime::Shell => runner.async_run(|_| {
-					Ok((
-						cmd.run::<Block, HostFunctionsOf<crate::service::ShellRuntimeExecutor>, _>(Some(info_provider)),
-						task_manager,
-					))
-				}),
-				Runtime::ContractsRococo => runner.async_run(|_| {
-					Ok((
-						cmd.run::<Block, HostFunctionsOf<crate::service::ContractsRococoRuntimeExecutor>, _>(Some(info_provider)),
-						task_manager,
-					))
-				}),
-				_ => Err("Chain doesn't support try-runtime".into()),
 			}
 		},
 		#[cfg(not(feature = "try-runtime"))]
@@ -496,6 +673,12 @@ pub fn run() -> Result<()> {
 		None => {
 			let runner = cli.create_runner(&cli.run.normalize())?;
 			let collator_options = cli.run.collator_options();
+			let fork_pruning = cli.fork_pruning.fork_pruning();
+			let provisional_finality_depth = cli.provisional_finality_depth;
+			let fee_asset_pool_share = cli.fee_asset_pool_share.map(Percent::from_percent);
+			let dev_seal = cli.dev_seal.dev_seal();
+			let collation_archive = &cli.collation_archive;
+			let authoring = &cli.authoring;
 
 			runner.run_node_until_exit(|config| async move {
 				let hwbench = (!cli.no_hardware_benchmarks).then_some(
@@ -508,13 +691,39 @@ pub fn run() -> Result<()> {
 					.map(|e| e.para_id)
 					.ok_or_else(|| "Could not find parachain extension in chain-spec.")?;
 
+				let id = ParaId::from(para_id);
+				let collation_archive = collation_archive.config(&config);
+				let authoring = authoring.authoring_options(&config)?;
+
+				if let Some(dev_seal) = dev_seal {
+					info!("Parachain id: {:?}", id);
+					info!("Running without relay chain, sealing blocks with {:?}", dev_seal);
+
+					return match config.chain_spec.runtime()? {
+						Runtime::InfraAssetSystem =>
+							crate::service::start_dev_seal_node::<
+								infra_asset_system_runtime::RuntimeApi,
+								_,
+							>(config, id, dev_seal, crate::rpc::create_trapped_assets)
+							.await
+							.map(|r| r.0)
+							.map_err(Into::into),
+						Runtime::ParachainTemplate =>
+							crate::service::start_dev_seal_node::<
+								parachain_template_runtime::RuntimeApi,
+								_,
+							>(config, id, dev_seal, crate::rpc::create_trapped_assets)
+							.await
+							.map(|r| r.0)
+							.map_err(Into::into),
+					}
+				}
+
 				let infrablockspace_cli = RelayChainCli::new(
 					&config,
 					[RelayChainCli::executable_name()].iter().chain(cli.relaychain_args.iter()),
 				);
 
-				let id = ParaId::from(para_id);
-
 				let parachain_account =
 					AccountIdConversion::<infrablockspace_primitives::AccountId>::into_account_truncating(&id);
 
@@ -539,18 +748,57 @@ pub fn run() -> Result<()> {
 					warn!("Detected relay chain node arguments together with --relay-chain-rpc-url. This command starts a minimal Polkadot node that only uses a network-related subset of all relay chain CLI options.");
 				}
 
-				match config.chain_spec.runtime() {
-					Runtime::Default => crate::service::start_generic_aura_node::<
+				match config.chain_spec.runtime()? {
+					Runtime::InfraAssetSystem => crate::service::start_generic_aura_node::<
 						infra_asset_system_runtime::RuntimeApi,
 						AuraId,
-					>(config, infrablockspace_config, collator_options, id, hwbench)
+						_,
+						_,
+					>(
+						config,
+						infrablockspace_config,
+						collator_options,
+						fork_pruning,
+						provisional_finality_depth,
+						collation_archive,
+						authoring,
+						fee_asset_pool_share.map(|max_share| FeeAssetReservation {
+							max_share,
+							fee_asset_of: fee_asset_reservation::infra_asset_system_fee_asset,
+						}),
+						id,
+						crate::rpc::create_trapped_assets,
+						crate::service::runtime_equivocation_reporter,
+						hwbench,
+					)
 					.await
 					.map(|r| r.0)
 					.map_err(Into::into),
-					Runtime::InfraAssetSystem => crate::service::start_generic_aura_node::<
-						infra_asset_system_runtime::RuntimeApi,
+					Runtime::ParachainTemplate => crate::service::start_generic_aura_node::<
+						parachain_template_runtime::RuntimeApi,
 						AuraId,
-					>(config, infrablockspace_config, collator_options, id, hwbench)
+						_,
+						_,
+					>(
+						config,
+						infrablockspace_config,
+						collator_options,
+						fork_pruning,
+						provisional_finality_depth,
+						collation_archive,
+						authoring,
+						// The fee asset of the template transactions is private to their
+						// `ChargeSystemToken` extension.
+						fee_asset_pool_share.and_then(|_| {
+							warn!("The parachain template doesn't support `--fee-asset-pool-share`");
+							None
+						}),
+						id,
+						crate::rpc::create_trapped_assets,
+						// The template runtime doesn't accept equivocation reports.
+						|_, _, _| None,
+						hwbench,
+					)
 					.await
 					.map(|r| r.0)
 					.map_err(Into::into),
@@ -696,3 +944,74 @@ impl CliConfiguration<Self> for RelayChainCli {
 		self.base.base.node_name()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unsupported_spec_name_is_rejected() {
+		let error = Runtime::from_spec_name("statemine").unwrap_err();
+		assert!(error.contains("Unsupported runtime 'statemine'"));
+		for runtime in Runtime::ALL {
+			assert!(error.contains(&*runtime.native_version().spec_name));
+		}
+	}
+
+	#[test]
+	fn chain_spec_without_code_is_rejected() {
+		let chain_spec = serde_json::json!({ "genesis": { "raw": { "top": {} } } });
+		assert!(Runtime::from_chain_spec_json(&chain_spec).is_err());
+	}
+
+	#[test]
+	fn runtime_is_resolved_from_the_code() {
+		for runtime in Runtime::ALL {
+			// Nodes built without the wasm runtimes can not resolve them.
+			if let Ok(code) = runtime.wasm_binary() {
+				assert_eq!(Runtime::from_code(code), Ok(runtime));
+			}
+		}
+	}
+
+	#[test]
+	fn runtime_of_loaded_chain_specs_is_remembered() {
+		for (id, runtime) in [
+			("infra-asset-system-dev", Runtime::InfraAssetSystem),
+			("parachain-template-local", Runtime::ParachainTemplate),
+		] {
+			let chain_spec = load_spec(id).unwrap();
+			assert_eq!(
+				RESOLVED_RUNTIMES.lock().unwrap().get(chain_spec.id()).copied(),
+				Some(runtime)
+			);
+			assert_eq!(chain_spec.runtime(), Ok(runtime));
+		}
+	}
+
+	#[test]
+	fn built_in_chain_specs_are_loaded() {
+		for (id, runtime) in [
+			("infra-asset-system-dev", Runtime::InfraAssetSystem),
+			("infra-asset-system-local", Runtime::InfraAssetSystem),
+			("infra-asset-system-genesis", Runtime::InfraAssetSystem),
+			("infra-asset-system", Runtime::InfraAssetSystem),
+			("parachain-template-dev", Runtime::ParachainTemplate),
+			("parachain-template-local", Runtime::ParachainTemplate),
+			("", Runtime::InfraAssetSystem),
+		] {
+			let chain_spec =
+				load_spec(id).unwrap_or_else(|e| panic!("'{}' fails to load: {}", id, e));
+			assert_eq!(chain_spec.runtime(), Ok(runtime), "runtime of '{}'", id);
+		}
+	}
+
+	#[test]
+	fn chain_spec_file_of_unsupported_runtime_is_rejected() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("chain-spec.json");
+		std::fs::write(&path, r#"{ "genesis": { "raw": { "top": {} } } }"#).unwrap();
+
+		assert!(load_spec(path.to_str().unwrap()).is_err());
+	}
+}
@@ -0,0 +1,225 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reservation of transaction pool space per fee asset.
+//!
+//! The transaction pool evicts the transactions of lowest priority when it is full, so a flood of
+//! transactions paying their fee with one system token can crowd out the transactions paying with
+//! the others. With a reservation, the transactions paying with the same asset may only occupy a
+//! share of the ready queue: once the share is exceeded, the transactions of lowest priority paying
+//! with that asset are dropped from the pool. Unlike invalid transactions, they are not banned and
+//! may be submitted again.
+
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use parachains_common::{Block, Hash};
+use sc_transaction_pool::{BasicPool, ChainApi, ValidatedTransaction};
+use sc_transaction_pool_api::{error::Error as TxPoolError, InPoolTransaction, TransactionPool};
+use sp_runtime::{
+	transaction_validity::TransactionPriority, types::SystemTokenId, OpaqueExtrinsic, Percent,
+};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+const LOG_TARGET: &str = "fee-asset-reservation";
+
+/// Asset paying the fee of a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeeAsset {
+	/// The native currency.
+	Native,
+	/// A system token.
+	SystemToken(SystemTokenId),
+}
+
+impl From<Option<SystemTokenId>> for FeeAsset {
+	fn from(system_token_id: Option<SystemTokenId>) -> Self {
+		system_token_id.map_or(FeeAsset::Native, FeeAsset::SystemToken)
+	}
+}
+
+/// Returns the asset paying the fee of a transaction, `None` for unsigned transactions.
+pub type FeeAssetOf = fn(&OpaqueExtrinsic) -> Option<FeeAsset>;
+
+/// Share of the ready queue of the transaction pool reserved per fee asset.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeAssetReservation {
+	/// Maximum share of the ready queue occupied by the transactions paying with the same asset.
+	pub max_share: Percent,
+	/// Returns the fee asset of the transactions of the runtime.
+	pub fee_asset_of: FeeAssetOf,
+}
+
+/// Fee asset of the transactions of the asset system runtime.
+pub fn infra_asset_system_fee_asset(xt: &OpaqueExtrinsic) -> Option<FeeAsset> {
+	let xt = infra_asset_system_runtime::UncheckedExtrinsic::decode(&mut &xt.encode()[..]).ok()?;
+	xt.signature
+		.map(|(_, _, extra)| infra_asset_system_runtime::fee_asset_of(&extra).cloned().into())
+}
+
+/// Ready transactions per fee asset, noted as they are imported.
+#[derive(Default)]
+struct ReadyByFeeAsset {
+	/// There are only a few fee assets.
+	assets: Vec<(FeeAsset, HashSet<Hash>)>,
+}
+
+impl ReadyByFeeAsset {
+	/// Note the import of the ready transaction `hash` paying with `fee_asset`, and returns the
+	/// transactions to drop so that at most `max` ready transactions pay with it.
+	///
+	/// `priority` returns the priority of a ready transaction, `None` if it left the ready queue.
+	/// Only the transactions paying with `fee_asset` are looked up, and only once the share of the
+	/// asset seems exceeded.
+	fn note_import(
+		&mut self,
+		hash: Hash,
+		fee_asset: FeeAsset,
+		max: usize,
+		priority: impl Fn(&Hash) -> Option<TransactionPriority>,
+	) -> Vec<Hash> {
+		let hashes = match self.assets.iter().position(|(asset, _)| *asset == fee_asset) {
+			Some(index) => &mut self.assets[index].1,
+			None => {
+				self.assets.push((fee_asset, HashSet::new()));
+				&mut self.assets.last_mut().expect("an asset was just pushed; qed").1
+			},
+		};
+		hashes.insert(hash);
+		if hashes.len() <= max {
+			return Vec::new()
+		}
+
+		// Forget the transactions which left the ready queue, e.g. included in a block.
+		let mut ready = hashes
+			.iter()
+			.filter_map(|hash| priority(hash).map(|priority| (priority, *hash)))
+			.collect::<Vec<_>>();
+		if ready.len() <= max {
+			*hashes = ready.into_iter().map(|(_, hash)| hash).collect();
+			return Vec::new()
+		}
+
+		ready.sort_by_key(|(priority, _)| *priority);
+		let excess = ready.len() - max;
+		let evicted = ready.drain(..excess).map(|(_, hash)| hash).collect::<Vec<_>>();
+		*hashes = ready.into_iter().map(|(_, hash)| hash).collect();
+		evicted
+	}
+}
+
+/// Enforce the `reservation` on `pool`, whose ready queue holds at most `ready_limit`
+/// transactions.
+pub async fn enforce_fee_asset_reservation<PoolApi>(
+	pool: Arc<BasicPool<PoolApi, Block>>,
+	reservation: FeeAssetReservation,
+	ready_limit: usize,
+) where
+	PoolApi: ChainApi<Block = Block> + 'static,
+	PoolApi::Error: From<TxPoolError>,
+{
+	let max_per_asset = reservation.max_share.mul_floor(ready_limit).max(1);
+	let mut ready_by_fee_asset = ReadyByFeeAsset::default();
+	let mut imports = pool.import_notification_stream();
+
+	while let Some(hash) = imports.next().await {
+		let fee_asset = match pool
+			.ready_transaction(&hash)
+			.and_then(|tx| (reservation.fee_asset_of)(tx.data()))
+		{
+			Some(fee_asset) => fee_asset,
+			None => continue,
+		};
+
+		let evicted =
+			ready_by_fee_asset.note_import(hash, fee_asset.clone(), max_per_asset, |hash| {
+				pool.ready_transaction(hash).map(|tx| *tx.priority())
+			});
+		if evicted.is_empty() {
+			continue
+		}
+
+		// Resubmitting a transaction as unknown drops it without banning it, as opposed to
+		// `remove_invalid`. The transactions depending on it are resubmitted as they are.
+		pool.pool().validated_pool().resubmit(
+			evicted
+				.iter()
+				.map(|hash| {
+					(
+						*hash,
+						ValidatedTransaction::Unknown(
+							*hash,
+							TxPoolError::ImmediatelyDropped.into(),
+						),
+					)
+				})
+				.collect::<HashMap<_, _>>(),
+		);
+
+		log::debug!(
+			target: LOG_TARGET,
+			"Dropped {} transaction(s) paying with {:?}, exceeding the reserved share of the pool.",
+			evicted.len(),
+			fee_asset,
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hash(n: u8) -> Hash {
+		Hash::repeat_byte(n)
+	}
+
+	#[test]
+	fn share_is_enforced_per_asset() {
+		let mut ready = ReadyByFeeAsset::default();
+		let priority = |hash: &Hash| Some(hash[0] as TransactionPriority);
+
+		assert!(ready.note_import(hash(3), FeeAsset::Native, 2, priority).is_empty());
+		assert!(ready.note_import(hash(2), FeeAsset::Native, 2, priority).is_empty());
+		// Other assets have their own share.
+		assert!(ready
+			.note_import(
+				hash(1),
+				FeeAsset::SystemToken(SystemTokenId { para_id: 1000, pallet_id: 50, asset_id: 1 }),
+				2,
+				priority
+			)
+			.is_empty());
+
+		// The transaction of lowest priority is dropped, even the imported one.
+		assert_eq!(ready.note_import(hash(4), FeeAsset::Native, 2, priority), vec![hash(2)]);
+		assert_eq!(ready.note_import(hash(1), FeeAsset::Native, 2, priority), vec![hash(1)]);
+	}
+
+	#[test]
+	fn transactions_leaving_the_ready_queue_are_forgotten() {
+		let mut ready = ReadyByFeeAsset::default();
+		let included = hash(1);
+		let priority = |hash: &Hash| (*hash != included).then_some(hash[0] as TransactionPriority);
+
+		assert!(ready.note_import(hash(1), FeeAsset::Native, 2, priority).is_empty());
+		assert!(ready.note_import(hash(2), FeeAsset::Native, 2, priority).is_empty());
+		// The included transaction doesn't count against the share.
+		assert!(ready.note_import(hash(3), FeeAsset::Native, 2, priority).is_empty());
+		assert_eq!(ready.assets[0].1, [hash(2), hash(3)].into_iter().collect::<HashSet<_>>());
+	}
+}
@@ -24,6 +24,8 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod fee_asset_reservation;
+mod replay_pov;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
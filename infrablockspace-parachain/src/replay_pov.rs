@@ -0,0 +1,272 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline replay of a PoV through the `validate_block` function of the runtime.
+//!
+//! The checks done by `validate_block` before executing the block are first done natively, so
+//! that a mismatch is reported with both values instead of the bare panic message of the runtime.
+
+use crate::cli::ReplayPovCmd;
+use codec::{Decode, Encode};
+use cumulus_primitives_core::{ParachainBlockData, PersistedValidationData, ValidationParams};
+use frame_support::traits::{ExtrinsicCall, IsSubType};
+use infrablockspace_node_primitives::POV_BOMB_LIMIT;
+use infrablockspace_parachain::primitives::{BlockData, HeadData, ValidationResult};
+use parachains_common::{Block, Header};
+use sc_executor::WasmExecutor;
+use sc_executor_common::runtime_blob::RuntimeBlob;
+use sp_runtime::{
+	traits::{BlakeTwo256, Block as BlockT, Extrinsic, Header as HeaderT},
+	types::PotVotesResult,
+};
+use std::{path::Path, time::Instant};
+
+/// Read `value` as a `0x` prefixed hex string or, otherwise, as the path to a file.
+fn read_hex_or_file(value: &str) -> Result<Vec<u8>, String> {
+	if value.starts_with("0x") {
+		sp_core::bytes::from_hex(value).map_err(|e| format!("Invalid hex string: {}", e))
+	} else {
+		read_file(Path::new(value))
+	}
+}
+
+/// Read the file at `path`, decoding it if it contains a `0x` prefixed hex string.
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+	let content =
+		std::fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+
+	match std::str::from_utf8(&content).map(str::trim) {
+		Ok(hex) if hex.starts_with("0x") => sp_core::bytes::from_hex(hex)
+			.map_err(|e| format!("Invalid hex string in '{}': {}", path.display(), e)),
+		_ => Ok(content),
+	}
+}
+
+/// Peak resident memory of this process in KiB, only available on Linux.
+fn peak_memory_kib() -> Option<u64> {
+	std::fs::read_to_string("/proc/self/status")
+		.ok()?
+		.lines()
+		.find_map(|line| line.strip_prefix("VmHWM:"))?
+		.trim()
+		.trim_end_matches("kB")
+		.trim()
+		.parse()
+		.ok()
+}
+
+/// Find the validation data of the `set_validation_data` inherent, like `validate_block` does.
+fn find_validation_data<Xt, R>(block: &Block) -> Option<PersistedValidationData>
+where
+	Xt: Decode + Extrinsic + ExtrinsicCall,
+	<Xt as Extrinsic>::Call: IsSubType<cumulus_pallet_parachain_system::Call<R>>,
+	R: cumulus_pallet_parachain_system::Config,
+{
+	block
+		.extrinsics()
+		.iter()
+		.map_while(|xt| Xt::decode(&mut &xt.encode()[..]).ok())
+		.take_while(|xt| !xt.is_signed().unwrap_or(true))
+		.find_map(|xt| match xt.call().is_sub_type() {
+			Some(cumulus_pallet_parachain_system::Call::set_validation_data { data }) =>
+				Some(data.validation_data.clone()),
+			_ => None,
+		})
+}
+
+impl ReplayPovCmd {
+	/// Run the command with the given runtime `code`.
+	///
+	/// `Xt` and `R` are the extrinsic and runtime types of the runtime of the parachain.
+	pub fn run<Xt, R>(&self, code: &[u8]) -> sc_cli::Result<()>
+	where
+		Xt: Decode + Extrinsic + ExtrinsicCall,
+		<Xt as Extrinsic>::Call: IsSubType<cumulus_pallet_parachain_system::Call<R>>,
+		R: cumulus_pallet_parachain_system::Config,
+	{
+		let _ = sc_tracing::logging::LoggerBuilder::new("").init();
+
+		let pov = read_file(&self.pov)?;
+		let block_data = sp_maybe_compressed_blob::decompress(&pov, POV_BOMB_LIMIT)
+			.map_err(|e| format!("Failed to decompress the PoV: {}", e))?
+			.into_owned();
+		let parent_head = read_hex_or_file(&self.parent_head)?;
+		let expected_vote_result = self
+			.expected_vote_result
+			.as_deref()
+			.map(|value| {
+				Option::<PotVotesResult>::decode(&mut &read_hex_or_file(value)?[..])
+					.map_err(|e| format!("Invalid expected vote result: {}", e))
+			})
+			.transpose()?;
+
+		println!("PoV size: {} bytes, {} bytes decompressed", pov.len(), block_data.len());
+
+		let mut failed_checks = self.check::<Xt, R>(&block_data, &parent_head);
+		if failed_checks.is_empty() {
+			println!("Checks before the block execution: ok");
+		}
+		for failure in &failed_checks {
+			println!("Check failed: {}", failure);
+		}
+
+		let params = ValidationParams {
+			parent_head: HeadData(parent_head),
+			block_data: BlockData(block_data),
+			relay_parent_number: self.relay_parent_number,
+			relay_parent_storage_root: self.relay_parent_storage_root,
+		};
+
+		let executor = WasmExecutor::<sp_io::SubstrateHostFunctions>::new(
+			sc_cli::execution_method_from_cli(
+				self.wasm_execution,
+				sc_cli::DEFAULT_WASMTIME_INSTANTIATION_STRATEGY,
+			),
+			Some(self.heap_pages),
+			1,
+			None,
+			1,
+		);
+		let blob = RuntimeBlob::uncompress_if_needed(code)
+			.map_err(|e| format!("Invalid runtime code: {}", e))?;
+		let mut ext = sp_io::TestExternalities::default();
+
+		let start = Instant::now();
+		let result =
+			executor.uncached_call(blob, &mut ext.ext(), false, "validate_block", &params.encode());
+		let elapsed = start.elapsed();
+
+		println!("Execution time: {:?}", elapsed);
+		println!("Heap pages: {}", self.heap_pages);
+		if let Some(peak_memory) = peak_memory_kib() {
+			println!("Peak resident memory of the process: {} KiB", peak_memory);
+		}
+
+		let result = match result {
+			Ok(result) => ValidationResult::decode(&mut &result[..])
+				.map_err(|e| format!("Failed to decode the validation result: {}", e))?,
+			Err(e) => {
+				println!("Validation failed: {}", e);
+				return Err(format!("`validate_block` failed: {}", e).into())
+			},
+		};
+
+		match Header::decode(&mut &result.head_data.0[..]) {
+			Ok(header) => println!("Head: #{} ({:?})", header.number(), header.hash()),
+			Err(_) => println!(
+				"Head data: 0x{}",
+				sp_core::hexdisplay::HexDisplay::from(&result.head_data.0)
+			),
+		}
+		println!("Upward messages: {}", result.upward_messages.len());
+		println!("Horizontal messages: {}", result.horizontal_messages.len());
+		println!("Processed downward messages: {}", result.processed_downward_messages);
+		println!("HRMP watermark: {}", result.hrmp_watermark);
+		println!("New validation code: {}", result.new_validation_code.is_some());
+		println!("Vote result: {:?}", result.vote_result);
+
+		if let Some(expected) = expected_vote_result {
+			if expected != result.vote_result {
+				let failure = format!(
+					"vote result mismatch, expected {:?}, got {:?}",
+					expected, result.vote_result,
+				);
+				println!("Check failed: {}", failure);
+				failed_checks.push(failure);
+			}
+		}
+
+		if failed_checks.is_empty() {
+			println!("Validation succeeded");
+			Ok(())
+		} else {
+			Err(format!("{} check(s) failed", failed_checks.len()).into())
+		}
+	}
+
+	/// Natively do the checks of `validate_block` preceding the execution of the block.
+	///
+	/// Returns the failed checks.
+	fn check<Xt, R>(&self, block_data: &[u8], parent_head: &[u8]) -> Vec<String>
+	where
+		Xt: Decode + Extrinsic + ExtrinsicCall,
+		<Xt as Extrinsic>::Call: IsSubType<cumulus_pallet_parachain_system::Call<R>>,
+		R: cumulus_pallet_parachain_system::Config,
+	{
+		let block_data = match ParachainBlockData::<Block>::decode(&mut &block_data[..]) {
+			Ok(block_data) => block_data,
+			Err(e) => return vec![format!("invalid parachain block data: {}", e)],
+		};
+		let parent_header = match Header::decode(&mut &parent_head[..]) {
+			Ok(header) => header,
+			Err(e) => return vec![format!("invalid parent head: {}", e)],
+		};
+
+		let (header, extrinsics, storage_proof) = block_data.deconstruct();
+		println!(
+			"Block: #{} ({:?}), {} extrinsics, {} bytes of storage proof",
+			header.number(),
+			header.hash(),
+			extrinsics.len(),
+			storage_proof.encoded_size(),
+		);
+
+		let mut failed_checks = Vec::new();
+		if parent_header.hash() != *header.parent_hash() {
+			failed_checks.push(format!(
+				"parent hash mismatch, the parent head hashes to {:?}, the block builds on {:?}",
+				parent_header.hash(),
+				header.parent_hash(),
+			));
+		}
+
+		let block = Block::new(header, extrinsics);
+		match find_validation_data::<Xt, R>(&block) {
+			Some(validation_data) => {
+				if validation_data.parent_head.0 != parent_head {
+					failed_checks.push(format!(
+						"parent head of the inherent doesn't match, the inherent has 0x{}",
+						sp_core::hexdisplay::HexDisplay::from(&validation_data.parent_head.0),
+					));
+				}
+				if validation_data.relay_parent_number != self.relay_parent_number {
+					failed_checks.push(format!(
+						"relay parent number of the inherent doesn't match, the inherent has {}",
+						validation_data.relay_parent_number,
+					));
+				}
+				if validation_data.relay_parent_storage_root != self.relay_parent_storage_root {
+					failed_checks.push(format!(
+						"relay parent storage root of the inherent doesn't match, the inherent has {:?}",
+						validation_data.relay_parent_storage_root,
+					));
+				}
+			},
+			None => failed_checks.push("could not find the `set_validation_data` inherent".into()),
+		}
+
+		if let Err(e) = storage_proof.to_memory_db::<BlakeTwo256>(Some(parent_header.state_root()))
+		{
+			failed_checks.push(format!(
+				"storage proof doesn't match the state root {:?} of the parent: {:?}",
+				parent_header.state_root(),
+				e,
+			));
+		}
+
+		failed_checks
+	}
+}
@@ -0,0 +1,106 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC to inspect the size breakdown of the collations built by the collator.
+
+use cumulus_client_collator::{CollationStats, CollationStatsRecorder, MAX_RECORDED_COLLATIONS};
+use cumulus_primitives_core::relay_chain::Hash as PHash;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Number of collations returned if no count is given.
+const DEFAULT_COUNT: u32 = 16;
+
+/// Size breakdown of a collation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollationSummary<Hash, Number> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// Number of the block.
+	pub number: Number,
+	/// Relay parent of the collation.
+	pub relay_parent: PHash,
+	/// `max_pov_size` given by the relay chain.
+	pub max_pov_size: u32,
+	/// Size of the compressed PoV.
+	pub pov_size: u64,
+	/// Size of the PoV before compression.
+	pub uncompressed_pov_size: u64,
+	/// Size of the encoded header.
+	pub header_size: u64,
+	/// Size of the encoded extrinsics.
+	pub extrinsics_size: u64,
+	/// Size of the encoded storage proof.
+	pub storage_proof_size: u64,
+	/// Number of extrinsics in the block.
+	pub extrinsic_count: u64,
+	/// Size of the encoded vote result.
+	pub vote_result_size: u64,
+}
+
+impl<Block: BlockT> From<CollationStats<Block>>
+	for CollationSummary<Block::Hash, NumberFor<Block>>
+{
+	fn from(stats: CollationStats<Block>) -> Self {
+		Self {
+			hash: stats.block_hash,
+			number: stats.block_number,
+			relay_parent: stats.relay_parent,
+			max_pov_size: stats.max_pov_size,
+			pov_size: stats.pov_size as u64,
+			uncompressed_pov_size: stats.uncompressed_pov_size as u64,
+			header_size: stats.header_size as u64,
+			extrinsics_size: stats.extrinsics_size as u64,
+			storage_proof_size: stats.storage_proof_size as u64,
+			extrinsic_count: stats.extrinsic_count as u64,
+			vote_result_size: stats.vote_result_size as u64,
+		}
+	}
+}
+
+/// Collator RPC methods.
+#[rpc(server)]
+pub trait CollationsApi<Hash, Number> {
+	/// Returns the size breakdown of the last `count` collations, the most recent first.
+	#[method(name = "collator_lastCollations")]
+	fn last_collations(&self, count: Option<u32>)
+		-> RpcResult<Vec<CollationSummary<Hash, Number>>>;
+}
+
+/// Implementation of [`CollationsApiServer`].
+pub struct Collations<Block: BlockT> {
+	recorder: CollationStatsRecorder<Block>,
+}
+
+impl<Block: BlockT> Collations<Block> {
+	/// Create a new instance.
+	pub fn new(recorder: CollationStatsRecorder<Block>) -> Self {
+		Self { recorder }
+	}
+}
+
+impl<Block: BlockT> CollationsApiServer<Block::Hash, NumberFor<Block>> for Collations<Block> {
+	fn last_collations(
+		&self,
+		count: Option<u32>,
+	) -> RpcResult<Vec<CollationSummary<Block::Hash, NumberFor<Block>>>> {
+		let count = (count.unwrap_or(DEFAULT_COUNT) as usize).min(MAX_RECORDED_COLLATIONS);
+
+		Ok(self.recorder.last_collations(count).into_iter().map(Into::into).collect())
+	}
+}
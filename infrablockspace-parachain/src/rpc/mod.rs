@@ -20,14 +20,23 @@
 
 use std::sync::Arc;
 
-use parachains_common::{AccountId, Balance, Block, Index as Nonce};
+use cumulus_client_collator::CollationStatsRecorder;
+use cumulus_client_consensus_common::ProvisionalFinalityStream;
+use cumulus_client_pov_recovery::RecoveryController;
+use parachains_common::{AccountId, Balance, Block, Hash, Index as Nonce};
 use sc_client_api::AuxStore;
+use sc_consensus_manual_seal::EngineCommand;
 pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 
+pub mod collations;
+pub mod provisional_finality;
+pub mod recovery;
+pub mod trapped_assets;
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = jsonrpsee::RpcModule<()>;
 
@@ -39,6 +48,16 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Controller of the pov-recovery, if it is running.
+	pub recovery: Option<RecoveryController<Block>>,
+	/// Provisional finality notifications, if enabled.
+	pub provisional_finality: Option<ProvisionalFinalityStream<Block>>,
+	/// Executor to drive the subscriptions.
+	pub subscription_executor: SubscriptionTaskExecutor,
+	/// Command sink of the dev-seal block authoring, if the node runs without a relay chain.
+	pub dev_seal: Option<futures::channel::mpsc::Sender<EngineCommand<Hash>>>,
+	/// Statistics of the built collations, if the node is a collator.
+	pub collation_stats: Option<CollationStatsRecorder<Block>>,
 }
 
 /// Instantiate all RPC extensions.
@@ -61,16 +80,58 @@ where
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashFor<Block>>,
 {
+	use collations::{Collations, CollationsApiServer};
 	use frame_rpc_system::{System, SystemApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use provisional_finality::{ProvisionalFinality, ProvisionalFinalityApiServer};
+	use recovery::{Recovery, RecoveryApiServer};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApiServer};
 	use substrate_state_trie_migration_rpc::{StateMigration, StateMigrationApiServer};
 
 	let mut module = RpcExtension::new(());
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps {
+		client,
+		pool,
+		deny_unsafe,
+		recovery,
+		provisional_finality,
+		subscription_executor,
+		dev_seal,
+		collation_stats,
+	} = deps;
 
 	module.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
 	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
 	module.merge(StateMigration::new(client.clone(), backend, deny_unsafe).into_rpc())?;
+	if let Some(recovery) = recovery {
+		module.merge(Recovery::new(recovery, deny_unsafe).into_rpc())?;
+	}
+	if let Some(provisional_finality) = provisional_finality {
+		module.merge(
+			ProvisionalFinality::new(provisional_finality, subscription_executor).into_rpc(),
+		)?;
+	}
+	if let Some(dev_seal) = dev_seal {
+		module.merge(ManualSeal::new(dev_seal).into_rpc())?;
+	}
+	if let Some(collation_stats) = collation_stats {
+		module.merge(Collations::new(collation_stats).into_rpc())?;
+	}
+
+	Ok(module)
+}
 
+/// Instantiate the RPC extensions of the runtimes indexing the assets trapped by XCM.
+pub fn create_trapped_assets<C>(client: Arc<C>) -> Result<RpcExtension, sc_service::Error>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: assets_common::runtime_api::TrappedAssetsApi<Block>,
+{
+	use trapped_assets::{TrappedAssetsApiServer, TrappedAssetsRpc};
+
+	let mut module = RpcExtension::new(());
+	module
+		.merge(TrappedAssetsRpc::new(client).into_rpc())
+		.map_err(|e| sc_service::Error::Application(e.into()))?;
 	Ok(module)
 }
@@ -0,0 +1,101 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC subscription to the parachain blocks included deep enough in the relay chain.
+
+use cumulus_client_consensus_common::{ProvisionalFinalityNotification, ProvisionalFinalityStream};
+use cumulus_primitives_core::relay_chain::Hash as PHash;
+use futures::{FutureExt, StreamExt};
+use jsonrpsee::{proc_macros::rpc, types::SubscriptionResult, SubscriptionSink};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// A parachain block included in the relay chain at least `depth` blocks deep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvisionallyFinalizedBlock<Hash, Number> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// Number of the block.
+	pub number: Number,
+	/// Relay chain block at which the block was included.
+	pub relay_parent: PHash,
+	/// Number of relay chain blocks built on top of `relay_parent`.
+	pub depth: u32,
+}
+
+impl<Block: BlockT> From<ProvisionalFinalityNotification<Block>>
+	for ProvisionallyFinalizedBlock<Block::Hash, NumberFor<Block>>
+{
+	fn from(notification: ProvisionalFinalityNotification<Block>) -> Self {
+		Self {
+			hash: notification.hash,
+			number: notification.number,
+			relay_parent: notification.relay_parent,
+			depth: notification.depth,
+		}
+	}
+}
+
+/// Provisional finality RPC methods.
+#[rpc(server)]
+pub trait ProvisionalFinalityApi<Hash, Number> {
+	/// Subscribe to the parachain blocks included in the relay chain deep enough.
+	///
+	/// A notification implies that the ancestors of the notified block are included as deep.
+	/// This is not finality, the notified blocks may still be reverted.
+	#[subscription(
+		name = "cumulus_subscribeProvisionalFinality" => "cumulus_provisionalFinality",
+		unsubscribe = "cumulus_unsubscribeProvisionalFinality",
+		item = ProvisionallyFinalizedBlock<Hash, Number>
+	)]
+	fn subscribe_provisional_finality(&self);
+}
+
+/// Implementation of [`ProvisionalFinalityApiServer`].
+pub struct ProvisionalFinality<Block: BlockT> {
+	stream: ProvisionalFinalityStream<Block>,
+	executor: SubscriptionTaskExecutor,
+}
+
+impl<Block: BlockT> ProvisionalFinality<Block> {
+	/// Create a new instance.
+	pub fn new(
+		stream: ProvisionalFinalityStream<Block>,
+		executor: SubscriptionTaskExecutor,
+	) -> Self {
+		Self { stream, executor }
+	}
+}
+
+impl<Block: BlockT> ProvisionalFinalityApiServer<Block::Hash, NumberFor<Block>>
+	for ProvisionalFinality<Block>
+{
+	fn subscribe_provisional_finality(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+		let stream = self
+			.stream
+			.subscribe(100_000)
+			.map(ProvisionallyFinalizedBlock::<Block::Hash, NumberFor<Block>>::from);
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("cumulus-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
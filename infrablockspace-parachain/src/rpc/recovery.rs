@@ -0,0 +1,213 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Operator RPC to trigger block recovery and to inspect the pov-recovery state.
+
+use cumulus_client_pov_recovery::{
+	status::{RecoveryControllerError, RecoveryResult, RecoveryStage},
+	RecoveryController, RecoveryKind,
+};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use sc_rpc::DenyUnsafe;
+use serde::{Deserialize, Serialize};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Error code returned when the recovery queue is full.
+const QUEUE_FULL_ERROR: i32 = 1;
+/// Error code returned when the recovery worker is not running.
+const WORKER_STOPPED_ERROR: i32 = 2;
+
+/// A block that is currently being recovered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRecovery<Hash, Number> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// Number of the block.
+	pub number: Number,
+	/// Current stage of the recovery.
+	pub stage: String,
+}
+
+/// Outcome of a finished recovery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryOutcome<Hash> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// `submitted`, `alreadyImported`, `unknownCandidate` or `failed`.
+	pub result: String,
+	/// Reason of the failure, if any.
+	pub error: Option<String>,
+}
+
+/// State of the pov-recovery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryStatus<Hash, Number> {
+	/// Blocks that are currently being recovered, ordered by block number.
+	pub pending: Vec<PendingRecovery<Hash, Number>>,
+	/// Outcome of the most recent recoveries, oldest first.
+	pub outcomes: Vec<RecoveryOutcome<Hash>>,
+}
+
+/// Block recovery RPC methods.
+#[rpc(server)]
+pub trait RecoveryApi<Hash, Number> {
+	/// Enqueue the recovery of the block with `hash`.
+	///
+	/// If `full` is `true`, all unknown ancestors of the block are recovered as well.
+	#[method(name = "cumulus_recoverBlock")]
+	fn recover_block(&self, hash: Hash, full: Option<bool>) -> RpcResult<()>;
+
+	/// Returns the blocks being recovered and the outcome of the most recent recoveries.
+	#[method(name = "cumulus_recoveryStatus")]
+	fn recovery_status(&self) -> RpcResult<RecoveryStatus<Hash, Number>>;
+}
+
+/// Implementation of [`RecoveryApiServer`].
+pub struct Recovery<Block: BlockT> {
+	controller: RecoveryController<Block>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<Block: BlockT> Recovery<Block> {
+	/// Create a new instance.
+	pub fn new(controller: RecoveryController<Block>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { controller, deny_unsafe }
+	}
+}
+
+impl<Block: BlockT> RecoveryApiServer<Block::Hash, NumberFor<Block>> for Recovery<Block> {
+	fn recover_block(&self, hash: Block::Hash, full: Option<bool>) -> RpcResult<()> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let kind = if full.unwrap_or(false) { RecoveryKind::Full } else { RecoveryKind::Simple };
+		self.controller.recover(hash, kind).map_err(|e| {
+			let (code, message) = match e {
+				RecoveryControllerError::QueueFull => (QUEUE_FULL_ERROR, "Recovery queue is full"),
+				RecoveryControllerError::Stopped =>
+					(WORKER_STOPPED_ERROR, "Recovery worker is not running"),
+			};
+			CallError::Custom(ErrorObject::owned(code, message, None::<()>)).into()
+		})
+	}
+
+	fn recovery_status(&self) -> RpcResult<RecoveryStatus<Block::Hash, NumberFor<Block>>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let status = self.controller.status();
+		Ok(RecoveryStatus {
+			pending: status
+				.pending
+				.into_iter()
+				.map(|p| PendingRecovery {
+					hash: p.hash,
+					number: p.number,
+					stage: match p.stage {
+						RecoveryStage::Queued => "queued",
+						RecoveryStage::FromPeers => "fromPeers",
+						RecoveryStage::FromRelayChain => "fromRelayChain",
+						RecoveryStage::WaitingForParent => "waitingForParent",
+					}
+					.into(),
+				})
+				.collect(),
+			outcomes: status
+				.outcomes
+				.into_iter()
+				.map(|o| {
+					let (result, error) = match o.result {
+						RecoveryResult::Submitted => ("submitted", None),
+						RecoveryResult::AlreadyImported => ("alreadyImported", None),
+						RecoveryResult::UnknownCandidate => ("unknownCandidate", None),
+						RecoveryResult::Failed(reason) => ("failed", Some(reason)),
+					};
+					RecoveryOutcome { hash: o.hash, result: result.into(), error }
+				})
+				.collect(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cumulus_client_pov_recovery::RecoveryControl;
+	use jsonrpsee::{rpc_params, types::EmptyServerParams as EmptyParams};
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, H256};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	#[tokio::test]
+	async fn recover_block_enqueues_a_request() {
+		let mut control = RecoveryControl::<Block>::new();
+		let api = Recovery::new(control.controller(), DenyUnsafe::No).into_rpc();
+
+		api.call::<_, ()>("cumulus_recoverBlock", rpc_params![H256::repeat_byte(1), true])
+			.await
+			.unwrap();
+
+		let request = control.receiver.try_next().unwrap().unwrap();
+		assert_eq!(request.hash, H256::repeat_byte(1));
+		assert_eq!(request.kind, RecoveryKind::Full);
+	}
+
+	#[tokio::test]
+	async fn recovery_is_unsafe() {
+		let control = RecoveryControl::<Block>::new();
+		let api = Recovery::new(control.controller(), DenyUnsafe::Yes).into_rpc();
+
+		assert!(api
+			.call::<_, RecoveryStatus<H256, u64>>("cumulus_recoveryStatus", EmptyParams::new())
+			.await
+			.is_err());
+	}
+
+	#[tokio::test]
+	async fn recovery_status_reports_the_recovery_state() {
+		let control = RecoveryControl::<Block>::new();
+		let api = Recovery::new(control.controller(), DenyUnsafe::No).into_rpc();
+
+		let status = api
+			.call::<_, RecoveryStatus<H256, u64>>("cumulus_recoveryStatus", EmptyParams::new())
+			.await
+			.unwrap();
+		assert!(status.pending.is_empty());
+		assert!(status.outcomes.is_empty());
+	}
+
+	#[tokio::test]
+	async fn full_queue_is_reported() {
+		let control = RecoveryControl::<Block>::new();
+		let api = Recovery::new(control.controller(), DenyUnsafe::No).into_rpc();
+
+		let mut results = Vec::new();
+		for _ in 0..16 {
+			results.push(
+				api.call::<_, ()>("cumulus_recoverBlock", rpc_params![H256::repeat_byte(1)])
+					.await
+					.is_ok(),
+			);
+		}
+		assert!(results[0]);
+		assert!(!results[15]);
+	}
+}
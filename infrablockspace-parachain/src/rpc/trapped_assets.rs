@@ -0,0 +1,289 @@
+// Copyright 2023 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC to list the assets trapped by XCM for an origin and build the programs claiming them.
+
+use std::sync::Arc;
+
+use assets_common::runtime_api::TrappedAssetsApi as TrappedAssetsRuntimeApi;
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+use xcm::{latest::MultiLocation, VersionedMultiAssets, VersionedMultiLocation, VersionedXcm};
+
+/// Error code returned when a location can't be decoded.
+const INVALID_LOCATION_ERROR: i32 = 1;
+/// Error code returned when the runtime API call fails.
+const RUNTIME_ERROR: i32 = 2;
+
+/// Assets trapped for an origin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrappedAssets {
+	/// SCALE-encoded `VersionedMultiAssets` trapped.
+	pub assets: Bytes,
+	/// Number of times the assets were trapped, i.e. of claims they allow.
+	pub count: u32,
+	/// SCALE-encoded `VersionedXcm` claiming the assets, if a beneficiary was given.
+	pub claim_program: Option<Bytes>,
+}
+
+/// Trapped assets RPC methods.
+#[rpc(server)]
+pub trait TrappedAssetsApi<BlockHash> {
+	/// Returns the assets trapped for the SCALE-encoded `VersionedMultiLocation` `origin`, with
+	/// the program claiming them for `beneficiary` if given.
+	#[method(name = "xcm_trappedAssets")]
+	fn trapped_assets(
+		&self,
+		origin: Bytes,
+		beneficiary: Option<Bytes>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<TrappedAssets>>;
+}
+
+/// Implementation of [`TrappedAssetsApiServer`].
+pub struct TrappedAssetsRpc<C, Block> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> TrappedAssetsRpc<C, Block> {
+	/// Create a new instance.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn error(code: i32, message: impl ToString) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(code, message.to_string(), None::<()>)).into()
+}
+
+fn decode_location(bytes: &Bytes) -> RpcResult<MultiLocation> {
+	VersionedMultiLocation::decode(&mut &bytes[..])
+		.ok()
+		.and_then(|location| location.try_into().ok())
+		.ok_or_else(|| error(INVALID_LOCATION_ERROR, "Invalid location"))
+}
+
+impl<C, Block> TrappedAssetsApiServer<Block::Hash> for TrappedAssetsRpc<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: TrappedAssetsRuntimeApi<Block>,
+{
+	fn trapped_assets(
+		&self,
+		origin: Bytes,
+		beneficiary: Option<Bytes>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<TrappedAssets>> {
+		let origin = decode_location(&origin)?;
+		let beneficiary = beneficiary.as_ref().map(decode_location).transpose()?;
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let api = self.client.runtime_api();
+
+		let trapped = api.query_trapped_assets(at, origin).map_err(|e| error(RUNTIME_ERROR, e))?;
+		trapped
+			.into_iter()
+			.map(|(assets, count)| {
+				let claim_program = beneficiary
+					.map(|beneficiary| {
+						api.claim_trapped_assets_program(at, assets.clone(), beneficiary)
+							.map(|program| VersionedXcm::from(program).encode().into())
+					})
+					.transpose()
+					.map_err(|e| error(RUNTIME_ERROR, e))?;
+				Ok(TrappedAssets {
+					assets: VersionedMultiAssets::from(assets).encode().into(),
+					count,
+					claim_program,
+				})
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpsee::rpc_params;
+	use sp_api::ApiRef;
+	use sp_blockchain::{BlockStatus, Info};
+	use sp_runtime::{
+		testing::{Block as RawBlock, ExtrinsicWrapper, H256},
+		traits::NumberFor,
+	};
+	use xcm::latest::prelude::*;
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	fn sibling(id: u32) -> MultiLocation {
+		(Parent, Parachain(id)).into()
+	}
+
+	fn trapped() -> MultiAssets {
+		(Parent, 100u128).into()
+	}
+
+	#[derive(Clone, Default)]
+	struct TestRuntimeApi;
+
+	sp_api::mock_impl_runtime_apis! {
+		impl TrappedAssetsRuntimeApi<Block> for TestRuntimeApi {
+			fn query_trapped_assets(origin: MultiLocation) -> Vec<(MultiAssets, u32)> {
+				if origin == sibling(2000) {
+					vec![(trapped(), 2)]
+				} else {
+					Vec::new()
+				}
+			}
+
+			fn claim_trapped_assets_program(
+				assets: MultiAssets,
+				beneficiary: MultiLocation,
+			) -> Xcm<()> {
+				Xcm(vec![
+					ClaimAsset { assets, ticket: Here.into() },
+					DepositAsset { assets: Wild(All), beneficiary },
+				])
+			}
+		}
+	}
+
+	struct TestClient;
+
+	impl ProvideRuntimeApi<Block> for TestClient {
+		type Api = TestRuntimeApi;
+
+		fn runtime_api(&self) -> ApiRef<'_, Self::Api> {
+			TestRuntimeApi.into()
+		}
+	}
+
+	impl HeaderBackend<Block> for TestClient {
+		fn header(&self, _: H256) -> sp_blockchain::Result<Option<<Block as BlockT>::Header>> {
+			Ok(None)
+		}
+
+		fn info(&self) -> Info<Block> {
+			Info {
+				best_hash: H256::repeat_byte(1),
+				best_number: 1,
+				genesis_hash: H256::zero(),
+				finalized_hash: H256::zero(),
+				finalized_number: 0,
+				finalized_state: None,
+				number_leaves: 1,
+				block_gap: None,
+			}
+		}
+
+		fn status(&self, _: H256) -> sp_blockchain::Result<BlockStatus> {
+			Ok(BlockStatus::Unknown)
+		}
+
+		fn number(&self, _: H256) -> sp_blockchain::Result<Option<NumberFor<Block>>> {
+			Ok(None)
+		}
+
+		fn hash(&self, _: NumberFor<Block>) -> sp_blockchain::Result<Option<H256>> {
+			Ok(None)
+		}
+	}
+
+	fn encoded(location: MultiLocation) -> Bytes {
+		VersionedMultiLocation::from(location).encode().into()
+	}
+
+	#[tokio::test]
+	async fn trapped_assets_are_listed_with_their_claim_program() {
+		let api = TrappedAssetsRpc::<_, Block>::new(Arc::new(TestClient)).into_rpc();
+		let beneficiary: MultiLocation = AccountId32 { network: None, id: [1; 32] }.into();
+
+		let trapped_assets = api
+			.call::<_, Vec<TrappedAssets>>(
+				"xcm_trappedAssets",
+				rpc_params![encoded(sibling(2000)), Some(encoded(beneficiary)), None::<H256>],
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(trapped_assets.len(), 1);
+		assert_eq!(trapped_assets[0].assets, VersionedMultiAssets::from(trapped()).encode().into());
+		assert_eq!(trapped_assets[0].count, 2);
+		let program = Xcm::<()>(vec![
+			ClaimAsset { assets: trapped(), ticket: Here.into() },
+			DepositAsset { assets: Wild(All), beneficiary },
+		]);
+		assert_eq!(
+			trapped_assets[0].claim_program,
+			Some(VersionedXcm::from(program).encode().into())
+		);
+	}
+
+	#[tokio::test]
+	async fn claim_program_needs_a_beneficiary() {
+		let api = TrappedAssetsRpc::<_, Block>::new(Arc::new(TestClient)).into_rpc();
+
+		let trapped_assets = api
+			.call::<_, Vec<TrappedAssets>>(
+				"xcm_trappedAssets",
+				rpc_params![encoded(sibling(2000)), None::<Bytes>, None::<H256>],
+			)
+			.await
+			.unwrap();
+		assert_eq!(trapped_assets.len(), 1);
+		assert_eq!(trapped_assets[0].claim_program, None);
+
+		let trapped_assets = api
+			.call::<_, Vec<TrappedAssets>>(
+				"xcm_trappedAssets",
+				rpc_params![encoded(sibling(2001)), None::<Bytes>, None::<H256>],
+			)
+			.await
+			.unwrap();
+		assert!(trapped_assets.is_empty());
+	}
+
+	#[tokio::test]
+	async fn invalid_locations_are_rejected() {
+		let api = TrappedAssetsRpc::<_, Block>::new(Arc::new(TestClient)).into_rpc();
+
+		assert!(api
+			.call::<_, Vec<TrappedAssets>>(
+				"xcm_trappedAssets",
+				rpc_params![Bytes(vec![0xff]), None::<Bytes>, None::<H256>],
+			)
+			.await
+			.is_err());
+		assert!(api
+			.call::<_, Vec<TrappedAssets>>(
+				"xcm_trappedAssets",
+				rpc_params![encoded(sibling(2000)), Some(Bytes(vec![0xff])), None::<H256>],
+			)
+			.await
+			.is_err());
+	}
+}
@@ -16,9 +16,23 @@
 
 use codec::Codec;
 use cumulus_client_cli::CollatorOptions;
-use cumulus_client_consensus_aura::{AuraConsensus, BuildAuraConsensusParams, SlotProportion};
+use cumulus_client_collator::{
+	CollationArchive, CollationArchiveConfig, CollationStatsRecorder, LeaderLease,
+	LeaderLeaseConfig,
+};
+use cumulus_client_consensus_aura::{
+	AuraAuthorVerifier, AuraConsensus, BuildAuraConsensusParams, RelayParentStaleness,
+	RelayParentStalenessConfig, RuntimeEquivocationReporter, SlotProportion,
+};
 use cumulus_client_consensus_common::{
+	follow_backed_heads, follow_included_depth, ForkPruning,
 	ParachainBlockImport as TParachainBlockImport, ParachainCandidate, ParachainConsensus,
+	ProvisionalFinalityStream, PruningPolicy,
+};
+use cumulus_client_network::{AuthorVerificationConfig, EquivocationReporter};
+use cumulus_client_pov_recovery::{
+	peer_recovery::BlockDataRequestHandler, BlockDataRequester, NetworkBlockDataRequester,
+	RecoveryControl,
 };
 use cumulus_client_service::{
 	build_network, build_relay_chain_interface, prepare_node_config, start_collator,
@@ -28,26 +42,38 @@ use cumulus_primitives_core::{
 	relay_chain::{Hash as PHash, PersistedValidationData},
 	ParaId,
 };
+use cumulus_primitives_parachain_inherent::{
+	MockValidationDataInherentDataProvider, MockXcmConfig,
+};
 use cumulus_relay_chain_interface::{RelayChainError, RelayChainInterface};
+use sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging;
 use sp_core::Pair;
 
-use jsonrpsee::RpcModule;
-
-use crate::rpc;
+use crate::{
+	fee_asset_reservation::{enforce_fee_asset_reservation, FeeAssetReservation},
+	rpc,
+};
 pub use parachains_common::{AccountId, Balance, Block, BlockNumber, Hash, Header, Index as Nonce};
 
 use cumulus_client_consensus_relay_chain::Verifier as RelayChainVerifier;
-use futures::lock::Mutex;
+use futures::{lock::Mutex, StreamExt};
 use sc_consensus::{
 	import_queue::{BasicQueue, Verifier as VerifierT},
 	BlockImportParams, ImportQueue,
 };
+use sc_consensus_manual_seal::{
+	consensus::aura::AuraConsensusDataProvider, EngineCommand, ManualSealParams,
+};
 use sc_executor::WasmExecutor;
-use sc_network_common::service::NetworkBlock;
+use sc_network::NetworkService;
+use sc_network_common::{
+	protocol::ProtocolName, request_responses::IncomingRequest, service::NetworkBlock,
+};
 use sc_network_sync::SyncingService;
 use sc_service::{Configuration, PartialComponents, TFullBackend, TFullClient, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorker, TelemetryWorkerHandle};
 use sp_api::{ApiExt, ConstructRuntimeApi};
+use sp_blockchain::HeaderBackend;
 use sp_consensus::CacheKeyId;
 use sp_consensus_aura::AuraApi;
 use sp_keystore::SyncCryptoStorePtr;
@@ -87,12 +113,28 @@ impl sc_executor::NativeExecutionDispatch for InfraAssetSytemExecutor {
 	}
 }
 
+// Native parachain template executor instance.
+pub struct ParachainTemplateExecutor;
+
+impl sc_executor::NativeExecutionDispatch for ParachainTemplateExecutor {
+	type ExtendHostFunctions = frame_benchmarking::benchmarking::HostFunctions;
+
+	fn dispatch(method: &str, data: &[u8]) -> Option<Vec<u8>> {
+		parachain_template_runtime::api::dispatch(method, data)
+	}
+
+	fn native_version() -> sc_executor::NativeVersion {
+		parachain_template_runtime::native_version()
+	}
+}
+
 /// Starts a `ServiceBuilder` for a full service.
 ///
 /// Use this macro if you don't actually need the full service, but just the builder in order to
 /// be able to perform chain operations.
 pub fn new_partial<RuntimeApi, BIQ>(
 	config: &Configuration,
+	fork_pruning: ForkPruning,
 	build_import_queue: BIQ,
 ) -> Result<
 	PartialComponents<
@@ -169,7 +211,12 @@ where
 		client.clone(),
 	);
 
-	let block_import = ParachainBlockImport::new(client.clone(), backend.clone());
+	let block_import = ParachainBlockImport::new_with_pruning(
+		client.clone(),
+		backend.clone(),
+		fork_pruning,
+		config.prometheus_registry(),
+	);
 
 	let import_queue = build_import_queue(
 		client.clone(),
@@ -193,6 +240,31 @@ where
 	Ok(params)
 }
 
+/// Report the blocks backed by the relay chain to the block import, when the fork pruning
+/// policy keeps them.
+fn spawn_backed_heads_follower<RuntimeApi>(
+	task_manager: &TaskManager,
+	fork_pruning: ForkPruning,
+	para_id: ParaId,
+	relay_chain_interface: Arc<dyn RelayChainInterface>,
+	block_import: ParachainBlockImport<RuntimeApi>,
+) where
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi>> + Send + Sync + 'static,
+{
+	if fork_pruning.policy != PruningPolicy::KeepBacked {
+		return
+	}
+
+	// The level monitor lock may be held while a block is imported.
+	task_manager.spawn_handle().spawn_blocking(
+		"cumulus-backed-heads",
+		None,
+		follow_backed_heads::<Block, _>(para_id, relay_chain_interface, move |hash| {
+			block_import.block_backed(hash)
+		}),
+	);
+}
+
 /// Start a shell node with the given parachain `Configuration` and relay chain `Configuration`.
 ///
 /// This is the actual implementation that is abstract over the executor and the runtime api for shell nodes.
@@ -201,6 +273,7 @@ async fn start_shell_node_impl<RuntimeApi, RB, BIQ, BIC>(
 	parachain_config: Configuration,
 	infrablockspace_config: Configuration,
 	collator_options: CollatorOptions,
+	fork_pruning: ForkPruning,
 	para_id: ParaId,
 	rpc_ext_builder: RB,
 	build_import_queue: BIQ,
@@ -244,14 +317,28 @@ where
 		bool,
 	) -> Result<Box<dyn ParachainConsensus<Block>>, sc_service::Error>,
 {
-	let parachain_config = prepare_node_config(parachain_config);
+	let mut parachain_config = prepare_node_config(parachain_config);
 
-	let params = new_partial::<RuntimeApi, BIQ>(&parachain_config, build_import_queue)?;
+	let params =
+		new_partial::<RuntimeApi, BIQ>(&parachain_config, fork_pruning, build_import_queue)?;
 	let (block_import, mut telemetry, telemetry_worker_handle) = params.other;
 
 	let client = params.client.clone();
 	let backend = params.backend.clone();
 
+	let block_data_protocol_name = cumulus_client_pov_recovery::peer_recovery::protocol_name(
+		client.chain_info().genesis_hash,
+		parachain_config.chain_spec.fork_id(),
+	);
+	let (block_data_protocol_config, block_data_request_receiver) =
+		cumulus_client_pov_recovery::peer_recovery::generate_protocol_config(
+			block_data_protocol_name.clone(),
+		);
+	parachain_config
+		.network
+		.request_response_protocols
+		.push(block_data_protocol_config);
+
 	let mut task_manager = params.task_manager;
 
 	let (relay_chain_interface, collator_key) = build_relay_chain_interface(
@@ -268,6 +355,14 @@ where
 		s => s.to_string().into(),
 	})?;
 
+	spawn_backed_heads_follower(
+		&task_manager,
+		fork_pruning,
+		para_id,
+		relay_chain_interface.clone(),
+		block_import.clone(),
+	);
+
 	let force_authoring = parachain_config.force_authoring;
 	let validator = parachain_config.role.is_authority();
 	let prometheus_registry = parachain_config.prometheus_registry().cloned();
@@ -283,9 +378,19 @@ where
 			spawn_handle: task_manager.spawn_handle(),
 			relay_chain_interface: relay_chain_interface.clone(),
 			import_queue: params.import_queue,
+			author_verification: None,
 		})
 		.await?;
 
+	let block_data_requester = start_block_data_protocol(
+		client.clone(),
+		network.clone(),
+		sync_service.clone(),
+		block_data_protocol_name,
+		block_data_request_receiver,
+		&task_manager,
+	);
+
 	let rpc_client = client.clone();
 	let rpc_builder = Box::new(move |_, _| rpc_ext_builder(rpc_client.clone()));
 
@@ -360,6 +465,11 @@ where
 			collator_key: collator_key.expect("Command line arguments do not allow this. qed"),
 			relay_chain_slot_duration,
 			recovery_handle: Box::new(overseer_handle),
+			block_data_requester: Some(block_data_requester),
+			recovery_control: None,
+			collation_stats: None,
+			collation_archive: None,
+			leader_lease: None,
 		};
 
 		start_collator(params).await?;
@@ -373,6 +483,8 @@ where
 			relay_chain_slot_duration,
 			import_queue: import_queue_service,
 			recovery_handle: Box::new(overseer_handle),
+			block_data_requester: Some(block_data_requester),
+			recovery_control: None,
 		};
 
 		start_full_node(params)?;
@@ -387,14 +499,20 @@ where
 ///
 /// This is the actual implementation that is abstract over the executor and the runtime api.
 #[sc_tracing::logging::prefix_logs_with("Parachain")]
-async fn start_node_impl<RuntimeApi, RB, BIQ, BIC>(
+async fn start_node_impl<RuntimeApi, RB, BIQ, BIC, BAV>(
 	parachain_config: Configuration,
 	infrablockspace_config: Configuration,
 	collator_options: CollatorOptions,
+	fork_pruning: ForkPruning,
+	provisional_finality_depth: Option<u32>,
+	collation_archive: Option<CollationArchiveConfig>,
+	leader_lease: Option<LeaderLeaseConfig>,
+	fee_asset_reservation: Option<FeeAssetReservation>,
 	para_id: ParaId,
-	_rpc_ext_builder: RB,
+	rpc_ext_builder: RB,
 	build_import_queue: BIQ,
 	build_consensus: BIC,
+	build_author_verification: BAV,
 	hwbench: Option<sc_sysinfo::HwBench>,
 ) -> sc_service::error::Result<(TaskManager, Arc<ParachainClient<RuntimeApi>>)>
 where
@@ -411,7 +529,8 @@ where
 		+ pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
 		+ cumulus_primitives_core::CollectCollationInfo<Block>,
 	sc_client_api::StateBackendFor<ParachainBackend, Block>: sp_api::StateBackend<BlakeTwo256>,
-	RB: Fn(Arc<ParachainClient<RuntimeApi>>) -> Result<jsonrpsee::RpcModule<()>, sc_service::Error>,
+	RB: Fn(Arc<ParachainClient<RuntimeApi>>) -> Result<jsonrpsee::RpcModule<()>, sc_service::Error>
+		+ 'static,
 	BIQ: FnOnce(
 		Arc<ParachainClient<RuntimeApi>>,
 		ParachainBlockImport<RuntimeApi>,
@@ -434,15 +553,34 @@ where
 		SyncCryptoStorePtr,
 		bool,
 	) -> Result<Box<dyn ParachainConsensus<Block>>, sc_service::Error>,
+	BAV: FnOnce(
+		Arc<ParachainClient<RuntimeApi>>,
+		Arc<sc_transaction_pool::FullPool<Block, ParachainClient<RuntimeApi>>>,
+		&TaskManager,
+	) -> Option<AuthorVerificationConfig<Block>>,
 {
-	let parachain_config = prepare_node_config(parachain_config);
+	let mut parachain_config = prepare_node_config(parachain_config);
 
-	let params = new_partial::<RuntimeApi, BIQ>(&parachain_config, build_import_queue)?;
+	let params =
+		new_partial::<RuntimeApi, BIQ>(&parachain_config, fork_pruning, build_import_queue)?;
 	let (block_import, mut telemetry, telemetry_worker_handle) = params.other;
 
 	let client = params.client.clone();
 	let backend = params.backend.clone();
 
+	let block_data_protocol_name = cumulus_client_pov_recovery::peer_recovery::protocol_name(
+		client.chain_info().genesis_hash,
+		parachain_config.chain_spec.fork_id(),
+	);
+	let (block_data_protocol_config, block_data_request_receiver) =
+		cumulus_client_pov_recovery::peer_recovery::generate_protocol_config(
+			block_data_protocol_name.clone(),
+		);
+	parachain_config
+		.network
+		.request_response_protocols
+		.push(block_data_protocol_config);
+
 	let mut task_manager = params.task_manager;
 	let (relay_chain_interface, collator_key) = build_relay_chain_interface(
 		infrablockspace_config,
@@ -458,6 +596,14 @@ where
 		s => s.to_string().into(),
 	})?;
 
+	spawn_backed_heads_follower(
+		&task_manager,
+		fork_pruning,
+		para_id,
+		relay_chain_interface.clone(),
+		block_import.clone(),
+	);
+
 	let force_authoring = parachain_config.force_authoring;
 	let validator = parachain_config.role.is_authority();
 	let prometheus_registry = parachain_config.prometheus_registry().cloned();
@@ -473,22 +619,78 @@ where
 			spawn_handle: task_manager.spawn_handle(),
 			relay_chain_interface: relay_chain_interface.clone(),
 			import_queue: params.import_queue,
+			author_verification: build_author_verification(
+				client.clone(),
+				transaction_pool.clone(),
+				&task_manager,
+			),
 		})
 		.await?;
 
+	let block_data_requester = start_block_data_protocol(
+		client.clone(),
+		network.clone(),
+		sync_service.clone(),
+		block_data_protocol_name,
+		block_data_request_receiver,
+		&task_manager,
+	);
+
+	let recovery_control = RecoveryControl::new();
+	let collation_stats =
+		validator.then(|| CollationStatsRecorder::new(prometheus_registry.as_ref()));
+	let collation_archive = collation_archive
+		.filter(|_| validator)
+		.map(CollationArchive::open)
+		.transpose()
+		.map_err(|e| format!("Failed to open the collation archive: {}", e))?;
+
+	let provisional_finality = provisional_finality_depth.map(|depth| {
+		let (sender, stream) = ProvisionalFinalityStream::<Block>::channel();
+		task_manager.spawn_handle().spawn(
+			"cumulus-provisional-finality",
+			None,
+			follow_included_depth(para_id, relay_chain_interface.clone(), depth, sender),
+		);
+		stream
+	});
+
+	if let Some(reservation) = fee_asset_reservation {
+		task_manager.spawn_handle().spawn(
+			"fee-asset-reservation",
+			None,
+			enforce_fee_asset_reservation(
+				transaction_pool.clone(),
+				reservation,
+				parachain_config.transaction_pool.ready.count,
+			),
+		);
+	}
+
 	let rpc_builder = {
 		let client = client.clone();
 		let transaction_pool = transaction_pool.clone();
+		let recovery = recovery_control.controller();
+		let collation_stats = collation_stats.clone();
 
 		let backend_for_rpc = backend.clone();
-		Box::new(move |deny_unsafe, _| {
+		Box::new(move |deny_unsafe, subscription_executor| {
 			let deps = rpc::FullDeps {
 				client: client.clone(),
 				pool: transaction_pool.clone(),
 				deny_unsafe,
+				recovery: Some(recovery.clone()),
+				provisional_finality: provisional_finality.clone(),
+				subscription_executor,
+				dev_seal: None,
+				collation_stats: collation_stats.clone(),
 			};
 
-			rpc::create_full(deps, backend_for_rpc.clone()).map_err(Into::into)
+			let mut module = rpc::create_full(deps, backend_for_rpc.clone())?;
+			module
+				.merge(rpc_ext_builder(client.clone())?)
+				.map_err(|e| sc_service::Error::Application(e.into()))?;
+			Ok(module)
 		})
 	};
 
@@ -562,6 +764,14 @@ where
 			collator_key: collator_key.expect("Command line arguments do not allow this. qed"),
 			relay_chain_slot_duration,
 			recovery_handle: Box::new(overseer_handle),
+			block_data_requester: Some(block_data_requester),
+			recovery_control: Some(recovery_control),
+			collation_stats,
+			collation_archive,
+			leader_lease: leader_lease
+				.map(LeaderLease::new)
+				.transpose()
+				.map_err(sc_service::Error::Other)?,
 		};
 
 		start_collator(params).await?;
@@ -575,6 +785,8 @@ where
 			relay_chain_slot_duration,
 			import_queue: import_queue_service,
 			recovery_handle: Box::new(overseer_handle),
+			block_data_requester: Some(block_data_requester),
+			recovery_control: Some(recovery_control),
 		};
 
 		start_full_node(params)?;
@@ -584,6 +796,35 @@ where
 
 	Ok((task_manager, client))
 }
+
+/// Serve block data requests from parachain peers and build the requester used by pov-recovery.
+fn start_block_data_protocol<RuntimeApi>(
+	client: Arc<ParachainClient<RuntimeApi>>,
+	network: Arc<NetworkService<Block, Hash>>,
+	sync_service: Arc<SyncingService<Block>>,
+	protocol_name: ProtocolName,
+	request_receiver: futures::channel::mpsc::Receiver<IncomingRequest>,
+	task_manager: &TaskManager,
+) -> Arc<dyn BlockDataRequester<Block>>
+where
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi>> + Send + Sync + 'static,
+	RuntimeApi::RuntimeApi: sp_api::Core<Block>
+		+ sp_api::ApiExt<
+			Block,
+			StateBackend = sc_client_api::StateBackendFor<ParachainBackend, Block>,
+		>,
+	sc_client_api::StateBackendFor<ParachainBackend, Block>: sp_api::StateBackend<BlakeTwo256>,
+{
+	// Serving a request re-executes the block.
+	task_manager.spawn_handle().spawn_blocking(
+		"cumulus-block-data-request-handler",
+		None,
+		BlockDataRequestHandler::new(client, request_receiver).run(),
+	);
+
+	Arc::new(NetworkBlockDataRequester::new(network, sync_service, protocol_name))
+}
+
 enum BuildOnAccess<R> {
 	Uninitialized(Option<Box<dyn FnOnce() -> R + Send + Sync>>),
 	Initialized(R),
@@ -753,16 +994,43 @@ where
 	Ok(BasicQueue::new(verifier, Box::new(block_import), None, &spawner, registry))
 }
 
+/// Options of the block authoring of an aura collator.
+#[derive(Debug, Clone, Default)]
+pub struct AuthoringOptions {
+	/// Skip authoring on relay parents lagging too far behind the best relay chain block.
+	pub relay_parent_staleness: Option<RelayParentStalenessConfig>,
+	/// Back off authoring when the finalized head lags behind the best head.
+	pub backoff_authoring_blocks: bool,
+	/// Only author while holding this leader lease, if given.
+	pub leader_lease: Option<LeaderLeaseConfig>,
+}
+
 /// Start an aura powered parachain node.
 /// (collective-polkadot and statemine/t use this)
-pub async fn start_generic_aura_node<RuntimeApi, AuraId: AppKey>(
+///
+/// `rpc_ext_builder` builds the RPC extensions specific to the runtime.
+pub async fn start_generic_aura_node<RuntimeApi, AuraId: AppKey, RB, BER>(
 	parachain_config: Configuration,
 	infrablockspace_config: Configuration,
 	collator_options: CollatorOptions,
+	fork_pruning: ForkPruning,
+	provisional_finality_depth: Option<u32>,
+	collation_archive: Option<CollationArchiveConfig>,
+	authoring: AuthoringOptions,
+	fee_asset_reservation: Option<FeeAssetReservation>,
 	para_id: ParaId,
+	rpc_ext_builder: RB,
+	build_equivocation_reporter: BER,
 	hwbench: Option<sc_sysinfo::HwBench>,
 ) -> sc_service::error::Result<(TaskManager, Arc<ParachainClient<RuntimeApi>>)>
 where
+	RB: Fn(Arc<ParachainClient<RuntimeApi>>) -> Result<jsonrpsee::RpcModule<()>, sc_service::Error>
+		+ 'static,
+	BER: FnOnce(
+		Arc<ParachainClient<RuntimeApi>>,
+		Arc<sc_transaction_pool::FullPool<Block, ParachainClient<RuntimeApi>>>,
+		&TaskManager,
+	) -> Option<Arc<dyn EquivocationReporter<Block>>>,
 	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi>> + Send + Sync + 'static,
 	RuntimeApi::RuntimeApi: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>
 		+ sp_api::Metadata<Block>
@@ -780,12 +1048,20 @@ where
 	<<AuraId as AppKey>::Pair as Pair>::Signature:
 		TryFrom<Vec<u8>> + std::hash::Hash + sp_runtime::traits::Member + Codec,
 {
-	start_node_impl::<RuntimeApi, _, _, _>(
+	let AuthoringOptions { relay_parent_staleness, backoff_authoring_blocks, leader_lease } =
+		authoring;
+
+	start_node_impl::<RuntimeApi, _, _, _, _>(
 		parachain_config,
 		infrablockspace_config,
 		collator_options,
+		fork_pruning,
+		provisional_finality_depth,
+		collation_archive,
+		leader_lease,
+		fee_asset_reservation,
 		para_id,
-		|_| Ok(RpcModule::new(())),
+		rpc_ext_builder,
 		aura_build_import_queue::<_, AuraId>,
 		|client,
 		 block_import,
@@ -805,6 +1081,16 @@ where
 			let prometheus_registry2 = prometheus_registry.map(|r| (*r).clone());
 			let relay_chain_for_aura = relay_chain_interface.clone();
 
+			let relay_parent_staleness = relay_parent_staleness.map(|config| {
+				let staleness = RelayParentStaleness::new(config, prometheus_registry);
+				spawn_handle.spawn(
+					"cumulus-relay-parent-staleness",
+					None,
+					staleness.track_best_number(relay_chain_interface.clone()),
+				);
+				staleness
+			});
+
 			let aura_consensus = BuildOnAccess::Uninitialized(Some(Box::new(move || {
 				let slot_duration =
 					cumulus_client_consensus_aura::slot_duration(&*client2).unwrap();
@@ -853,7 +1139,8 @@ where
 							},
 						block_import: block_import2,
 						para_client: client2,
-						backoff_authoring_blocks: Option::<()>::None,
+						backoff_authoring_blocks: backoff_authoring_blocks
+							.then(BackoffAuthoringOnFinalizedHeadLagging::<BlockNumber>::default),
 						sync_oracle,
 						keystore,
 						force_authoring,
@@ -863,6 +1150,7 @@ where
 						// And a maximum of 750ms if slots are skipped
 						max_block_proposal_slot_portion: Some(SlotProportion::new(1f32 / 16f32)),
 						telemetry: telemetry2,
+						relay_parent_staleness,
 					},
 				)
 			})));
@@ -914,11 +1202,267 @@ where
 
 			Ok(parachain_consensus)
 		},
+		|client, transaction_pool, task_manager| {
+			Some(AuthorVerificationConfig {
+				verifier: Arc::new(AuraAuthorVerifier::<<AuraId as AppKey>::Pair, _>::new(
+					client.clone(),
+				)),
+				reporter: build_equivocation_reporter(client, transaction_pool, task_manager),
+			})
+		},
 		hwbench,
 	)
 	.await
 }
 
+/// Build the reporter submitting the equivocations detected in block announcements to the
+/// runtime, for runtimes implementing the [`CollatorEquivocationApi`].
+///
+/// [`CollatorEquivocationApi`]: cumulus_primitives_core::CollatorEquivocationApi
+pub fn runtime_equivocation_reporter<RuntimeApi>(
+	client: Arc<ParachainClient<RuntimeApi>>,
+	transaction_pool: Arc<sc_transaction_pool::FullPool<Block, ParachainClient<RuntimeApi>>>,
+	task_manager: &TaskManager,
+) -> Option<Arc<dyn EquivocationReporter<Block>>>
+where
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi>> + Send + Sync + 'static,
+	RuntimeApi::RuntimeApi: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>
+		+ sp_api::ApiExt<
+			Block,
+			StateBackend = sc_client_api::StateBackendFor<ParachainBackend, Block>,
+		> + cumulus_primitives_core::CollatorEquivocationApi<Block>,
+	sc_client_api::StateBackendFor<ParachainBackend, Block>: sp_api::StateBackend<BlakeTwo256>,
+{
+	Some(Arc::new(RuntimeEquivocationReporter::new(
+		client,
+		transaction_pool,
+		task_manager.spawn_handle(),
+	)))
+}
+
+/// Block production of a node running the parachain runtime without a relay chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevSeal {
+	/// Seal a block as soon as a transaction is imported into the pool.
+	Instant,
+	/// Seal a block at the given interval.
+	Interval(Duration),
+}
+
+/// Build the import queue of a node started with [`start_dev_seal_node`].
+///
+/// Blocks are imported straight into the client with the longest chain fork choice, as there is
+/// no relay chain choosing the best block for the `ParachainBlockImport`.
+pub fn dev_seal_build_import_queue<RuntimeApi>(
+	client: Arc<ParachainClient<RuntimeApi>>,
+	_: ParachainBlockImport<RuntimeApi>,
+	config: &Configuration,
+	_: Option<TelemetryHandle>,
+	task_manager: &TaskManager,
+) -> Result<sc_consensus::DefaultImportQueue<Block, ParachainClient<RuntimeApi>>, sc_service::Error>
+where
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi>> + Send + Sync + 'static,
+	RuntimeApi::RuntimeApi: sp_api::ApiExt<
+			Block,
+			StateBackend = sc_client_api::StateBackendFor<ParachainBackend, Block>,
+		> + sp_block_builder::BlockBuilder<Block>,
+	sc_client_api::StateBackendFor<ParachainBackend, Block>: sp_api::StateBackend<BlakeTwo256>,
+{
+	Ok(sc_consensus_manual_seal::import_queue(
+		Box::new(client),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	))
+}
+
+/// The timestamp in milliseconds of the genesis of the chains sealed by
+/// [`start_dev_seal_node`], 2023-01-01 00:00:00 UTC.
+pub const DEV_SEAL_START_TIMESTAMP: u64 = 1_672_531_200_000;
+
+/// Start a node running the parachain runtime standalone, without a relay chain.
+///
+/// Blocks are sealed locally according to `dev_seal` and on demand with the `engine_createBlock`
+/// RPC. The parachain inherent is mocked with [`MockValidationDataInherentDataProvider`] and the
+/// timestamp advances by one slot per block from [`DEV_SEAL_START_TIMESTAMP`], which makes the
+/// produced blocks deterministic.
+/// `rpc_ext_builder` builds the RPC extensions specific to the runtime.
+#[sc_tracing::logging::prefix_logs_with("Parachain")]
+pub async fn start_dev_seal_node<RuntimeApi, RB>(
+	parachain_config: Configuration,
+	para_id: ParaId,
+	dev_seal: DevSeal,
+	rpc_ext_builder: RB,
+) -> sc_service::error::Result<(TaskManager, Arc<ParachainClient<RuntimeApi>>)>
+where
+	RB: Fn(Arc<ParachainClient<RuntimeApi>>) -> Result<jsonrpsee::RpcModule<()>, sc_service::Error>
+		+ 'static,
+	RuntimeApi: ConstructRuntimeApi<Block, ParachainClient<RuntimeApi>> + Send + Sync + 'static,
+	RuntimeApi::RuntimeApi: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>
+		+ sp_api::Metadata<Block>
+		+ sp_session::SessionKeys<Block>
+		+ sp_api::ApiExt<
+			Block,
+			StateBackend = sc_client_api::StateBackendFor<ParachainBackend, Block>,
+		> + sp_offchain::OffchainWorkerApi<Block>
+		+ sp_block_builder::BlockBuilder<Block>
+		+ sp_consensus_aura::AuraApi<Block, sp_consensus_aura::sr25519::AuthorityId>
+		+ frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>
+		+ pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	sc_client_api::StateBackendFor<ParachainBackend, Block>: sp_api::StateBackend<BlakeTwo256>,
+{
+	let params = new_partial::<RuntimeApi, _>(
+		&parachain_config,
+		ForkPruning::default(),
+		dev_seal_build_import_queue,
+	)?;
+	let (_, mut telemetry, _) = params.other;
+
+	let client = params.client.clone();
+	let backend = params.backend.clone();
+	let mut task_manager = params.task_manager;
+	let transaction_pool = params.transaction_pool.clone();
+	let prometheus_registry = parachain_config.prometheus_registry().cloned();
+
+	let (network, system_rpc_tx, tx_handler_controller, start_network, sync_service) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &parachain_config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue: params.import_queue,
+			block_announce_validator_builder: None,
+			warp_sync_params: None,
+		})?;
+
+	let (command_sink, rpc_commands) = futures::channel::mpsc::channel(1024);
+
+	let rpc_builder = {
+		let client = client.clone();
+		let transaction_pool = transaction_pool.clone();
+
+		let backend_for_rpc = backend.clone();
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = rpc::FullDeps {
+				client: client.clone(),
+				pool: transaction_pool.clone(),
+				deny_unsafe,
+				recovery: None,
+				provisional_finality: None,
+				subscription_executor,
+				dev_seal: Some(command_sink.clone()),
+				collation_stats: None,
+			};
+
+			let mut module = rpc::create_full(deps, backend_for_rpc.clone())?;
+			module
+				.merge(rpc_ext_builder(client.clone())?)
+				.map_err(|e| sc_service::Error::Application(e.into()))?;
+			Ok(module)
+		})
+	};
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		rpc_builder,
+		client: client.clone(),
+		transaction_pool: transaction_pool.clone(),
+		task_manager: &mut task_manager,
+		config: parachain_config,
+		keystore: params.keystore_container.sync_keystore(),
+		backend: backend.clone(),
+		network,
+		sync_service,
+		system_rpc_tx,
+		tx_handler_controller,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	let seal_commands = match dev_seal {
+		DevSeal::Instant => transaction_pool
+			.pool()
+			.validated_pool()
+			.import_notification_stream()
+			.map(|_| EngineCommand::SealNewBlock {
+				create_empty: false,
+				finalize: true,
+				parent_hash: None,
+				sender: None,
+			})
+			.boxed(),
+		DevSeal::Interval(interval) => futures::stream::unfold((), move |()| async move {
+			futures_timer::Delay::new(interval).await;
+			let command = EngineCommand::SealNewBlock {
+				create_empty: true,
+				finalize: true,
+				parent_hash: None,
+				sender: None,
+			};
+			Some((command, ()))
+		})
+		.boxed(),
+	};
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|t| t.handle()),
+	);
+
+	let slot_duration = cumulus_client_consensus_aura::slot_duration(&*client)?;
+	let client_for_cidp = client.clone();
+	let create_inherent_data_providers = move |parent: Hash, ()| {
+		let client = client_for_cidp.clone();
+		async move {
+			let current_para_block = client
+				.header(parent)?
+				.ok_or_else(|| format!("Unknown parent block {:?}", parent))?
+				.number;
+
+			// Each block advances the timestamp by one slot, so that every block gets a new slot
+			// regardless of the time at which it is sealed.
+			let timestamp = sp_timestamp::InherentDataProvider::new(sp_timestamp::Timestamp::new(
+				DEV_SEAL_START_TIMESTAMP +
+					slot_duration.as_millis() * (current_para_block as u64 + 1),
+			));
+
+			let parachain_inherent = MockValidationDataInherentDataProvider {
+				current_para_block,
+				relay_offset: 1000,
+				relay_blocks_per_para_block: 2,
+				para_blocks_per_relay_epoch: 0,
+				relay_randomness_config: (),
+				xcm_config: MockXcmConfig::new(&*client, parent, para_id, Default::default()),
+				raw_downward_messages: Vec::new(),
+				raw_horizontal_messages: Vec::new(),
+			};
+
+			Ok::<_, Box<dyn std::error::Error + Send + Sync>>((timestamp, parachain_inherent))
+		}
+	};
+
+	task_manager.spawn_essential_handle().spawn_blocking(
+		"dev-seal",
+		None,
+		sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+			// Sealed blocks become the best block with the longest chain fork choice, unlike with
+			// the `ParachainBlockImport` which leaves it to the relay chain.
+			block_import: client.clone(),
+			env: proposer_factory,
+			client: client.clone(),
+			pool: transaction_pool,
+			commands_stream: futures::stream::select(seal_commands, rpc_commands),
+			select_chain: sc_consensus::LongestChain::new(backend),
+			consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client.clone()))),
+			create_inherent_data_providers,
+		}),
+	);
+
+	start_network.start_network();
+
+	Ok((task_manager, client))
+}
+
 /// Checks that the hardware meets the requirements and print a warning otherwise.
 fn warn_if_slow_hardware(hwbench: &sc_sysinfo::HwBench) {
 	// Polkadot para-chains should generally use these requirements to ensure that the relay-chain
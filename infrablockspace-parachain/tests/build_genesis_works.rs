@@ -0,0 +1,72 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::{path::Path, process::Command};
+use tempfile::tempdir;
+
+const PRESET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/presets/infra-asset-system-local.toml");
+
+fn build_genesis(preset: &Path, output: &Path) -> std::process::Output {
+	Command::new(cargo_bin("infrablockspace-parachain"))
+		.arg("build-genesis")
+		.arg(preset)
+		.arg("--output")
+		.arg(output)
+		.output()
+		.unwrap()
+}
+
+/// The `build-genesis` command builds a raw chain spec from the shipped preset.
+#[test]
+fn build_genesis_works() {
+	let tmp_dir = tempdir().expect("could not create a temp dir");
+	let output = tmp_dir.path().join("chain-spec.json");
+
+	assert!(build_genesis(Path::new(PRESET), &output).status.success());
+
+	let chain_spec: serde_json::Value =
+		serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+	assert_eq!(chain_spec["id"], "infra_asset_system_local");
+	assert_eq!(chain_spec["para_id"], 1000);
+	assert!(chain_spec["genesis"]["raw"]["top"].is_object());
+}
+
+/// The `build-genesis` command rejects presets with unknown fields or without collators.
+#[test]
+fn build_genesis_rejects_invalid_presets() {
+	let tmp_dir = tempdir().expect("could not create a temp dir");
+	let output = tmp_dir.path().join("chain-spec.json");
+	let preset = std::fs::read_to_string(PRESET).unwrap();
+
+	let typo = tmp_dir.path().join("typo.toml");
+	std::fs::write(&typo, preset.replace("para_id = 1000\nsudo", "paraid = 1000\nsudo")).unwrap();
+	let result = build_genesis(&typo, &output);
+	assert!(!result.status.success());
+	assert!(String::from_utf8_lossy(&result.stderr).contains("unknown field"));
+
+	let no_collators = tmp_dir.path().join("no_collators.toml");
+	let preset = preset
+		.replace("[[collators]]\naccount = \"//Alice\"\n", "")
+		.replace("[[collators]]\naccount = \"//Bob\"\n", "")
+		.replace("sudo = \"//Alice\"\n", "sudo = \"//Alice\"\ncollators = []\n");
+	std::fs::write(&no_collators, preset).unwrap();
+	let result = build_genesis(&no_collators, &output);
+	assert!(!result.status.success());
+	assert!(String::from_utf8_lossy(&result.stderr).contains("At least one collator"));
+
+	assert!(!output.exists());
+}
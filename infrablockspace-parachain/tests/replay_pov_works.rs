@@ -0,0 +1,56 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+use assert_cmd::cargo::cargo_bin;
+use std::process::Command;
+use tempfile::tempdir;
+
+/// The `replay-pov` command reports the failing check of an invalid PoV.
+#[test]
+fn replay_pov_reports_invalid_block_data() {
+	let tmp_dir = tempdir().expect("could not create a temp dir");
+	let pov = tmp_dir.path().join("pov.hex");
+	std::fs::write(&pov, "0x0102030405").unwrap();
+
+	let output = Command::new(cargo_bin("infrablockspace-parachain"))
+		.arg("replay-pov")
+		.arg(&pov)
+		.args(["--runtime", "Infra Asset System"])
+		.args(["--parent-head", "0x00"])
+		.args(["--relay-parent-number", "1"])
+		.args(["--relay-parent-storage-root", &format!("0x{}", "00".repeat(32))])
+		.args(["--wasm-execution", "interpreted-i-know-what-i-do"])
+		.output()
+		.unwrap();
+
+	assert!(!output.status.success());
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(stdout.contains("Check failed: invalid parachain block data"));
+	assert!(stdout.contains("Validation failed"));
+}
+
+/// The `replay-pov` command requires the runtime code or the runtime name.
+#[test]
+fn replay_pov_requires_a_runtime() {
+	let output = Command::new(cargo_bin("infrablockspace-parachain"))
+		.args(["replay-pov", "pov.bin", "--parent-head", "0x00", "--relay-parent-number", "1"])
+		.args(["--relay-parent-storage-root", &format!("0x{}", "00".repeat(32))])
+		.output()
+		.unwrap();
+
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("Either `--wasm` or `--runtime`"));
+}
@@ -193,6 +193,12 @@ pub mod pallet {
 	}
 
 	impl<T: Config> Pallet<T> {
+		/// Returns the maximum weight of a message executed from the queue, heavier messages being
+		/// kept as overweight.
+		pub fn max_individual_weight() -> Weight {
+			Configuration::<T>::get().max_individual
+		}
+
 		/// Service the message queue up to some given weight `limit`.
 		///
 		/// Returns the weight consumed by executing messages in the queue.
@@ -0,0 +1,89 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarking setup for cumulus-pallet-xcm-barrier
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as XcmBarrier;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, BenchmarkError};
+use frame_support::{
+	assert_ok,
+	traits::{EnsureOrigin, Get},
+	BoundedVec,
+};
+use xcm::latest::prelude::*;
+
+fn sibling(para_id: u32) -> MultiLocation {
+	MultiLocation::new(1, X1(Parachain(para_id)))
+}
+
+/// Fill the free execution origins up to one below the maximum.
+fn fill_free_execution_origins<T: Config>() {
+	let mut para_id = 1;
+	while FreeExecutionOrigins::<T>::count() + 1 < T::MaxFreeExecutionOrigins::get() {
+		FreeExecutionOrigins::<T>::insert(sibling(para_id), Weight::MAX);
+		para_id += 1;
+	}
+}
+
+benchmarks! {
+	allow_free_execution {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		fill_free_execution_origins::<T>();
+		let location = sibling(0);
+	}: {
+		assert_ok!(
+			<XcmBarrier<T>>::allow_free_execution(origin, Box::new(location.into()), Weight::MAX)
+		);
+	}
+	verify {
+		assert_eq!(Pallet::<T>::free_execution_weight(&location), Some(Weight::MAX));
+	}
+
+	revoke_free_execution {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		fill_free_execution_origins::<T>();
+		let location = sibling(0);
+		FreeExecutionOrigins::<T>::insert(location, Weight::MAX);
+	}: {
+		assert_ok!(<XcmBarrier<T>>::revoke_free_execution(origin, Box::new(location.into())));
+	}
+	verify {
+		assert_eq!(Pallet::<T>::free_execution_weight(&location), None);
+	}
+
+	set_denied_instructions {
+		let p in 0 .. T::MaxDeniedInstructions::get();
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let patterns = (0..p)
+			.map(|para_id| DeniedInstruction::ReserveTransferTo(sibling(para_id)))
+			.collect::<Vec<_>>();
+		let patterns: BoundedVec<_, _> =
+			patterns.try_into().expect("At most `MaxDeniedInstructions` patterns; qed");
+	}: {
+		assert_ok!(<XcmBarrier<T>>::set_denied_instructions(origin, patterns.clone()));
+	}
+	verify {
+		assert_eq!(DeniedInstructions::<T>::get(), patterns);
+	}
+}
+
+impl_benchmark_test_suite!(XcmBarrier, crate::tests::new_test_ext(), crate::tests::Test);
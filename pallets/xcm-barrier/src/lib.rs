@@ -0,0 +1,648 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pallet storing the configuration of a governed XCM barrier.
+//!
+//! Holds the origins allowed to execute XCM without paying, each with the maximum weight of their
+//! messages, and the patterns of instructions denied whatever the origin. Both are managed by the
+//! `AdminOrigin`, usually root or the relay chain governance. The barriers reading this
+//! configuration are in `parachains-common`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, DecodeLimit, Encode, MaxEncodedLen};
+use frame_support::weights::Weight;
+pub use pallet::*;
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+use xcm::{
+	latest::{Instruction, MultiLocation},
+	DoubleEncoded, MAX_XCM_DECODE_DEPTH,
+};
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::WeightInfo;
+
+/// Calls dispatched by another call, e.g. the calls of a batch or a proxy.
+pub trait InnerCalls<Call> {
+	/// Returns the calls dispatched by `call`.
+	fn inner_calls(call: &Call) -> Vec<&Call>;
+}
+
+impl<Call> InnerCalls<Call> for () {
+	fn inner_calls(_: &Call) -> Vec<&Call> {
+		Vec::new()
+	}
+}
+
+/// Pattern of instructions denied by the barrier.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum DeniedInstruction {
+	/// Any `Transact`.
+	Transact,
+	/// `Transact` of a call of the pallet with the given index, of any of its calls if
+	/// `call_index` is `None`, including the calls dispatched by the transacted call.
+	TransactCall { pallet_index: u8, call_index: Option<u8> },
+	/// Reserve transfers with the given location as reserve or destination.
+	ReserveTransferTo(MultiLocation),
+	/// Teleports to the given location.
+	TeleportTo(MultiLocation),
+	/// Any `ExportMessage`.
+	ExportMessage,
+	/// Any `UniversalOrigin`.
+	UniversalOrigin,
+}
+
+impl DeniedInstruction {
+	/// Returns `true` if `instruction` matches this pattern.
+	///
+	/// The calls of `Transact` are not decoded here, [`Self::TransactCall`] patterns are matched
+	/// against them by [`Pallet::is_denied`].
+	pub fn matches<Call>(&self, instruction: &Instruction<Call>) -> bool {
+		use Instruction::*;
+
+		match (self, instruction) {
+			(Self::Transact, Transact { .. }) => true,
+			(
+				Self::ReserveTransferTo(location),
+				InitiateReserveWithdraw { reserve: dest, .. } |
+				DepositReserveAsset { dest, .. } |
+				TransferReserveAsset { dest, .. },
+			) => dest == location,
+			(Self::TeleportTo(location), InitiateTeleport { dest, .. }) => dest == location,
+			(Self::ExportMessage, ExportMessage { .. }) => true,
+			(Self::UniversalOrigin, UniversalOrigin(_)) => true,
+			_ => false,
+		}
+	}
+
+	/// Returns `true` if the encoded call, starting with its pallet and call indices, matches
+	/// this pattern.
+	pub fn matches_call(&self, encoded_call: &[u8]) -> bool {
+		match (self, encoded_call) {
+			(Self::TransactCall { pallet_index, call_index }, [pallet, call, ..]) =>
+				pallet == pallet_index && call_index.map_or(true, |index| index == *call),
+			_ => false,
+		}
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use xcm::VersionedMultiLocation;
+
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Origin allowed to change the configuration of the barrier.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origins allowed to execute XCM without paying at genesis, or when the pallet is added
+		/// to a running chain, with the maximum weight of their messages.
+		type InitialFreeExecutionOrigins: Get<Vec<(MultiLocation, Weight)>>;
+
+		/// Maximum number of origins allowed to execute XCM without paying.
+		#[pallet::constant]
+		type MaxFreeExecutionOrigins: Get<u32>;
+
+		/// Maximum number of denied instruction patterns.
+		#[pallet::constant]
+		type MaxDeniedInstructions: Get<u32>;
+
+		/// Returns the calls dispatched by a call, which are matched by the
+		/// [`DeniedInstruction::TransactCall`] patterns like the transacted call.
+		type InnerCalls: InnerCalls<<Self as frame_system::Config>::RuntimeCall>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// Origins allowed to execute XCM without paying, with the maximum weight of their messages.
+	#[pallet::storage]
+	pub type FreeExecutionOrigins<T: Config> =
+		CountedStorageMap<_, Blake2_128Concat, MultiLocation, Weight, OptionQuery>;
+
+	/// Patterns of the instructions denied whatever the origin of the message.
+	#[pallet::storage]
+	pub type DeniedInstructions<T: Config> =
+		StorageValue<_, BoundedVec<DeniedInstruction, T::MaxDeniedInstructions>, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The origin may execute XCM without paying, up to the given weight per message.
+		FreeExecutionAllowed { location: MultiLocation, max_weight: Weight },
+		/// The origin may no longer execute XCM without paying.
+		FreeExecutionRevoked { location: MultiLocation },
+		/// The denied instruction patterns were replaced.
+		DeniedInstructionsSet { patterns: BoundedVec<DeniedInstruction, T::MaxDeniedInstructions> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The location can't be converted to the latest XCM version.
+		BadVersion,
+		/// The maximum number of origins allowed to execute XCM without paying is reached.
+		TooManyFreeExecutionOrigins,
+	}
+
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig;
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			Pallet::<T>::initialize_free_execution_origins();
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			// The pallet was added to a running chain.
+			if StorageVersion::get::<Pallet<T>>() == 0 {
+				let count = Pallet::<T>::initialize_free_execution_origins();
+				STORAGE_VERSION.put::<Pallet<T>>();
+				T::DbWeight::get().reads_writes(count as u64 + 1, count as u64 * 2 + 1)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok(StorageVersion::get::<Pallet<T>>().encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let previous = StorageVersion::decode(&mut &state[..])
+				.map_err(|_| "the storage version before the upgrade can't be decoded")?;
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"the free execution origins are not initialized"
+			);
+			if previous == 0 {
+				let expected = T::InitialFreeExecutionOrigins::get()
+					.len()
+					.min(T::MaxFreeExecutionOrigins::get() as usize);
+				ensure!(
+					FreeExecutionOrigins::<T>::count() as usize == expected,
+					"the initial free execution origins are not stored"
+				);
+			}
+			Ok(())
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Allow `location` to execute XCM without paying, up to `max_weight` per message.
+		///
+		/// Replaces the maximum weight if `location` was already allowed.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::allow_free_execution())]
+		pub fn allow_free_execution(
+			origin: OriginFor<T>,
+			location: Box<VersionedMultiLocation>,
+			max_weight: Weight,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let location: MultiLocation =
+				(*location).try_into().map_err(|()| Error::<T>::BadVersion)?;
+
+			ensure!(
+				FreeExecutionOrigins::<T>::contains_key(location) ||
+					FreeExecutionOrigins::<T>::count() < T::MaxFreeExecutionOrigins::get(),
+				Error::<T>::TooManyFreeExecutionOrigins,
+			);
+			FreeExecutionOrigins::<T>::insert(location, max_weight);
+
+			Self::deposit_event(Event::FreeExecutionAllowed { location, max_weight });
+			Ok(())
+		}
+
+		/// Revoke the free execution of XCM from `location`.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::revoke_free_execution())]
+		pub fn revoke_free_execution(
+			origin: OriginFor<T>,
+			location: Box<VersionedMultiLocation>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let location: MultiLocation =
+				(*location).try_into().map_err(|()| Error::<T>::BadVersion)?;
+
+			if FreeExecutionOrigins::<T>::take(location).is_some() {
+				Self::deposit_event(Event::FreeExecutionRevoked { location });
+			}
+			Ok(())
+		}
+
+		/// Replace the patterns of the instructions denied whatever the origin of the message.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::set_denied_instructions(patterns.len() as u32))]
+		pub fn set_denied_instructions(
+			origin: OriginFor<T>,
+			patterns: BoundedVec<DeniedInstruction, T::MaxDeniedInstructions>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			DeniedInstructions::<T>::put(&patterns);
+
+			Self::deposit_event(Event::DeniedInstructionsSet { patterns });
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Returns the maximum weight of the messages `location` may execute without paying, if
+	/// allowed.
+	pub fn free_execution_weight(location: &MultiLocation) -> Option<Weight> {
+		FreeExecutionOrigins::<T>::get(location)
+	}
+
+	/// Returns `true` if any of `instructions` matches a denied pattern.
+	///
+	/// The instructions of the error handler and appendix are checked too, as well as the calls
+	/// dispatched by the calls of `Transact`. Calls which can't be decoded are denied if there is
+	/// any [`DeniedInstruction::TransactCall`] pattern.
+	pub fn is_denied<Call>(instructions: &[Instruction<Call>]) -> bool {
+		let patterns = DeniedInstructions::<T>::get();
+		!patterns.is_empty() && Self::matches_any(&patterns, instructions)
+	}
+
+	fn matches_any<Call>(
+		patterns: &[DeniedInstruction],
+		instructions: &[Instruction<Call>],
+	) -> bool {
+		instructions.iter().any(|instruction| match instruction {
+			Instruction::SetErrorHandler(xcm) | Instruction::SetAppendix(xcm) =>
+				Self::matches_any(patterns, &xcm.0),
+			Instruction::Transact { call, .. } =>
+				patterns.iter().any(|pattern| pattern.matches(instruction)) ||
+					Self::transact_is_denied(patterns, call),
+			instruction => patterns.iter().any(|pattern| pattern.matches(instruction)),
+		})
+	}
+
+	fn transact_is_denied<Call>(
+		patterns: &[DeniedInstruction],
+		call: &DoubleEncoded<Call>,
+	) -> bool {
+		if !patterns
+			.iter()
+			.any(|pattern| matches!(pattern, DeniedInstruction::TransactCall { .. }))
+		{
+			return false
+		}
+
+		// The call is decoded as the runtime call, whatever the call type of the message, with
+		// the depth limit of the executor.
+		let call = Vec::<u8>::decode(&mut &call.encode()[..]).ok().and_then(|encoded| {
+			<T as frame_system::Config>::RuntimeCall::decode_all_with_depth_limit(
+				MAX_XCM_DECODE_DEPTH,
+				&mut &encoded[..],
+			)
+			.ok()
+		});
+		// Such a call would fail to dispatch anyway.
+		call.map_or(true, |call| Self::call_is_denied(patterns, &call))
+	}
+
+	fn call_is_denied(
+		patterns: &[DeniedInstruction],
+		call: &<T as frame_system::Config>::RuntimeCall,
+	) -> bool {
+		call.using_encoded(|encoded| patterns.iter().any(|pattern| pattern.matches_call(encoded))) ||
+			T::InnerCalls::inner_calls(call)
+				.into_iter()
+				.any(|inner_call| Self::call_is_denied(patterns, inner_call))
+	}
+
+	/// Store the initial origins allowed to execute XCM without paying, returns their number.
+	///
+	/// At most `MaxFreeExecutionOrigins` origins are stored, the others are ignored.
+	fn initialize_free_execution_origins() -> usize {
+		let mut origins = T::InitialFreeExecutionOrigins::get();
+		let max = T::MaxFreeExecutionOrigins::get() as usize;
+		if origins.len() > max {
+			log::warn!(
+				target: "runtime::xcm-barrier",
+				"Ignoring {} initial free execution origins above the maximum of {}",
+				origins.len() - max,
+				max,
+			);
+			origins.truncate(max);
+		}
+
+		let count = origins.len();
+		for (location, max_weight) in origins {
+			FreeExecutionOrigins::<T>::insert(location, max_weight);
+		}
+		count
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate as xcm_barrier;
+
+	use frame_support::{assert_noop, assert_ok, parameter_types, traits::Hooks};
+	use frame_system::EnsureRoot;
+	use sp_core::H256;
+	use sp_runtime::{
+		testing::Header,
+		traits::{BlakeTwo256, IdentityLookup},
+		BuildStorage,
+		DispatchError::BadOrigin,
+	};
+	use xcm::{
+		latest::{prelude::*, Xcm},
+		DoubleEncoded, VersionedMultiLocation,
+	};
+
+	type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+	type Block = frame_system::mocking::MockBlock<Test>;
+
+	frame_support::construct_runtime!(
+		pub enum Test where
+			Block = Block,
+			NodeBlock = Block,
+			UncheckedExtrinsic = UncheckedExtrinsic,
+		{
+			System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+			XcmBarrier: xcm_barrier::{Pallet, Call, Storage, Event<T>, Config},
+			Utility: pallet_utility::{Pallet, Call, Event},
+		}
+	);
+
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub static InitialFreeExecutionOrigins: Vec<(MultiLocation, Weight)> =
+			vec![(MultiLocation::parent(), Weight::MAX)];
+	}
+
+	impl frame_system::Config for Test {
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type RuntimeEvent = RuntimeEvent;
+		type BlockHashCount = BlockHashCount;
+		type BlockLength = ();
+		type BlockWeights = ();
+		type Version = ();
+		type PalletInfo = PalletInfo;
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type DbWeight = ();
+		type BaseCallFilter = frame_support::traits::Everything;
+		type SystemWeightInfo = ();
+		type SS58Prefix = ();
+		type OnSetCode = ();
+		type MaxConsumers = frame_support::traits::ConstU32<16>;
+	}
+
+	impl pallet_utility::Config for Test {
+		type RuntimeEvent = RuntimeEvent;
+		type RuntimeCall = RuntimeCall;
+		type PalletsOrigin = OriginCaller;
+		type WeightInfo = ();
+	}
+
+	/// The calls of the batches of `pallet_utility`.
+	pub struct BatchedCalls;
+	impl InnerCalls<RuntimeCall> for BatchedCalls {
+		fn inner_calls(call: &RuntimeCall) -> Vec<&RuntimeCall> {
+			match call {
+				RuntimeCall::Utility(pallet_utility::Call::batch { calls }) =>
+					calls.iter().collect(),
+				_ => Vec::new(),
+			}
+		}
+	}
+
+	impl Config for Test {
+		type RuntimeEvent = RuntimeEvent;
+		type AdminOrigin = EnsureRoot<u64>;
+		type InitialFreeExecutionOrigins = InitialFreeExecutionOrigins;
+		type MaxFreeExecutionOrigins = frame_support::traits::ConstU32<2>;
+		type MaxDeniedInstructions = frame_support::traits::ConstU32<4>;
+		type InnerCalls = BatchedCalls;
+		type WeightInfo = ();
+	}
+
+	pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
+		GenesisConfig::default().build_storage().unwrap().into()
+	}
+
+	fn sibling(para_id: u32) -> MultiLocation {
+		MultiLocation::new(1, X1(Parachain(para_id)))
+	}
+
+	fn versioned(location: MultiLocation) -> Box<VersionedMultiLocation> {
+		Box::new(location.into())
+	}
+
+	#[test]
+	fn free_execution_origins_are_managed_by_admin() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(
+				XcmBarrier::free_execution_weight(&MultiLocation::parent()),
+				Some(Weight::MAX)
+			);
+			assert_eq!(XcmBarrier::free_execution_weight(&sibling(1000)), None);
+
+			assert_noop!(
+				XcmBarrier::allow_free_execution(
+					RuntimeOrigin::signed(1),
+					versioned(sibling(1000)),
+					Weight::zero()
+				),
+				BadOrigin,
+			);
+
+			let max_weight = Weight::from_parts(1_000, 0);
+			assert_ok!(XcmBarrier::allow_free_execution(
+				RuntimeOrigin::root(),
+				versioned(sibling(1000)),
+				max_weight
+			));
+			assert_eq!(XcmBarrier::free_execution_weight(&sibling(1000)), Some(max_weight));
+
+			assert_noop!(
+				XcmBarrier::allow_free_execution(
+					RuntimeOrigin::root(),
+					versioned(sibling(1001)),
+					max_weight
+				),
+				Error::<Test>::TooManyFreeExecutionOrigins,
+			);
+
+			assert_ok!(XcmBarrier::revoke_free_execution(
+				RuntimeOrigin::root(),
+				versioned(sibling(1000))
+			));
+			assert_eq!(XcmBarrier::free_execution_weight(&sibling(1000)), None);
+			assert_ok!(XcmBarrier::allow_free_execution(
+				RuntimeOrigin::root(),
+				versioned(sibling(1001)),
+				max_weight
+			));
+		});
+	}
+
+	#[test]
+	fn pallet_added_to_running_chain_is_initialized() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			assert_eq!(XcmBarrier::free_execution_weight(&MultiLocation::parent()), None);
+
+			XcmBarrier::on_runtime_upgrade();
+			assert_eq!(
+				XcmBarrier::free_execution_weight(&MultiLocation::parent()),
+				Some(Weight::MAX)
+			);
+
+			// Revoked origins are not restored by later upgrades.
+			assert_ok!(XcmBarrier::revoke_free_execution(
+				RuntimeOrigin::root(),
+				versioned(MultiLocation::parent())
+			));
+			XcmBarrier::on_runtime_upgrade();
+			assert_eq!(XcmBarrier::free_execution_weight(&MultiLocation::parent()), None);
+		});
+	}
+
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn upgrade_checks_the_initialization() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let state = XcmBarrier::pre_upgrade().unwrap();
+			assert!(XcmBarrier::post_upgrade(state.clone()).is_err());
+
+			XcmBarrier::on_runtime_upgrade();
+			assert_ok!(XcmBarrier::post_upgrade(state));
+		});
+	}
+
+	#[test]
+	fn initial_free_execution_origins_are_bounded() {
+		InitialFreeExecutionOrigins::set(vec![
+			(sibling(1000), Weight::MAX),
+			(sibling(1001), Weight::MAX),
+			(sibling(1002), Weight::MAX),
+		]);
+
+		new_test_ext().execute_with(|| {
+			assert_eq!(FreeExecutionOrigins::<Test>::count(), 2);
+			assert_eq!(XcmBarrier::free_execution_weight(&sibling(1002)), None);
+		});
+	}
+
+	#[test]
+	fn denied_instructions_match_nested_instructions() {
+		new_test_ext().execute_with(|| {
+			let transact = |call: Vec<u8>| -> Instruction<()> {
+				Transact {
+					origin_kind: OriginKind::Superuser,
+					require_weight_at_most: Weight::zero(),
+					call: DoubleEncoded::from(call),
+				}
+			};
+			let reserve_transfer = |dest| -> Instruction<()> {
+				TransferReserveAsset { assets: MultiAssets::new(), dest, xcm: Xcm(vec![]) }
+			};
+			let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+			let set_code = RuntimeCall::System(frame_system::Call::set_code { code: vec![] });
+
+			assert!(!XcmBarrier::is_denied(&[transact(set_code.encode())]));
+
+			assert_ok!(XcmBarrier::set_denied_instructions(
+				RuntimeOrigin::root(),
+				vec![
+					DeniedInstruction::TransactCall { pallet_index: 0, call_index: Some(2) },
+					DeniedInstruction::ReserveTransferTo(MultiLocation::parent()),
+				]
+				.try_into()
+				.unwrap(),
+			));
+
+			assert!(XcmBarrier::is_denied(&[ClearOrigin, transact(set_code.encode())]));
+			assert!(!XcmBarrier::is_denied(&[transact(remark.encode())]));
+			assert!(XcmBarrier::is_denied(&[SetAppendix(Xcm(vec![reserve_transfer(
+				MultiLocation::parent()
+			)]))]));
+			assert!(XcmBarrier::is_denied(&[SetErrorHandler(Xcm(vec![reserve_transfer(
+				MultiLocation::parent()
+			)]))]));
+			assert!(!XcmBarrier::is_denied(&[reserve_transfer(sibling(1000))]));
+		});
+	}
+
+	#[test]
+	fn denied_calls_are_matched_through_inner_calls() {
+		new_test_ext().execute_with(|| {
+			let transact = |call: Vec<u8>| -> Instruction<()> {
+				Transact {
+					origin_kind: OriginKind::Superuser,
+					require_weight_at_most: Weight::zero(),
+					call: DoubleEncoded::from(call),
+				}
+			};
+			let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+			let set_code = RuntimeCall::System(frame_system::Call::set_code { code: vec![] });
+			let batch =
+				|calls| RuntimeCall::Utility(pallet_utility::Call::batch { calls }).encode();
+
+			assert_ok!(XcmBarrier::set_denied_instructions(
+				RuntimeOrigin::root(),
+				vec![DeniedInstruction::TransactCall { pallet_index: 0, call_index: Some(2) }]
+					.try_into()
+					.unwrap(),
+			));
+
+			assert!(!XcmBarrier::is_denied(&[transact(batch(vec![remark.clone()]))]));
+			assert!(XcmBarrier::is_denied(&[transact(batch(vec![remark.clone(), set_code]))]));
+			// Calls which can't be checked are denied.
+			assert!(XcmBarrier::is_denied(&[transact(vec![0])]));
+		});
+	}
+}
@@ -0,0 +1,80 @@
+// Copyright 2020-2021 Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights of `cumulus_pallet_xcm_barrier`.
+//!
+//! Not benchmarked yet: the governance calls are charged their storage accesses, a flat execution
+//! time and, for the denied instructions, a time per pattern.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+// The weight info trait for `cumulus_pallet_xcm_barrier`.
+pub trait WeightInfo {
+	fn allow_free_execution() -> Weight;
+	fn revoke_free_execution() -> Weight;
+	fn set_denied_instructions(p: u32) -> Weight;
+}
+
+/// Weights for cumulus_pallet_xcm_barrier using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: XcmBarrier FreeExecutionOrigins (r:1 w:1)
+	// Storage: XcmBarrier CounterForFreeExecutionOrigins (r:1 w:1)
+	fn allow_free_execution() -> Weight {
+		Weight::from_parts(20_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: XcmBarrier FreeExecutionOrigins (r:1 w:1)
+	// Storage: XcmBarrier CounterForFreeExecutionOrigins (r:1 w:1)
+	fn revoke_free_execution() -> Weight {
+		Weight::from_parts(18_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: XcmBarrier DeniedInstructions (r:0 w:1)
+	fn set_denied_instructions(p: u32) -> Weight {
+		Weight::from_parts(12_000_000 as u64, 0)
+			.saturating_add(Weight::from_parts(150_000 as u64, 0).saturating_mul(p as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn allow_free_execution() -> Weight {
+		Weight::from_parts(20_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	fn revoke_free_execution() -> Weight {
+		Weight::from_parts(18_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	fn set_denied_instructions(p: u32) -> Weight {
+		Weight::from_parts(12_000_000 as u64, 0)
+			.saturating_add(Weight::from_parts(150_000 as u64, 0).saturating_mul(p as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+}
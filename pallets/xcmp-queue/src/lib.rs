@@ -40,8 +40,9 @@ pub use weights::WeightInfo;
 
 use codec::{Decode, DecodeLimit, Encode};
 use cumulus_primitives_core::{
-	relay_chain::BlockNumber as RelayBlockNumber, ChannelStatus, GetChannelInfo, MessageSendError,
-	ParaId, XcmpMessageFormat, XcmpMessageHandler, XcmpMessageSource,
+	relay_chain::BlockNumber as RelayBlockNumber, ChannelStatus, GetChannelInfo,
+	InspectMessageQueues, MessageSendError, ParaId, XcmVersionReport, XcmpMessageFormat,
+	XcmpMessageHandler, XcmpMessageSource,
 };
 use frame_support::{
 	traits::{EnsureOrigin, Get},
@@ -55,7 +56,10 @@ use rand_chacha::{
 use scale_info::TypeInfo;
 use sp_runtime::RuntimeDebug;
 use sp_std::{convert::TryFrom, prelude::*};
-use xcm::{latest::prelude::*, VersionedXcm, WrapVersion, MAX_XCM_DECODE_DEPTH};
+use xcm::{
+	latest::prelude::*, Version as XcmVersion, VersionedMultiLocation, VersionedXcm, WrapVersion,
+	MAX_XCM_DECODE_DEPTH,
+};
 use xcm_executor::traits::ConvertOrigin;
 
 pub use pallet::*;
@@ -277,6 +281,33 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Pin the XCM version of the messages sent to a sibling parachain, overriding the
+		/// version negotiated with it.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `recipient`: The sibling parachain.
+		/// - `version`: The XCM version to convert the messages to, or `None` to use the
+		///   negotiated version again.
+		#[pallet::call_index(9)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational,))]
+		pub fn pin_xcm_version(
+			origin: OriginFor<T>,
+			recipient: ParaId,
+			version: Option<XcmVersion>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			if let Some(version) = version {
+				ensure!(
+					VersionedXcm::<()>::from(Xcm(Vec::new())).into_version(version).is_ok(),
+					Error::<T>::UnsupportedXcmVersion
+				);
+			}
+			VersionReports::<T>::mutate(recipient, |record| record.pinned = version);
+			Self::deposit_event(Event::XcmVersionPinned { recipient, version });
+
+			Ok(())
+		}
 	}
 
 	#[pallet::event]
@@ -301,6 +332,9 @@ pub mod pallet {
 		},
 		/// An XCM from the overweight queue was executed with the given actual weight used.
 		OverweightServiced { index: OverweightIndex, used: Weight },
+		/// The XCM version of the messages sent to a sibling parachain was pinned, or unpinned if
+		/// `None`.
+		XcmVersionPinned { recipient: ParaId, version: Option<XcmVersion> },
 	}
 
 	#[pallet::error]
@@ -315,6 +349,8 @@ pub mod pallet {
 		BadOverweightIndex,
 		/// Provided weight is possibly not enough to execute the message.
 		WeightOverLimit,
+		/// Messages can't be converted to the XCM version.
+		UnsupportedXcmVersion,
 	}
 
 	/// Status of the inbound XCMP channels.
@@ -375,6 +411,25 @@ pub mod pallet {
 	/// Whether or not the XCMP queue is suspended from executing incoming XCMs or not.
 	#[pallet::storage]
 	pub(super) type QueueSuspended<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The XCM version used to send messages to each sibling parachain, the version pinned for it
+	/// and the last failure to queue a message for it.
+	#[pallet::storage]
+	pub(super) type VersionReports<T: Config> =
+		StorageMap<_, Blake2_128Concat, ParaId, VersionRecord, ValueQuery>;
+}
+
+/// The XCM versions of the messages sent to a sibling parachain.
+#[derive(Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct VersionRecord {
+	/// The version of the last message sent to the sibling parachain.
+	pub sent: Option<XcmVersion>,
+	/// The version all messages to the sibling parachain are converted to, overriding the
+	/// negotiated one.
+	pub pinned: Option<XcmVersion>,
+	/// Why the last message to the sibling parachain couldn't be queued, if no message was queued
+	/// since.
+	pub last_failure: Option<MessageSendError>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug, TypeInfo)]
@@ -577,6 +632,40 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Wraps `xcm` in the version pinned for `recipient`, or else in the version negotiated with
+	/// it.
+	fn wrap_version(
+		recipient: ParaId,
+		dest: &MultiLocation,
+		xcm: Xcm<()>,
+	) -> Result<VersionedXcm<()>, ()> {
+		match VersionReports::<T>::get(recipient).pinned {
+			Some(version) => VersionedXcm::from(xcm).into_version(version),
+			None => T::VersionWrapper::wrap_version(dest, xcm),
+		}
+	}
+
+	/// Returns the maximum weight of a message executed from the queues, heavier messages being
+	/// kept as overweight.
+	pub fn max_individual_weight() -> Weight {
+		QueueConfig::<T>::get().xcmp_max_individual_weight
+	}
+
+	/// Returns the XCM version report of each sibling parachain messages were sent to or a version
+	/// was pinned for.
+	pub fn version_reports() -> Vec<(ParaId, XcmVersionReport)> {
+		VersionReports::<T>::iter()
+			.map(|(recipient, record)| {
+				let report = XcmVersionReport {
+					version: record.sent,
+					pinned: record.pinned,
+					last_failure: record.last_failure,
+				};
+				(recipient, report)
+			})
+			.collect()
+	}
+
 	pub fn send_blob_message(recipient: ParaId, blob: Vec<u8>) -> Result<u32, MessageSendError> {
 		Self::send_fragment(recipient, XcmpMessageFormat::ConcatenatedEncodedBlob, blob)
 	}
@@ -1166,7 +1255,7 @@ impl<T: Config> SendXcm for Pallet<T> {
 				let xcm = msg.take().ok_or(SendError::MissingArgument)?;
 				let id = ParaId::from(*id);
 				let price = T::PriceForSiblingDelivery::price_for_sibling_delivery(id, &xcm);
-				let versioned_xcm = T::VersionWrapper::wrap_version(&d, xcm)
+				let versioned_xcm = Self::wrap_version(id, &d, xcm)
 					.map_err(|()| SendError::DestinationUnsupported)?;
 				Ok(((id, versioned_xcm), price))
 			},
@@ -1181,13 +1270,70 @@ impl<T: Config> SendXcm for Pallet<T> {
 
 	fn deliver((id, xcm): (ParaId, VersionedXcm<()>)) -> Result<XcmHash, SendError> {
 		let hash = xcm.using_encoded(sp_io::hashing::blake2_256);
+		let version = match &xcm {
+			VersionedXcm::V2(_) => xcm::v2::VERSION,
+			VersionedXcm::V3(_) => xcm::v3::VERSION,
+		};
 
 		match Self::send_fragment(id, XcmpMessageFormat::ConcatenatedVersionedXcm, xcm) {
 			Ok(_) => {
+				VersionReports::<T>::mutate(id, |record| {
+					record.sent = Some(version);
+					record.last_failure = None;
+				});
 				Self::deposit_event(Event::XcmpMessageSent { message_hash: Some(hash) });
 				Ok(hash)
 			},
-			Err(e) => Err(SendError::Transport(<&'static str>::from(e))),
+			Err(e) => {
+				// Only recorded for the known recipients, so that failing to send to arbitrary
+				// parachains doesn't grow the storage.
+				VersionReports::<T>::mutate_exists(id, |record| {
+					if let Some(record) = record {
+						record.last_failure = Some(e);
+					}
+				});
+				Err(SendError::Transport(<&'static str>::from(e)))
+			},
 		}
 	}
 }
+
+impl<T: Config> InspectMessageQueues for Pallet<T> {
+	fn clear_messages() {
+		let _ = <OutboundXcmpMessages<T>>::clear(u32::MAX, None);
+		let _ = <SignalMessages<T>>::clear(u32::MAX, None);
+		<OutboundXcmpStatus<T>>::kill();
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		<OutboundXcmpStatus<T>>::get()
+			.into_iter()
+			.filter_map(|details| {
+				let mut messages = Vec::new();
+				for page_index in details.first_index..details.last_index {
+					let page = <OutboundXcmpMessages<T>>::get(details.recipient, page_index);
+					let mut data = &page[..];
+					if XcmpMessageFormat::decode_with_depth_limit(MAX_XCM_DECODE_DEPTH, &mut data) !=
+						Ok(XcmpMessageFormat::ConcatenatedVersionedXcm)
+					{
+						continue
+					}
+					while !data.is_empty() {
+						match VersionedXcm::<()>::decode_with_depth_limit(
+							MAX_XCM_DECODE_DEPTH,
+							&mut data,
+						) {
+							Ok(xcm) => messages.push(xcm),
+							Err(_) => break,
+						}
+					}
+				}
+				if messages.is_empty() {
+					return None
+				}
+				let destination = MultiLocation::new(1, X1(Parachain(details.recipient.into())));
+				Some((destination.into(), messages))
+			})
+			.collect()
+	}
+}
@@ -17,7 +17,7 @@ use super::*;
 use cumulus_primitives_core::XcmpMessageHandler;
 use frame_support::{assert_noop, assert_ok};
 use mock::{new_test_ext, RuntimeCall, RuntimeOrigin, Test, XcmpQueue};
-use sp_runtime::traits::BadOrigin;
+use sp_runtime::{traits::BadOrigin, DispatchResult};
 
 #[test]
 fn one_message_does_not_panic() {
@@ -341,3 +341,105 @@ fn xcmp_queue_consumes_dest_and_msg_on_ok_validate() {
 		);
 	});
 }
+
+#[test]
+fn get_messages_decodes_the_queued_xcms() {
+	new_test_ext().execute_with(|| {
+		let recipient = ParaId::from(5555);
+		let first = VersionedXcm::<()>::from(Xcm(vec![Trap(5)]));
+		let second = VersionedXcm::<()>::from(Xcm(vec![ClearOrigin]));
+		let mut page = XcmpMessageFormat::ConcatenatedVersionedXcm.encode();
+		page.extend(first.encode());
+		page.extend(second.encode());
+		<OutboundXcmpMessages<Test>>::insert(recipient, 0, page);
+		let mut details = OutboundChannelDetails::new(recipient);
+		details.last_index = 1;
+		<OutboundXcmpStatus<Test>>::put(vec![details]);
+
+		assert_eq!(
+			XcmpQueue::get_messages(),
+			vec![((Parent, Parachain(5555)).into(), vec![first, second])]
+		);
+
+		XcmpQueue::clear_messages();
+		assert!(XcmpQueue::get_messages().is_empty());
+		assert!(<OutboundXcmpMessages<Test>>::iter().next().is_none());
+	});
+}
+
+#[test]
+fn pinned_xcm_version_is_used_for_the_recipient() {
+	new_test_ext().execute_with(|| {
+		let recipient = ParaId::from(5555);
+		let dest: MultiLocation = (Parent, Parachain(5555)).into();
+		let message = Xcm::<()>(vec![ClearOrigin]);
+
+		assert_noop!(
+			XcmpQueue::pin_xcm_version(RuntimeOrigin::signed(2), recipient, Some(2)),
+			BadOrigin
+		);
+		assert_noop!(
+			XcmpQueue::pin_xcm_version(RuntimeOrigin::root(), recipient, Some(99)),
+			Error::<Test>::UnsupportedXcmVersion
+		);
+		assert_ok!(XcmpQueue::pin_xcm_version(RuntimeOrigin::root(), recipient, Some(2)));
+
+		let ((_, versioned_xcm), _) =
+			<XcmpQueue as SendXcm>::validate(&mut Some(dest), &mut Some(message.clone())).unwrap();
+		assert_eq!(versioned_xcm, VersionedXcm::from(message.clone()).into_version(2).unwrap());
+
+		// There is no channel to the recipient. The failure is rolled back with the extrinsic
+		// sending the message, but recorded otherwise.
+		assert_noop!(
+			frame_support::storage::with_storage_layer(|| -> DispatchResult {
+				send_xcm::<XcmpQueue>(dest, message.clone()).map_err(|_| "SendFailure")?;
+				Ok(())
+			}),
+			"SendFailure"
+		);
+		assert_eq!(VersionReports::<Test>::get(recipient).last_failure, None);
+		assert!(send_xcm::<XcmpQueue>(dest, message.clone()).is_err());
+		assert_eq!(
+			XcmpQueue::version_reports(),
+			vec![(
+				recipient,
+				XcmVersionReport {
+					version: None,
+					pinned: Some(2),
+					last_failure: Some(MessageSendError::NoChannel),
+				}
+			)]
+		);
+
+		// The failures of unknown recipients are not recorded.
+		let unknown: MultiLocation = (Parent, Parachain(5556)).into();
+		assert!(send_xcm::<XcmpQueue>(unknown, message).is_err());
+		assert!(!VersionReports::<Test>::contains_key(ParaId::from(5556)));
+
+		assert_ok!(XcmpQueue::pin_xcm_version(RuntimeOrigin::root(), recipient, None));
+		assert_eq!(VersionReports::<Test>::get(recipient).pinned, None);
+	});
+}
+
+#[test]
+fn validate_does_not_write_the_version_reports() {
+	new_test_ext().execute_with(|| {
+		let recipient = ParaId::from(5555);
+		let dest: MultiLocation = (Parent, Parachain(5555)).into();
+		assert_ok!(XcmpQueue::pin_xcm_version(RuntimeOrigin::root(), recipient, Some(2)));
+		let reports = XcmpQueue::version_reports();
+
+		// `UnpaidExecution` has no XCM v2 equivalent.
+		let message =
+			Xcm::<()>(vec![UnpaidExecution { weight_limit: Unlimited, check_origin: None }]);
+		assert_eq!(
+			<XcmpQueue as SendXcm>::validate(&mut Some(dest), &mut Some(message)),
+			Err(SendError::DestinationUnsupported)
+		);
+		assert_eq!(XcmpQueue::version_reports(), reports);
+
+		let message = Xcm::<()>(vec![ClearOrigin]);
+		assert_ok!(<XcmpQueue as SendXcm>::validate(&mut Some(dest), &mut Some(message)));
+		assert_eq!(XcmpQueue::version_reports(), reports);
+	});
+}
@@ -182,6 +182,7 @@ async fn start_node_impl(
 			spawn_handle: task_manager.spawn_handle(),
 			relay_chain_interface: relay_chain_interface.clone(),
 			import_queue: params.import_queue,
+			author_verification: None,
 		})
 		.await?;
 
@@ -285,6 +286,11 @@ async fn start_node_impl(
 			collator_key: collator_key.expect("Command line arguments do not allow this. qed"),
 			relay_chain_slot_duration,
 			recovery_handle: Box::new(overseer_handle),
+			block_data_requester: None,
+			recovery_control: None,
+			collation_stats: None,
+			collation_archive: None,
+			leader_lease: None,
 		};
 
 		start_collator(params).await?;
@@ -298,6 +304,8 @@ async fn start_node_impl(
 			relay_chain_slot_duration,
 			import_queue: import_queue_service,
 			recovery_handle: Box::new(overseer_handle),
+			block_data_requester: None,
+			recovery_control: None,
 		};
 
 		start_full_node(params)?;
@@ -410,6 +418,7 @@ fn build_consensus(
 		// And a maximum of 750ms if slots are skipped
 		max_block_proposal_slot_portion: Some(SlotProportion::new(1f32 / 16f32)),
 		telemetry,
+		relay_parent_staleness: None,
 	};
 
 	Ok(AuraConsensus::build::<sp_consensus_aura::sr25519::AuthorityPair, _, _, _, _, _, _>(params))
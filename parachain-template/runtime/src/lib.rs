@@ -46,7 +46,7 @@ pub use sp_runtime::{
 	types::{VoteAssetId, VoteWeight},
 	MultiAddress, Perbill, Permill,
 };
-use xcm_config::{XcmConfig, XcmOriginToTransactDispatchOrigin};
+use xcm_config::{XcmConfig, XcmOriginToTransactDispatchOrigin, XcmRouter};
 
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -186,7 +186,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("template-parachain"),
 	impl_name: create_runtime_str!("template-parachain"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -514,6 +514,8 @@ impl system_token_aggregator::Config for Runtime {
 	type AssetMultiLocationGetter = AssetLink;
 }
 
+impl pallet_trapped_assets::Config for Runtime {}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub enum Runtime where
@@ -550,6 +552,7 @@ construct_runtime!(
 
 		AssetLink: pallet_asset_link = 34,
 		SystemTokenAggregator: system_token_aggregator = 35,
+		TrappedAssets: pallet_trapped_assets = 36,
 	}
 );
 
@@ -695,12 +698,52 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl assets_common::runtime_api::TrappedAssetsApi<Block> for Runtime {
+		fn query_trapped_assets(origin: xcm::latest::MultiLocation) -> Vec<(xcm::latest::MultiAssets, u32)> {
+			TrappedAssets::trapped_assets(&origin)
+		}
+
+		fn claim_trapped_assets_program(
+			assets: xcm::latest::MultiAssets,
+			beneficiary: xcm::latest::MultiLocation,
+		) -> xcm::latest::Xcm<()> {
+			TrappedAssets::claim_program(assets, beneficiary)
+		}
+	}
+
+	impl assets_common::runtime_api::DryRunApi<Block, RuntimeCall, RuntimeEvent, OriginCaller> for Runtime {
+		fn dry_run_call(
+			origin: OriginCaller,
+			call: RuntimeCall,
+		) -> Result<assets_common::runtime_api::CallDryRunEffects<RuntimeEvent>, assets_common::runtime_api::DryRunError> {
+			Ok(assets_common::dry_run::dry_run_call::<Runtime, XcmRouter>(origin.into(), call))
+		}
+
+		fn dry_run_xcm(
+			origin_location: xcm::VersionedMultiLocation,
+			xcm: xcm::VersionedXcm<RuntimeCall>,
+		) -> Result<assets_common::runtime_api::XcmDryRunEffects<RuntimeEvent>, assets_common::runtime_api::DryRunError> {
+			assets_common::dry_run::dry_run_xcm::<
+				Runtime,
+				XcmRouter,
+				XcmConfig,
+				xcm_config::MessageQueueWeightLimit,
+			>(origin_location, xcm)
+		}
+	}
+
 	impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
 		fn collect_collation_info(header: &<Block as BlockT>::Header) -> cumulus_primitives_core::CollationInfo {
 			ParachainSystem::collect_collation_info(header)
 		}
 	}
 
+	impl cumulus_primitives_core::XcmpVersionsApi<Block> for Runtime {
+		fn xcmp_versions() -> Vec<(cumulus_primitives_core::ParaId, cumulus_primitives_core::XcmVersionReport)> {
+			XcmpQueue::version_reports()
+		}
+	}
+
 	#[cfg(feature = "try-runtime")]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
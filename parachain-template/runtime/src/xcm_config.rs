@@ -1,5 +1,5 @@
 use super::{
-	AccountId, AllPalletsWithSystem, AssetLink, Assets, Authorship, Balance, Balances,
+	AccountId, AllPalletsWithSystem, AssetLink, Assets, Authorship, Balance, Balances, DmpQueue,
 	InfrablockspaceXcm, ParachainInfo, ParachainSystem, Runtime, RuntimeCall, RuntimeEvent,
 	RuntimeOrigin, WeightToFee, XcmpQueue,
 };
@@ -10,9 +10,10 @@ use frame_support::{
 	weights::Weight,
 };
 use infrablockspace_parachain::primitives::Sibling;
+use pallet_trapped_assets::IndexTrappedAssets;
 use pallet_xcm::XcmPassthrough;
 use parachains_common::{xcm_config::AssetFeeAsExistentialDepositMultiplier, AssetId};
-use sp_runtime::traits::ConvertInto;
+use sp_runtime::traits::{Convert, ConvertInto};
 use xcm::latest::prelude::*;
 use xcm_builder::{
 	AccountId32Aliases, AllowExplicitUnpaidExecutionFrom, AllowSubscriptionsFrom,
@@ -212,9 +213,15 @@ impl xcm_executor::Config for XcmConfig {
 	);
 
 	type ResponseHandler = InfrablockspaceXcm;
-	type AssetTrap =
-		TrappistDropAssets<AssetId, AssetLink, Assets, Balances, InfrablockspaceXcm, AccountId>;
-	type AssetClaims = InfrablockspaceXcm;
+	type AssetTrap = TrappistDropAssets<
+		AssetId,
+		AssetLink,
+		Assets,
+		Balances,
+		IndexTrappedAssets<Runtime, InfrablockspaceXcm>,
+		AccountId,
+	>;
+	type AssetClaims = IndexTrappedAssets<Runtime, InfrablockspaceXcm>;
 	type SubscriptionService = InfrablockspaceXcm;
 	type PalletInstancesInfo = AllPalletsWithSystem;
 	type MaxAssetsIntoHolding = MaxAssetsIntoHolding;
@@ -227,6 +234,19 @@ impl xcm_executor::Config for XcmConfig {
 	type SafeCallFilter = Everything;
 }
 
+/// The weight the message queue of `origin` lets a single message use: the DMP queue for the
+/// Relay Chain, the XCMP queue for the other locations.
+pub struct MessageQueueWeightLimit;
+impl Convert<MultiLocation, Weight> for MessageQueueWeightLimit {
+	fn convert(origin: MultiLocation) -> Weight {
+		if origin == MultiLocation::parent() {
+			DmpQueue::max_individual_weight()
+		} else {
+			XcmpQueue::max_individual_weight()
+		}
+	}
+}
+
 /// No local origins on this chain are allowed to dispatch XCM sends/executions.
 pub type LocalOriginToLocation = SignedToAccountId32<RuntimeOrigin, AccountId, RelayNetwork>;
 
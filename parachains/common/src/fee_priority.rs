@@ -0,0 +1,308 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transaction priority based on the value of the fee paid in system tokens.
+//!
+//! `ChargeSystemToken` prioritizes transactions by their fee and tip in units of the system token
+//! paying for them, so that a tip in a cheap system token outbids the same tip in a valuable one.
+//! [`PrioritizeByFeeValue`] replaces it and computes the priority from the tip converted to the
+//! native currency, using the same rates as `TransactionFeeCharger`.
+
+use crate::types::{AssetIdForTrustBackedAssets, Balance};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::{DispatchInfo, PostDispatchInfo},
+	traits::{tokens::BalanceConversion, Get},
+};
+use pallet_system_token_payment::ChargeSystemToken;
+use pallet_transaction_payment::ChargeTransactionPayment;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Convert, DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{TransactionValidity, TransactionValidityError},
+	types::SystemTokenId,
+	DispatchResult, FixedPointOperand,
+};
+use sp_std::{fmt, marker::PhantomData};
+
+/// Conversion of native balances to system token balances.
+pub trait SystemTokenConversion {
+	/// Returns the balance of the system token worth `balance` of the native currency, `None` if
+	/// the system token has no conversion rate.
+	fn to_system_token_balance(
+		balance: Balance,
+		system_token_id: &SystemTokenId,
+	) -> Option<Balance>;
+}
+
+/// [`SystemTokenConversion`] through the local asset holding the system token.
+///
+/// `SA` returns the local asset holding a system token and `CON` converts native balances to
+/// balances of that asset.
+pub struct LocalAssetConversion<CON, SA>(PhantomData<(CON, SA)>);
+
+impl<CON, SA> SystemTokenConversion for LocalAssetConversion<CON, SA>
+where
+	CON: BalanceConversion<Balance, AssetIdForTrustBackedAssets, Balance>,
+	SA: Convert<SystemTokenId, Option<AssetIdForTrustBackedAssets>>,
+{
+	fn to_system_token_balance(
+		balance: Balance,
+		system_token_id: &SystemTokenId,
+	) -> Option<Balance> {
+		let asset_id = SA::convert(system_token_id.clone())?;
+		CON::to_asset_balance(balance, asset_id).ok()
+	}
+}
+
+/// Returns the value in the native currency of `tip`, paid in `system_token_id`.
+///
+/// `tip * reference / CON(reference)`, the tip is worth nothing if the system token has no
+/// conversion rate.
+fn tip_value<CON: SystemTokenConversion>(
+	tip: Balance,
+	system_token_id: Option<&SystemTokenId>,
+	reference: Balance,
+) -> Balance {
+	let system_token_id = match system_token_id {
+		Some(system_token_id) => system_token_id,
+		None => return tip,
+	};
+
+	match CON::to_system_token_balance(reference, system_token_id) {
+		Some(rate) if rate > 0 => sp_runtime::helpers_128bit::multiply_by_rational_with_rounding(
+			tip,
+			reference,
+			rate,
+			sp_runtime::Rounding::Down,
+		)
+		.unwrap_or(Balance::MAX),
+		_ => 0,
+	}
+}
+
+/// Replaces `ChargeSystemToken`, with a priority computed from the value of the tip in the native
+/// currency.
+///
+/// The tip, given in units of the system token paying the fee, is converted with `CON`, which
+/// should use the rates of the `TransactionFeeCharger` of the runtime. The fields, encoding and
+/// identifier are the ones of `ChargeSystemToken`, to which the extension is delegated, so that
+/// extrinsics are built the same way.
+#[derive(Encode, Decode, TypeInfo)]
+#[scale_info(skip_type_params(T, CON))]
+pub struct PrioritizeByFeeValue<T: frame_system::Config, CON> {
+	#[codec(compact)]
+	tip: Balance,
+	system_token_id: Option<SystemTokenId>,
+	vote_candidate: Option<T::AccountId>,
+	#[codec(skip)]
+	_phantom: PhantomData<CON>,
+}
+
+impl<T: frame_system::Config, CON> PrioritizeByFeeValue<T, CON> {
+	/// Create a new instance, with the same arguments as `ChargeSystemToken::from`.
+	pub fn from(
+		tip: Balance,
+		system_token_id: Option<SystemTokenId>,
+		vote_candidate: Option<T::AccountId>,
+	) -> Self {
+		Self { tip, system_token_id, vote_candidate, _phantom: PhantomData }
+	}
+
+	/// Returns the tip, in units of the asset paying the fee.
+	pub fn tip(&self) -> Balance {
+		self.tip
+	}
+
+	/// Returns the system token paying the fee, `None` for the native currency.
+	pub fn system_token_id(&self) -> Option<&SystemTokenId> {
+		self.system_token_id.as_ref()
+	}
+
+	/// Returns the candidate voted for by the transaction.
+	pub fn vote_candidate(&self) -> Option<&T::AccountId> {
+		self.vote_candidate.as_ref()
+	}
+}
+
+impl<T, CON> PrioritizeByFeeValue<T, CON>
+where
+	T: pallet_system_token_payment::Config + pallet_transaction_payment::Config,
+	pallet_transaction_payment::BalanceOf<T>: From<Balance>,
+{
+	/// The `ChargeSystemToken` extension this one delegates to.
+	fn inner(&self) -> ChargeSystemToken<T> {
+		ChargeSystemToken::<T>::from(
+			self.tip.into(),
+			self.system_token_id.clone(),
+			self.vote_candidate.clone(),
+		)
+	}
+}
+
+impl<T, CON> PrioritizeByFeeValue<T, CON>
+where
+	T: pallet_balances::Config<Balance = Balance>,
+	CON: SystemTokenConversion,
+{
+	/// Returns the value of the tip in the native currency.
+	///
+	/// The existential deposit is used as reference amount of the conversion.
+	fn tip_value(&self) -> Balance {
+		tip_value::<CON>(
+			self.tip,
+			self.system_token_id.as_ref(),
+			<T as pallet_balances::Config>::ExistentialDeposit::get(),
+		)
+	}
+}
+
+impl<T: frame_system::Config, CON> Clone for PrioritizeByFeeValue<T, CON> {
+	fn clone(&self) -> Self {
+		Self::from(self.tip, self.system_token_id.clone(), self.vote_candidate.clone())
+	}
+}
+
+impl<T: frame_system::Config, CON> PartialEq for PrioritizeByFeeValue<T, CON> {
+	fn eq(&self, other: &Self) -> bool {
+		self.tip == other.tip &&
+			self.system_token_id == other.system_token_id &&
+			self.vote_candidate == other.vote_candidate
+	}
+}
+
+impl<T: frame_system::Config, CON> Eq for PrioritizeByFeeValue<T, CON> {}
+
+impl<T: frame_system::Config, CON> fmt::Debug for PrioritizeByFeeValue<T, CON> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"PrioritizeByFeeValue<{:?}, {:?}, {:?}>",
+			self.tip, self.system_token_id, self.vote_candidate
+		)
+	}
+}
+
+impl<T, CON> SignedExtension for PrioritizeByFeeValue<T, CON>
+where
+	T: pallet_balances::Config<Balance = Balance>
+		+ pallet_system_token_payment::Config
+		+ pallet_transaction_payment::Config
+		+ Send
+		+ Sync,
+	T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	pallet_transaction_payment::BalanceOf<T>: From<Balance> + Send + Sync + FixedPointOperand,
+	ChargeSystemToken<T>: SignedExtension<AccountId = T::AccountId, Call = T::RuntimeCall>,
+	CON: SystemTokenConversion + Send + Sync + 'static,
+{
+	const IDENTIFIER: &'static str = <ChargeSystemToken<T> as SignedExtension>::IDENTIFIER;
+	type AccountId = T::AccountId;
+	type Call = T::RuntimeCall;
+	type AdditionalSigned = <ChargeSystemToken<T> as SignedExtension>::AdditionalSigned;
+	type Pre = <ChargeSystemToken<T> as SignedExtension>::Pre;
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		self.inner().additional_signed()
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let mut valid = self.inner().validate(who, call, info, len)?;
+
+		let tip = self.tip_value();
+		let fee =
+			pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, tip.into());
+		valid.priority = ChargeTransactionPayment::<T>::get_priority(info, len, tip.into(), fee);
+		Ok(valid)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.inner().pre_dispatch(who, call, info, len)
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		ChargeSystemToken::<T>::post_dispatch(pre, info, post_info, len, result)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Asset 1 is worth half the native currency, asset 2 has no rate.
+	struct HalfPrice;
+	impl BalanceConversion<Balance, AssetIdForTrustBackedAssets, Balance> for HalfPrice {
+		type Error = ();
+
+		fn to_asset_balance(
+			balance: Balance,
+			asset_id: AssetIdForTrustBackedAssets,
+		) -> Result<Balance, ()> {
+			match asset_id {
+				1 => Ok(balance * 2),
+				_ => Err(()),
+			}
+		}
+	}
+
+	/// The system tokens of para 1000 are held under their own id, the others are unknown.
+	struct LocalAsset;
+	impl Convert<SystemTokenId, Option<AssetIdForTrustBackedAssets>> for LocalAsset {
+		fn convert(system_token_id: SystemTokenId) -> Option<AssetIdForTrustBackedAssets> {
+			(system_token_id.para_id == 1000).then_some(system_token_id.asset_id)
+		}
+	}
+
+	type Conversion = LocalAssetConversion<HalfPrice, LocalAsset>;
+
+	fn system_token_id(para_id: u32, asset_id: AssetIdForTrustBackedAssets) -> SystemTokenId {
+		SystemTokenId { para_id, pallet_id: 50, asset_id }
+	}
+
+	#[test]
+	fn native_tip_is_its_own_value() {
+		assert_eq!(tip_value::<Conversion>(100, None, 10), 100);
+	}
+
+	#[test]
+	fn tip_is_converted_with_the_rate_of_the_local_asset() {
+		assert_eq!(tip_value::<Conversion>(100, Some(&system_token_id(1000, 1)), 10), 50);
+	}
+
+	#[test]
+	fn tip_without_rate_is_worthless() {
+		// No rate for the asset.
+		assert_eq!(tip_value::<Conversion>(100, Some(&system_token_id(1000, 2)), 10), 0);
+		// Same asset id, but held by another chain and not linked to a local asset.
+		assert_eq!(tip_value::<Conversion>(100, Some(&system_token_id(2000, 1)), 10), 0);
+	}
+}
@@ -15,6 +15,7 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod fee_priority;
 pub mod impls;
 pub mod xcm_config;
 pub use constants::*;
@@ -34,6 +34,60 @@ where
 	}
 }
 
+/// Deny executing the XCM if any of its instructions matches a pattern denied by
+/// `cumulus_pallet_xcm_barrier`, to be used as the `Deny` of [`DenyThenTry`].
+pub struct DenyGovernedInstructions<T>(PhantomData<T>);
+impl<T: cumulus_pallet_xcm_barrier::Config> ShouldExecute for DenyGovernedInstructions<T> {
+	fn should_execute<RuntimeCall>(
+		origin: &MultiLocation,
+		message: &mut [Instruction<RuntimeCall>],
+		_max_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		if cumulus_pallet_xcm_barrier::Pallet::<T>::is_denied(message) {
+			log::debug!(
+				target: "xcm::barriers",
+				"DenyGovernedInstructions denied a message from {:?}",
+				origin,
+			);
+			return Err(())
+		}
+		Ok(())
+	}
+}
+
+/// Allow unpaid execution from the origins allowed by `cumulus_pallet_xcm_barrier`, up to their
+/// maximum weight.
+///
+/// A message starting with `UnpaidExecution` is only allowed if its weight limit covers the
+/// weight of the message, like with `AllowExplicitUnpaidExecutionFrom`.
+pub struct AllowGovernedUnpaidExecutionFrom<T>(PhantomData<T>);
+impl<T: cumulus_pallet_xcm_barrier::Config> ShouldExecute for AllowGovernedUnpaidExecutionFrom<T> {
+	fn should_execute<RuntimeCall>(
+		origin: &MultiLocation,
+		message: &mut [Instruction<RuntimeCall>],
+		max_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		log::trace!(
+			target: "xcm::barriers",
+			"AllowGovernedUnpaidExecutionFrom origin: {:?}, message: {:?}, max_weight: {:?}",
+			origin, message, max_weight,
+		);
+		let max_free_weight =
+			cumulus_pallet_xcm_barrier::Pallet::<T>::free_execution_weight(origin).ok_or(())?;
+		if !max_weight.all_lte(max_free_weight) {
+			return Err(())
+		}
+		match message.first() {
+			Some(UnpaidExecution { weight_limit: Limited(limit), .. })
+				if !limit.all_gte(max_weight) =>
+				Err(()),
+			_ => Ok(()),
+		}
+	}
+}
+
 // See issue <https://github.com/paritytech/polkadot/issues/5233>
 pub struct DenyReserveTransferToRelayChain;
 impl ShouldExecute for DenyReserveTransferToRelayChain {
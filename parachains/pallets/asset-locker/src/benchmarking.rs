@@ -0,0 +1,72 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarking setup for pallet-asset-locker
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as AssetLocker;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, BenchmarkError};
+use frame_support::{
+	assert_ok,
+	traits::{EnsureOrigin, Get},
+};
+use xcm::latest::prelude::*;
+
+fn sibling(para_id: u32) -> MultiLocation {
+	MultiLocation::new(1, X1(Parachain(para_id)))
+}
+
+/// Fill the trusted unlockers and lockers up to one below the maximum.
+fn fill_trusted_locations<T: Config>() {
+	let mut para_id = 1;
+	while TrustedUnlockers::<T>::count() + 1 < T::MaxTrustedLocations::get() {
+		TrustedUnlockers::<T>::insert(sibling(para_id), ());
+		para_id += 1;
+	}
+	while TrustedLockers::<T>::count() + 1 < T::MaxTrustedLocations::get() {
+		TrustedLockers::<T>::insert(sibling(para_id), ());
+		para_id += 1;
+	}
+}
+
+benchmarks! {
+	set_trusted_unlocker {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		fill_trusted_locations::<T>();
+		let location = sibling(0);
+	}: {
+		assert_ok!(<AssetLocker<T>>::set_trusted_unlocker(origin, Box::new(location.into()), true));
+	}
+	verify {
+		assert!(TrustedUnlockers::<T>::contains_key(location));
+	}
+
+	set_trusted_locker {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		fill_trusted_locations::<T>();
+		let location = sibling(0);
+	}: {
+		assert_ok!(<AssetLocker<T>>::set_trusted_locker(origin, Box::new(location.into()), true));
+	}
+	verify {
+		assert!(TrustedLockers::<T>::contains_key(location));
+	}
+}
+
+impl_benchmark_test_suite!(AssetLocker, crate::mock::new_test_ext(), crate::mock::Test);
@@ -0,0 +1,469 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Asset Locker Pallet
+//!
+//! Locking of fungible assets through XCM, over the frozen balances of `pallet_assets`.
+//!
+//! ## Overview
+//!
+//! The pallet implements the [`AssetLock`] of the XCM executor:
+//!
+//! - `LockAsset` locks an amount of a local asset of its origin, which only the given unlocker may
+//!   unlock with `UnlockAsset`. Each unlocker holds its own lock, and the locked amount of an asset
+//!   is the sum of its locks, frozen in `pallet_assets` through this pallet as its `Freezer`.
+//! - `NoteUnlockable` records that an asset of an account is locked on another chain for this
+//!   chain to unlock, and `RequestUnlock` reduces the record before asking the locker to unlock.
+//!
+//! Only the `TrustedUnlockers` may hold locks on local assets, and only the `TrustedLockers` may
+//! note assets locked for this chain. Both allow-lists are set by the `AdminOrigin`, usually root
+//! or the relay chain governance, through `set_trusted_unlocker` and `set_trusted_locker`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::tokens::{fungibles::Inspect, Balance},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{Saturating, Zero};
+	use sp_std::{boxed::Box, prelude::*};
+	use xcm::{latest::prelude::*, VersionedAssetId, VersionedMultiLocation};
+	use xcm_executor::traits::{AssetLock, Convert, Enact, LockError, MatchesFungibles};
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Identifier of the local assets.
+		type AssetId: Member + Parameter + Copy + MaxEncodedLen;
+
+		/// Balance of the local assets.
+		type Balance: Balance;
+
+		/// The local assets which can be locked, using this pallet as their `Freezer`.
+		type Assets: Inspect<Self::AccountId, AssetId = Self::AssetId, Balance = Self::Balance>;
+
+		/// Matcher of the local assets which can be locked.
+		type AssetMatcher: MatchesFungibles<Self::AssetId, Self::Balance>;
+
+		/// Converter of the locations of the owners of assets to local accounts.
+		type AccountIdConverter: Convert<MultiLocation, Self::AccountId>;
+
+		/// Origin allowed to change the trusted unlockers and lockers.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Maximum number of unlockers of each asset of an account.
+		#[pallet::constant]
+		type MaxLocks: Get<u32>;
+
+		/// Maximum number of trusted unlockers, and of trusted lockers.
+		#[pallet::constant]
+		type MaxTrustedLocations: Get<u32>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// Locations which may hold locks on the local assets.
+	#[pallet::storage]
+	pub type TrustedUnlockers<T: Config> =
+		CountedStorageMap<_, Blake2_128Concat, MultiLocation, (), OptionQuery>;
+
+	/// Locations which may note assets locked on them for this chain to unlock.
+	#[pallet::storage]
+	pub type TrustedLockers<T: Config> =
+		CountedStorageMap<_, Blake2_128Concat, MultiLocation, (), OptionQuery>;
+
+	/// Locks of the local assets of the accounts, with the location which may unlock each.
+	#[pallet::storage]
+	pub type Locks<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AssetId,
+		BoundedVec<(T::Balance, MultiLocation), T::MaxLocks>,
+		ValueQuery,
+	>;
+
+	/// Amounts of the assets of the accounts locked on other chains, which this chain may unlock,
+	/// by account, locker and asset.
+	#[pallet::storage]
+	pub type RemoteLocks<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Blake2_128Concat, MultiLocation>,
+			NMapKey<Blake2_128Concat, AssetId>,
+		),
+		u128,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A location was allowed, or no longer allowed, to hold locks on the local assets.
+		UnlockerTrusted { location: VersionedMultiLocation, trusted: bool },
+		/// A location was allowed, or no longer allowed, to note assets locked for this chain.
+		LockerTrusted { location: VersionedMultiLocation, trusted: bool },
+		/// A local asset was locked, `amount` being the amount locked by `unlocker`.
+		Locked {
+			who: T::AccountId,
+			asset_id: T::AssetId,
+			amount: T::Balance,
+			unlocker: VersionedMultiLocation,
+		},
+		/// A local asset was unlocked, `amount` being the amount unlocked.
+		Unlocked {
+			who: T::AccountId,
+			asset_id: T::AssetId,
+			amount: T::Balance,
+			unlocker: VersionedMultiLocation,
+		},
+		/// An asset locked on another chain was noted as unlockable by this chain.
+		UnlockableNoted {
+			who: T::AccountId,
+			locker: VersionedMultiLocation,
+			asset_id: VersionedAssetId,
+			amount: u128,
+		},
+		/// The unlock of an asset locked on another chain was requested, `amount` being the amount
+		/// left unlockable.
+		UnlockRequested {
+			who: T::AccountId,
+			locker: VersionedMultiLocation,
+			asset_id: VersionedAssetId,
+			amount: u128,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The location could not be converted to the latest XCM version.
+		BadLocation,
+		/// There are already `MaxTrustedLocations` trusted locations.
+		TooManyTrustedLocations,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Allow `location` to hold locks on the local assets, or no longer allow it.
+		///
+		/// The locks it already holds may still be unlocked.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::set_trusted_unlocker())]
+		pub fn set_trusted_unlocker(
+			origin: OriginFor<T>,
+			location: Box<VersionedMultiLocation>,
+			trusted: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let location: MultiLocation =
+				(*location).try_into().map_err(|()| Error::<T>::BadLocation)?;
+
+			if trusted {
+				ensure!(
+					TrustedUnlockers::<T>::contains_key(location) ||
+						TrustedUnlockers::<T>::count() < T::MaxTrustedLocations::get(),
+					Error::<T>::TooManyTrustedLocations,
+				);
+				TrustedUnlockers::<T>::insert(location, ());
+			} else {
+				TrustedUnlockers::<T>::remove(location);
+			}
+			Self::deposit_event(Event::UnlockerTrusted { location: location.into(), trusted });
+			Ok(())
+		}
+
+		/// Allow `location` to note assets locked on it for this chain to unlock, or no longer
+		/// allow it.
+		///
+		/// The assets it already noted may still be unlocked.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::set_trusted_locker())]
+		pub fn set_trusted_locker(
+			origin: OriginFor<T>,
+			location: Box<VersionedMultiLocation>,
+			trusted: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let location: MultiLocation =
+				(*location).try_into().map_err(|()| Error::<T>::BadLocation)?;
+
+			if trusted {
+				ensure!(
+					TrustedLockers::<T>::contains_key(location) ||
+						TrustedLockers::<T>::count() < T::MaxTrustedLocations::get(),
+					Error::<T>::TooManyTrustedLocations,
+				);
+				TrustedLockers::<T>::insert(location, ());
+			} else {
+				TrustedLockers::<T>::remove(location);
+			}
+			Self::deposit_event(Event::LockerTrusted { location: location.into(), trusted });
+			Ok(())
+		}
+	}
+
+	/// Lock of a local asset, ready to be enacted.
+	pub struct LockTicket<T: Config> {
+		who: T::AccountId,
+		asset_id: T::AssetId,
+		amount: T::Balance,
+		unlocker: MultiLocation,
+		locks: BoundedVec<(T::Balance, MultiLocation), T::MaxLocks>,
+	}
+
+	impl<T: Config> Enact for LockTicket<T> {
+		fn enact(self) -> Result<(), LockError> {
+			Locks::<T>::insert(&self.who, self.asset_id, self.locks);
+			Pallet::<T>::deposit_event(Event::Locked {
+				who: self.who,
+				asset_id: self.asset_id,
+				amount: self.amount,
+				unlocker: self.unlocker.into(),
+			});
+			Ok(())
+		}
+	}
+
+	/// Unlock of a local asset, ready to be enacted.
+	pub struct UnlockTicket<T: Config> {
+		who: T::AccountId,
+		asset_id: T::AssetId,
+		amount: T::Balance,
+		unlocker: MultiLocation,
+		locks: BoundedVec<(T::Balance, MultiLocation), T::MaxLocks>,
+	}
+
+	impl<T: Config> Enact for UnlockTicket<T> {
+		fn enact(self) -> Result<(), LockError> {
+			if self.locks.is_empty() {
+				Locks::<T>::remove(&self.who, self.asset_id);
+			} else {
+				Locks::<T>::insert(&self.who, self.asset_id, self.locks);
+			}
+			Pallet::<T>::deposit_event(Event::Unlocked {
+				who: self.who,
+				asset_id: self.asset_id,
+				amount: self.amount,
+				unlocker: self.unlocker.into(),
+			});
+			Ok(())
+		}
+	}
+
+	/// Reduction of an asset locked on another chain, ready to be enacted.
+	pub struct ReduceTicket<T: Config> {
+		who: T::AccountId,
+		locker: MultiLocation,
+		asset_id: AssetId,
+		amount: u128,
+	}
+
+	impl<T: Config> Enact for ReduceTicket<T> {
+		fn enact(self) -> Result<(), LockError> {
+			let key = (&self.who, &self.locker, &self.asset_id);
+			if self.amount.is_zero() {
+				RemoteLocks::<T>::remove(key);
+			} else {
+				RemoteLocks::<T>::insert(key, self.amount);
+			}
+			Pallet::<T>::deposit_event(Event::UnlockRequested {
+				who: self.who,
+				locker: self.locker.into(),
+				asset_id: self.asset_id.into(),
+				amount: self.amount,
+			});
+			Ok(())
+		}
+	}
+
+	impl<T: Config> AssetLock for Pallet<T> {
+		type LockTicket = LockTicket<T>;
+		type UnlockTicket = UnlockTicket<T>;
+		type ReduceTicket = ReduceTicket<T>;
+
+		fn prepare_lock(
+			unlocker: MultiLocation,
+			asset: MultiAsset,
+			owner: MultiLocation,
+		) -> Result<LockTicket<T>, LockError> {
+			ensure!(TrustedUnlockers::<T>::contains_key(unlocker), LockError::NotTrusted);
+			let (asset_id, amount) = Self::match_local_asset(&asset)?;
+			let who = T::AccountIdConverter::convert_ref(owner).map_err(|_| LockError::BadOwner)?;
+
+			// Locking again for the same unlocker only locks the difference.
+			let mut locks = Locks::<T>::get(&who, asset_id);
+			let additional = match locks.iter_mut().find(|(_, location)| location == &unlocker) {
+				Some((locked, _)) => {
+					let additional = amount.saturating_sub(*locked);
+					*locked = (*locked).max(amount);
+					additional
+				},
+				None => {
+					locks.try_push((amount, unlocker)).map_err(|_| LockError::NoResources)?;
+					amount
+				},
+			};
+			// The reducible balance already excludes the amounts locked before.
+			ensure!(
+				additional <= T::Assets::reducible_balance(asset_id, &who, false),
+				LockError::AssetNotOwned
+			);
+
+			Ok(LockTicket { who, asset_id, amount, unlocker, locks })
+		}
+
+		fn prepare_unlock(
+			unlocker: MultiLocation,
+			asset: MultiAsset,
+			owner: MultiLocation,
+		) -> Result<UnlockTicket<T>, LockError> {
+			let (asset_id, amount) = Self::match_local_asset(&asset)?;
+			let who = T::AccountIdConverter::convert_ref(owner).map_err(|_| LockError::BadOwner)?;
+
+			let mut locks = Locks::<T>::get(&who, asset_id);
+			let (locked, _) = locks
+				.iter_mut()
+				.find(|(_, location)| location == &unlocker)
+				.ok_or(LockError::NotLocked)?;
+			ensure!(amount <= *locked, LockError::NotEnoughLocked);
+			*locked -= amount;
+			locks.retain(|(locked, _)| !locked.is_zero());
+
+			Ok(UnlockTicket { who, asset_id, amount, unlocker, locks })
+		}
+
+		fn note_unlockable(
+			locker: MultiLocation,
+			asset: MultiAsset,
+			owner: MultiLocation,
+		) -> Result<(), LockError> {
+			ensure!(TrustedLockers::<T>::contains_key(locker), LockError::NotTrusted);
+			let amount = match asset.fun {
+				Fungible(amount) => amount,
+				NonFungible(_) => return Err(LockError::Unimplemented),
+			};
+			let who = T::AccountIdConverter::convert_ref(owner).map_err(|_| LockError::BadOwner)?;
+			let asset_id = asset.id;
+
+			// The locker notes the total amount locked, not its increase.
+			let amount = RemoteLocks::<T>::mutate((&who, &locker, &asset_id), |unlockable| {
+				let amount = unlockable.unwrap_or_default().max(amount);
+				*unlockable = Some(amount);
+				amount
+			});
+			Self::deposit_event(Event::UnlockableNoted {
+				who,
+				locker: locker.into(),
+				asset_id: asset_id.into(),
+				amount,
+			});
+			Ok(())
+		}
+
+		fn prepare_reduce_unlockable(
+			locker: MultiLocation,
+			asset: MultiAsset,
+			owner: MultiLocation,
+		) -> Result<ReduceTicket<T>, LockError> {
+			let amount = match asset.fun {
+				Fungible(amount) => amount,
+				NonFungible(_) => return Err(LockError::Unimplemented),
+			};
+			let who = T::AccountIdConverter::convert_ref(owner).map_err(|_| LockError::BadOwner)?;
+			let asset_id = asset.id;
+
+			let unlockable =
+				RemoteLocks::<T>::get((&who, &locker, &asset_id)).ok_or(LockError::NotLocked)?;
+			ensure!(amount <= unlockable, LockError::NotEnoughLocked);
+
+			Ok(ReduceTicket { who, locker, asset_id, amount: unlockable - amount })
+		}
+	}
+
+	impl<T: Config> pallet_assets::FrozenBalance<T::AssetId, T::AccountId, T::Balance> for Pallet<T> {
+		fn frozen_balance(asset_id: T::AssetId, who: &T::AccountId) -> Option<T::Balance> {
+			let locked = Self::locked(who, asset_id);
+			(!locked.is_zero()).then_some(locked)
+		}
+
+		fn died(asset_id: T::AssetId, who: &T::AccountId) {
+			Locks::<T>::remove(who, asset_id);
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Returns the amount of a local asset of `who` locked by all its unlockers.
+		pub fn locked(who: &T::AccountId, asset_id: T::AssetId) -> T::Balance {
+			Locks::<T>::get(who, asset_id)
+				.iter()
+				.fold(Zero::zero(), |total: T::Balance, (locked, _)| total.saturating_add(*locked))
+		}
+
+		/// Returns the locks of the local assets of `who`, with the location which may unlock each.
+		pub fn locks_of(
+			who: &T::AccountId,
+		) -> Vec<(T::AssetId, T::Balance, VersionedMultiLocation)> {
+			Locks::<T>::iter_prefix(who)
+				.flat_map(|(asset_id, locks)| {
+					locks
+						.into_iter()
+						.map(move |(locked, unlocker)| (asset_id, locked, unlocker.into()))
+				})
+				.collect()
+		}
+
+		/// Returns the assets of `who` locked on other chains which this chain may unlock, with
+		/// their locker.
+		pub fn unlockable_of(
+			who: &T::AccountId,
+		) -> Vec<(VersionedMultiLocation, VersionedAssetId, u128)> {
+			RemoteLocks::<T>::iter_prefix((who.clone(),))
+				.map(|((locker, asset_id), amount)| (locker.into(), asset_id.into(), amount))
+				.collect()
+		}
+
+		fn match_local_asset(asset: &MultiAsset) -> Result<(T::AssetId, T::Balance), LockError> {
+			let (asset_id, amount) =
+				T::AssetMatcher::matches_fungibles(asset).map_err(|_| LockError::UnknownAsset)?;
+			ensure!(!amount.is_zero(), LockError::NotApplicable);
+			Ok((asset_id, amount))
+		}
+	}
+}
@@ -0,0 +1,193 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate as asset_locker;
+use frame_support::{
+	parameter_types,
+	traits::{
+		tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+		ConstU32, ConstU64, Everything,
+	},
+};
+use frame_system::EnsureRoot;
+use pallet_assets::FrozenBalance;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::{cell::RefCell, collections::BTreeMap};
+use xcm::latest::prelude::*;
+use xcm_executor::traits::{Convert, Error as MatchError, MatchesFungibles};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		AssetLocker: asset_locker,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+thread_local! {
+	/// Balances of the accounts, by asset and account.
+	static BALANCES: RefCell<BTreeMap<(u32, u64), u128>> = RefCell::new(BTreeMap::new());
+}
+
+/// Fungible assets kept in memory, frozen by `AssetLocker` like `pallet_assets` would.
+pub struct Assets;
+
+impl Assets {
+	pub fn set_balance(asset: u32, who: &u64, amount: u128) {
+		BALANCES.with(|balances| balances.borrow_mut().insert((asset, *who), amount));
+	}
+}
+
+impl fungibles::Inspect<u64> for Assets {
+	type AssetId = u32;
+	type Balance = u128;
+
+	fn total_issuance(asset: u32) -> u128 {
+		BALANCES.with(|balances| {
+			balances
+				.borrow()
+				.iter()
+				.filter(|((id, _), _)| *id == asset)
+				.map(|(_, b)| *b)
+				.sum()
+		})
+	}
+
+	fn minimum_balance(_asset: u32) -> u128 {
+		1
+	}
+
+	fn balance(asset: u32, who: &u64) -> u128 {
+		BALANCES.with(|balances| balances.borrow().get(&(asset, *who)).copied().unwrap_or_default())
+	}
+
+	fn reducible_balance(asset: u32, who: &u64, _keep_alive: bool) -> u128 {
+		let frozen = AssetLocker::frozen_balance(asset, who).unwrap_or_default();
+		Self::balance(asset, who).saturating_sub(frozen)
+	}
+
+	fn can_deposit(_asset: u32, _who: &u64, _amount: u128, _mint: bool) -> DepositConsequence {
+		DepositConsequence::Success
+	}
+
+	fn can_withdraw(asset: u32, who: &u64, amount: u128) -> WithdrawConsequence<u128> {
+		if Self::reducible_balance(asset, who, false) < amount {
+			WithdrawConsequence::Frozen
+		} else {
+			WithdrawConsequence::Success
+		}
+	}
+
+	fn asset_exists(asset: u32) -> bool {
+		asset == 1
+	}
+}
+
+/// Matches the assets located at `GeneralIndex(id)`.
+pub struct AssetMatcher;
+impl MatchesFungibles<u32, u128> for AssetMatcher {
+	fn matches_fungibles(asset: &MultiAsset) -> Result<(u32, u128), MatchError> {
+		match asset {
+			MultiAsset {
+				id: Concrete(MultiLocation { parents: 0, interior: X1(GeneralIndex(id)) }),
+				fun: Fungible(amount),
+			} => Ok((*id as u32, *amount)),
+			_ => Err(MatchError::AssetNotHandled),
+		}
+	}
+}
+
+/// Converts the locations of `AccountIndex64` junctions to accounts.
+pub struct AccountIdConverter;
+impl Convert<MultiLocation, u64> for AccountIdConverter {
+	fn convert(location: MultiLocation) -> Result<u64, MultiLocation> {
+		match location {
+			MultiLocation { parents: 0, interior: X1(AccountIndex64 { index, .. }) } => Ok(index),
+			location => Err(location),
+		}
+	}
+}
+
+parameter_types! {
+	pub const MaxLocks: u32 = 2;
+}
+
+impl Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type AssetId = u32;
+	type Balance = u128;
+	type Assets = Assets;
+	type AssetMatcher = AssetMatcher;
+	type AccountIdConverter = AccountIdConverter;
+	type AdminOrigin = EnsureRoot<u64>;
+	type MaxLocks = MaxLocks;
+	type MaxTrustedLocations = ConstU32<3>;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	BALANCES.with(|balances| balances.borrow_mut().clear());
+
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		// The sibling parachains 2000 and 2001 are trusted with any asset.
+		for para_id in [2000, 2001] {
+			TrustedUnlockers::<Test>::insert(MultiLocation::new(1, X1(Parachain(para_id))), ());
+			TrustedLockers::<Test>::insert(MultiLocation::new(1, X1(Parachain(para_id))), ());
+		}
+	});
+	ext
+}
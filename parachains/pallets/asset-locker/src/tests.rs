@@ -0,0 +1,183 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{mock::*, Error, Event, Locks, RemoteLocks, TrustedLockers, TrustedUnlockers};
+use frame_support::{assert_noop, assert_ok, traits::tokens::fungibles::Inspect};
+use pallet_assets::FrozenBalance;
+use sp_runtime::DispatchError;
+use xcm::{latest::prelude::*, VersionedAssetId, VersionedMultiLocation};
+use xcm_executor::traits::{AssetLock, Enact, LockError};
+
+const ALICE: u64 = 1;
+
+fn owner(index: u64) -> MultiLocation {
+	AccountIndex64 { network: None, index }.into()
+}
+
+fn sibling(id: u32) -> MultiLocation {
+	(Parent, Parachain(id)).into()
+}
+
+fn asset(id: u128, amount: u128) -> MultiAsset {
+	(GeneralIndex(id), amount).into()
+}
+
+fn lock(unlocker: u32, amount: u128) -> Result<(), LockError> {
+	AssetLocker::prepare_lock(sibling(unlocker), asset(1, amount), owner(ALICE))?.enact()
+}
+
+fn unlock(unlocker: u32, amount: u128) -> Result<(), LockError> {
+	AssetLocker::prepare_unlock(sibling(unlocker), asset(1, amount), owner(ALICE))?.enact()
+}
+
+#[test]
+fn lock_and_unlock_work() {
+	new_test_ext().execute_with(|| {
+		Assets::set_balance(1, &ALICE, 100);
+
+		assert!(lock(2000, 60).is_ok());
+		assert_eq!(AssetLocker::frozen_balance(1, &ALICE), Some(60));
+		assert_eq!(Assets::reducible_balance(1, &ALICE, false), 40);
+
+		// Locking again for the same unlocker only locks the difference.
+		assert!(lock(2000, 50).is_ok());
+		assert_eq!(AssetLocker::locked(&ALICE, 1), 60);
+
+		// Each unlocker holds its own lock on the rest of the balance.
+		assert!(matches!(lock(2001, 41), Err(LockError::AssetNotOwned)));
+		assert!(lock(2001, 40).is_ok());
+		assert_eq!(AssetLocker::frozen_balance(1, &ALICE), Some(100));
+		assert_eq!(
+			AssetLocker::locks_of(&ALICE),
+			vec![
+				(1, 60, VersionedMultiLocation::from(sibling(2000))),
+				(1, 40, VersionedMultiLocation::from(sibling(2001))),
+			]
+		);
+
+		// Only the unlocker may unlock, up to its lock.
+		assert!(matches!(unlock(2002, 10), Err(LockError::NotLocked)));
+		assert!(matches!(unlock(2000, 61), Err(LockError::NotEnoughLocked)));
+		assert!(unlock(2000, 20).is_ok());
+		assert_eq!(AssetLocker::locked(&ALICE, 1), 80);
+		assert!(unlock(2000, 40).is_ok());
+		assert_eq!(Locks::<Test>::get(ALICE, 1).len(), 1);
+		assert!(unlock(2001, 40).is_ok());
+		assert!(!Locks::<Test>::contains_key(ALICE, 1));
+		assert_eq!(AssetLocker::frozen_balance(1, &ALICE), None);
+	});
+}
+
+#[test]
+fn lock_rejects_untrusted_unlockers_and_unknown_assets() {
+	new_test_ext().execute_with(|| {
+		Assets::set_balance(1, &ALICE, 100);
+
+		assert!(matches!(lock(3000, 10), Err(LockError::NotTrusted)));
+		assert!(matches!(
+			AssetLocker::prepare_lock(sibling(2000), (Parent, 10).into(), owner(ALICE)),
+			Err(LockError::UnknownAsset)
+		));
+		assert!(matches!(
+			AssetLocker::prepare_lock(sibling(2000), asset(1, 10), sibling(2000)),
+			Err(LockError::BadOwner)
+		));
+		assert!(AssetLocker::locks_of(&ALICE).is_empty());
+	});
+}
+
+#[test]
+fn note_and_reduce_unlockable_work() {
+	new_test_ext().execute_with(|| {
+		let remote_asset: MultiAsset = ((Parent, Parachain(2000), GeneralIndex(7)), 100).into();
+		let key = (ALICE, sibling(2000), remote_asset.id);
+
+		assert!(matches!(
+			AssetLocker::note_unlockable(sibling(3000), remote_asset.clone(), owner(ALICE)),
+			Err(LockError::NotTrusted)
+		));
+		assert!(
+			AssetLocker::note_unlockable(sibling(2000), remote_asset.clone(), owner(ALICE)).is_ok()
+		);
+		assert_eq!(RemoteLocks::<Test>::get(key.clone()), Some(100));
+		assert_eq!(
+			AssetLocker::unlockable_of(&ALICE),
+			vec![(VersionedMultiLocation::from(key.1), VersionedAssetId::from(key.2), 100)]
+		);
+
+		let reduce = |amount| {
+			let mut asset = remote_asset.clone();
+			asset.fun = Fungible(amount);
+			AssetLocker::prepare_reduce_unlockable(sibling(2000), asset, owner(ALICE))
+		};
+		assert!(matches!(reduce(101), Err(LockError::NotEnoughLocked)));
+		assert!(reduce(30).and_then(Enact::enact).is_ok());
+		assert_eq!(RemoteLocks::<Test>::get(key.clone()), Some(70));
+		assert!(reduce(70).and_then(Enact::enact).is_ok());
+		assert_eq!(RemoteLocks::<Test>::get(key.clone()), None);
+		assert!(matches!(reduce(1), Err(LockError::NotLocked)));
+	});
+}
+
+#[test]
+fn trusted_locations_are_set_by_the_admin() {
+	new_test_ext().execute_with(|| {
+		let location = || Box::new(VersionedMultiLocation::from(sibling(3000)));
+
+		assert_noop!(
+			AssetLocker::set_trusted_unlocker(RuntimeOrigin::signed(ALICE), location(), true),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(AssetLocker::set_trusted_unlocker(RuntimeOrigin::root(), location(), true));
+		assert!(TrustedUnlockers::<Test>::contains_key(sibling(3000)));
+		System::assert_last_event(
+			Event::UnlockerTrusted { location: sibling(3000).into(), trusted: true }.into(),
+		);
+
+		// At most `MaxTrustedLocations` locations are trusted.
+		assert_noop!(
+			AssetLocker::set_trusted_unlocker(
+				RuntimeOrigin::root(),
+				Box::new(sibling(3001).into()),
+				true
+			),
+			Error::<Test>::TooManyTrustedLocations
+		);
+		assert_ok!(AssetLocker::set_trusted_unlocker(RuntimeOrigin::root(), location(), false));
+		assert!(!TrustedUnlockers::<Test>::contains_key(sibling(3000)));
+
+		assert_ok!(AssetLocker::set_trusted_locker(RuntimeOrigin::root(), location(), true));
+		assert!(TrustedLockers::<Test>::contains_key(sibling(3000)));
+		assert_ok!(AssetLocker::set_trusted_locker(RuntimeOrigin::root(), location(), false));
+		assert!(!TrustedLockers::<Test>::contains_key(sibling(3000)));
+	});
+}
+
+#[test]
+fn untrusted_unlocker_keeps_its_locks_unlockable() {
+	new_test_ext().execute_with(|| {
+		Assets::set_balance(1, &ALICE, 100);
+		assert!(lock(2000, 60).is_ok());
+
+		assert_ok!(AssetLocker::set_trusted_unlocker(
+			RuntimeOrigin::root(),
+			Box::new(sibling(2000).into()),
+			false
+		));
+		assert!(matches!(lock(2000, 70), Err(LockError::NotTrusted)));
+		assert!(unlock(2000, 60).is_ok());
+		assert_eq!(AssetLocker::frozen_balance(1, &ALICE), None);
+	});
+}
@@ -0,0 +1,67 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weights of `pallet_asset_locker`.
+//!
+//! Only the governance calls are weighed here, not benchmarked yet. The XCM lock instructions are
+//! weighed by the runtime, `Weight::MAX` until `pallet_xcm_benchmarks` covers them.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+// The weight info trait for `pallet_asset_locker`.
+pub trait WeightInfo {
+	fn set_trusted_unlocker() -> Weight;
+	fn set_trusted_locker() -> Weight;
+}
+
+/// Weights for pallet_asset_locker using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: AssetLocker TrustedUnlockers (r:1 w:1)
+	// Storage: AssetLocker CounterForTrustedUnlockers (r:1 w:1)
+	fn set_trusted_unlocker() -> Weight {
+		Weight::from_parts(19_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: AssetLocker TrustedLockers (r:1 w:1)
+	// Storage: AssetLocker CounterForTrustedLockers (r:1 w:1)
+	fn set_trusted_locker() -> Weight {
+		Weight::from_parts(19_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn set_trusted_unlocker() -> Weight {
+		Weight::from_parts(19_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	fn set_trusted_locker() -> Weight {
+		Weight::from_parts(19_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+}
@@ -0,0 +1,141 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarking setup for pallet-asset-swap
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as AssetSwap;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::{
+	assert_ok,
+	traits::tokens::fungibles::{Inspect, Mutate},
+};
+use frame_system::{EventRecord, RawOrigin};
+use sp_runtime::traits::Zero;
+
+const SEED: u32 = 0;
+
+fn assert_last_event<T: Config>(generic_event: <T as Config>::RuntimeEvent) {
+	let events = frame_system::Pallet::<T>::events();
+	let system_event: <T as frame_system::Config>::RuntimeEvent = generic_event.into();
+	// compare to the last event record
+	let EventRecord { event, .. } = &events[events.len() - 1];
+	assert_eq!(event, &system_event);
+}
+
+fn units<T: Config>(amount: u32) -> T::Balance {
+	T::Balance::from(amount) * T::Balance::from(1_000_000u32)
+}
+
+fn create_funded_user<T: Config>(
+	name: &'static str,
+	assets: (T::AssetId, T::AssetId),
+) -> T::AccountId {
+	let user = account(name, 0, SEED);
+	assert_ok!(T::Assets::mint_into(assets.0, &user, units::<T>(1_000)));
+	assert_ok!(T::Assets::mint_into(assets.1, &user, units::<T>(1_000)));
+	user
+}
+
+/// Create a pool of two new assets with liquidity, returning the assets.
+fn create_pool_with_liquidity<T: Config>() -> (T::AssetId, T::AssetId) {
+	let assets = (T::BenchmarkHelper::create_asset(1), T::BenchmarkHelper::create_asset(2));
+	let provider = create_funded_user::<T>("provider", assets);
+	assert_ok!(AssetSwap::<T>::create_pool(
+		RawOrigin::Signed(provider.clone()).into(),
+		assets.0,
+		assets.1
+	));
+	assert_ok!(AssetSwap::<T>::add_liquidity(
+		RawOrigin::Signed(provider).into(),
+		assets.0,
+		assets.1,
+		units::<T>(100),
+		units::<T>(200),
+		Zero::zero(),
+		Zero::zero(),
+	));
+	assets
+}
+
+benchmarks! {
+	create_pool {
+		let assets = (T::BenchmarkHelper::create_asset(1), T::BenchmarkHelper::create_asset(2));
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = AssetSwap::<T>::pool_id(assets.0, assets.1).unwrap();
+	}: _(RawOrigin::Signed(caller.clone()), assets.0, assets.1)
+	verify {
+		let account = AssetSwap::<T>::pool_account(&pool_id);
+		assert_last_event::<T>(Event::PoolCreated { creator: caller, pool_id, account }.into());
+	}
+
+	add_liquidity {
+		let assets = create_pool_with_liquidity::<T>();
+		let caller = create_funded_user::<T>("caller", assets);
+	}: _(
+		RawOrigin::Signed(caller.clone()),
+		assets.0,
+		assets.1,
+		units::<T>(10),
+		units::<T>(20),
+		Zero::zero(),
+		Zero::zero()
+	)
+	verify {
+		let pool_id = AssetSwap::<T>::pool_id(assets.0, assets.1).unwrap();
+		assert!(!Shares::<T>::get(pool_id, &caller).is_zero());
+	}
+
+	remove_liquidity {
+		let assets = create_pool_with_liquidity::<T>();
+		let caller = create_funded_user::<T>("caller", assets);
+		assert_ok!(AssetSwap::<T>::add_liquidity(
+			RawOrigin::Signed(caller.clone()).into(),
+			assets.0,
+			assets.1,
+			units::<T>(10),
+			units::<T>(20),
+			Zero::zero(),
+			Zero::zero(),
+		));
+		let pool_id = AssetSwap::<T>::pool_id(assets.0, assets.1).unwrap();
+		let shares = Shares::<T>::get(pool_id, &caller);
+	}: _(RawOrigin::Signed(caller.clone()), assets.0, assets.1, shares, Zero::zero(), Zero::zero())
+	verify {
+		assert!(Shares::<T>::get(pool_id, &caller).is_zero());
+	}
+
+	swap_exact_in {
+		let assets = create_pool_with_liquidity::<T>();
+		let caller = create_funded_user::<T>("caller", assets);
+		let before = T::Assets::balance(assets.1, &caller);
+	}: _(RawOrigin::Signed(caller.clone()), assets.0, assets.1, units::<T>(10), Zero::zero())
+	verify {
+		assert!(T::Assets::balance(assets.1, &caller) > before);
+	}
+
+	swap_exact_out {
+		let assets = create_pool_with_liquidity::<T>();
+		let caller = create_funded_user::<T>("caller", assets);
+		let before = T::Assets::balance(assets.1, &caller);
+	}: _(RawOrigin::Signed(caller.clone()), assets.0, assets.1, units::<T>(10), units::<T>(100))
+	verify {
+		assert_eq!(T::Assets::balance(assets.1, &caller), before + units::<T>(10));
+	}
+}
+
+impl_benchmark_test_suite!(AssetSwap, crate::mock::new_test_ext(), crate::mock::Test,);
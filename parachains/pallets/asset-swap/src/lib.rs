@@ -0,0 +1,529 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Asset Swap Pallet
+//!
+//! Constant-product liquidity pools for pairs of fungible assets.
+//!
+//! ## Overview
+//!
+//! Anyone can create a pool for a pair of existing assets. The reserves of a pool are held by an
+//! account derived from the `PalletId` and the pair, and liquidity providers receive shares of the
+//! pool in exchange for their deposits. The first deposit sets the price of the pool, and
+//! `MinimumLiquidity` of its shares are never withdrawn so that the pool can't be emptied.
+//!
+//! Swaps keep the product of the reserves constant, minus the `LiquidityProviderFee` which is left
+//! in the pool for its liquidity providers. Swaps are either made by a signed account, or for assets
+//! of the XCM holding register (see [`Pallet::swap_exact_in_for`]), which are minted into and
+//! burned from the pool account.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+#[frame_support::pallet]
+pub mod pallet {
+	pub use crate::weights::WeightInfo;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::tokens::{
+			fungibles::{Inspect, Mutate, Transfer},
+			Balance,
+		},
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_core::U256;
+	use sp_runtime::{
+		helpers_128bit::multiply_by_rational_with_rounding,
+		traits::{AccountIdConversion, CheckedAdd, Zero},
+		Permill, Rounding, SaturatedConversion, Saturating,
+	};
+
+	/// A pool, identified by its pair of assets in ascending order.
+	pub type PoolIdOf<T> = (<T as Config>::AssetId, <T as Config>::AssetId);
+
+	/// Creation of assets for the benchmarks.
+	#[cfg(feature = "runtime-benchmarks")]
+	pub trait BenchmarkHelper<AssetId> {
+		/// Create a sufficient asset with a minimum balance of 1, returning its identifier.
+		fn create_asset(seed: u32) -> AssetId;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Identifier of the assets.
+		type AssetId: Member + Parameter + Ord + Copy + MaxEncodedLen;
+
+		/// Balance of the assets, also used for the shares of the pools.
+		type Balance: Balance;
+
+		/// The assets which can be pooled.
+		type Assets: Inspect<Self::AccountId, AssetId = Self::AssetId, Balance = Self::Balance>
+			+ Mutate<Self::AccountId>
+			+ Transfer<Self::AccountId>;
+
+		/// Identifier from which the accounts of the pools are derived.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Fee taken from the assets given in swaps, left in the pools.
+		#[pallet::constant]
+		type LiquidityProviderFee: Get<Permill>;
+
+		/// Shares of each pool which are never withdrawn.
+		#[pallet::constant]
+		type MinimumLiquidity: Get<Self::Balance>;
+
+		/// The weight information of this pallet.
+		type WeightInfo: WeightInfo;
+
+		/// Creation of assets for the benchmarks.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper<Self::AssetId>;
+	}
+
+	/// Total shares of the existing pools.
+	#[pallet::storage]
+	pub type Pools<T: Config> = StorageMap<_, Blake2_128Concat, PoolIdOf<T>, T::Balance>;
+
+	/// Shares of the pools held by their liquidity providers.
+	#[pallet::storage]
+	pub type Shares<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		PoolIdOf<T>,
+		Blake2_128Concat,
+		T::AccountId,
+		T::Balance,
+		ValueQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A pool was created.
+		PoolCreated { creator: T::AccountId, pool_id: PoolIdOf<T>, account: T::AccountId },
+		/// Liquidity was added to a pool, amounts are given in the order of the pair of the pool.
+		LiquidityAdded {
+			who: T::AccountId,
+			pool_id: PoolIdOf<T>,
+			amount1: T::Balance,
+			amount2: T::Balance,
+			shares: T::Balance,
+		},
+		/// Liquidity was removed from a pool, amounts are given in the order of the pair of the
+		/// pool.
+		LiquidityRemoved {
+			who: T::AccountId,
+			pool_id: PoolIdOf<T>,
+			amount1: T::Balance,
+			amount2: T::Balance,
+			shares: T::Balance,
+		},
+		/// Assets were swapped, `who` is `None` for assets of the XCM holding register.
+		Swapped {
+			who: Option<T::AccountId>,
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_in: T::Balance,
+			amount_out: T::Balance,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A pool can't be made of twice the same asset.
+		IdenticalAssets,
+		/// The asset doesn't exist.
+		UnknownAsset,
+		/// The pool already exists.
+		PoolExists,
+		/// The pool doesn't exist.
+		PoolNotFound,
+		/// The amount must be greater than zero.
+		ZeroAmount,
+		/// The pool doesn't hold enough liquidity.
+		InsufficientLiquidity,
+		/// The account doesn't hold enough shares of the pool.
+		InsufficientShares,
+		/// An amount is below the minimum accepted.
+		BelowMinimum,
+		/// An amount is above the maximum accepted.
+		AboveMaximum,
+		/// An arithmetic overflow occurred.
+		Overflow,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Create a pool for the pair of `asset1` and `asset2`.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::create_pool())]
+		pub fn create_pool(
+			origin: OriginFor<T>,
+			asset1: T::AssetId,
+			asset2: T::AssetId,
+		) -> DispatchResult {
+			let creator = ensure_signed(origin)?;
+			let pool_id = Self::pool_id(asset1, asset2)?;
+			ensure!(!Pools::<T>::contains_key(pool_id), Error::<T>::PoolExists);
+			ensure!(
+				T::Assets::asset_exists(asset1) && T::Assets::asset_exists(asset2),
+				Error::<T>::UnknownAsset
+			);
+
+			let account = Self::pool_account(&pool_id);
+			// The pool account may hold non-sufficient assets.
+			frame_system::Pallet::<T>::inc_providers(&account);
+			Pools::<T>::insert(pool_id, T::Balance::zero());
+
+			Self::deposit_event(Event::PoolCreated { creator, pool_id, account });
+			Ok(())
+		}
+
+		/// Add liquidity to the pool of `asset1` and `asset2`, receiving shares of the pool.
+		///
+		/// The first deposit sets the price of the pool. The following ones deposit the most of
+		/// the desired amounts at the price of the pool, failing if this is less than the minimum
+		/// amounts.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::add_liquidity())]
+		pub fn add_liquidity(
+			origin: OriginFor<T>,
+			asset1: T::AssetId,
+			asset2: T::AssetId,
+			amount1_desired: T::Balance,
+			amount2_desired: T::Balance,
+			amount1_min: T::Balance,
+			amount2_min: T::Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pool_id = Self::pool_id(asset1, asset2)?;
+			let total_shares = Pools::<T>::get(pool_id).ok_or(Error::<T>::PoolNotFound)?;
+			let ((desired1, desired2), (min1, min2)) = if pool_id.0 == asset1 {
+				((amount1_desired, amount2_desired), (amount1_min, amount2_min))
+			} else {
+				((amount2_desired, amount1_desired), (amount2_min, amount1_min))
+			};
+			ensure!(!desired1.is_zero() && !desired2.is_zero(), Error::<T>::ZeroAmount);
+
+			let account = Self::pool_account(&pool_id);
+			let (reserve1, reserve2) =
+				(T::Assets::balance(pool_id.0, &account), T::Assets::balance(pool_id.1, &account));
+
+			let (amount1, amount2, shares, total_shares) = if total_shares.is_zero() {
+				let shares = Self::integer_sqrt_of_product(desired1, desired2)?;
+				let minimum_liquidity = T::MinimumLiquidity::get();
+				ensure!(shares > minimum_liquidity, Error::<T>::InsufficientLiquidity);
+				(desired1, desired2, shares - minimum_liquidity, shares)
+			} else {
+				ensure!(
+					!reserve1.is_zero() && !reserve2.is_zero(),
+					Error::<T>::InsufficientLiquidity
+				);
+				let optimal2 = Self::mul_div(desired1, reserve2, reserve1, Rounding::Down)?;
+				let (amount1, amount2) = if optimal2 <= desired2 {
+					ensure!(optimal2 >= min2, Error::<T>::BelowMinimum);
+					(desired1, optimal2)
+				} else {
+					let optimal1 = Self::mul_div(desired2, reserve1, reserve2, Rounding::Down)?;
+					ensure!(optimal1 <= desired1, Error::<T>::AboveMaximum);
+					ensure!(optimal1 >= min1, Error::<T>::BelowMinimum);
+					(optimal1, desired2)
+				};
+				let shares = Self::mul_div(amount1, total_shares, reserve1, Rounding::Down)?
+					.min(Self::mul_div(amount2, total_shares, reserve2, Rounding::Down)?);
+				ensure!(!shares.is_zero(), Error::<T>::ZeroAmount);
+				let total_shares = total_shares.checked_add(&shares).ok_or(Error::<T>::Overflow)?;
+				(amount1, amount2, shares, total_shares)
+			};
+
+			T::Assets::transfer(pool_id.0, &who, &account, amount1, true)?;
+			T::Assets::transfer(pool_id.1, &who, &account, amount2, true)?;
+			Pools::<T>::insert(pool_id, total_shares);
+			Shares::<T>::mutate(pool_id, &who, |held| *held = held.saturating_add(shares));
+
+			Self::deposit_event(Event::LiquidityAdded { who, pool_id, amount1, amount2, shares });
+			Ok(())
+		}
+
+		/// Remove liquidity from the pool of `asset1` and `asset2`, burning `shares` of the pool.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::remove_liquidity())]
+		pub fn remove_liquidity(
+			origin: OriginFor<T>,
+			asset1: T::AssetId,
+			asset2: T::AssetId,
+			shares: T::Balance,
+			amount1_min: T::Balance,
+			amount2_min: T::Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pool_id = Self::pool_id(asset1, asset2)?;
+			let total_shares = Pools::<T>::get(pool_id).ok_or(Error::<T>::PoolNotFound)?;
+			let (min1, min2) = if pool_id.0 == asset1 {
+				(amount1_min, amount2_min)
+			} else {
+				(amount2_min, amount1_min)
+			};
+			ensure!(!shares.is_zero(), Error::<T>::ZeroAmount);
+			let held = Shares::<T>::get(pool_id, &who);
+			ensure!(held >= shares, Error::<T>::InsufficientShares);
+
+			let account = Self::pool_account(&pool_id);
+			let (reserve1, reserve2) =
+				(T::Assets::balance(pool_id.0, &account), T::Assets::balance(pool_id.1, &account));
+			let amount1 = Self::mul_div(shares, reserve1, total_shares, Rounding::Down)?;
+			let amount2 = Self::mul_div(shares, reserve2, total_shares, Rounding::Down)?;
+			ensure!(amount1 >= min1 && amount2 >= min2, Error::<T>::BelowMinimum);
+
+			T::Assets::transfer(pool_id.0, &account, &who, amount1, true)?;
+			T::Assets::transfer(pool_id.1, &account, &who, amount2, true)?;
+			Pools::<T>::insert(pool_id, total_shares - shares);
+			if held == shares {
+				Shares::<T>::remove(pool_id, &who);
+			} else {
+				Shares::<T>::insert(pool_id, &who, held - shares);
+			}
+
+			Self::deposit_event(Event::LiquidityRemoved { who, pool_id, amount1, amount2, shares });
+			Ok(())
+		}
+
+		/// Swap exactly `amount_in` of `asset_in` for at least `amount_out_min` of `asset_out`.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::swap_exact_in())]
+		pub fn swap_exact_in(
+			origin: OriginFor<T>,
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_in: T::Balance,
+			amount_out_min: T::Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::swap_exact_in_for(Some(&who), asset_in, asset_out, amount_in, amount_out_min)
+				.map(|_| ())
+		}
+
+		/// Swap at most `amount_in_max` of `asset_in` for exactly `amount_out` of `asset_out`.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::swap_exact_out())]
+		pub fn swap_exact_out(
+			origin: OriginFor<T>,
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_out: T::Balance,
+			amount_in_max: T::Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::swap_exact_out_for(Some(&who), asset_in, asset_out, amount_out, amount_in_max)
+				.map(|_| ())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Returns the identifier of the pool of `asset1` and `asset2`.
+		pub fn pool_id(asset1: T::AssetId, asset2: T::AssetId) -> Result<PoolIdOf<T>, Error<T>> {
+			ensure!(asset1 != asset2, Error::<T>::IdenticalAssets);
+			Ok(if asset1 < asset2 { (asset1, asset2) } else { (asset2, asset1) })
+		}
+
+		/// Returns the account holding the reserves of the pool.
+		pub fn pool_account(pool_id: &PoolIdOf<T>) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(pool_id)
+		}
+
+		/// Returns the reserves of `asset1` and `asset2` held by their pool.
+		pub fn pool_reserves(
+			asset1: T::AssetId,
+			asset2: T::AssetId,
+		) -> Result<(T::Balance, T::Balance), Error<T>> {
+			let pool_id = Self::pool_id(asset1, asset2)?;
+			ensure!(Pools::<T>::contains_key(pool_id), Error::<T>::PoolNotFound);
+			let account = Self::pool_account(&pool_id);
+			Ok((T::Assets::balance(asset1, &account), T::Assets::balance(asset2, &account)))
+		}
+
+		/// Returns the amount of `asset_out` received for exactly `amount_in` of `asset_in`.
+		pub fn quote_exact_in(
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_in: T::Balance,
+		) -> Result<T::Balance, Error<T>> {
+			let (reserve_in, reserve_out) = Self::pool_reserves(asset_in, asset_out)?;
+			ensure!(
+				!reserve_in.is_zero() && !reserve_out.is_zero(),
+				Error::<T>::InsufficientLiquidity
+			);
+
+			let amount_in = T::LiquidityProviderFee::get().left_from_one().mul_floor(amount_in);
+			let amount_out = Self::mul_div(
+				reserve_out,
+				amount_in,
+				reserve_in.checked_add(&amount_in).ok_or(Error::<T>::Overflow)?,
+				Rounding::Down,
+			)?;
+			Self::ensure_reserve_left(asset_out, reserve_out, amount_out)?;
+			Ok(amount_out)
+		}
+
+		/// Returns the amount of `asset_in` needed to receive exactly `amount_out` of `asset_out`.
+		pub fn quote_exact_out(
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_out: T::Balance,
+		) -> Result<T::Balance, Error<T>> {
+			let (reserve_in, reserve_out) = Self::pool_reserves(asset_in, asset_out)?;
+			ensure!(
+				!reserve_in.is_zero() && !reserve_out.is_zero(),
+				Error::<T>::InsufficientLiquidity
+			);
+			Self::ensure_reserve_left(asset_out, reserve_out, amount_out)?;
+
+			let reserve_out_left = T::LiquidityProviderFee::get()
+				.left_from_one()
+				.mul_floor(reserve_out - amount_out);
+			ensure!(!reserve_out_left.is_zero(), Error::<T>::InsufficientLiquidity);
+			Self::mul_div(reserve_in, amount_out, reserve_out_left, Rounding::Up)
+		}
+
+		/// Swap exactly `amount_in` of `asset_in` for at least `amount_out_min` of `asset_out`,
+		/// returning the amount of `asset_out` received.
+		///
+		/// The assets are transferred from and to `who`, or minted into and burned from the pool
+		/// account for assets of the XCM holding register when `who` is `None`.
+		pub fn swap_exact_in_for(
+			who: Option<&T::AccountId>,
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_in: T::Balance,
+			amount_out_min: T::Balance,
+		) -> Result<T::Balance, DispatchError> {
+			ensure!(!amount_in.is_zero(), Error::<T>::ZeroAmount);
+			let amount_out = Self::quote_exact_in(asset_in, asset_out, amount_in)?;
+			ensure!(
+				!amount_out.is_zero() && amount_out >= amount_out_min,
+				Error::<T>::BelowMinimum
+			);
+
+			Self::do_swap(who, asset_in, asset_out, amount_in, amount_out)?;
+			Ok(amount_out)
+		}
+
+		/// Swap at most `amount_in_max` of `asset_in` for exactly `amount_out` of `asset_out`,
+		/// returning the amount of `asset_in` spent.
+		///
+		/// The assets are transferred like with [`Self::swap_exact_in_for`].
+		pub fn swap_exact_out_for(
+			who: Option<&T::AccountId>,
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_out: T::Balance,
+			amount_in_max: T::Balance,
+		) -> Result<T::Balance, DispatchError> {
+			ensure!(!amount_out.is_zero(), Error::<T>::ZeroAmount);
+			let amount_in = Self::quote_exact_out(asset_in, asset_out, amount_out)?;
+			ensure!(amount_in <= amount_in_max, Error::<T>::AboveMaximum);
+
+			Self::do_swap(who, asset_in, asset_out, amount_in, amount_out)?;
+			Ok(amount_in)
+		}
+
+		fn do_swap(
+			who: Option<&T::AccountId>,
+			asset_in: T::AssetId,
+			asset_out: T::AssetId,
+			amount_in: T::Balance,
+			amount_out: T::Balance,
+		) -> DispatchResult {
+			let account = Self::pool_account(&Self::pool_id(asset_in, asset_out)?);
+			match who {
+				Some(who) => {
+					T::Assets::transfer(asset_in, who, &account, amount_in, true)?;
+					T::Assets::transfer(asset_out, &account, who, amount_out, true)?;
+				},
+				None => {
+					T::Assets::mint_into(asset_in, &account, amount_in)?;
+					T::Assets::burn_from(asset_out, &account, amount_out)?;
+				},
+			}
+
+			Self::deposit_event(Event::Swapped {
+				who: who.cloned(),
+				asset_in,
+				asset_out,
+				amount_in,
+				amount_out,
+			});
+			Ok(())
+		}
+
+		/// Ensure the pool keeps at least the minimum balance of `asset` after paying `amount`.
+		fn ensure_reserve_left(
+			asset: T::AssetId,
+			reserve: T::Balance,
+			amount: T::Balance,
+		) -> Result<(), Error<T>> {
+			ensure!(
+				amount < reserve && reserve - amount >= T::Assets::minimum_balance(asset),
+				Error::<T>::InsufficientLiquidity
+			);
+			Ok(())
+		}
+
+		/// Returns `a * b / c`.
+		fn mul_div(
+			a: T::Balance,
+			b: T::Balance,
+			c: T::Balance,
+			rounding: Rounding,
+		) -> Result<T::Balance, Error<T>> {
+			let result = multiply_by_rational_with_rounding(
+				a.saturated_into(),
+				b.saturated_into(),
+				c.saturated_into(),
+				rounding,
+			)
+			.ok_or(Error::<T>::Overflow)?;
+			T::Balance::try_from(result).map_err(|_| Error::<T>::Overflow)
+		}
+
+		/// Returns `sqrt(a * b)`.
+		fn integer_sqrt_of_product(a: T::Balance, b: T::Balance) -> Result<T::Balance, Error<T>> {
+			let product =
+				U256::from(a.saturated_into::<u128>()) * U256::from(b.saturated_into::<u128>());
+			T::Balance::try_from(product.integer_sqrt().low_u128())
+				.map_err(|_| Error::<T>::Overflow)
+		}
+	}
+}
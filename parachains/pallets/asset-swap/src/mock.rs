@@ -0,0 +1,226 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate as asset_swap;
+use frame_support::{
+	parameter_types,
+	traits::{
+		tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+		ConstU128, ConstU32, ConstU64, Everything,
+	},
+	PalletId,
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError, DispatchResult, Permill, TokenError,
+};
+use std::{cell::RefCell, collections::BTreeMap};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		AssetSwap: asset_swap,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+thread_local! {
+	/// Minimum balance of the existing assets.
+	static MINIMUM_BALANCES: RefCell<BTreeMap<u32, u128>> = RefCell::new(BTreeMap::new());
+	/// Balances of the accounts, by asset and account.
+	static BALANCES: RefCell<BTreeMap<(u32, u64), u128>> = RefCell::new(BTreeMap::new());
+}
+
+/// Fungible assets kept in memory.
+pub struct Assets;
+
+impl Assets {
+	pub fn create(asset: u32, minimum_balance: u128) {
+		MINIMUM_BALANCES.with(|assets| assets.borrow_mut().insert(asset, minimum_balance));
+	}
+
+	fn set_balance(asset: u32, who: &u64, amount: u128) {
+		BALANCES.with(|balances| {
+			if amount == 0 {
+				balances.borrow_mut().remove(&(asset, *who));
+			} else {
+				balances.borrow_mut().insert((asset, *who), amount);
+			}
+		});
+	}
+}
+
+impl fungibles::Inspect<u64> for Assets {
+	type AssetId = u32;
+	type Balance = u128;
+
+	fn total_issuance(asset: u32) -> u128 {
+		BALANCES.with(|balances| {
+			balances
+				.borrow()
+				.iter()
+				.filter(|((id, _), _)| *id == asset)
+				.map(|(_, b)| *b)
+				.sum()
+		})
+	}
+
+	fn minimum_balance(asset: u32) -> u128 {
+		MINIMUM_BALANCES.with(|assets| assets.borrow().get(&asset).copied().unwrap_or_default())
+	}
+
+	fn balance(asset: u32, who: &u64) -> u128 {
+		BALANCES.with(|balances| balances.borrow().get(&(asset, *who)).copied().unwrap_or_default())
+	}
+
+	fn reducible_balance(asset: u32, who: &u64, keep_alive: bool) -> u128 {
+		let balance = Self::balance(asset, who);
+		if keep_alive {
+			balance.saturating_sub(Self::minimum_balance(asset))
+		} else {
+			balance
+		}
+	}
+
+	fn can_deposit(asset: u32, who: &u64, amount: u128, _mint: bool) -> DepositConsequence {
+		if !Self::asset_exists(asset) {
+			DepositConsequence::UnknownAsset
+		} else if Self::balance(asset, who) + amount < Self::minimum_balance(asset) {
+			DepositConsequence::BelowMinimum
+		} else {
+			DepositConsequence::Success
+		}
+	}
+
+	fn can_withdraw(asset: u32, who: &u64, amount: u128) -> WithdrawConsequence<u128> {
+		if Self::balance(asset, who) < amount {
+			WithdrawConsequence::NoFunds
+		} else {
+			WithdrawConsequence::Success
+		}
+	}
+
+	fn asset_exists(asset: u32) -> bool {
+		MINIMUM_BALANCES.with(|assets| assets.borrow().contains_key(&asset))
+	}
+}
+
+impl fungibles::Mutate<u64> for Assets {
+	fn mint_into(asset: u32, who: &u64, amount: u128) -> DispatchResult {
+		Self::can_deposit(asset, who, amount, true).into_result()?;
+		Self::set_balance(asset, who, Self::balance(asset, who) + amount);
+		Ok(())
+	}
+
+	fn burn_from(asset: u32, who: &u64, amount: u128) -> Result<u128, DispatchError> {
+		let balance = Self::balance(asset, who);
+		if balance < amount {
+			return Err(TokenError::NoFunds.into())
+		}
+		Self::set_balance(asset, who, balance - amount);
+		Ok(amount)
+	}
+}
+
+impl fungibles::Transfer<u64> for Assets {
+	fn transfer(
+		asset: u32,
+		source: &u64,
+		dest: &u64,
+		amount: u128,
+		keep_alive: bool,
+	) -> Result<u128, DispatchError> {
+		if Self::reducible_balance(asset, source, keep_alive) < amount {
+			return Err(TokenError::NoFunds.into())
+		}
+		Self::can_deposit(asset, dest, amount, false).into_result()?;
+		Self::set_balance(asset, source, Self::balance(asset, source) - amount);
+		Self::set_balance(asset, dest, Self::balance(asset, dest) + amount);
+		Ok(amount)
+	}
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl BenchmarkHelper<u32> for Assets {
+	fn create_asset(seed: u32) -> u32 {
+		Assets::create(seed, 1);
+		seed
+	}
+}
+
+parameter_types! {
+	pub const AssetSwapPalletId: PalletId = PalletId(*b"py/swaps");
+	pub const LiquidityProviderFee: Permill = Permill::from_parts(3_000);
+}
+
+impl Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type AssetId = u32;
+	type Balance = u128;
+	type Assets = Assets;
+	type PalletId = AssetSwapPalletId;
+	type LiquidityProviderFee = LiquidityProviderFee;
+	type MinimumLiquidity = ConstU128<1_000>;
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = Assets;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	MINIMUM_BALANCES.with(|assets| assets.borrow_mut().clear());
+	BALANCES.with(|balances| balances.borrow_mut().clear());
+
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
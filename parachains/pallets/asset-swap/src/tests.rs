@@ -0,0 +1,168 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{mock::*, Error, Pools, Shares};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::tokens::fungibles::{Inspect, Mutate},
+};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+
+/// Create assets 1 and 2 and their pool, funding Alice and Bob.
+fn setup_pool() {
+	Assets::create(1, 10);
+	Assets::create(2, 10);
+	for who in [ALICE, BOB] {
+		assert_ok!(Assets::mint_into(1, &who, 100_000));
+		assert_ok!(Assets::mint_into(2, &who, 100_000));
+	}
+	assert_ok!(AssetSwap::create_pool(RuntimeOrigin::signed(ALICE), 1, 2));
+}
+
+fn pool_balance(asset: u32) -> u128 {
+	Assets::balance(asset, &AssetSwap::pool_account(&(1, 2)))
+}
+
+#[test]
+fn create_pool_works() {
+	new_test_ext().execute_with(|| {
+		Assets::create(1, 10);
+		Assets::create(2, 10);
+
+		assert_noop!(
+			AssetSwap::create_pool(RuntimeOrigin::signed(ALICE), 1, 1),
+			Error::<Test>::IdenticalAssets
+		);
+		assert_noop!(
+			AssetSwap::create_pool(RuntimeOrigin::signed(ALICE), 1, 3),
+			Error::<Test>::UnknownAsset
+		);
+
+		// The pool of a pair is the same in both orders.
+		assert_ok!(AssetSwap::create_pool(RuntimeOrigin::signed(ALICE), 2, 1));
+		assert_eq!(Pools::<Test>::get((1, 2)), Some(0));
+		assert_noop!(
+			AssetSwap::create_pool(RuntimeOrigin::signed(ALICE), 1, 2),
+			Error::<Test>::PoolExists
+		);
+	});
+}
+
+#[test]
+fn add_and_remove_liquidity_works() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+
+		// The first deposit sets the price, the minimum liquidity is locked.
+		assert_ok!(AssetSwap::add_liquidity(
+			RuntimeOrigin::signed(ALICE),
+			1,
+			2,
+			10_000,
+			40_000,
+			0,
+			0
+		));
+		assert_eq!(Pools::<Test>::get((1, 2)), Some(20_000));
+		assert_eq!(Shares::<Test>::get((1, 2), ALICE), 19_000);
+
+		// The following deposits are made at the price of the pool, amounts in any order.
+		assert_noop!(
+			AssetSwap::add_liquidity(RuntimeOrigin::signed(BOB), 2, 1, 4_000, 5_000, 4_000, 1_001),
+			Error::<Test>::BelowMinimum
+		);
+		assert_ok!(AssetSwap::add_liquidity(RuntimeOrigin::signed(BOB), 2, 1, 4_000, 5_000, 0, 0));
+		assert_eq!(Shares::<Test>::get((1, 2), BOB), 2_000);
+		assert_eq!((pool_balance(1), pool_balance(2)), (11_000, 44_000));
+
+		assert_noop!(
+			AssetSwap::remove_liquidity(RuntimeOrigin::signed(BOB), 1, 2, 2_001, 0, 0),
+			Error::<Test>::InsufficientShares
+		);
+		assert_ok!(AssetSwap::remove_liquidity(RuntimeOrigin::signed(BOB), 1, 2, 2_000, 0, 0));
+		assert_eq!(Assets::balance(1, &BOB), 100_000);
+		assert_eq!(Assets::balance(2, &BOB), 100_000);
+
+		// The minimum liquidity stays in the pool.
+		assert_ok!(AssetSwap::remove_liquidity(RuntimeOrigin::signed(ALICE), 1, 2, 19_000, 0, 0));
+		assert_eq!(Shares::<Test>::get((1, 2), ALICE), 0);
+		assert_eq!((pool_balance(1), pool_balance(2)), (500, 2_000));
+	});
+}
+
+#[test]
+fn swaps_keep_the_product_of_the_reserves() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+		assert_ok!(AssetSwap::add_liquidity(
+			RuntimeOrigin::signed(ALICE),
+			1,
+			2,
+			10_000,
+			40_000,
+			0,
+			0
+		));
+
+		// 1_000 minus the fee of 0.3% buys 40_000 * 997 / 10_997.
+		assert_eq!(AssetSwap::quote_exact_in(1, 2, 1_000), Ok(3_626));
+		assert_noop!(
+			AssetSwap::swap_exact_in(RuntimeOrigin::signed(BOB), 1, 2, 1_000, 3_627),
+			Error::<Test>::BelowMinimum
+		);
+		assert_ok!(AssetSwap::swap_exact_in(RuntimeOrigin::signed(BOB), 1, 2, 1_000, 3_626));
+		assert_eq!((pool_balance(1), pool_balance(2)), (11_000, 36_374));
+
+		// Buying back the same amount costs more, rounded up.
+		assert_eq!(AssetSwap::quote_exact_out(2, 1, 1_000), Ok(3_649));
+		assert_noop!(
+			AssetSwap::swap_exact_out(RuntimeOrigin::signed(BOB), 2, 1, 1_000, 3_648),
+			Error::<Test>::AboveMaximum
+		);
+		assert_ok!(AssetSwap::swap_exact_out(RuntimeOrigin::signed(BOB), 2, 1, 1_000, 3_649));
+		assert_eq!(Assets::balance(1, &BOB), 100_000);
+		assert_eq!(Assets::balance(2, &BOB), 100_000 - 23);
+
+		// The pool can't be drained below the minimum balance.
+		assert_noop!(
+			AssetSwap::swap_exact_out(RuntimeOrigin::signed(BOB), 1, 2, 40_023 - 9, u128::MAX),
+			Error::<Test>::InsufficientLiquidity
+		);
+	});
+}
+
+#[test]
+fn swaps_for_the_holding_register_mint_and_burn() {
+	new_test_ext().execute_with(|| {
+		setup_pool();
+		assert_ok!(AssetSwap::add_liquidity(
+			RuntimeOrigin::signed(ALICE),
+			1,
+			2,
+			10_000,
+			40_000,
+			0,
+			0
+		));
+		let issuance = (Assets::total_issuance(1), Assets::total_issuance(2));
+
+		assert_eq!(AssetSwap::swap_exact_in_for(None, 1, 2, 1_000, 0), Ok(3_626));
+		assert_eq!(Assets::total_issuance(1), issuance.0 + 1_000);
+		assert_eq!(Assets::total_issuance(2), issuance.1 - 3_626);
+		assert_eq!((pool_balance(1), pool_balance(2)), (11_000, 36_374));
+	});
+}
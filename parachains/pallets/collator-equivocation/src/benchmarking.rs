@@ -0,0 +1,69 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarking setup for pallet-collator-equivocation
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as CollatorEquivocation;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_system::RawOrigin;
+use sp_application_crypto::RuntimeAppPublic;
+use sp_consensus_aura::{digests::CompatibleDigestItem, Slot};
+use sp_runtime::{
+	traits::{Hash as HashT, Header as HeaderT, One},
+	DigestItem,
+};
+use sp_std::prelude::*;
+
+type AuthoritySignature<T> =
+	<<T as pallet_aura::Config>::AuthorityId as RuntimeAppPublic>::Signature;
+
+/// Build a header of the given slot sealed by `author`.
+fn sealed_header<T: Config>(author: &T::AuthorityId, slot: Slot, state_root: T::Hash) -> T::Header {
+	let mut header = T::Header::new(
+		One::one(),
+		Default::default(),
+		state_root,
+		Default::default(),
+		Default::default(),
+	);
+	header
+		.digest_mut()
+		.push(<DigestItem as CompatibleDigestItem<AuthoritySignature<T>>>::aura_pre_digest(slot));
+	let seal = author.sign(&header.hash()).expect("The key was generated in the keystore; qed");
+	header
+		.digest_mut()
+		.push(<DigestItem as CompatibleDigestItem<AuthoritySignature<T>>>::aura_seal(seal));
+	header
+}
+
+benchmarks! {
+	report_equivocation_unsigned {
+		let author = T::AuthorityId::generate_pair(None);
+		pallet_aura::Pallet::<T>::change_authorities(
+			vec![author.clone()].try_into().expect("One authority is always allowed; qed"),
+		);
+		let slot = pallet_aura::Pallet::<T>::current_slot();
+		let first_header = sealed_header::<T>(&author, slot, Default::default());
+		let second_header = sealed_header::<T>(&author, slot, T::Hashing::hash(b"fork"));
+	}: _(RawOrigin::None, Box::new(first_header), Box::new(second_header))
+	verify {
+		assert_eq!(LastReportedSlot::<T>::get(&author), Some(slot));
+	}
+}
+
+impl_benchmark_test_suite!(CollatorEquivocation, crate::mock::new_test_ext(), crate::mock::Test,);
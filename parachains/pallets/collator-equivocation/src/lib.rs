@@ -0,0 +1,216 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collator Equivocation Pallet
+//!
+//! Reports of AuRa block authors sealing different blocks for the same slot.
+//!
+//! ## Overview
+//!
+//! Nodes detecting an equivocation in the block announcements submit both headers through the
+//! unsigned [`Pallet::report_equivocation_unsigned`] call, which is only accepted from the local
+//! node. The seals of both headers are checked against the current AuRa authorities and
+//! [`Event::EquivocationReported`] is emitted.
+//!
+//! Reports for slots older than `ReportLongevity`, or not newer than the last reported slot of
+//! the same offender, are rejected.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+#[frame_support::pallet]
+pub mod pallet {
+	pub use crate::weights::WeightInfo;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_application_crypto::RuntimeAppPublic;
+	use sp_consensus_aura::{digests::CompatibleDigestItem, Slot};
+	use sp_runtime::traits::Header as HeaderT;
+	use sp_std::prelude::*;
+
+	type AuthoritySignature<T> =
+		<<T as pallet_aura::Config>::AuthorityId as RuntimeAppPublic>::Signature;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_aura::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Number of slots during which an equivocation can be reported.
+		#[pallet::constant]
+		type ReportLongevity: Get<u64>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// The last slot for which an equivocation of the author was reported.
+	#[pallet::storage]
+	pub type LastReportedSlot<T: Config> =
+		StorageMap<_, Twox64Concat, T::AuthorityId, Slot, OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The author of the slot sealed two different blocks for it.
+		EquivocationReported { offender: T::AuthorityId, slot: Slot },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The headers are not sealed by the author of the same slot, or are the same block.
+		InvalidProof,
+		/// The slot is too old to be reported.
+		StaleReport,
+		/// An equivocation of the author was already reported for this slot or a later one.
+		DuplicateReport,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Report that the author of the slot of both headers sealed them both.
+		///
+		/// The headers are given with their seal.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::report_equivocation_unsigned())]
+		pub fn report_equivocation_unsigned(
+			origin: OriginFor<T>,
+			first_header: Box<T::Header>,
+			second_header: Box<T::Header>,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+
+			let (offender, slot) = Self::check_equivocation(&first_header, &second_header)?;
+			LastReportedSlot::<T>::insert(&offender, slot);
+			Self::deposit_event(Event::EquivocationReported { offender, slot });
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (first_header, second_header) = match call {
+				Call::report_equivocation_unsigned { first_header, second_header } =>
+					(first_header, second_header),
+				_ => return Err(InvalidTransaction::Call.into()),
+			};
+
+			// Reports are only submitted by the node detecting the equivocation.
+			if !matches!(source, TransactionSource::Local | TransactionSource::InBlock) {
+				return Err(InvalidTransaction::Call.into())
+			}
+
+			let (offender, slot) =
+				Self::check_equivocation(first_header, second_header).map_err(|err| match err {
+					Error::<T>::StaleReport => InvalidTransaction::Stale,
+					_ => InvalidTransaction::BadProof,
+				})?;
+
+			ValidTransaction::with_tag_prefix("CollatorEquivocation")
+				.priority(TransactionPriority::max_value())
+				.and_provides((offender, slot))
+				.longevity(T::ReportLongevity::get())
+				.propagate(false)
+				.build()
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The call reporting the equivocation of the given headers, if they are a valid proof.
+		pub fn report_equivocation_call(
+			first_header: T::Header,
+			second_header: T::Header,
+		) -> Option<Call<T>> {
+			Self::check_equivocation(&first_header, &second_header).ok()?;
+			Some(Call::report_equivocation_unsigned {
+				first_header: Box::new(first_header),
+				second_header: Box::new(second_header),
+			})
+		}
+
+		/// Check that both headers are different blocks sealed by the author of the same slot.
+		///
+		/// The author is looked up in the current AuRa authorities. Returns the author and the
+		/// slot.
+		pub fn check_equivocation(
+			first_header: &T::Header,
+			second_header: &T::Header,
+		) -> Result<(T::AuthorityId, Slot), Error<T>> {
+			let (slot, first_pre_hash, first_seal) =
+				Self::unseal(first_header).ok_or(Error::<T>::InvalidProof)?;
+			let (second_slot, second_pre_hash, second_seal) =
+				Self::unseal(second_header).ok_or(Error::<T>::InvalidProof)?;
+			ensure!(
+				slot == second_slot && first_pre_hash != second_pre_hash,
+				Error::<T>::InvalidProof
+			);
+
+			let current_slot = pallet_aura::Pallet::<T>::current_slot();
+			ensure!(
+				slot.saturating_add(T::ReportLongevity::get()) >= current_slot,
+				Error::<T>::StaleReport
+			);
+
+			let authorities = pallet_aura::Pallet::<T>::authorities();
+			if authorities.is_empty() {
+				return Err(Error::<T>::InvalidProof)
+			}
+			let offender = authorities[*slot as usize % authorities.len()].clone();
+			ensure!(
+				offender.verify(&first_pre_hash, &first_seal) &&
+					offender.verify(&second_pre_hash, &second_seal),
+				Error::<T>::InvalidProof
+			);
+
+			ensure!(
+				LastReportedSlot::<T>::get(&offender).map_or(true, |last| last < slot),
+				Error::<T>::DuplicateReport
+			);
+
+			Ok((offender, slot))
+		}
+
+		// Returns the slot, the hash of the header without its seal, and the seal.
+		fn unseal(header: &T::Header) -> Option<(Slot, T::Hash, AuthoritySignature<T>)> {
+			let mut header = header.clone();
+			let seal = header.digest_mut().pop()?;
+			let seal = CompatibleDigestItem::<AuthoritySignature<T>>::as_aura_seal(&seal)?;
+			let slot = header
+				.digest()
+				.logs()
+				.iter()
+				.find_map(CompatibleDigestItem::<AuthoritySignature<T>>::as_aura_pre_digest)?;
+			Some((slot, header.hash(), seal))
+		}
+	}
+}
@@ -0,0 +1,145 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{mock::*, Error, Event, LastReportedSlot};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::Hooks, unsigned::ValidateUnsigned};
+use sp_consensus_aura::{
+	digests::CompatibleDigestItem, sr25519::AuthoritySignature, Slot, AURA_ENGINE_ID,
+};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{
+	testing::Header,
+	traits::Header as HeaderT,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	DigestItem,
+};
+
+/// Build a header of the given slot sealed by `author`.
+fn sealed_header(author: &sr25519::Pair, slot: u64, state_root: H256) -> Header {
+	let mut header =
+		Header::new(1, Default::default(), state_root, Default::default(), Default::default());
+	header.digest_mut().push(
+		<DigestItem as CompatibleDigestItem<AuthoritySignature>>::aura_pre_digest(slot.into()),
+	);
+	let seal = author.sign(header.hash().as_ref());
+	header
+		.digest_mut()
+		.push(<DigestItem as CompatibleDigestItem<AuthoritySignature>>::aura_seal(seal.into()));
+	header
+}
+
+/// Two different headers of the given slot sealed by `author`.
+fn equivocation(author: &sr25519::Pair, slot: u64) -> (Header, Header) {
+	(
+		sealed_header(author, slot, H256::repeat_byte(1)),
+		sealed_header(author, slot, H256::repeat_byte(2)),
+	)
+}
+
+fn report(headers: (Header, Header)) -> frame_support::dispatch::DispatchResult {
+	CollatorEquivocation::report_equivocation_unsigned(
+		RuntimeOrigin::none(),
+		Box::new(headers.0),
+		Box::new(headers.1),
+	)
+}
+
+/// Move AuRa to the given slot.
+fn set_slot(slot: u64) {
+	System::deposit_log(DigestItem::PreRuntime(AURA_ENGINE_ID, Slot::from(slot).encode()));
+	Aura::on_initialize(System::block_number());
+}
+
+#[test]
+fn equivocation_of_the_slot_author_is_reported() {
+	new_test_ext().execute_with(|| {
+		// Slot 4 is authored by the second of the three authorities.
+		let author = &authority_keys()[1];
+
+		assert_ok!(report(equivocation(author, 4)));
+
+		let offender = author.public().into();
+		assert_eq!(LastReportedSlot::<Test>::get(&offender), Some(4.into()));
+		System::assert_last_event(Event::EquivocationReported { offender, slot: 4.into() }.into());
+
+		// The same equivocation, or an older one, is not reported twice.
+		assert_noop!(report(equivocation(author, 4)), Error::<Test>::DuplicateReport);
+		assert_noop!(report(equivocation(author, 1)), Error::<Test>::DuplicateReport);
+		assert_ok!(report(equivocation(author, 7)));
+	});
+}
+
+#[test]
+fn invalid_proofs_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let keys = authority_keys();
+
+		// Not the author of the slot.
+		assert_noop!(report(equivocation(&keys[0], 4)), Error::<Test>::InvalidProof);
+
+		// Different slots.
+		let first = sealed_header(&keys[1], 4, H256::repeat_byte(1));
+		let second = sealed_header(&keys[1], 7, H256::repeat_byte(2));
+		assert_noop!(report((first, second)), Error::<Test>::InvalidProof);
+
+		// The same block sealed twice.
+		let first = sealed_header(&keys[1], 4, H256::repeat_byte(1));
+		let second = sealed_header(&keys[1], 4, H256::repeat_byte(1));
+		assert_noop!(report((first, second)), Error::<Test>::InvalidProof);
+
+		// Unsealed header.
+		let (first, mut second) = equivocation(&keys[1], 4);
+		second.digest_mut().pop();
+		assert_noop!(report((first, second)), Error::<Test>::InvalidProof);
+	});
+}
+
+#[test]
+fn stale_equivocations_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let author = &authority_keys()[1];
+		set_slot(4 + REPORT_LONGEVITY + 1);
+
+		assert_noop!(report(equivocation(author, 4)), Error::<Test>::StaleReport);
+	});
+}
+
+#[test]
+fn only_local_reports_are_valid() {
+	new_test_ext().execute_with(|| {
+		let (first_header, second_header) = equivocation(&authority_keys()[1], 4);
+		let call = crate::Call::report_equivocation_unsigned {
+			first_header: Box::new(first_header),
+			second_header: Box::new(second_header),
+		};
+
+		assert_ok!(CollatorEquivocation::validate_unsigned(TransactionSource::Local, &call));
+		assert_eq!(
+			CollatorEquivocation::validate_unsigned(TransactionSource::External, &call),
+			Err(InvalidTransaction::Call.into()),
+		);
+
+		let (first_header, _) = equivocation(&authority_keys()[1], 4);
+		let call = crate::Call::report_equivocation_unsigned {
+			first_header: Box::new(first_header.clone()),
+			second_header: Box::new(first_header),
+		};
+		assert_eq!(
+			CollatorEquivocation::validate_unsigned(TransactionSource::Local, &call),
+			Err(InvalidTransaction::BadProof.into()),
+		);
+	});
+}
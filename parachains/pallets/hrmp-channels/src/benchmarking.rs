@@ -0,0 +1,131 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarking setup for pallet-hrmp-channels
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as HrmpChannels;
+use cumulus_primitives_core::ParaId;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, BenchmarkError};
+use frame_support::{
+	assert_ok,
+	traits::{EnsureOrigin, Get},
+};
+use sp_std::prelude::*;
+use xcm::latest::prelude::*;
+
+/// Returns the `n`-th sibling, skipping the parachain itself.
+fn sibling<T: Config>(n: u32) -> ParaId {
+	let id = ParaId::from(2000 + n);
+	if id == T::SelfParaId::get() {
+		ParaId::from(3000 + n)
+	} else {
+		id
+	}
+}
+
+benchmarks! {
+	open_channel {
+		let origin = T::ChannelManagerOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		T::BenchmarkHelper::prepare_relay_sender();
+		let recipient = sibling::<T>(0);
+	}: {
+		assert_ok!(HrmpChannels::<T>::open_channel(origin, recipient, 8, 1024));
+	}
+	verify {
+		assert_eq!(
+			Channels::<T>::get(ChannelDirection::Outbound, recipient),
+			Some(ChannelStatus::Requested)
+		);
+	}
+
+	accept_channel {
+		let origin = T::ChannelManagerOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		T::BenchmarkHelper::prepare_relay_sender();
+		let sender = sibling::<T>(0);
+		OpenRequests::<T>::insert(sender, (8, 1024));
+	}: {
+		assert_ok!(HrmpChannels::<T>::accept_channel(origin, sender));
+	}
+	verify {
+		assert!(!OpenRequests::<T>::contains_key(sender));
+		assert_eq!(
+			Channels::<T>::get(ChannelDirection::Inbound, sender),
+			Some(ChannelStatus::Accepted)
+		);
+	}
+
+	close_channel {
+		let origin = T::ChannelManagerOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		T::BenchmarkHelper::prepare_relay_sender();
+		let recipient = sibling::<T>(0);
+		Channels::<T>::insert(ChannelDirection::Outbound, recipient, ChannelStatus::Open);
+	}: {
+		assert_ok!(HrmpChannels::<T>::close_channel(origin, T::SelfParaId::get(), recipient));
+	}
+	verify {
+		assert_eq!(
+			Channels::<T>::get(ChannelDirection::Outbound, recipient),
+			Some(ChannelStatus::Closing)
+		);
+	}
+
+	forget_request {
+		let origin = T::ChannelManagerOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		let sender = sibling::<T>(0);
+		Channels::<T>::insert(ChannelDirection::Inbound, sender, ChannelStatus::Accepted);
+		OpenRequests::<T>::insert(sender, (8, 1024));
+	}: {
+		assert_ok!(HrmpChannels::<T>::forget_request(origin, ChannelDirection::Inbound, sender));
+	}
+	verify {
+		assert!(!Channels::<T>::contains_key(ChannelDirection::Inbound, sender));
+		assert!(!OpenRequests::<T>::contains_key(sender));
+	}
+
+	handle_notification {
+		let recipient = sibling::<T>(0);
+		Channels::<T>::insert(ChannelDirection::Outbound, recipient, ChannelStatus::Requested);
+		let notification = HrmpChannelAccepted { recipient: recipient.into() };
+	}: {
+		HrmpChannels::<T>::handle_notification(notification);
+	}
+	verify {
+		assert_eq!(
+			Channels::<T>::get(ChannelDirection::Outbound, recipient),
+			Some(ChannelStatus::Accepted)
+		);
+	}
+
+	sync_channels {
+		let c in 0 .. 100;
+		// Channels opened without going through the pallet, each read and written.
+		let mut open = (0..c).map(sibling::<T>).collect::<Vec<_>>();
+		open.sort();
+	}: {
+		assert_eq!(HrmpChannels::<T>::sync_channels(ChannelDirection::Outbound, &open), c);
+	}
+	verify {
+		assert_eq!(Channels::<T>::iter_prefix(ChannelDirection::Outbound).count() as u32, c);
+	}
+}
+
+impl_benchmark_test_suite!(HrmpChannels, crate::mock::new_test_ext(), crate::mock::Test);
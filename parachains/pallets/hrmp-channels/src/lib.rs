@@ -0,0 +1,538 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HRMP Channels Pallet
+//!
+//! Management of the HRMP channels of the parachain.
+//!
+//! ## Overview
+//!
+//! Opening, accepting and closing HRMP channels is done on the relay chain, by the parachain
+//! itself. This pallet sends the `Transact` of the requests to the relay chain through the
+//! upward message queue, on behalf of [`Config::ChannelManagerOrigin`]:
+//!
+//! - [`Pallet::open_channel`] requests a channel to a sibling, with `hrmp_init_open_channel`,
+//! - [`Pallet::accept_channel`] accepts the request of a sibling, with `hrmp_accept_open_channel`,
+//! - [`Pallet::close_channel`] closes a channel, with `hrmp_close_channel`.
+//!
+//! The requests the relay chain drops or rejects stay pending, until
+//! [`Pallet::forget_request`] forgets them so that they may be made again.
+//!
+//! [`HandleHrmpNotifications`] wraps the handler of the downward messages, to process the
+//! notifications sent by the relay chain about the channels of the parachain. The open requests
+//! of the siblings allowed by [`Config::AutoAccept`] are accepted right away, the others are
+//! kept in [`OpenRequests`] for [`Config::ChannelManagerOrigin`] to decide.
+//!
+//! The state of the channels is kept in sync with the `relevant_messaging_state` of
+//! parachain-system, the pallet being its `OnSystemEvent`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+#[frame_support::pallet]
+pub mod pallet {
+	pub use crate::weights::WeightInfo;
+	use codec::Encode;
+	use cumulus_pallet_parachain_system::OnSystemEvent;
+	use cumulus_primitives_core::{
+		relay_chain::BlockNumber as RelayBlockNumber, AbridgedHrmpChannel, DmpMessageHandler,
+		ParaId, PersistedValidationData,
+	};
+	use frame_support::{pallet_prelude::*, traits::Contains};
+	use frame_system::pallet_prelude::*;
+	use sp_std::{marker::PhantomData, prelude::*};
+	use xcm::{latest::prelude::*, VersionedXcm};
+
+	/// The direction of a channel, from the point of view of the parachain.
+	#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub enum ChannelDirection {
+		/// The channel from a sibling to the parachain.
+		Inbound,
+		/// The channel from the parachain to a sibling.
+		Outbound,
+	}
+
+	/// The state of a channel.
+	#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub enum ChannelStatus {
+		/// The opening of the channel was requested.
+		Requested,
+		/// The opening of the channel was accepted, it opens at the next session.
+		Accepted,
+		/// The channel is open.
+		Open,
+		/// The closing of the channel was requested, it closes at the next session.
+		Closing,
+	}
+
+	/// The identifier of a channel, encoded as the one of the relay chain.
+	#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub struct HrmpChannelId {
+		pub sender: ParaId,
+		pub recipient: ParaId,
+	}
+
+	/// The calls of the `hrmp` pallet of the relay chain sent by the pallet.
+	#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+	pub enum HrmpCall {
+		#[codec(index = 0)]
+		InitOpenChannel { recipient: ParaId, max_capacity: u32, max_message_size: u32 },
+		#[codec(index = 1)]
+		AcceptOpenChannel { sender: ParaId },
+		#[codec(index = 2)]
+		CloseChannel { channel_id: HrmpChannelId },
+	}
+
+	/// Preparation of the benchmarks.
+	#[cfg(feature = "runtime-benchmarks")]
+	pub trait BenchmarkHelper {
+		/// Make the requests to the relay chain sendable through [`Config::XcmSender`].
+		fn prepare_relay_sender();
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	impl BenchmarkHelper for () {
+		fn prepare_relay_sender() {}
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The origin allowed to manage the channels.
+		type ChannelManagerOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The id of the parachain.
+		type SelfParaId: Get<ParaId>;
+
+		/// The siblings whose open requests are accepted right away.
+		type AutoAccept: Contains<ParaId>;
+
+		/// How to send the requests to the relay chain.
+		type XcmSender: SendXcm;
+
+		/// The index of the `hrmp` pallet in the relay chain runtime.
+		#[pallet::constant]
+		type RelayHrmpPalletIndex: Get<u8>;
+
+		/// The maximum weight of the requests on the relay chain.
+		#[pallet::constant]
+		type RelayCallWeight: Get<Weight>;
+
+		/// The fee paid to the relay chain for each request, out of the sovereign account of the
+		/// parachain. The requests are sent unpaid if `None`.
+		type RelayFee: Get<Option<MultiAsset>>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+
+		/// Preparation of the benchmarks.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper;
+	}
+
+	/// The known channels of the parachain and their state.
+	#[pallet::storage]
+	pub type Channels<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, ChannelDirection, Twox64Concat, ParaId, ChannelStatus>;
+
+	/// The open requests of the siblings waiting to be accepted, with their proposed
+	/// `(max_capacity, max_message_size)`.
+	#[pallet::storage]
+	pub type OpenRequests<T: Config> = StorageMap<_, Twox64Concat, ParaId, (u32, u32)>;
+
+	/// The notifications of the relay chain left for lack of weight, handled first by the next
+	/// downward messages.
+	#[pallet::storage]
+	pub type DeferredNotifications<T: Config> = StorageValue<_, Vec<Instruction<()>>, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The opening of a channel to `recipient` was requested.
+		OpenChannelRequested { recipient: ParaId, max_capacity: u32, max_message_size: u32 },
+		/// `sender` requested to open a channel to the parachain.
+		OpenRequestReceived { sender: ParaId, max_capacity: u32, max_message_size: u32 },
+		/// The channel from `sender` was accepted.
+		ChannelAccepted { sender: ParaId },
+		/// `recipient` accepted the channel requested by the parachain.
+		OpenChannelAccepted { recipient: ParaId },
+		/// The closing of a channel was requested by the parachain.
+		CloseChannelRequested { sender: ParaId, recipient: ParaId },
+		/// A channel is closing at the next session.
+		ChannelClosing { sender: ParaId, recipient: ParaId },
+		/// A channel was opened.
+		ChannelOpened { sender: ParaId, recipient: ParaId },
+		/// A channel was closed.
+		ChannelClosed { sender: ParaId, recipient: ParaId },
+		/// The pending request of the channel of `direction` with `sibling` was forgotten.
+		RequestForgotten { direction: ChannelDirection, sibling: ParaId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A channel can't be opened to the parachain itself.
+		SelfChannel,
+		/// The channel is already known.
+		ChannelExists,
+		/// The parachain is neither the sender nor the recipient of the channel.
+		NotParticipant,
+		/// The request couldn't be sent to the relay chain.
+		SendFailure,
+		/// There is no pending request for the channel.
+		NoPendingRequest,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Request to open a channel from the parachain to `recipient`.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::open_channel())]
+		pub fn open_channel(
+			origin: OriginFor<T>,
+			recipient: ParaId,
+			max_capacity: u32,
+			max_message_size: u32,
+		) -> DispatchResult {
+			T::ChannelManagerOrigin::ensure_origin(origin)?;
+			ensure!(recipient != T::SelfParaId::get(), Error::<T>::SelfChannel);
+			ensure!(
+				!Channels::<T>::contains_key(ChannelDirection::Outbound, recipient),
+				Error::<T>::ChannelExists
+			);
+
+			Self::send_to_relay(HrmpCall::InitOpenChannel {
+				recipient,
+				max_capacity,
+				max_message_size,
+			})?;
+			Channels::<T>::insert(ChannelDirection::Outbound, recipient, ChannelStatus::Requested);
+			Self::deposit_event(Event::OpenChannelRequested {
+				recipient,
+				max_capacity,
+				max_message_size,
+			});
+			Ok(())
+		}
+
+		/// Accept the request of `sender` to open a channel to the parachain.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::accept_channel())]
+		pub fn accept_channel(origin: OriginFor<T>, sender: ParaId) -> DispatchResult {
+			T::ChannelManagerOrigin::ensure_origin(origin)?;
+			Self::do_accept_channel(sender)
+		}
+
+		/// Request to close the channel from `sender` to `recipient`, one of them being the
+		/// parachain.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::close_channel())]
+		pub fn close_channel(
+			origin: OriginFor<T>,
+			sender: ParaId,
+			recipient: ParaId,
+		) -> DispatchResult {
+			T::ChannelManagerOrigin::ensure_origin(origin)?;
+			let (direction, sibling) =
+				Self::direction_of(sender, recipient).ok_or(Error::<T>::NotParticipant)?;
+
+			Self::send_to_relay(HrmpCall::CloseChannel {
+				channel_id: HrmpChannelId { sender, recipient },
+			})?;
+			Channels::<T>::mutate(direction, sibling, |status| {
+				if status.is_some() {
+					*status = Some(ChannelStatus::Closing);
+				}
+			});
+			Self::deposit_event(Event::CloseChannelRequested { sender, recipient });
+			Ok(())
+		}
+
+		/// Forget the pending request of the channel of `direction` with `sibling`, e.g. one
+		/// the relay chain dropped or rejected, so that it may be made again.
+		///
+		/// The requests of the parachain waiting for the sibling to accept them, the channels
+		/// accepted by the parachain but not opened yet and the requests of the sibling waiting
+		/// to be accepted are pending.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::forget_request())]
+		pub fn forget_request(
+			origin: OriginFor<T>,
+			direction: ChannelDirection,
+			sibling: ParaId,
+		) -> DispatchResult {
+			T::ChannelManagerOrigin::ensure_origin(origin)?;
+			let pending = matches!(
+				Channels::<T>::get(direction, sibling),
+				Some(ChannelStatus::Requested | ChannelStatus::Accepted)
+			);
+			let requested =
+				direction == ChannelDirection::Inbound && OpenRequests::<T>::contains_key(sibling);
+			ensure!(pending || requested, Error::<T>::NoPendingRequest);
+
+			if pending {
+				Channels::<T>::remove(direction, sibling);
+			}
+			if requested {
+				OpenRequests::<T>::remove(sibling);
+			}
+			Self::deposit_event(Event::RequestForgotten { direction, sibling });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Returns the direction of the channel from `sender` to `recipient` and the sibling at
+		/// its other end, if the parachain is one of them.
+		fn direction_of(sender: ParaId, recipient: ParaId) -> Option<(ChannelDirection, ParaId)> {
+			let self_id = T::SelfParaId::get();
+			if sender == self_id && recipient != self_id {
+				Some((ChannelDirection::Outbound, recipient))
+			} else if recipient == self_id && sender != self_id {
+				Some((ChannelDirection::Inbound, sender))
+			} else {
+				None
+			}
+		}
+
+		/// Returns the program executing `call` on the relay chain.
+		fn relay_program(call: HrmpCall) -> Xcm<()> {
+			let transact = Transact {
+				origin_kind: OriginKind::Native,
+				require_weight_at_most: T::RelayCallWeight::get(),
+				call: (T::RelayHrmpPalletIndex::get(), call).encode().into(),
+			};
+			match T::RelayFee::get() {
+				Some(fee) => Xcm(vec![
+					WithdrawAsset(fee.clone().into()),
+					BuyExecution { fees: fee, weight_limit: Unlimited },
+					transact,
+					RefundSurplus,
+					DepositAsset {
+						assets: Wild(AllCounted(1)),
+						beneficiary: Parachain(T::SelfParaId::get().into()).into(),
+					},
+				]),
+				None => Xcm(vec![
+					UnpaidExecution { weight_limit: Unlimited, check_origin: None },
+					transact,
+				]),
+			}
+		}
+
+		fn send_to_relay(call: HrmpCall) -> DispatchResult {
+			send_xcm::<T::XcmSender>(Parent.into(), Self::relay_program(call))
+				.map_err(|_| Error::<T>::SendFailure)?;
+			Ok(())
+		}
+
+		fn do_accept_channel(sender: ParaId) -> DispatchResult {
+			Self::send_to_relay(HrmpCall::AcceptOpenChannel { sender })?;
+			OpenRequests::<T>::remove(sender);
+			Channels::<T>::insert(ChannelDirection::Inbound, sender, ChannelStatus::Accepted);
+			Self::deposit_event(Event::ChannelAccepted { sender });
+			Ok(())
+		}
+
+		/// Returns the HRMP notification of the relay chain making up `data`, if any.
+		fn notification_of(data: &[u8]) -> Option<Instruction<()>> {
+			let message = VersionedXcm::<()>::decode(&mut &data[..]).ok()?;
+			match Xcm::<()>::try_from(message).ok()?.0.as_slice() {
+				[notification @ (HrmpNewChannelOpenRequest { .. } |
+				HrmpChannelAccepted { .. } |
+				HrmpChannelClosing { .. })] => Some(notification.clone()),
+				_ => None,
+			}
+		}
+
+		/// Processes a notification of the relay chain, returning the weight used.
+		pub(crate) fn handle_notification(notification: Instruction<()>) -> Weight {
+			match notification {
+				HrmpNewChannelOpenRequest { sender, max_message_size, max_capacity } => {
+					let sender = ParaId::from(sender);
+					if T::AutoAccept::contains(&sender) {
+						match Self::do_accept_channel(sender) {
+							Ok(()) => return T::WeightInfo::accept_channel(),
+							Err(e) => log::warn!(
+								target: "xcm::hrmp-channels",
+								"Failed to accept the channel from {:?}: {:?}",
+								sender,
+								e,
+							),
+						}
+					}
+					OpenRequests::<T>::insert(sender, (max_capacity, max_message_size));
+					Self::deposit_event(Event::OpenRequestReceived {
+						sender,
+						max_capacity,
+						max_message_size,
+					});
+				},
+				HrmpChannelAccepted { recipient } => {
+					let recipient = ParaId::from(recipient);
+					Channels::<T>::mutate(ChannelDirection::Outbound, recipient, |status| {
+						if *status == Some(ChannelStatus::Requested) {
+							*status = Some(ChannelStatus::Accepted);
+						}
+					});
+					Self::deposit_event(Event::OpenChannelAccepted { recipient });
+				},
+				HrmpChannelClosing { sender, recipient, .. } => {
+					let (sender, recipient) = (ParaId::from(sender), ParaId::from(recipient));
+					if let Some((direction, sibling)) = Self::direction_of(sender, recipient) {
+						Channels::<T>::mutate(direction, sibling, |status| {
+							if status.is_some() {
+								*status = Some(ChannelStatus::Closing);
+							}
+						});
+					}
+					Self::deposit_event(Event::ChannelClosing { sender, recipient });
+				},
+				_ => {},
+			}
+			T::WeightInfo::handle_notification()
+		}
+
+		/// Updates the channels of `direction` to the `open` ones, sorted by para id, returning
+		/// the number of channels visited.
+		pub(crate) fn sync_channels(direction: ChannelDirection, open: &[ParaId]) -> u32 {
+			let channel = |sibling| match direction {
+				ChannelDirection::Inbound => (sibling, T::SelfParaId::get()),
+				ChannelDirection::Outbound => (T::SelfParaId::get(), sibling),
+			};
+
+			let known = Channels::<T>::iter_prefix(direction).collect::<Vec<_>>();
+			let visited = known.len().saturating_add(open.len()) as u32;
+			for (sibling, status) in known {
+				let (sender, recipient) = channel(sibling);
+				match (status, open.binary_search(&sibling).is_ok()) {
+					(ChannelStatus::Requested | ChannelStatus::Accepted, true) => {
+						Channels::<T>::insert(direction, sibling, ChannelStatus::Open);
+						Self::deposit_event(Event::ChannelOpened { sender, recipient });
+					},
+					(ChannelStatus::Open | ChannelStatus::Closing, false) => {
+						Channels::<T>::remove(direction, sibling);
+						Self::deposit_event(Event::ChannelClosed { sender, recipient });
+					},
+					_ => {},
+				}
+			}
+
+			// Channels opened without going through the pallet.
+			for &sibling in open {
+				if !Channels::<T>::contains_key(direction, sibling) {
+					let (sender, recipient) = channel(sibling);
+					Channels::<T>::insert(direction, sibling, ChannelStatus::Open);
+					Self::deposit_event(Event::ChannelOpened { sender, recipient });
+				}
+			}
+			visited
+		}
+	}
+
+	impl<T> OnSystemEvent for Pallet<T>
+	where
+		T: Config + cumulus_pallet_parachain_system::Config,
+	{
+		fn on_validation_data(_data: &PersistedValidationData) {
+			if let Some(state) =
+				cumulus_pallet_parachain_system::Pallet::<T>::relevant_messaging_state()
+			{
+				let ids = |channels: &[(ParaId, AbridgedHrmpChannel)]| {
+					channels.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+				};
+				let visited =
+					Self::sync_channels(ChannelDirection::Inbound, &ids(&state.ingress_channels))
+						.saturating_add(Self::sync_channels(
+							ChannelDirection::Outbound,
+							&ids(&state.egress_channels),
+						));
+				// The hook has no weight of its own, its work is accounted in the block weight.
+				frame_system::Pallet::<T>::register_extra_weight_unchecked(
+					T::WeightInfo::sync_channels(visited),
+					DispatchClass::Mandatory,
+				);
+			}
+		}
+
+		fn on_validation_code_applied() {}
+	}
+
+	/// [`DmpMessageHandler`] processing the HRMP notifications of the relay chain and passing the
+	/// other messages to `Inner`.
+	///
+	/// The notifications which don't fit in the weight limit are kept in
+	/// [`DeferredNotifications`] until the next downward messages.
+	pub struct HandleHrmpNotifications<T, Inner>(PhantomData<(T, Inner)>);
+
+	impl<T: Config, Inner: DmpMessageHandler> DmpMessageHandler for HandleHrmpNotifications<T, Inner> {
+		fn handle_dmp_messages(
+			iter: impl Iterator<Item = (RelayBlockNumber, Vec<u8>)>,
+			limit: Weight,
+		) -> Weight {
+			// Accepting the open requests is the heaviest handling.
+			let max_notification_weight =
+				T::WeightInfo::handle_notification().max(T::WeightInfo::accept_channel());
+			let deferred = DeferredNotifications::<T>::get();
+			let mut used = T::DbWeight::get().reads(1);
+			let mut left = Vec::new();
+			// Once a notification is left, the next ones are too, to keep their order.
+			let mut handle = |notification| {
+				if left.is_empty() && used.saturating_add(max_notification_weight).all_lte(limit) {
+					used.saturating_accrue(Pallet::<T>::handle_notification(notification));
+				} else {
+					left.push(notification);
+				}
+			};
+
+			let had_deferred = !deferred.is_empty();
+			deferred.into_iter().for_each(&mut handle);
+			let others = iter
+				.filter(|(_, data)| match Pallet::<T>::notification_of(data) {
+					Some(notification) => {
+						handle(notification);
+						false
+					},
+					None => true,
+				})
+				.collect::<Vec<_>>();
+
+			if had_deferred || !left.is_empty() {
+				DeferredNotifications::<T>::put(left);
+				used.saturating_accrue(T::DbWeight::get().writes(1));
+			}
+			used.saturating_add(Inner::handle_dmp_messages(
+				others.into_iter(),
+				limit.saturating_sub(used),
+			))
+		}
+	}
+}
@@ -0,0 +1,165 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate as hrmp_channels;
+use cumulus_primitives_core::{
+	relay_chain::BlockNumber as RelayBlockNumber, DmpMessageHandler, ParaId,
+};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU32, ConstU64, ConstU8, Contains, Everything},
+	weights::Weight,
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+use xcm::latest::prelude::*;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		HrmpChannels: hrmp_channels,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+/// Accepts the requests of the sibling `2000`.
+pub struct TrustedSibling;
+
+impl Contains<ParaId> for TrustedSibling {
+	fn contains(id: &ParaId) -> bool {
+		*id == ParaId::from(2000)
+	}
+}
+
+parameter_types! {
+	pub SelfParaId: ParaId = ParaId::from(1000);
+	pub RelayCallWeight: Weight = Weight::from_parts(1_000_000_000, 65_536);
+	pub const RelayFee: Option<MultiAsset> = None;
+}
+
+impl Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type ChannelManagerOrigin = EnsureRoot<u64>;
+	type SelfParaId = SelfParaId;
+	type AutoAccept = TrustedSibling;
+	type XcmSender = TestSender;
+	type RelayHrmpPalletIndex = ConstU8<60>;
+	type RelayCallWeight = RelayCallWeight;
+	type RelayFee = RelayFee;
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
+thread_local! {
+	/// Messages delivered by the sender.
+	static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+	/// Downward messages passed on by `HandleHrmpNotifications`.
+	static PASSED: RefCell<Vec<Vec<u8>>> = RefCell::new(Vec::new());
+}
+
+/// Sender delivering all messages to [`SENT`].
+pub struct TestSender;
+
+impl SendXcm for TestSender {
+	type Ticket = (MultiLocation, Xcm<()>);
+
+	fn validate(
+		dest: &mut Option<MultiLocation>,
+		msg: &mut Option<Xcm<()>>,
+	) -> SendResult<Self::Ticket> {
+		let dest = dest.take().ok_or(SendError::MissingArgument)?;
+		let msg = msg.take().ok_or(SendError::MissingArgument)?;
+		Ok(((dest, msg), MultiAssets::new()))
+	}
+
+	fn deliver(ticket: Self::Ticket) -> Result<XcmHash, SendError> {
+		SENT.with(|sent| sent.borrow_mut().push(ticket));
+		Ok([0; 32])
+	}
+}
+
+/// Handler recording the downward messages to [`PASSED`].
+pub struct TestDmpHandler;
+
+impl DmpMessageHandler for TestDmpHandler {
+	fn handle_dmp_messages(
+		iter: impl Iterator<Item = (RelayBlockNumber, Vec<u8>)>,
+		_limit: Weight,
+	) -> Weight {
+		PASSED.with(|passed| passed.borrow_mut().extend(iter.map(|(_, data)| data)));
+		Weight::zero()
+	}
+}
+
+pub type DmpHandler = HandleHrmpNotifications<Test, TestDmpHandler>;
+
+pub fn sent() -> Vec<(MultiLocation, Xcm<()>)> {
+	SENT.with(|sent| sent.borrow().clone())
+}
+
+pub fn passed() -> Vec<Vec<u8>> {
+	PASSED.with(|passed| passed.borrow().clone())
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	SENT.with(|sent| sent.borrow_mut().clear());
+	PASSED.with(|passed| passed.borrow_mut().clear());
+
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,266 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	mock::*, weights::WeightInfo, ChannelDirection, ChannelStatus, Channels, DeferredNotifications,
+	Error, HrmpCall, HrmpChannelId, OpenRequests,
+};
+use codec::Encode;
+use cumulus_primitives_core::{DmpMessageHandler, ParaId};
+use frame_support::{assert_noop, assert_ok, weights::Weight};
+use sp_runtime::DispatchError;
+use xcm::{latest::prelude::*, VersionedXcm};
+
+fn relay_program(call: HrmpCall) -> Xcm<()> {
+	Xcm(vec![
+		UnpaidExecution { weight_limit: Unlimited, check_origin: None },
+		Transact {
+			origin_kind: OriginKind::Native,
+			require_weight_at_most: Weight::from_parts(1_000_000_000, 65_536),
+			call: (60u8, call).encode().into(),
+		},
+	])
+}
+
+fn downward(instruction: Instruction<()>) -> (u32, Vec<u8>) {
+	(1, VersionedXcm::from(Xcm(vec![instruction])).encode())
+}
+
+#[test]
+fn open_channel_sends_the_request_to_the_relay_chain() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			HrmpChannels::open_channel(RuntimeOrigin::signed(1), 2000.into(), 8, 1024),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			HrmpChannels::open_channel(RuntimeOrigin::root(), 1000.into(), 8, 1024),
+			Error::<Test>::SelfChannel
+		);
+
+		assert_ok!(HrmpChannels::open_channel(RuntimeOrigin::root(), 2000.into(), 8, 1024));
+		assert_eq!(
+			sent(),
+			vec![(
+				Parent.into(),
+				relay_program(HrmpCall::InitOpenChannel {
+					recipient: 2000.into(),
+					max_capacity: 8,
+					max_message_size: 1024
+				})
+			)]
+		);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Outbound, ParaId::from(2000)),
+			Some(ChannelStatus::Requested)
+		);
+		assert_noop!(
+			HrmpChannels::open_channel(RuntimeOrigin::root(), 2000.into(), 8, 1024),
+			Error::<Test>::ChannelExists
+		);
+	});
+}
+
+#[test]
+fn close_channel_requires_the_parachain_in_the_channel() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			HrmpChannels::close_channel(RuntimeOrigin::root(), 2000.into(), 2001.into()),
+			Error::<Test>::NotParticipant
+		);
+
+		Channels::<Test>::insert(
+			ChannelDirection::Inbound,
+			ParaId::from(2000),
+			ChannelStatus::Open,
+		);
+		assert_ok!(HrmpChannels::close_channel(RuntimeOrigin::root(), 2000.into(), 1000.into()));
+		assert_eq!(
+			sent(),
+			vec![(
+				Parent.into(),
+				relay_program(HrmpCall::CloseChannel {
+					channel_id: HrmpChannelId { sender: 2000.into(), recipient: 1000.into() }
+				})
+			)]
+		);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Inbound, ParaId::from(2000)),
+			Some(ChannelStatus::Closing)
+		);
+	});
+}
+
+#[test]
+fn open_requests_of_allowed_siblings_are_accepted() {
+	new_test_ext().execute_with(|| {
+		let request =
+			|sender| HrmpNewChannelOpenRequest { sender, max_message_size: 1024, max_capacity: 8 };
+		let other = downward(Instruction::<()>::ClearOrigin);
+		DmpHandler::handle_dmp_messages(
+			vec![downward(request(2000)), other.clone(), downward(request(2001))].into_iter(),
+			Weight::MAX,
+		);
+
+		assert_eq!(passed(), vec![other.1]);
+		assert_eq!(
+			sent(),
+			vec![(
+				Parent.into(),
+				relay_program(HrmpCall::AcceptOpenChannel { sender: 2000.into() })
+			)]
+		);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Inbound, ParaId::from(2000)),
+			Some(ChannelStatus::Accepted)
+		);
+		assert_eq!(OpenRequests::<Test>::get(ParaId::from(2001)), Some((8, 1024)));
+
+		assert_ok!(HrmpChannels::accept_channel(RuntimeOrigin::root(), 2001.into()));
+		assert_eq!(OpenRequests::<Test>::get(ParaId::from(2001)), None);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Inbound, ParaId::from(2001)),
+			Some(ChannelStatus::Accepted)
+		);
+	});
+}
+
+#[test]
+fn notifications_beyond_the_limit_are_deferred() {
+	new_test_ext().execute_with(|| {
+		let request =
+			|sender| HrmpNewChannelOpenRequest { sender, max_message_size: 1024, max_capacity: 8 };
+		let other = downward(Instruction::<()>::ClearOrigin);
+		let limit = <() as WeightInfo>::accept_channel();
+		let used = DmpHandler::handle_dmp_messages(
+			vec![downward(request(2001)), other.clone(), downward(request(2002))].into_iter(),
+			limit,
+		);
+
+		assert!(used.all_lte(limit));
+		assert_eq!(passed(), vec![other.1]);
+		assert_eq!(OpenRequests::<Test>::get(ParaId::from(2001)), Some((8, 1024)));
+		assert_eq!(OpenRequests::<Test>::get(ParaId::from(2002)), None);
+		assert_eq!(DeferredNotifications::<Test>::get(), vec![request(2002)]);
+
+		// The deferred notifications are handled first by the next downward messages.
+		DmpHandler::handle_dmp_messages(vec![downward(request(2003))].into_iter(), Weight::MAX);
+		assert_eq!(OpenRequests::<Test>::get(ParaId::from(2002)), Some((8, 1024)));
+		assert_eq!(OpenRequests::<Test>::get(ParaId::from(2003)), Some((8, 1024)));
+		assert!(DeferredNotifications::<Test>::get().is_empty());
+	});
+}
+
+#[test]
+fn channels_follow_the_messaging_state() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(HrmpChannels::open_channel(RuntimeOrigin::root(), 2000.into(), 8, 1024));
+		DmpHandler::handle_dmp_messages(
+			vec![downward(HrmpChannelAccepted { recipient: 2000 })].into_iter(),
+			Weight::MAX,
+		);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Outbound, ParaId::from(2000)),
+			Some(ChannelStatus::Accepted)
+		);
+
+		assert_eq!(
+			HrmpChannels::sync_channels(ChannelDirection::Outbound, &[2000.into(), 2001.into()]),
+			3
+		);
+		assert_eq!(
+			Channels::<Test>::iter_prefix(ChannelDirection::Outbound)
+				.collect::<Vec<_>>()
+				.len(),
+			2
+		);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Outbound, ParaId::from(2000)),
+			Some(ChannelStatus::Open)
+		);
+
+		DmpHandler::handle_dmp_messages(
+			vec![downward(HrmpChannelClosing { initiator: 2000, sender: 1000, recipient: 2000 })]
+				.into_iter(),
+			Weight::MAX,
+		);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Outbound, ParaId::from(2000)),
+			Some(ChannelStatus::Closing)
+		);
+
+		assert_eq!(HrmpChannels::sync_channels(ChannelDirection::Outbound, &[2001.into()]), 3);
+		assert_eq!(Channels::<Test>::get(ChannelDirection::Outbound, ParaId::from(2000)), None);
+		assert_eq!(
+			Channels::<Test>::get(ChannelDirection::Outbound, ParaId::from(2001)),
+			Some(ChannelStatus::Open)
+		);
+	});
+}
+
+#[test]
+fn pending_requests_can_be_forgotten() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(HrmpChannels::open_channel(RuntimeOrigin::root(), 2001.into(), 8, 1024));
+		OpenRequests::<Test>::insert(ParaId::from(2002), (8, 1024));
+		Channels::<Test>::insert(
+			ChannelDirection::Inbound,
+			ParaId::from(2003),
+			ChannelStatus::Open,
+		);
+
+		assert_noop!(
+			HrmpChannels::forget_request(
+				RuntimeOrigin::signed(1),
+				ChannelDirection::Outbound,
+				2001.into()
+			),
+			DispatchError::BadOrigin
+		);
+		// Open channels are closed instead.
+		assert_noop!(
+			HrmpChannels::forget_request(
+				RuntimeOrigin::root(),
+				ChannelDirection::Inbound,
+				2003.into()
+			),
+			Error::<Test>::NoPendingRequest
+		);
+		assert_noop!(
+			HrmpChannels::forget_request(
+				RuntimeOrigin::root(),
+				ChannelDirection::Outbound,
+				2002.into()
+			),
+			Error::<Test>::NoPendingRequest
+		);
+
+		// The request the relay chain dropped may be made again.
+		assert_ok!(HrmpChannels::forget_request(
+			RuntimeOrigin::root(),
+			ChannelDirection::Outbound,
+			2001.into()
+		));
+		assert_eq!(Channels::<Test>::get(ChannelDirection::Outbound, ParaId::from(2001)), None);
+		assert_ok!(HrmpChannels::open_channel(RuntimeOrigin::root(), 2001.into(), 8, 1024));
+
+		assert_ok!(HrmpChannels::forget_request(
+			RuntimeOrigin::root(),
+			ChannelDirection::Inbound,
+			2002.into()
+		));
+		assert_eq!(OpenRequests::<Test>::get(ParaId::from(2002)), None);
+	});
+}
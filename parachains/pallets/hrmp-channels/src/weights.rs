@@ -0,0 +1,136 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weights of `pallet_hrmp_channels`.
+//!
+//! Not benchmarked yet: the storage accesses are the ones of the calls with the requests sent
+//! upward through `pallet_xcm`, with a flat execution time per call and per visited channel.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+// The weight info trait for `pallet_hrmp_channels`.
+pub trait WeightInfo {
+	fn open_channel() -> Weight;
+	fn accept_channel() -> Weight;
+	fn close_channel() -> Weight;
+	fn forget_request() -> Weight;
+	fn handle_notification() -> Weight;
+	fn sync_channels(c: u32) -> Weight;
+}
+
+/// Weights for pallet_hrmp_channels using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: HrmpChannels Channels (r:1 w:1)
+	// Storage: InfrablockspaceXcm SupportedVersion (r:1 w:0)
+	// Storage: InfrablockspaceXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: InfrablockspaceXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	fn open_channel() -> Weight {
+		Weight::from_parts(32_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: InfrablockspaceXcm SupportedVersion (r:1 w:0)
+	// Storage: InfrablockspaceXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: InfrablockspaceXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	// Storage: HrmpChannels OpenRequests (r:0 w:1)
+	// Storage: HrmpChannels Channels (r:0 w:1)
+	fn accept_channel() -> Weight {
+		Weight::from_parts(30_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+	}
+	// Storage: InfrablockspaceXcm SupportedVersion (r:1 w:0)
+	// Storage: InfrablockspaceXcm VersionDiscoveryQueue (r:1 w:1)
+	// Storage: InfrablockspaceXcm SafeXcmVersion (r:1 w:0)
+	// Storage: ParachainSystem HostConfiguration (r:1 w:0)
+	// Storage: ParachainSystem PendingUpwardMessages (r:1 w:1)
+	// Storage: HrmpChannels Channels (r:1 w:1)
+	fn close_channel() -> Weight {
+		Weight::from_parts(30_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: HrmpChannels Channels (r:1 w:1)
+	// Storage: HrmpChannels OpenRequests (r:1 w:1)
+	fn forget_request() -> Weight {
+		Weight::from_parts(14_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Storage: HrmpChannels Channels (r:1 w:1)
+	fn handle_notification() -> Weight {
+		Weight::from_parts(12_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: ParachainSystem RelevantMessagingState (r:1 w:0)
+	// Storage: HrmpChannels Channels (r:1 w:1)
+	/// The range of component `c` is `[0, 100]`.
+	fn sync_channels(c: u32) -> Weight {
+		Weight::from_parts(4_000_000 as u64, 0)
+			.saturating_add(Weight::from_parts(6_000_000 as u64, 0).saturating_mul(c as u64))
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(c as u64)))
+			.saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(c as u64)))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn open_channel() -> Weight {
+		Weight::from_parts(32_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	fn accept_channel() -> Weight {
+		Weight::from_parts(30_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+	}
+	fn close_channel() -> Weight {
+		Weight::from_parts(30_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	fn forget_request() -> Weight {
+		Weight::from_parts(14_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	fn handle_notification() -> Weight {
+		Weight::from_parts(12_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	fn sync_channels(c: u32) -> Weight {
+		Weight::from_parts(4_000_000 as u64, 0)
+			.saturating_add(Weight::from_parts(6_000_000 as u64, 0).saturating_mul(c as u64))
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(c as u64)))
+			.saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(c as u64)))
+	}
+}
@@ -0,0 +1,151 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trapped Assets Pallet
+//!
+//! Index of the assets trapped by the XCM executor, by origin.
+//!
+//! ## Overview
+//!
+//! The XCM executor traps the assets left in the holding register under a hash of their origin and
+//! assets, which can't be listed by origin. [`IndexTrappedAssets`] wraps the asset trap and claims
+//! of the runtime, e.g. `pallet_xcm`, to also record the trapped assets by origin, so that they can
+//! be found and claimed back with the program built by [`Pallet::claim_program`].
+//!
+//! The assets trapped before the pallet was added are recorded by the
+//! [`migration::IndexPreviousTraps`] migration.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub mod migration;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use sp_core::H256;
+	use sp_runtime::traits::{BlakeTwo256, Hash};
+	use sp_std::{marker::PhantomData, prelude::*};
+	use xcm::{latest::prelude::*, VersionedMultiAssets, VersionedMultiLocation};
+	use xcm_executor::{
+		traits::{ClaimAssets, DropAssets},
+		Assets,
+	};
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(crate::migration::STORAGE_VERSION)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {}
+
+	/// Assets trapped for each origin, with the number of times they were trapped, by the hash
+	/// of their ticket.
+	#[pallet::storage]
+	pub type Trapped<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		VersionedMultiLocation,
+		Identity,
+		H256,
+		(VersionedMultiAssets, u32),
+	>;
+
+	/// [`DropAssets`] and [`ClaimAssets`] of `Inner`, recording the trapped assets in [`Trapped`].
+	///
+	/// `Inner` must trap the assets under the hash of their origin and of the assets converted to
+	/// the latest version, as `pallet_xcm` does.
+	pub struct IndexTrappedAssets<T, Inner>(PhantomData<(T, Inner)>);
+
+	impl<T: Config, Inner: DropAssets> DropAssets for IndexTrappedAssets<T, Inner> {
+		fn drop_assets(origin: &MultiLocation, assets: Assets, context: &XcmContext) -> Weight {
+			if assets.is_empty() {
+				return Weight::zero()
+			}
+			let versioned = VersionedMultiAssets::from(MultiAssets::from(assets.clone()));
+			let hash = BlakeTwo256::hash_of(&(origin, &versioned));
+			Trapped::<T>::mutate(VersionedMultiLocation::from(*origin), hash, |trapped| {
+				let count = trapped.as_ref().map_or(0, |(_, count)| *count);
+				*trapped = Some((versioned, count.saturating_add(1)));
+			});
+
+			Inner::drop_assets(origin, assets, context)
+				.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+		}
+	}
+
+	impl<T: Config, Inner: ClaimAssets> ClaimAssets for IndexTrappedAssets<T, Inner> {
+		fn claim_assets(
+			origin: &MultiLocation,
+			ticket: &MultiLocation,
+			what: &MultiAssets,
+			context: &XcmContext,
+		) -> bool {
+			if !Inner::claim_assets(origin, ticket, what, context) {
+				return false
+			}
+			// Claims with a ticket of another version were trapped before the last upgrade of XCM
+			// and aren't recorded.
+			if ticket == &MultiLocation::here() {
+				let hash =
+					BlakeTwo256::hash_of(&(origin, VersionedMultiAssets::from(what.clone())));
+				Trapped::<T>::mutate_exists(
+					VersionedMultiLocation::from(*origin),
+					hash,
+					|trapped| {
+						let left = trapped.as_mut().map(|(_, count)| {
+							*count = count.saturating_sub(1);
+							*count
+						});
+						if left == Some(0) {
+							*trapped = None;
+						}
+					},
+				);
+			}
+			true
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Returns the assets trapped for `origin`, with the number of times they were trapped.
+		pub fn trapped_assets(origin: &MultiLocation) -> Vec<(MultiAssets, u32)> {
+			Trapped::<T>::iter_prefix_values(VersionedMultiLocation::from(*origin))
+				.filter_map(|(assets, count)| Some((MultiAssets::try_from(assets).ok()?, count)))
+				.collect()
+		}
+
+		/// Returns the program claiming `assets` trapped for its origin, paying for its execution
+		/// with the first of them and depositing them all to `beneficiary`.
+		pub fn claim_program(assets: MultiAssets, beneficiary: MultiLocation) -> Xcm<()> {
+			let mut program = vec![ClaimAsset { assets: assets.clone(), ticket: Here.into() }];
+			if let Some(fees) = assets.get(0) {
+				program.push(BuyExecution { fees: fees.clone(), weight_limit: Unlimited });
+			}
+			program
+				.push(DepositAsset { assets: Wild(AllCounted(assets.len() as u32)), beneficiary });
+			Xcm(program)
+		}
+	}
+}
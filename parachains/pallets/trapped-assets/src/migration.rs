@@ -0,0 +1,96 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migration indexing the assets trapped before the pallet was added.
+
+use crate::{Config, Pallet, Trapped};
+use frame_support::{
+	log,
+	pallet_prelude::*,
+	storage::{storage_prefix, unhashed},
+	traits::{OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+};
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use sp_std::{marker::PhantomData, prelude::*};
+use xcm::{latest::MultiLocation, VersionedMultiAssets, VersionedMultiLocation};
+
+/// The current storage version.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+/// Records in [`Trapped`] the assets trapped before [`crate::IndexTrappedAssets`] was set up.
+///
+/// The asset trap only stores the hashes of the trapped assets, which can't be reversed. The
+/// origins and assets of the earlier traps, e.g. collected from the `AssetsTrapped` events of
+/// `pallet_xcm`, are given by `Traps`, and those still trapped in the `AssetTraps` of `XcmPallet`,
+/// the `pallet_xcm` instance of the runtime, are recorded.
+///
+/// Nothing is done while `Traps` is empty, so that the migration runs once the traps of the chain
+/// are known.
+pub struct IndexPreviousTraps<T, Traps, XcmPallet>(PhantomData<(T, Traps, XcmPallet)>);
+
+impl<T, Traps, XcmPallet> OnRuntimeUpgrade for IndexPreviousTraps<T, Traps, XcmPallet>
+where
+	T: Config,
+	Traps: Get<Vec<(MultiLocation, VersionedMultiAssets)>>,
+	XcmPallet: PalletInfoAccess,
+{
+	fn on_runtime_upgrade() -> Weight {
+		if StorageVersion::get::<Pallet<T>>() >= STORAGE_VERSION {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let traps = Traps::get();
+		if traps.is_empty() {
+			log::warn!(
+				target: "runtime::trapped-assets",
+				"No assets trapped before the upgrade are given, not indexing them",
+			);
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut indexed = 0u64;
+		for (origin, assets) in traps.iter() {
+			let hash = BlakeTwo256::hash_of(&(origin, assets));
+			let count = asset_traps::<XcmPallet>(hash);
+			if count > 0 {
+				Trapped::<T>::insert(
+					VersionedMultiLocation::from(*origin),
+					hash,
+					(assets.clone(), count),
+				);
+				indexed += 1;
+			}
+		}
+		STORAGE_VERSION.put::<Pallet<T>>();
+		log::info!(
+			target: "runtime::trapped-assets",
+			"Indexed {} of {} assets trapped before the upgrade",
+			indexed,
+			traps.len(),
+		);
+
+		T::DbWeight::get().reads_writes(traps.len() as u64 + 1, indexed + 1)
+	}
+}
+
+/// The number of times the assets of `hash` were trapped, in the `AssetTraps` of the `pallet_xcm`
+/// instance `XcmPallet`.
+fn asset_traps<XcmPallet: PalletInfoAccess>(hash: H256) -> u32 {
+	// `AssetTraps` is hashed with `Identity`.
+	let key = [&storage_prefix(XcmPallet::name().as_bytes(), b"AssetTraps")[..], hash.as_bytes()]
+		.concat();
+	unhashed::get_or_default(&key)
+}
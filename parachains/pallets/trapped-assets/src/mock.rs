@@ -0,0 +1,145 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate as trapped_assets;
+use frame_support::{
+	pallet_prelude::ValueQuery,
+	parameter_types,
+	traits::{ConstU32, ConstU64, CrateVersion, Everything, PalletInfoAccess},
+	Identity,
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, Hash, IdentityLookup},
+};
+use xcm::{latest::prelude::*, VersionedMultiAssets};
+use xcm_executor::{
+	traits::{ClaimAssets, DropAssets},
+	Assets,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		TrappedAssets: trapped_assets,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl Config for Test {}
+
+/// Number of times the assets were trapped, by the hash of their ticket, like
+/// `pallet_xcm::AssetTraps`.
+#[frame_support::storage_alias]
+pub type AssetTraps = StorageMap<XcmPallet, Identity, H256, u32, ValueQuery>;
+
+parameter_types! {
+	/// Assets trapped before the upgrade indexing them.
+	pub static PreviousTraps: Vec<(MultiLocation, VersionedMultiAssets)> = Vec::new();
+}
+
+/// Asset trap hashing the trapped assets like `pallet_xcm`.
+pub struct XcmPallet;
+
+impl PalletInfoAccess for XcmPallet {
+	fn index() -> usize {
+		99
+	}
+
+	fn name() -> &'static str {
+		"XcmPallet"
+	}
+
+	fn module_name() -> &'static str {
+		"pallet_xcm"
+	}
+
+	fn crate_version() -> CrateVersion {
+		CrateVersion::new(0, 9, 37)
+	}
+}
+
+impl DropAssets for XcmPallet {
+	fn drop_assets(origin: &MultiLocation, assets: Assets, _context: &XcmContext) -> Weight {
+		let versioned = VersionedMultiAssets::from(MultiAssets::from(assets));
+		let hash = BlakeTwo256::hash_of(&(&origin, &versioned));
+		AssetTraps::mutate(hash, |count| *count += 1);
+		Weight::zero()
+	}
+}
+
+impl ClaimAssets for XcmPallet {
+	fn claim_assets(
+		origin: &MultiLocation,
+		ticket: &MultiLocation,
+		what: &MultiAssets,
+		_context: &XcmContext,
+	) -> bool {
+		if ticket != &MultiLocation::here() {
+			return false
+		}
+		let hash = BlakeTwo256::hash_of(&(origin, VersionedMultiAssets::from(what.clone())));
+		AssetTraps::mutate(hash, |count| match *count {
+			0 => false,
+			_ => {
+				*count -= 1;
+				true
+			},
+		})
+	}
+}
+
+pub type AssetTrap = IndexTrappedAssets<Test, XcmPallet>;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	sp_io::TestExternalities::new(t)
+}
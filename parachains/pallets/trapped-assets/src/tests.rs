@@ -0,0 +1,117 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{migration::IndexPreviousTraps, mock::*, Trapped};
+use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+use xcm::{latest::prelude::*, VersionedMultiAssets};
+use xcm_executor::traits::{ClaimAssets, DropAssets};
+
+fn context() -> XcmContext {
+	XcmContext { origin: None, message_hash: [0; 32], topic: None }
+}
+
+fn sibling(id: u32) -> MultiLocation {
+	(Parent, Parachain(id)).into()
+}
+
+#[test]
+fn trapped_assets_are_indexed_by_origin() {
+	new_test_ext().execute_with(|| {
+		let assets: MultiAssets = vec![(Here, 100).into(), (GeneralIndex(1), 10).into()].into();
+
+		AssetTrap::drop_assets(&sibling(2000), assets.clone().into(), &context());
+		AssetTrap::drop_assets(&sibling(2000), assets.clone().into(), &context());
+		AssetTrap::drop_assets(
+			&sibling(2001),
+			MultiAssets::from(vec![(Here, 5).into()]).into(),
+			&context(),
+		);
+		// Nothing is trapped without assets.
+		AssetTrap::drop_assets(&sibling(2002), MultiAssets::new().into(), &context());
+
+		assert_eq!(TrappedAssets::trapped_assets(&sibling(2000)), vec![(assets.clone(), 2)]);
+		assert_eq!(TrappedAssets::trapped_assets(&sibling(2001)).len(), 1);
+		assert!(TrappedAssets::trapped_assets(&sibling(2002)).is_empty());
+
+		// The record is removed with the last claim.
+		let origin = sibling(2000);
+		assert!(AssetTrap::claim_assets(&origin, &Here.into(), &assets, &context()));
+		assert_eq!(TrappedAssets::trapped_assets(&origin), vec![(assets.clone(), 1)]);
+		assert!(AssetTrap::claim_assets(&origin, &Here.into(), &assets, &context()));
+		assert!(TrappedAssets::trapped_assets(&origin).is_empty());
+		assert_eq!(
+			Trapped::<Test>::iter_prefix(xcm::VersionedMultiLocation::from(origin)).count(),
+			0
+		);
+
+		// Failed claims leave the records untouched.
+		assert!(!AssetTrap::claim_assets(&origin, &Here.into(), &assets, &context()));
+		assert!(!AssetTrap::claim_assets(
+			&sibling(2001),
+			&Here.into(),
+			&MultiAssets::from(vec![(Here, 4).into()]),
+			&context()
+		));
+		assert_eq!(TrappedAssets::trapped_assets(&sibling(2001)).len(), 1);
+	});
+}
+
+#[test]
+fn claim_program_pays_with_the_first_asset() {
+	let assets: MultiAssets = vec![(Here, 100).into(), (GeneralIndex(1), 10).into()].into();
+	let beneficiary: MultiLocation = AccountId32 { network: None, id: [1; 32] }.into();
+
+	assert_eq!(
+		TrappedAssets::claim_program(assets.clone(), beneficiary),
+		Xcm(vec![
+			ClaimAsset { assets: assets.clone(), ticket: Here.into() },
+			BuyExecution { fees: (Here, 100).into(), weight_limit: Unlimited },
+			DepositAsset { assets: Wild(AllCounted(2)), beneficiary },
+		])
+	);
+}
+
+#[test]
+fn migration_indexes_the_previous_traps() {
+	new_test_ext().execute_with(|| {
+		let assets: MultiAssets = vec![(Here, 100).into()].into();
+		let versioned = VersionedMultiAssets::from(assets.clone());
+
+		// Trapped twice before the upgrade, without being indexed.
+		XcmPallet::drop_assets(&sibling(2000), assets.clone().into(), &context());
+		XcmPallet::drop_assets(&sibling(2000), assets.clone().into(), &context());
+		assert!(TrappedAssets::trapped_assets(&sibling(2000)).is_empty());
+
+		// Nothing is done until the previous traps are given.
+		IndexPreviousTraps::<Test, PreviousTraps, XcmPallet>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<TrappedAssets>(), 0);
+
+		// Only the given traps still trapped are indexed.
+		PreviousTraps::set(vec![(sibling(2000), versioned.clone()), (sibling(2001), versioned)]);
+		IndexPreviousTraps::<Test, PreviousTraps, XcmPallet>::on_runtime_upgrade();
+		assert_eq!(TrappedAssets::trapped_assets(&sibling(2000)), vec![(assets.clone(), 2)]);
+		assert!(TrappedAssets::trapped_assets(&sibling(2001)).is_empty());
+		assert_eq!(StorageVersion::get::<TrappedAssets>(), 1);
+
+		// The indexed traps are claimed like the new ones.
+		assert!(AssetTrap::claim_assets(&sibling(2000), &Here.into(), &assets, &context()));
+		assert_eq!(TrappedAssets::trapped_assets(&sibling(2000)), vec![(assets.clone(), 1)]);
+
+		// The migration only runs once.
+		XcmPallet::drop_assets(&sibling(2000), assets.clone().into(), &context());
+		IndexPreviousTraps::<Test, PreviousTraps, XcmPallet>::on_runtime_upgrade();
+		assert_eq!(TrappedAssets::trapped_assets(&sibling(2000)), vec![(assets, 1)]);
+	});
+}
@@ -0,0 +1,59 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarking setup for pallet-xcm-rate-limiter
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as XcmRateLimiter;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
+use frame_support::{
+	assert_ok,
+	traits::{Currency, Get},
+};
+use sp_runtime::traits::{Bounded, Zero};
+use xcm::latest::prelude::*;
+
+fn funded_account<T: Config>() -> T::AccountId {
+	let who: T::AccountId = account("sender", 0, 0);
+	T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2u32.into());
+	who
+}
+
+fn largest_message<T: Config>() -> u32 {
+	T::MaxBytesPerAccount::get().min(T::MaxBytesPerDestination::get())
+}
+
+benchmarks! {
+	prune_usage {
+		let who = funded_account::<T>();
+		let destination = MultiLocation::new(1, X1(Parachain(2000)));
+		assert_ok!(XcmRateLimiter::<T>::reserve_deposit(
+			who.clone(),
+			destination,
+			largest_message::<T>()
+		));
+		let window = T::BlockNumber::zero();
+	}: {
+		assert!(XcmRateLimiter::<T>::prune_usage(window));
+	}
+	verify {
+		assert!(!AccountUsage::<T>::contains_key(window, &who));
+		assert!(T::Currency::reserved_balance(&who).is_zero());
+	}
+}
+
+impl_benchmark_test_suite!(XcmRateLimiter, crate::mock::new_test_ext(), crate::mock::Test);
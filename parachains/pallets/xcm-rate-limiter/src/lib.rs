@@ -0,0 +1,325 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! XCM Rate Limiter Pallet
+//!
+//! Rate limits and deposits for the XCM messages sent by the accounts of the chain.
+//!
+//! ## Overview
+//!
+//! [`RateLimitedRouter`] wraps the XCM router of `pallet_xcm`, so that accounts may send their
+//! own programs without flooding the message queues. The messages sent by an account start with
+//! the `DescendOrigin` into it prepended by `pallet_xcm`. For each of them:
+//!
+//! - the account and the destination may only send up to a number of messages and bytes in each
+//!   window of [`Config::Window`] blocks,
+//! - the account reserves [`Config::MessageDeposit`] plus [`Config::ByteDeposit`] for each
+//!   encoded byte, until the usage of the window is pruned in `on_idle` after the window.
+//!
+//! The other messages, e.g. those sent by the XCM executor, are routed as is.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::WeightInfo;
+	use codec::Encode;
+	use cumulus_primitives_core::InspectMessageQueues;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, ReservableCurrency},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{One, Saturating};
+	use sp_std::{marker::PhantomData, prelude::*};
+	use xcm::{latest::prelude::*, VersionedMultiLocation, VersionedXcm};
+	use xcm_executor::traits::Convert;
+
+	pub(crate) type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Messages and bytes sent in a window of blocks.
+	#[derive(
+		Clone, Copy, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen,
+	)]
+	pub struct Usage {
+		/// The number of messages sent in the window.
+		pub messages: u32,
+		/// The number of encoded bytes sent in the window.
+		pub bytes: u32,
+	}
+
+	impl Usage {
+		fn record(&mut self, size: u32) {
+			self.messages.saturating_inc();
+			self.bytes.saturating_accrue(size);
+		}
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Converts the location of a local account into its `AccountId`.
+		type AccountIdConverter: Convert<MultiLocation, Self::AccountId>;
+
+		/// The currency the deposits are reserved in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The deposit reserved for each message.
+		#[pallet::constant]
+		type MessageDeposit: Get<BalanceOf<Self>>;
+
+		/// The deposit reserved for each encoded byte of a message.
+		#[pallet::constant]
+		type ByteDeposit: Get<BalanceOf<Self>>;
+
+		/// The number of blocks of a window.
+		#[pallet::constant]
+		type Window: Get<Self::BlockNumber>;
+
+		/// The maximum number of messages an account may send in a window.
+		#[pallet::constant]
+		type MaxMessagesPerAccount: Get<u32>;
+
+		/// The maximum number of encoded bytes an account may send in a window.
+		#[pallet::constant]
+		type MaxBytesPerAccount: Get<u32>;
+
+		/// The maximum number of messages the accounts may send to a destination in a window.
+		#[pallet::constant]
+		type MaxMessagesPerDestination: Get<u32>;
+
+		/// The maximum number of encoded bytes the accounts may send to a destination in a
+		/// window.
+		#[pallet::constant]
+		type MaxBytesPerDestination: Get<u32>;
+
+		/// Weight information for the messages sent and the pruning of the windows.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// Messages and bytes sent by each account in the windows not pruned yet, with the deposit
+	/// reserved for them.
+	#[pallet::storage]
+	pub type AccountUsage<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Blake2_128Concat,
+		T::AccountId,
+		(Usage, BalanceOf<T>),
+		ValueQuery,
+	>;
+
+	/// Messages and bytes sent by the accounts to each destination in the windows not pruned
+	/// yet.
+	#[pallet::storage]
+	pub type DestinationUsage<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Blake2_128Concat,
+		MultiLocation,
+		Usage,
+		ValueQuery,
+	>;
+
+	/// The oldest window whose usage may not be pruned yet.
+	#[pallet::storage]
+	pub type NextWindowToPrune<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An account reserved `deposit` to send a message to `destination`.
+		DepositReserved {
+			who: T::AccountId,
+			destination: VersionedMultiLocation,
+			deposit: BalanceOf<T>,
+		},
+		/// The deposit of an account for the messages of a past window was released.
+		DepositReleased { who: T::AccountId, deposit: BalanceOf<T> },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(_: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			if !remaining_weight.all_gte(weight) {
+				return Weight::zero()
+			}
+
+			let current = Self::current_window();
+			let mut window = NextWindowToPrune::<T>::get();
+			while window < current {
+				let next = weight.saturating_add(T::WeightInfo::prune_usage());
+				if !remaining_weight.all_gte(next) {
+					break
+				}
+				weight = next;
+				if !Self::prune_usage(window) {
+					window.saturating_inc();
+				}
+			}
+			NextWindowToPrune::<T>::put(window);
+			weight
+		}
+	}
+
+	/// [`SendXcm`] of `Router`, rate limiting the messages sent by accounts and reserving their
+	/// deposits.
+	pub struct RateLimitedRouter<T, Router>(PhantomData<(T, Router)>);
+
+	impl<T: Config, Router: SendXcm> SendXcm for RateLimitedRouter<T, Router> {
+		type Ticket = (Router::Ticket, Option<(T::AccountId, MultiLocation, u32)>);
+
+		fn validate(
+			dest: &mut Option<MultiLocation>,
+			msg: &mut Option<Xcm<()>>,
+		) -> SendResult<Self::Ticket> {
+			let destination = *dest.as_ref().ok_or(SendError::MissingArgument)?;
+			let sender = msg.as_ref().and_then(Pallet::<T>::sender_of);
+			let size = msg.as_ref().map_or(0, |msg| msg.encoded_size() as u32);
+
+			let (ticket, price) = Router::validate(dest, msg)?;
+			let charge = match sender {
+				Some(who) => {
+					Pallet::<T>::ensure_within_limits(&who, &destination, size)?;
+					Some((who, destination, size))
+				},
+				None => None,
+			};
+			Ok(((ticket, charge), price))
+		}
+
+		fn deliver((ticket, charge): Self::Ticket) -> Result<XcmHash, SendError> {
+			if let Some((who, destination, size)) = charge {
+				Pallet::<T>::reserve_deposit(who, destination, size)?;
+			}
+			Router::deliver(ticket)
+		}
+	}
+
+	impl<T, Router: InspectMessageQueues> InspectMessageQueues for RateLimitedRouter<T, Router> {
+		fn clear_messages() {
+			Router::clear_messages()
+		}
+
+		fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+			Router::get_messages()
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Returns the account sending `message`, which starts with the `DescendOrigin` into it.
+		fn sender_of(message: &Xcm<()>) -> Option<T::AccountId> {
+			match message.0.first() {
+				Some(DescendOrigin(interior)) =>
+					T::AccountIdConverter::convert(MultiLocation::new(0, *interior)).ok(),
+				_ => None,
+			}
+		}
+
+		fn current_window() -> T::BlockNumber {
+			frame_system::Pallet::<T>::block_number() / T::Window::get().max(One::one())
+		}
+
+		fn deposit_for(size: u32) -> BalanceOf<T> {
+			T::MessageDeposit::get()
+				.saturating_add(T::ByteDeposit::get().saturating_mul(size.into()))
+		}
+
+		pub(crate) fn ensure_within_limits(
+			who: &T::AccountId,
+			destination: &MultiLocation,
+			size: u32,
+		) -> Result<(), SendError> {
+			let window = Self::current_window();
+			let within = |usage: Usage, max_messages, max_bytes| {
+				usage.messages < max_messages && usage.bytes.saturating_add(size) <= max_bytes
+			};
+			ensure!(
+				within(
+					AccountUsage::<T>::get(window, who).0,
+					T::MaxMessagesPerAccount::get(),
+					T::MaxBytesPerAccount::get()
+				) && within(
+					DestinationUsage::<T>::get(window, destination),
+					T::MaxMessagesPerDestination::get(),
+					T::MaxBytesPerDestination::get()
+				),
+				SendError::Transport("RateLimited")
+			);
+			ensure!(
+				T::Currency::can_reserve(who, Self::deposit_for(size)),
+				SendError::Transport("CannotReserveDeposit")
+			);
+			Ok(())
+		}
+
+		pub(crate) fn reserve_deposit(
+			who: T::AccountId,
+			destination: MultiLocation,
+			size: u32,
+		) -> Result<(), SendError> {
+			let deposit = Self::deposit_for(size);
+			T::Currency::reserve(&who, deposit)
+				.map_err(|_| SendError::Transport("CannotReserveDeposit"))?;
+
+			let window = Self::current_window();
+			AccountUsage::<T>::mutate(window, &who, |(usage, reserved)| {
+				usage.record(size);
+				reserved.saturating_accrue(deposit);
+			});
+			DestinationUsage::<T>::mutate(window, destination, |usage| usage.record(size));
+			Self::deposit_event(Event::DepositReserved {
+				who,
+				destination: destination.into(),
+				deposit,
+			});
+			Ok(())
+		}
+
+		/// Prunes one usage of `window`, releasing the deposit of an account, and returns whether
+		/// there was one.
+		pub(crate) fn prune_usage(window: T::BlockNumber) -> bool {
+			if let Some((who, (_, deposit))) = AccountUsage::<T>::drain_prefix(window).next() {
+				T::Currency::unreserve(&who, deposit);
+				Self::deposit_event(Event::DepositReleased { who, deposit });
+				true
+			} else {
+				DestinationUsage::<T>::drain_prefix(window).next().is_some()
+			}
+		}
+	}
+}
@@ -0,0 +1,161 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate as xcm_rate_limiter;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU32, ConstU64, Everything},
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+use xcm::latest::prelude::*;
+use xcm_executor::traits::Convert;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		XcmRateLimiter: xcm_rate_limiter,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// Converts the location of an `AccountIndex64` into the account.
+pub struct IndexAsAccountId;
+
+impl Convert<MultiLocation, u64> for IndexAsAccountId {
+	fn convert(location: MultiLocation) -> Result<u64, MultiLocation> {
+		match location {
+			MultiLocation { parents: 0, interior: X1(AccountIndex64 { index, .. }) } => Ok(index),
+			_ => Err(location),
+		}
+	}
+}
+
+parameter_types! {
+	pub const Window: u64 = 10;
+}
+
+impl Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type AccountIdConverter = IndexAsAccountId;
+	type Currency = Balances;
+	type MessageDeposit = ConstU64<10>;
+	type ByteDeposit = ConstU64<1>;
+	type Window = Window;
+	type MaxMessagesPerAccount = ConstU32<2>;
+	type MaxBytesPerAccount = ConstU32<1_000>;
+	type MaxMessagesPerDestination = ConstU32<3>;
+	type MaxBytesPerDestination = ConstU32<1_000>;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	/// Messages delivered by the router.
+	static SENT: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
+}
+
+/// Router delivering all messages to [`SENT`].
+pub struct TestRouter;
+
+impl SendXcm for TestRouter {
+	type Ticket = (MultiLocation, Xcm<()>);
+
+	fn validate(
+		dest: &mut Option<MultiLocation>,
+		msg: &mut Option<Xcm<()>>,
+	) -> SendResult<Self::Ticket> {
+		let dest = dest.take().ok_or(SendError::MissingArgument)?;
+		let msg = msg.take().ok_or(SendError::MissingArgument)?;
+		Ok(((dest, msg), MultiAssets::new()))
+	}
+
+	fn deliver(ticket: Self::Ticket) -> Result<XcmHash, SendError> {
+		SENT.with(|sent| sent.borrow_mut().push(ticket));
+		Ok([0; 32])
+	}
+}
+
+pub type Router = RateLimitedRouter<Test, TestRouter>;
+
+pub fn sent() -> Vec<(MultiLocation, Xcm<()>)> {
+	SENT.with(|sent| sent.borrow().clone())
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	SENT.with(|sent| sent.borrow_mut().clear());
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000), (2, 1_000), (3, 1_000), (4, 10)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
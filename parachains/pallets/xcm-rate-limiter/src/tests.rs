@@ -0,0 +1,165 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{mock::*, AccountUsage, DestinationUsage, Event, NextWindowToPrune, Usage, WeightInfo};
+use codec::Encode;
+use frame_support::{
+	traits::{Get, Hooks},
+	weights::Weight,
+};
+use frame_system::EventRecord;
+use xcm::latest::prelude::*;
+
+fn sibling(id: u32) -> MultiLocation {
+	(Parent, Parachain(id)).into()
+}
+
+fn from_account(index: u64) -> Xcm<()> {
+	Xcm(vec![DescendOrigin(X1(AccountIndex64 { network: None, index })), ClearOrigin])
+}
+
+fn last_event() -> RuntimeEvent {
+	let EventRecord { event, .. } = System::events().pop().expect("an event was deposited");
+	event
+}
+
+#[test]
+fn messages_of_accounts_reserve_a_deposit_and_are_counted() {
+	new_test_ext().execute_with(|| {
+		let message = from_account(1);
+		let size = message.encoded_size() as u32;
+		let deposit = 10 + size as u64;
+
+		assert!(send_xcm::<Router>(sibling(2000), message.clone()).is_ok());
+		assert_eq!(Balances::reserved_balance(1), deposit);
+		assert_eq!(AccountUsage::<Test>::get(0, 1), (Usage { messages: 1, bytes: size }, deposit));
+		assert_eq!(
+			DestinationUsage::<Test>::get(0, sibling(2000)),
+			Usage { messages: 1, bytes: size }
+		);
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::XcmRateLimiter(Event::DepositReserved {
+				who: 1,
+				destination: sibling(2000).into(),
+				deposit,
+			})
+		);
+		assert_eq!(sent(), vec![(sibling(2000), message)]);
+	});
+}
+
+#[test]
+fn other_messages_are_routed_as_is() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..5 {
+			assert!(send_xcm::<Router>(sibling(2000), Xcm(vec![ClearOrigin])).is_ok());
+		}
+		assert_eq!(sent().len(), 5);
+		assert!((1..=4).all(|who| Balances::reserved_balance(who) == 0));
+		assert_eq!(DestinationUsage::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn accounts_are_rate_limited_per_window() {
+	new_test_ext().execute_with(|| {
+		assert!(send_xcm::<Router>(sibling(2000), from_account(1)).is_ok());
+		assert!(send_xcm::<Router>(sibling(2001), from_account(1)).is_ok());
+		assert_eq!(
+			send_xcm::<Router>(sibling(2002), from_account(1)),
+			Err(SendError::Transport("RateLimited"))
+		);
+
+		// The limits are reset in the next window.
+		System::set_block_number(10);
+		assert!(send_xcm::<Router>(sibling(2002), from_account(1)).is_ok());
+		assert_eq!(sent().len(), 3);
+	});
+}
+
+#[test]
+fn destinations_are_rate_limited_per_window() {
+	new_test_ext().execute_with(|| {
+		assert!(send_xcm::<Router>(sibling(2000), from_account(1)).is_ok());
+		assert!(send_xcm::<Router>(sibling(2000), from_account(2)).is_ok());
+		assert!(send_xcm::<Router>(sibling(2000), from_account(3)).is_ok());
+		assert_eq!(
+			send_xcm::<Router>(sibling(2000), from_account(3)),
+			Err(SendError::Transport("RateLimited"))
+		);
+		assert!(send_xcm::<Router>(sibling(2001), from_account(3)).is_ok());
+	});
+}
+
+#[test]
+fn messages_are_not_sent_without_the_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			send_xcm::<Router>(sibling(2000), from_account(4)),
+			Err(SendError::Transport("CannotReserveDeposit"))
+		);
+		assert!(sent().is_empty());
+		assert_eq!(Balances::reserved_balance(4), 0);
+	});
+}
+
+#[test]
+fn past_windows_are_pruned_on_idle() {
+	new_test_ext().execute_with(|| {
+		assert!(send_xcm::<Router>(sibling(2000), from_account(1)).is_ok());
+		assert!(send_xcm::<Router>(sibling(2001), from_account(2)).is_ok());
+
+		// The current window is kept.
+		XcmRateLimiter::on_idle(1, Weight::MAX);
+		assert_eq!(AccountUsage::<Test>::iter().count(), 2);
+		assert_eq!(NextWindowToPrune::<Test>::get(), 0);
+
+		System::set_block_number(10);
+		assert!(send_xcm::<Router>(sibling(2000), from_account(3)).is_ok());
+		XcmRateLimiter::on_idle(10, Weight::MAX);
+
+		assert_eq!(AccountUsage::<Test>::iter_prefix(0).count(), 0);
+		assert_eq!(DestinationUsage::<Test>::iter_prefix(0).count(), 0);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert!(System::events().iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::XcmRateLimiter(Event::DepositReleased { who: 1, .. })
+		)));
+		assert_eq!(AccountUsage::<Test>::iter_prefix(1).count(), 1);
+		assert!(Balances::reserved_balance(3) > 0);
+		assert_eq!(NextWindowToPrune::<Test>::get(), 1);
+	});
+}
+
+#[test]
+fn pruning_is_limited_by_the_remaining_weight() {
+	new_test_ext().execute_with(|| {
+		assert!(send_xcm::<Router>(sibling(2000), from_account(1)).is_ok());
+		assert!(send_xcm::<Router>(sibling(2001), from_account(2)).is_ok());
+		System::set_block_number(10);
+
+		let base = <Test as frame_system::Config>::DbWeight::get().reads_writes(1, 1);
+		let one_usage = base.saturating_add(<() as WeightInfo>::prune_usage());
+		assert_eq!(XcmRateLimiter::on_idle(10, one_usage), one_usage);
+		assert_eq!(AccountUsage::<Test>::iter_prefix(0).count(), 1);
+		assert_eq!(NextWindowToPrune::<Test>::get(), 0);
+
+		XcmRateLimiter::on_idle(10, Weight::MAX);
+		assert_eq!(AccountUsage::<Test>::iter_prefix(0).count(), 0);
+		assert_eq!(NextWindowToPrune::<Test>::get(), 1);
+	});
+}
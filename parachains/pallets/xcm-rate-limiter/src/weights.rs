@@ -0,0 +1,56 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weights of `pallet_xcm_rate_limiter`.
+//!
+//! The routed messages are weighed by the `send` benchmark of `pallet_xcm`, which goes through
+//! [`crate::RateLimitedRouter`]. `prune_usage` is charged its storage accesses and a flat
+//! execution time until its benchmark is run.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+// The weight info trait for `pallet_xcm_rate_limiter`.
+pub trait WeightInfo {
+	fn prune_usage() -> Weight;
+}
+
+/// Weights for pallet_xcm_rate_limiter using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: XcmRateLimiter AccountUsage (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: System Events (r:0 w:1)
+	fn prune_usage() -> Weight {
+		Weight::from_parts(24_000_000 as u64, 0)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn prune_usage() -> Weight {
+		Weight::from_parts(24_000_000 as u64, 0)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+}
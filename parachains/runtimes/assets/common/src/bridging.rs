@@ -0,0 +1,336 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bridging of the assets system to other global consensus systems, through sibling bridge hubs.
+//!
+//! The bridges are listed in a table of [`Bridge`], as with [`NetworkExportTable`]: the bridged
+//! network, the location of the bridge hub exporting to it and the fee paid to the bridge hub out
+//! of the sovereign account of the parachain, if any.
+
+use cumulus_primitives_core::InspectMessageQueues;
+use frame_support::traits::{Contains, ContainsPair, Get};
+use sp_std::{marker::PhantomData, prelude::*};
+use xcm::{latest::prelude::*, VersionedMultiLocation, VersionedXcm};
+use xcm_builder::{NetworkExportTable, SovereignPaidRemoteExporter};
+use xcm_executor::traits::ExportXcm;
+
+/// A bridged network, the location of the bridge hub exporting to it and the fee it is paid.
+pub type Bridge = (NetworkId, MultiLocation, Option<MultiAsset>);
+
+type Exporter<Bridges, Router, UniversalLocation> =
+	SovereignPaidRemoteExporter<NetworkExportTable<Bridges>, Router, UniversalLocation>;
+
+/// The networks bridged by `Bridges`.
+pub struct BridgedNetworks<Bridges>(PhantomData<Bridges>);
+impl<Bridges: Get<Vec<Bridge>>> Contains<NetworkId> for BridgedNetworks<Bridges> {
+	fn contains(network: &NetworkId) -> bool {
+		Bridges::get().iter().any(|(bridged, ..)| bridged == network)
+	}
+}
+
+/// The bridge hubs of `Bridges`, which may speak for their bridged network with `UniversalOrigin`.
+pub struct BridgedUniversalAliases<Bridges>(PhantomData<Bridges>);
+impl<Bridges: Get<Vec<Bridge>>> Contains<(MultiLocation, Junction)>
+	for BridgedUniversalAliases<Bridges>
+{
+	fn contains((origin, alias): &(MultiLocation, Junction)) -> bool {
+		Bridges::get().iter().any(|(network, bridge_hub, _)| {
+			bridge_hub == origin && *alias == GlobalConsensus(*network)
+		})
+	}
+}
+
+/// Accepts the assets of a bridged network as reserved by their origin in that network.
+pub struct IsBridgedReserve<Bridges>(PhantomData<Bridges>);
+impl<Bridges: Get<Vec<Bridge>>> ContainsPair<MultiAsset, MultiLocation>
+	for IsBridgedReserve<Bridges>
+{
+	fn contains(asset: &MultiAsset, origin: &MultiLocation) -> bool {
+		let bridged = match origin.interior.global_consensus() {
+			Ok(network) => BridgedNetworks::<Bridges>::contains(&network),
+			Err(()) => false,
+		};
+		bridged && matches!(asset.id, Concrete(ref id) if id.starts_with(origin))
+	}
+}
+
+/// Router sending the messages for the bridged networks of `Bridges` to their bridge hub, as
+/// `ExportMessage` through `Router`.
+pub struct ToBridgeHubRouter<Bridges, Router, UniversalLocation>(
+	PhantomData<(Bridges, Router, UniversalLocation)>,
+);
+
+impl<Bridges, Router, UniversalLocation> SendXcm
+	for ToBridgeHubRouter<Bridges, Router, UniversalLocation>
+where
+	Bridges: Get<Vec<Bridge>>,
+	Router: SendXcm,
+	UniversalLocation: Get<InteriorMultiLocation>,
+{
+	type Ticket = <Exporter<Bridges, Router, UniversalLocation> as SendXcm>::Ticket;
+
+	fn validate(
+		dest: &mut Option<MultiLocation>,
+		msg: &mut Option<Xcm<()>>,
+	) -> SendResult<Self::Ticket> {
+		Exporter::<Bridges, Router, UniversalLocation>::validate(dest, msg)
+	}
+
+	fn deliver(ticket: Self::Ticket) -> Result<XcmHash, SendError> {
+		Exporter::<Bridges, Router, UniversalLocation>::deliver(ticket)
+	}
+}
+
+impl<Bridges, Router, UniversalLocation> InspectMessageQueues
+	for ToBridgeHubRouter<Bridges, Router, UniversalLocation>
+{
+	// The messages are queued, and inspected, by `Router`.
+	fn clear_messages() {}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		Vec::new()
+	}
+}
+
+/// [`ExportXcm`] forwarding the `ExportMessage` of the local origins to the bridge hub of their
+/// network with [`ToBridgeHubRouter`], descending into the origin of the message first.
+pub struct ForwardExportToBridgeHub<Bridges, Router, UniversalLocation>(
+	PhantomData<(Bridges, Router, UniversalLocation)>,
+);
+
+impl<Bridges, Router, UniversalLocation> ExportXcm
+	for ForwardExportToBridgeHub<Bridges, Router, UniversalLocation>
+where
+	Bridges: Get<Vec<Bridge>>,
+	Router: SendXcm,
+	UniversalLocation: Get<InteriorMultiLocation>,
+{
+	type Ticket = <ToBridgeHubRouter<Bridges, Router, UniversalLocation> as SendXcm>::Ticket;
+
+	fn validate(
+		network: NetworkId,
+		_channel: u32,
+		universal_source: &mut Option<InteriorMultiLocation>,
+		destination: &mut Option<InteriorMultiLocation>,
+		message: &mut Option<Xcm<()>>,
+	) -> SendResult<Self::Ticket> {
+		let universal_location = UniversalLocation::get();
+		// Only the origins within the parachain may be spoken for.
+		match universal_source {
+			Some(source) if source.starts_with(&universal_location) => {},
+			Some(_) => return Err(SendError::NotApplicable),
+			None => return Err(SendError::MissingArgument),
+		}
+		let source = universal_source.take().ok_or(SendError::MissingArgument)?;
+		let remote = destination.take().ok_or(SendError::MissingArgument)?;
+		let xcm = message.take().ok_or(SendError::MissingArgument)?;
+
+		let mut origin = Here;
+		for junction in source.iter().skip(universal_location.len()) {
+			origin.push(*junction).map_err(|_| SendError::Unroutable)?;
+		}
+		let dest = bridged_destination(&universal_location, network, remote)?;
+		let xcm = match origin {
+			Here => xcm,
+			origin => Xcm(Some(DescendOrigin(origin)).into_iter().chain(xcm.0).collect()),
+		};
+		ToBridgeHubRouter::<Bridges, Router, UniversalLocation>::validate(
+			&mut Some(dest),
+			&mut Some(xcm),
+		)
+	}
+
+	fn deliver(ticket: Self::Ticket) -> Result<XcmHash, SendError> {
+		ToBridgeHubRouter::<Bridges, Router, UniversalLocation>::deliver(ticket)
+	}
+}
+
+/// The location of `remote` in the bridged `network`, as seen from `universal_location`.
+fn bridged_destination(
+	universal_location: &InteriorMultiLocation,
+	network: NetworkId,
+	remote: InteriorMultiLocation,
+) -> Result<MultiLocation, SendError> {
+	Ok(MultiLocation::new(
+		universal_location.len() as u8,
+		remote
+			.pushed_front_with(GlobalConsensus(network))
+			.map_err(|_| SendError::Unroutable)?,
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::StartsWithExplicitGlobalConsensusExcept;
+
+	const BRIDGED: NetworkId = NetworkId::ByGenesis([1; 32]);
+	const UNBRIDGED: NetworkId = NetworkId::ByGenesis([2; 32]);
+	const LOCAL: NetworkId = NetworkId::ByGenesis([3; 32]);
+	const ALICE: [u8; 32] = [1; 32];
+
+	frame_support::parameter_types! {
+		pub Bridges: Vec<Bridge> = vec![(BRIDGED, MultiLocation::new(1, X1(Parachain(1002))), None)];
+		pub UniversalLocation: InteriorMultiLocation = X2(GlobalConsensus(LOCAL), Parachain(1000));
+	}
+
+	/// Router accepting every message, whose ticket is the message and its destination.
+	struct TestRouter;
+	impl SendXcm for TestRouter {
+		type Ticket = (MultiLocation, Xcm<()>);
+
+		fn validate(
+			dest: &mut Option<MultiLocation>,
+			msg: &mut Option<Xcm<()>>,
+		) -> SendResult<Self::Ticket> {
+			let dest = dest.take().ok_or(SendError::MissingArgument)?;
+			let msg = msg.take().ok_or(SendError::MissingArgument)?;
+			Ok(((dest, msg), MultiAssets::new()))
+		}
+
+		fn deliver(_: Self::Ticket) -> Result<XcmHash, SendError> {
+			Ok([0; 32])
+		}
+	}
+
+	type Forwarder = ForwardExportToBridgeHub<Bridges, TestRouter, UniversalLocation>;
+
+	/// The message exported by the bridge hub of `BRIDGED`, with its destination.
+	fn exported(ticket: (MultiLocation, Xcm<()>)) -> (InteriorMultiLocation, Xcm<()>) {
+		let (bridge_hub, message) = ticket;
+		assert_eq!(bridge_hub, MultiLocation::new(1, X1(Parachain(1002))));
+		message
+			.0
+			.into_iter()
+			.find_map(|instruction| match instruction {
+				ExportMessage { network, destination, xcm } if network == BRIDGED =>
+					Some((destination, xcm)),
+				_ => None,
+			})
+			.expect("the bridge hub is asked to export the message")
+	}
+
+	#[test]
+	fn bridge_hubs_speak_for_their_network_only() {
+		let bridge_hub = MultiLocation::new(1, X1(Parachain(1002)));
+		assert!(BridgedUniversalAliases::<Bridges>::contains(&(
+			bridge_hub,
+			GlobalConsensus(BRIDGED)
+		)));
+		assert!(!BridgedUniversalAliases::<Bridges>::contains(&(
+			bridge_hub,
+			GlobalConsensus(UNBRIDGED)
+		)));
+		assert!(!BridgedUniversalAliases::<Bridges>::contains(&(
+			MultiLocation::new(1, X1(Parachain(2000))),
+			GlobalConsensus(BRIDGED)
+		)));
+	}
+
+	#[test]
+	fn assets_of_bridged_networks_are_accepted() {
+		type Excluded = StartsWithExplicitGlobalConsensusExcept<BridgedNetworks<Bridges>>;
+		let bridged_asset = MultiLocation::new(2, X2(GlobalConsensus(BRIDGED), Parachain(1000)));
+		let unbridged_asset =
+			MultiLocation::new(2, X2(GlobalConsensus(UNBRIDGED), Parachain(1000)));
+
+		assert!(!Excluded::contains(&bridged_asset));
+		assert!(Excluded::contains(&unbridged_asset));
+		assert!(!Excluded::contains(&MultiLocation::new(1, X1(Parachain(1000)))));
+
+		let origin = MultiLocation::new(2, X1(GlobalConsensus(BRIDGED)));
+		assert!(IsBridgedReserve::<Bridges>::contains(&(bridged_asset, 1_000u128).into(), &origin));
+		assert!(!IsBridgedReserve::<Bridges>::contains(
+			&(unbridged_asset, 1_000u128).into(),
+			&origin
+		));
+	}
+
+	#[test]
+	fn sources_outside_the_parachain_are_not_forwarded() {
+		let mut source = Some(X2(GlobalConsensus(LOCAL), Parachain(2000)));
+		let mut destination = Some(X1(Parachain(1000)));
+		let mut message = Some(Xcm(vec![ClearOrigin]));
+
+		assert_eq!(
+			Forwarder::validate(BRIDGED, 0, &mut source, &mut destination, &mut message),
+			Err(SendError::NotApplicable)
+		);
+		// The arguments are left to the next exporter.
+		assert!(source.is_some() && destination.is_some() && message.is_some());
+	}
+
+	#[test]
+	fn local_origins_are_descended_into() {
+		let (ticket, _) = Forwarder::validate(
+			BRIDGED,
+			0,
+			&mut Some(X3(
+				GlobalConsensus(LOCAL),
+				Parachain(1000),
+				AccountId32 { network: None, id: ALICE },
+			)),
+			&mut Some(X1(Parachain(1000))),
+			&mut Some(Xcm(vec![ClearOrigin])),
+		)
+		.unwrap();
+
+		assert_eq!(
+			exported(ticket),
+			(
+				X1(Parachain(1000)),
+				Xcm(vec![DescendOrigin(X1(AccountId32 { network: None, id: ALICE })), ClearOrigin])
+			)
+		);
+	}
+
+	#[test]
+	fn the_parachain_origin_is_kept() {
+		let (ticket, _) = Forwarder::validate(
+			BRIDGED,
+			0,
+			&mut Some(UniversalLocation::get()),
+			&mut Some(X1(Parachain(1000))),
+			&mut Some(Xcm(vec![ClearOrigin])),
+		)
+		.unwrap();
+
+		assert_eq!(exported(ticket), (X1(Parachain(1000)), Xcm(vec![ClearOrigin])));
+	}
+
+	#[test]
+	fn bridged_destinations_go_up_to_the_universal_location() {
+		let universal_location = UniversalLocation::get();
+		let dest = bridged_destination(&universal_location, BRIDGED, X1(Parachain(1000))).unwrap();
+
+		assert_eq!(dest, MultiLocation::new(2, X2(GlobalConsensus(BRIDGED), Parachain(1000))));
+		assert_eq!(
+			dest.reanchored(
+				&MultiLocation::new(2, X1(GlobalConsensus(BRIDGED))),
+				universal_location
+			),
+			Ok(X1(Parachain(1000)).into())
+		);
+		// The unbridged networks have no bridge hub to export to.
+		assert!(Forwarder::validate(
+			UNBRIDGED,
+			0,
+			&mut Some(universal_location),
+			&mut Some(X1(Parachain(1000))),
+			&mut Some(Xcm(vec![ClearOrigin])),
+		)
+		.is_err());
+	}
+}
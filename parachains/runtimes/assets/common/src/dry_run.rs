@@ -0,0 +1,236 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry-run of calls and XCM programs, for implementing the
+//! [`DryRunApi`](crate::runtime_api::DryRunApi).
+
+use crate::runtime_api::{CallDryRunEffects, DryRunError, XcmDryRunEffects};
+use cumulus_primitives_core::InspectMessageQueues;
+use frame_support::{dispatch::PostDispatchInfo, storage::unhashed};
+use sp_runtime::traits::{BlakeTwo256, Convert, Dispatchable, Hash};
+use sp_std::{marker::PhantomData, vec::Vec};
+use xcm::{latest::prelude::*, VersionedMultiLocation, VersionedXcm};
+use xcm_executor::{traits::WeightTrader, Assets, XcmExecutor};
+
+/// Storage key of the fees charged by the [`FeeRecordingTrader`] during a dry-run.
+const CHARGED_FEES_KEY: &[u8] = b":dry_run:charged_fees:";
+
+/// Dispatches `call` from `origin`, collecting the events it emits and the messages it sends
+/// through `Router`.
+///
+/// The events and messages queued before are cleared, so this must be called from a runtime API
+/// whose changes to the state are discarded.
+pub fn dry_run_call<Runtime, Router>(
+	origin: Runtime::RuntimeOrigin,
+	call: Runtime::RuntimeCall,
+) -> CallDryRunEffects<Runtime::RuntimeEvent>
+where
+	Runtime: frame_system::Config,
+	Runtime::RuntimeCall:
+		Dispatchable<RuntimeOrigin = Runtime::RuntimeOrigin, PostInfo = PostDispatchInfo>,
+	Router: InspectMessageQueues,
+{
+	Router::clear_messages();
+	frame_system::Pallet::<Runtime>::reset_events();
+
+	let execution_result = call.dispatch(origin);
+
+	CallDryRunEffects {
+		execution_result,
+		emitted_events: emitted_events::<Runtime>(),
+		forwarded_xcms: Router::get_messages(),
+	}
+}
+
+/// Executes `xcm` as received from `origin_location` with the executor of `XcmConfig`,
+/// collecting the events it emits, the messages it sends through `Router` and the fees charged by
+/// the `Trader`.
+///
+/// The program may use up to the weight `WeightLimit` gives for its origin, which should be the
+/// limit of the queue executing the messages of the origin.
+///
+/// The events and messages queued before are cleared, so this must be called from a runtime API
+/// whose changes to the state are discarded.
+pub fn dry_run_xcm<Runtime, Router, XcmConfig, WeightLimit>(
+	origin_location: VersionedMultiLocation,
+	xcm: VersionedXcm<Runtime::RuntimeCall>,
+) -> Result<XcmDryRunEffects<Runtime::RuntimeEvent>, DryRunError>
+where
+	Runtime: frame_system::Config,
+	Router: InspectMessageQueues,
+	XcmConfig: xcm_executor::Config<RuntimeCall = Runtime::RuntimeCall>,
+	WeightLimit: Convert<MultiLocation, Weight>,
+{
+	let origin_location = MultiLocation::try_from(origin_location)
+		.map_err(|()| DryRunError::VersionedConversionFailed)?;
+	let xcm = Xcm::<Runtime::RuntimeCall>::try_from(xcm)
+		.map_err(|()| DryRunError::VersionedConversionFailed)?;
+	let hash = BlakeTwo256::hash_of(&xcm).into();
+
+	let weight_limit = WeightLimit::convert(origin_location);
+
+	Router::clear_messages();
+	frame_system::Pallet::<Runtime>::reset_events();
+	unhashed::kill(CHARGED_FEES_KEY);
+
+	let execution_result =
+		XcmExecutor::<RecordFees<XcmConfig>>::execute_xcm(origin_location, xcm, hash, weight_limit);
+
+	Ok(XcmDryRunEffects {
+		execution_result,
+		emitted_events: emitted_events::<Runtime>(),
+		forwarded_xcms: Router::get_messages(),
+		charged_fees: charged_fees().into(),
+	})
+}
+
+/// `XcmConfig` with its `Trader` recording the fees it charges, for [`dry_run_xcm`].
+pub struct RecordFees<XcmConfig>(PhantomData<XcmConfig>);
+
+impl<XcmConfig: xcm_executor::Config> xcm_executor::Config for RecordFees<XcmConfig> {
+	type RuntimeCall = XcmConfig::RuntimeCall;
+	type XcmSender = XcmConfig::XcmSender;
+	type AssetTransactor = XcmConfig::AssetTransactor;
+	type OriginConverter = XcmConfig::OriginConverter;
+	type IsReserve = XcmConfig::IsReserve;
+	type IsTeleporter = XcmConfig::IsTeleporter;
+	type UniversalLocation = XcmConfig::UniversalLocation;
+	type Barrier = XcmConfig::Barrier;
+	type Weigher = XcmConfig::Weigher;
+	type Trader = FeeRecordingTrader<XcmConfig::Trader>;
+	type ResponseHandler = XcmConfig::ResponseHandler;
+	type AssetTrap = XcmConfig::AssetTrap;
+	type AssetClaims = XcmConfig::AssetClaims;
+	type SubscriptionService = XcmConfig::SubscriptionService;
+	type PalletInstancesInfo = XcmConfig::PalletInstancesInfo;
+	type MaxAssetsIntoHolding = XcmConfig::MaxAssetsIntoHolding;
+	type AssetLocker = XcmConfig::AssetLocker;
+	type AssetExchanger = XcmConfig::AssetExchanger;
+	type FeeManager = XcmConfig::FeeManager;
+	type MessageExporter = XcmConfig::MessageExporter;
+	type UniversalAliases = XcmConfig::UniversalAliases;
+	type CallDispatcher = XcmConfig::CallDispatcher;
+	type SafeCallFilter = XcmConfig::SafeCallFilter;
+}
+
+/// `Trader` recording the fees it charges, net of the refunds, for [`dry_run_xcm`].
+pub struct FeeRecordingTrader<Trader>(Trader);
+
+impl<Trader: WeightTrader> WeightTrader for FeeRecordingTrader<Trader> {
+	fn new() -> Self {
+		Self(Trader::new())
+	}
+
+	fn buy_weight(&mut self, weight: Weight, payment: Assets) -> Result<Assets, XcmError> {
+		let unused = self.0.buy_weight(weight, payment.clone())?;
+		let charged = unused
+			.assets_iter()
+			.fold(payment, |charged, asset| charged.checked_sub(asset).unwrap_or_else(|c| c));
+		let mut fees = charged_fees();
+		fees.subsume_assets(charged);
+		unhashed::put(CHARGED_FEES_KEY, &MultiAssets::from(fees));
+		Ok(unused)
+	}
+
+	fn refund_weight(&mut self, weight: Weight) -> Option<MultiAsset> {
+		let refund = self.0.refund_weight(weight)?;
+		let fees = charged_fees().checked_sub(refund.clone()).unwrap_or_else(|fees| fees);
+		unhashed::put(CHARGED_FEES_KEY, &MultiAssets::from(fees));
+		Some(refund)
+	}
+}
+
+/// Returns the fees recorded by the [`FeeRecordingTrader`].
+fn charged_fees() -> Assets {
+	unhashed::get::<MultiAssets>(CHARGED_FEES_KEY)
+		.map(Into::into)
+		.unwrap_or_default()
+}
+
+fn emitted_events<Runtime: frame_system::Config>() -> Vec<Runtime::RuntimeEvent> {
+	frame_system::Pallet::<Runtime>::read_events_no_consensus()
+		.into_iter()
+		.map(|record| record.event)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Charges one unit of the native asset per unit of ref time.
+	struct PerRefTime(u128);
+
+	impl WeightTrader for PerRefTime {
+		fn new() -> Self {
+			Self(0)
+		}
+
+		fn buy_weight(&mut self, weight: Weight, payment: Assets) -> Result<Assets, XcmError> {
+			let amount = weight.ref_time() as u128;
+			let unused =
+				payment.checked_sub((Here, amount).into()).map_err(|_| XcmError::TooExpensive)?;
+			self.0 += amount;
+			Ok(unused)
+		}
+
+		fn refund_weight(&mut self, weight: Weight) -> Option<MultiAsset> {
+			let amount = (weight.ref_time() as u128).min(self.0);
+			self.0 -= amount;
+			(amount > 0).then(|| (Here, amount).into())
+		}
+	}
+
+	fn fees() -> MultiAssets {
+		charged_fees().into()
+	}
+
+	#[test]
+	fn fees_are_recorded_net_of_refunds() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let mut trader = FeeRecordingTrader::<PerRefTime>::new();
+			assert_eq!(fees(), MultiAssets::new());
+
+			let unused = trader
+				.buy_weight(Weight::from_parts(60, 0), MultiAssets::from((Here, 100)).into())
+				.unwrap();
+			assert_eq!(MultiAssets::from(unused), (Here, 40).into());
+			assert_eq!(fees(), (Here, 60).into());
+
+			let unused = trader
+				.buy_weight(Weight::from_parts(10, 0), MultiAssets::from((Here, 10)).into())
+				.unwrap();
+			assert!(unused.is_empty());
+			assert_eq!(fees(), (Here, 70).into());
+
+			assert_eq!(trader.refund_weight(Weight::from_parts(30, 0)), Some((Here, 30).into()));
+			assert_eq!(fees(), (Here, 40).into());
+		});
+	}
+
+	#[test]
+	fn failed_purchases_charge_nothing() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let mut trader = FeeRecordingTrader::<PerRefTime>::new();
+
+			assert!(trader
+				.buy_weight(Weight::from_parts(200, 0), MultiAssets::from((Here, 100)).into())
+				.is_err());
+			assert_eq!(fees(), MultiAssets::new());
+			assert_eq!(trader.refund_weight(Weight::from_parts(10, 0)), None);
+			assert_eq!(fees(), MultiAssets::new());
+		});
+	}
+}
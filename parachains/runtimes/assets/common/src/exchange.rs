@@ -0,0 +1,136 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! XCM asset exchange backed by the pools of `pallet_asset_swap`.
+
+use crate::runtime_api::FungiblesAccessError;
+use sp_runtime::{traits::Zero, SaturatedConversion};
+use sp_std::{marker::PhantomData, vec::Vec};
+use xcm::latest::prelude::*;
+use xcm_executor::{
+	traits::{AssetExchange, MatchesFungibles},
+	Assets,
+};
+
+/// [`AssetExchange`] swapping a single fungible asset of the holding register for another through
+/// their pool.
+///
+/// When `maximal`, all of the given asset is swapped for at least the wanted amount. Otherwise just
+/// enough of the given asset is swapped for exactly the wanted amount, the rest being returned.
+pub struct PoolAssetExchanger<Runtime, Matcher>(PhantomData<(Runtime, Matcher)>);
+impl<Runtime, Matcher> AssetExchange for PoolAssetExchanger<Runtime, Matcher>
+where
+	Runtime: pallet_asset_swap::Config,
+	Matcher: MatchesFungibles<Runtime::AssetId, Runtime::Balance>,
+{
+	fn exchange_asset(
+		_origin: Option<&MultiLocation>,
+		give: Assets,
+		want: &MultiAssets,
+		maximal: bool,
+	) -> Result<Assets, Assets> {
+		let given: Vec<MultiAsset> = give.fungible_assets_iter().collect();
+		let (given, wanted) = match (&given[..], want.inner()) {
+			([given], [wanted]) if give.len() == 1 => (given.clone(), wanted.clone()),
+			_ => return Err(give),
+		};
+		let ((asset_in, amount_in), (asset_out, amount_out)) =
+			match (Matcher::matches_fungibles(&given), Matcher::matches_fungibles(&wanted)) {
+				(Ok(given), Ok(wanted)) => (given, wanted),
+				_ => return Err(give),
+			};
+
+		let swapped = if maximal {
+			pallet_asset_swap::Pallet::<Runtime>::swap_exact_in_for(
+				None, asset_in, asset_out, amount_in, amount_out,
+			)
+			.map(|received| (received, Zero::zero()))
+		} else {
+			pallet_asset_swap::Pallet::<Runtime>::swap_exact_out_for(
+				None, asset_in, asset_out, amount_out, amount_in,
+			)
+			.map(|spent| (amount_out, amount_in - spent))
+		};
+
+		match swapped {
+			Ok((received, unused)) => {
+				let mut assets: Assets =
+					MultiAsset { id: wanted.id, fun: Fungible(received.saturated_into()) }.into();
+				if !unused.is_zero() {
+					assets.subsume(MultiAsset {
+						id: given.id,
+						fun: Fungible(unused.saturated_into()),
+					});
+				}
+				Ok(assets)
+			},
+			Err(error) => {
+				log::debug!(
+					target: "xcm::exchange_asset",
+					"PoolAssetExchanger failed to swap {:?} for {:?}: {:?}",
+					given, wanted, error,
+				);
+				Err(give)
+			},
+		}
+	}
+}
+
+/// Matches the local asset identifier of the [`AssetId`] of a fungible.
+fn matches_asset_id<Runtime, Matcher>(id: AssetId) -> Result<Runtime::AssetId, FungiblesAccessError>
+where
+	Runtime: pallet_asset_swap::Config,
+	Matcher: MatchesFungibles<Runtime::AssetId, Runtime::Balance>,
+{
+	Matcher::matches_fungibles(&MultiAsset { id, fun: Fungible(0) })
+		.map(|(asset, _)| asset)
+		.map_err(|_| FungiblesAccessError::AssetIdConversionFailed)
+}
+
+/// Returns the [`MultiAsset`] received when swapping exactly `give` for `want`, or `None` without
+/// a pool or the liquidity for the swap.
+pub fn quote_exact_in<Runtime, Matcher>(
+	give: MultiAsset,
+	want: AssetId,
+) -> Result<Option<MultiAsset>, FungiblesAccessError>
+where
+	Runtime: pallet_asset_swap::Config,
+	Matcher: MatchesFungibles<Runtime::AssetId, Runtime::Balance>,
+{
+	let (asset_in, amount_in) = Matcher::matches_fungibles(&give)
+		.map_err(|_| FungiblesAccessError::AssetIdConversionFailed)?;
+	let asset_out = matches_asset_id::<Runtime, Matcher>(want.clone())?;
+	Ok(pallet_asset_swap::Pallet::<Runtime>::quote_exact_in(asset_in, asset_out, amount_in)
+		.ok()
+		.map(|amount_out| MultiAsset { id: want, fun: Fungible(amount_out.saturated_into()) }))
+}
+
+/// Returns the [`MultiAsset`] to give when swapping for exactly `want`, or `None` without a pool
+/// or the liquidity for the swap.
+pub fn quote_exact_out<Runtime, Matcher>(
+	give: AssetId,
+	want: MultiAsset,
+) -> Result<Option<MultiAsset>, FungiblesAccessError>
+where
+	Runtime: pallet_asset_swap::Config,
+	Matcher: MatchesFungibles<Runtime::AssetId, Runtime::Balance>,
+{
+	let asset_in = matches_asset_id::<Runtime, Matcher>(give.clone())?;
+	let (asset_out, amount_out) = Matcher::matches_fungibles(&want)
+		.map_err(|_| FungiblesAccessError::AssetIdConversionFailed)?;
+	Ok(pallet_asset_swap::Pallet::<Runtime>::quote_exact_out(asset_in, asset_out, amount_out)
+		.ok()
+		.map(|amount_in| MultiAsset { id: give, fun: Fungible(amount_in.saturated_into()) }))
+}
@@ -15,6 +15,9 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod bridging;
+pub mod dry_run;
+pub mod exchange;
 pub mod fungible_conversion;
 pub mod matching;
 pub mod runtime_api;
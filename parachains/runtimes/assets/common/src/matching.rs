@@ -49,6 +49,20 @@ impl<Network: Get<NetworkId>> Contains<MultiLocation>
 	}
 }
 
+/// Matches the locations starting explicitly with the `GlobalConsensus` of a network not
+/// contained in `Allowed`, e.g. to accept the assets of the bridged networks only.
+pub struct StartsWithExplicitGlobalConsensusExcept<Allowed>(sp_std::marker::PhantomData<Allowed>);
+impl<Allowed: Contains<NetworkId>> Contains<MultiLocation>
+	for StartsWithExplicitGlobalConsensusExcept<Allowed>
+{
+	fn contains(t: &MultiLocation) -> bool {
+		match t.interior.global_consensus() {
+			Ok(requested_network) => !Allowed::contains(&requested_network),
+			_ => false,
+		}
+	}
+}
+
 frame_support::parameter_types! {
 	pub LocalMultiLocationPattern: MultiLocation = MultiLocation::new(0, Here);
 	pub ParentLocation: MultiLocation = MultiLocation::parent();
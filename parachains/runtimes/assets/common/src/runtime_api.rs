@@ -13,12 +13,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Runtime API definition for fungibles.
+//! Runtime API definition for fungibles, their swaps and the dry-run of XCM programs.
 
 use codec::{Codec, Decode, Encode};
-use frame_support::RuntimeDebug;
+use frame_support::{dispatch::DispatchResultWithPostInfo, RuntimeDebug};
 use sp_std::vec::Vec;
-use xcm::latest::MultiAsset;
+use xcm::{
+	latest::{AssetId, MultiAsset, MultiAssets, MultiLocation, Outcome, Xcm},
+	VersionedMultiAsset, VersionedMultiLocation, VersionedXcm,
+};
 
 /// The possible errors that can happen querying the storage of assets.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
@@ -29,6 +32,37 @@ pub enum FungiblesAccessError {
 	AmountToBalanceConversionFailed,
 }
 
+/// The possible errors that can happen dry-running a call or an XCM program.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum DryRunError {
+	/// A versioned location or program couldn't be converted to the latest version.
+	VersionedConversionFailed,
+}
+
+/// Effects of dry-running a `RuntimeCall`.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct CallDryRunEffects<Event> {
+	/// The result of the dispatch.
+	pub execution_result: DispatchResultWithPostInfo,
+	/// The events emitted by the dispatch.
+	pub emitted_events: Vec<Event>,
+	/// The messages which would have been sent, by destination.
+	pub forwarded_xcms: Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)>,
+}
+
+/// Effects of dry-running an XCM program.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct XcmDryRunEffects<Event> {
+	/// The outcome of the execution, with the weight used.
+	pub execution_result: Outcome,
+	/// The events emitted by the execution.
+	pub emitted_events: Vec<Event>,
+	/// The messages which would have been sent, by destination.
+	pub forwarded_xcms: Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)>,
+	/// The fees charged by the `Trader` for the execution, net of the refunds.
+	pub charged_fees: MultiAssets,
+}
+
 sp_api::decl_runtime_apis! {
 	/// The API for querying account's balances from runtime.
 	pub trait FungiblesApi<AccountId>
@@ -38,4 +72,53 @@ sp_api::decl_runtime_apis! {
 		/// Returns the list of all [`MultiAsset`] that an `AccountId` has.
 		fn query_account_balances(account: AccountId) -> Result<Vec<MultiAsset>, FungiblesAccessError>;
 	}
+
+	/// The API for querying the assets locked through XCM from runtime.
+	pub trait AssetLocksApi<AccountId>
+	where
+		AccountId: Codec,
+	{
+		/// Returns the [`MultiAsset`]s of an `AccountId` locked on this chain, with the location
+		/// which may unlock each.
+		fn query_locked_assets(account: AccountId) -> Result<Vec<(MultiAsset, VersionedMultiLocation)>, FungiblesAccessError>;
+		/// Returns the assets of an `AccountId` locked on other chains which this chain may unlock,
+		/// with the location of their locker.
+		fn query_unlockable_assets(account: AccountId) -> Vec<(VersionedMultiAsset, VersionedMultiLocation)>;
+	}
+
+	/// The API for quoting swaps through the asset pools from runtime.
+	pub trait AssetSwapApi {
+		/// Returns the [`MultiAsset`] received when swapping exactly `give` for `want`, or `None`
+		/// without a pool or the liquidity for the swap.
+		fn quote_exact_in(give: MultiAsset, want: AssetId) -> Result<Option<MultiAsset>, FungiblesAccessError>;
+		/// Returns the [`MultiAsset`] to give when swapping `give` for exactly `want`, or `None`
+		/// without a pool or the liquidity for the swap.
+		fn quote_exact_out(give: AssetId, want: MultiAsset) -> Result<Option<MultiAsset>, FungiblesAccessError>;
+	}
+
+	/// The API for querying and claiming the assets trapped by XCM from runtime.
+	pub trait TrappedAssetsApi {
+		/// Returns the [`MultiAssets`] trapped for `origin`, with the number of times they were
+		/// trapped.
+		fn query_trapped_assets(origin: MultiLocation) -> Vec<(MultiAssets, u32)>;
+		/// Returns the program which `origin` may send to claim `assets` trapped for it and
+		/// deposit them to `beneficiary`.
+		fn claim_trapped_assets_program(assets: MultiAssets, beneficiary: MultiLocation) -> Xcm<()>;
+	}
+
+	/// The API for dry-running calls and XCM programs against the current state from runtime.
+	///
+	/// Nothing is committed: the effects are only returned.
+	pub trait DryRunApi<Call, Event, OriginCaller>
+	where
+		Call: Codec,
+		Event: Codec,
+		OriginCaller: Codec,
+	{
+		/// Dispatches `call` from `origin`, returning its result, events and sent messages.
+		fn dry_run_call(origin: OriginCaller, call: Call) -> Result<CallDryRunEffects<Event>, DryRunError>;
+		/// Executes `xcm` as received from `origin_location`, returning its outcome, events and
+		/// sent messages.
+		fn dry_run_xcm(origin_location: VersionedMultiLocation, xcm: VersionedXcm<Call>) -> Result<XcmDryRunEffects<Event>, DryRunError>;
+	}
 }
@@ -61,9 +61,10 @@ use sp_api::impl_runtime_apis;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, ConvertInto},
+	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, Convert, ConvertInto},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult,
+	types::SystemTokenId,
+	ApplyExtrinsicResult, Permill,
 };
 
 use sp_std::prelude::*;
@@ -77,7 +78,10 @@ use frame_support::{
 	construct_runtime,
 	dispatch::DispatchClass,
 	parameter_types,
-	traits::{AsEnsureOriginWithArg, ConstU32, ConstU64, ConstU8, EitherOfDiverse, InstanceFilter},
+	traits::{
+		AsEnsureOriginWithArg, ConstU32, ConstU64, ConstU8, EitherOfDiverse, InstanceFilter,
+		Nothing, PalletInfoAccess,
+	},
 	weights::{ConstantMultiplier, Weight},
 	PalletId, RuntimeDebug,
 };
@@ -89,12 +93,15 @@ pub use pallet_sudo::Call as SudoCall;
 use pallet_system_token_payment::{CreditToBucket, TransactionFeeCharger};
 pub use parachains_common as common;
 use parachains_common::{
-	impls::DealWithFees, opaque, AccountId, AssetIdForTrustBackedAssets, AuraId, Balance,
-	BlockNumber, Hash, Header, Index, Signature, AVERAGE_ON_INITIALIZE_RATIO, HOURS,
-	MAXIMUM_BLOCK_WEIGHT, NORMAL_DISPATCH_RATIO, SLOT_DURATION,
+	fee_priority::{LocalAssetConversion, PrioritizeByFeeValue},
+	impls::DealWithFees,
+	opaque, AccountId, AssetIdForTrustBackedAssets, AuraId, Balance, BlockNumber, Hash, Header,
+	Index, Signature, AVERAGE_ON_INITIALIZE_RATIO, HOURS, MAXIMUM_BLOCK_WEIGHT,
+	NORMAL_DISPATCH_RATIO, SLOT_DURATION,
 };
 use xcm_config::{
-	DotLocation, TrustBackedAssetsConvertedConcreteId, XcmConfig, XcmOriginToTransactDispatchOrigin,
+	DotLocation, PoolAssetsConvertedConcreteId, TrustBackedAssetsConvertedConcreteId, XcmConfig,
+	XcmOriginToTransactDispatchOrigin, XcmRouter,
 };
 
 #[cfg(any(feature = "std", test))]
@@ -104,7 +111,12 @@ pub use sp_runtime::BuildStorage;
 use infrablockspace_runtime_common::{BlockHashCount, SlowAdjustingFeeUpdate};
 use infrablockspace_runtime_parachains::system_token_aggregator;
 use pallet_xcm::{EnsureXcm, IsMajorityOfBody};
-use xcm::latest::BodyId;
+use xcm::latest::{
+	BodyId,
+	Junction::{GeneralIndex, PalletInstance, Parachain},
+	Junctions::X3,
+	MultiAsset, MultiLocation,
+};
 use xcm_executor::XcmExecutor;
 
 use weights::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight};
@@ -120,10 +132,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("Infra Asset System"),
 	impl_name: create_runtime_str!("Infra Asset System"),
 	authoring_version: 1,
-	spec_version: 9370,
+	spec_version: 9380,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 11,
+	transaction_version: 12,
 	state_version: 0,
 };
 
@@ -236,13 +248,40 @@ parameter_types! {
 	pub const FeeTreasuryId: PalletId = PalletId(*b"infrapid");
 }
 
+/// Conversion of the fees to system tokens, also used to prioritize transactions by the value of
+/// their tip.
+pub type FeeConversion = pallet_assets::BalanceToAssetBalance<Balances, Runtime, ConvertInto>;
+
+/// Local asset holding a system token.
+///
+/// The system tokens of this chain are held under their own id in `Assets`, the others under the
+/// asset linked to their location in `AssetLink`.
+pub struct SystemTokenAsset;
+impl Convert<SystemTokenId, Option<AssetIdForTrustBackedAssets>> for SystemTokenAsset {
+	fn convert(system_token_id: SystemTokenId) -> Option<AssetIdForTrustBackedAssets> {
+		let pallet_index = u8::try_from(system_token_id.pallet_id).ok()?;
+		if ParachainInfo::parachain_id() == system_token_id.para_id.into() &&
+			usize::from(pallet_index) == <Assets as PalletInfoAccess>::index()
+		{
+			return Some(system_token_id.asset_id)
+		}
+
+		let location = MultiLocation::new(
+			1,
+			X3(
+				Parachain(system_token_id.para_id),
+				PalletInstance(pallet_index),
+				GeneralIndex(system_token_id.asset_id.into()),
+			),
+		);
+		pallet_asset_link::AssetMultiLocationId::<Runtime>::get(location)
+	}
+}
+
 impl pallet_system_token_payment::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Assets = Assets;
-	type OnChargeSystemToken = TransactionFeeCharger<
-		pallet_assets::BalanceToAssetBalance<Balances, Runtime, ConvertInto>,
-		CreditToBucket<Runtime>,
-	>;
+	type OnChargeSystemToken = TransactionFeeCharger<FeeConversion, CreditToBucket<Runtime>>;
 	type VotingHandler = ParachainSystem;
 	type PalletId = FeeTreasuryId;
 }
@@ -284,7 +323,7 @@ impl pallet_assets::Config for Runtime {
 	type MetadataDepositPerByte = MetadataDepositPerByte;
 	type ApprovalDeposit = ApprovalDeposit;
 	type StringLimit = AssetsStringLimit;
-	type Freezer = ();
+	type Freezer = AssetLocker;
 	type Extra = ();
 	type WeightInfo = weights::pallet_assets::WeightInfo<Runtime>;
 	type CallbackHandle = ();
@@ -294,6 +333,39 @@ impl pallet_assets::Config for Runtime {
 	type BenchmarkHelper = ();
 }
 
+parameter_types! {
+	pub const AssetSwapPalletId: PalletId = PalletId(*b"py/aswap");
+	pub const LiquidityProviderFee: Permill = Permill::from_parts(3_000); // 0.3%
+	pub const MinimumLiquidity: Balance = 1_000;
+}
+
+impl pallet_asset_swap::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AssetId = AssetIdForTrustBackedAssets;
+	type Balance = Balance;
+	type Assets = Assets;
+	type PalletId = AssetSwapPalletId;
+	type LiquidityProviderFee = LiquidityProviderFee;
+	type MinimumLiquidity = MinimumLiquidity;
+	type WeightInfo = pallet_asset_swap::weights::SubstrateWeight<Runtime>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = AssetSwapBenchmarkHelper;
+}
+
+/// Creates the assets of the pools benchmarked by `pallet_asset_swap`.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct AssetSwapBenchmarkHelper;
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_asset_swap::BenchmarkHelper<AssetIdForTrustBackedAssets> for AssetSwapBenchmarkHelper {
+	fn create_asset(seed: u32) -> AssetIdForTrustBackedAssets {
+		use frame_support::traits::tokens::fungibles::Create;
+		let admin: AccountId = frame_benchmarking::account("asset_admin", seed, 0);
+		<Assets as Create<AccountId>>::create(seed, admin, true, 1)
+			.expect("the benchmarked assets don't exist yet; qed");
+		seed
+	}
+}
+
 parameter_types! {
 	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
 	pub const DepositBase: Balance = deposit(1, 88);
@@ -482,9 +554,9 @@ parameter_types! {
 
 impl cumulus_pallet_parachain_system::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	type OnSystemEvent = ();
+	type OnSystemEvent = HrmpChannels;
 	type SelfParaId = parachain_info::Pallet<Runtime>;
-	type DmpMessageHandler = DmpQueue;
+	type DmpMessageHandler = pallet_hrmp_channels::HandleHrmpNotifications<Runtime, DmpQueue>;
 	type ReservedDmpWeight = ReservedDmpWeight;
 	type OutboundXcmpMessageSource = XcmpQueue;
 	type XcmpMessageHandler = XcmpQueue;
@@ -494,6 +566,46 @@ impl cumulus_pallet_parachain_system::Config for Runtime {
 
 impl parachain_info::Config for Runtime {}
 
+parameter_types! {
+	/// The index of the `hrmp` pallet in the relay chain runtime, `Hrmp: parachains_hrmp = 60` in
+	/// the `construct_runtime!` of the InfraBlockspace relay runtime, kept from the Polkadot
+	/// runtime it is derived from. It must follow the relay runtime if the pallet is moved.
+	pub const RelayHrmpPalletIndex: u8 = 60;
+	pub RelayHrmpCallWeight: Weight = Weight::from_parts(1_000_000_000, 65_536);
+	/// The requests are sent unpaid, as the chain is a system parachain.
+	pub const RelayHrmpFee: Option<MultiAsset> = None;
+}
+
+impl pallet_hrmp_channels::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ChannelManagerOrigin = EnsureRoot<AccountId>;
+	type SelfParaId = parachain_info::Pallet<Runtime>;
+	type AutoAccept = Nothing;
+	type XcmSender =
+		cumulus_primitives_utility::ParentAsUmp<ParachainSystem, InfrablockspaceXcm, ()>;
+	type RelayHrmpPalletIndex = RelayHrmpPalletIndex;
+	type RelayCallWeight = RelayHrmpCallWeight;
+	type RelayFee = RelayHrmpFee;
+	type WeightInfo = pallet_hrmp_channels::weights::SubstrateWeight<Runtime>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = HrmpChannelsBenchmarkHelper;
+}
+
+/// Sets the XCM version of the requests sent to the relay chain by `pallet_hrmp_channels` in
+/// its benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct HrmpChannelsBenchmarkHelper;
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_hrmp_channels::BenchmarkHelper for HrmpChannelsBenchmarkHelper {
+	fn prepare_relay_sender() {
+		InfrablockspaceXcm::force_default_xcm_version(
+			RuntimeOrigin::root(),
+			Some(xcm::latest::VERSION),
+		)
+		.expect("root may set the default XCM version; qed");
+	}
+}
+
 impl cumulus_pallet_aura_ext::Config for Runtime {}
 
 impl cumulus_pallet_xcmp_queue::Config for Runtime {
@@ -517,6 +629,56 @@ impl cumulus_pallet_dmp_queue::Config for Runtime {
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
 }
 
+parameter_types! {
+	/// The relay chain may execute XCM for free when the pallet is added to a running chain.
+	pub InitialFreeExecutionOrigins: Vec<(MultiLocation, Weight)> =
+		vec![(MultiLocation::parent(), Weight::MAX)];
+}
+
+/// Calls dispatched by the utility, proxy, multisig and sudo calls, so that the calls denied in
+/// `Transact` by the XCM barrier can't be wrapped in them.
+pub struct DispatchedCalls;
+impl cumulus_pallet_xcm_barrier::InnerCalls<RuntimeCall> for DispatchedCalls {
+	fn inner_calls(call: &RuntimeCall) -> Vec<&RuntimeCall> {
+		match call {
+			RuntimeCall::Utility(
+				pallet_utility::Call::batch { calls } |
+				pallet_utility::Call::batch_all { calls } |
+				pallet_utility::Call::force_batch { calls },
+			) => calls.iter().collect(),
+			RuntimeCall::Utility(
+				pallet_utility::Call::as_derivative { call, .. } |
+				pallet_utility::Call::dispatch_as { call, .. } |
+				pallet_utility::Call::with_weight { call, .. },
+			) |
+			RuntimeCall::Proxy(
+				pallet_proxy::Call::proxy { call, .. } |
+				pallet_proxy::Call::proxy_announced { call, .. },
+			) |
+			RuntimeCall::Multisig(
+				pallet_multisig::Call::as_multi_threshold_1 { call, .. } |
+				pallet_multisig::Call::as_multi { call, .. },
+			) |
+			RuntimeCall::Sudo(
+				pallet_sudo::Call::sudo { call } |
+				pallet_sudo::Call::sudo_unchecked_weight { call, .. } |
+				pallet_sudo::Call::sudo_as { call, .. },
+			) => vec![&**call],
+			_ => Vec::new(),
+		}
+	}
+}
+
+impl cumulus_pallet_xcm_barrier::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AdminOrigin = AssetsForceOrigin;
+	type InitialFreeExecutionOrigins = InitialFreeExecutionOrigins;
+	type MaxFreeExecutionOrigins = ConstU32<100>;
+	type MaxDeniedInstructions = ConstU32<32>;
+	type InnerCalls = DispatchedCalls;
+	type WeightInfo = cumulus_pallet_xcm_barrier::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	pub const Period: u32 = 6 * HOURS;
 	pub const Offset: u32 = 0;
@@ -542,6 +704,13 @@ impl pallet_aura::Config for Runtime {
 	type MaxAuthorities = ConstU32<100_000>;
 }
 
+impl pallet_collator_equivocation::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	// One session worth of slots.
+	type ReportLongevity = ConstU64<{ 6 * HOURS as u64 }>;
+	type WeightInfo = pallet_collator_equivocation::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	pub const PotId: PalletId = PalletId(*b"PotStake");
 	pub const MaxCandidates: u32 = 1000;
@@ -648,12 +817,16 @@ construct_runtime!(
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>} = 22,
 		Aura: pallet_aura::{Pallet, Storage, Config<T>} = 23,
 		AuraExt: cumulus_pallet_aura_ext::{Pallet, Storage, Config} = 24,
+		CollatorEquivocation: pallet_collator_equivocation::{Pallet, Call, Storage, Event<T>, ValidateUnsigned} = 25,
 
 		// XCM helpers.
 		XcmpQueue: cumulus_pallet_xcmp_queue::{Pallet, Call, Storage, Event<T>} = 30,
 		InfrablockspaceXcm: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin, Config} = 31,
 		CumulusXcm: cumulus_pallet_xcm::{Pallet, Event<T>, Origin} = 32,
 		DmpQueue: cumulus_pallet_dmp_queue::{Pallet, Call, Storage, Event<T>} = 33,
+		XcmBarrier: cumulus_pallet_xcm_barrier::{Pallet, Call, Storage, Event<T>, Config} = 34,
+		XcmRateLimiter: pallet_xcm_rate_limiter::{Pallet, Storage, Event<T>} = 35,
+		HrmpChannels: pallet_hrmp_channels::{Pallet, Call, Storage, Event<T>} = 36,
 
 		// Handy utilities.
 		Utility: pallet_utility::{Pallet, Call, Event} = 40,
@@ -665,6 +838,9 @@ construct_runtime!(
 		Uniques: pallet_uniques::{Pallet, Call, Storage, Event<T>} = 51,
 		AssetLink: pallet_asset_link = 52,
 		SystemTokenAggregator: system_token_aggregator = 53,
+		AssetSwap: pallet_asset_swap::{Pallet, Call, Storage, Event<T>} = 54,
+		AssetLocker: pallet_asset_locker::{Pallet, Call, Storage, Event<T>} = 55,
+		TrappedAssets: pallet_trapped_assets::{Pallet, Storage} = 56,
 	}
 );
 
@@ -685,8 +861,15 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_system_token_payment::ChargeSystemToken<Runtime>,
+	PrioritizeByFeeValue<Runtime, LocalAssetConversion<FeeConversion, SystemTokenAsset>>,
 );
+
+/// The system token paying the fee of a transaction with the signed extensions `extra`, `None`
+/// when it pays with the native token.
+pub fn fee_asset_of(extra: &SignedExtra) -> Option<&SystemTokenId> {
+	let (.., fee_payment) = extra;
+	fee_payment.system_token_id()
+}
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
@@ -714,6 +897,8 @@ mod benches {
 	define_benchmarks!(
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_assets, Assets]
+		[pallet_asset_swap, AssetSwap]
+		[pallet_collator_equivocation, CollatorEquivocation]
 		[pallet_balances, Balances]
 		[pallet_multisig, Multisig]
 		[pallet_proxy, Proxy]
@@ -723,6 +908,10 @@ mod benches {
 		[pallet_timestamp, Timestamp]
 		[pallet_collator_selection, CollatorSelection]
 		[cumulus_pallet_xcmp_queue, XcmpQueue]
+		[cumulus_pallet_xcm_barrier, XcmBarrier]
+		[pallet_asset_locker, AssetLocker]
+		[pallet_xcm_rate_limiter, XcmRateLimiter]
+		[pallet_hrmp_channels, HrmpChannels]
 		// XCM
 		[pallet_xcm, InfrablockspaceXcm]
 		// NOTE: Make sure you point to the individual modules below.
@@ -889,12 +1078,104 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl assets_common::runtime_api::AssetSwapApi<Block> for Runtime {
+		fn quote_exact_in(
+			give: xcm::latest::MultiAsset,
+			want: xcm::latest::AssetId,
+		) -> Result<Option<xcm::latest::MultiAsset>, assets_common::runtime_api::FungiblesAccessError> {
+			assets_common::exchange::quote_exact_in::<Runtime, PoolAssetsConvertedConcreteId>(give, want)
+		}
+
+		fn quote_exact_out(
+			give: xcm::latest::AssetId,
+			want: xcm::latest::MultiAsset,
+		) -> Result<Option<xcm::latest::MultiAsset>, assets_common::runtime_api::FungiblesAccessError> {
+			assets_common::exchange::quote_exact_out::<Runtime, PoolAssetsConvertedConcreteId>(give, want)
+		}
+	}
+
+	impl assets_common::runtime_api::AssetLocksApi<
+		Block,
+		AccountId,
+	> for Runtime
+	{
+		fn query_locked_assets(account: AccountId) -> Result<Vec<(xcm::latest::MultiAsset, xcm::VersionedMultiLocation)>, assets_common::runtime_api::FungiblesAccessError> {
+			use assets_common::fungible_conversion::MultiAssetConverter;
+			AssetLocker::locks_of(&account)
+				.into_iter()
+				.map(|(asset_id, amount, unlocker)| {
+					Ok((TrustBackedAssetsConvertedConcreteId::convert_ref((asset_id, amount))?, unlocker))
+				})
+				.collect()
+		}
+
+		fn query_unlockable_assets(account: AccountId) -> Vec<(xcm::VersionedMultiAsset, xcm::VersionedMultiLocation)> {
+			AssetLocker::unlockable_of(&account)
+				.into_iter()
+				.filter_map(|(locker, asset_id, amount)| {
+					let asset_id = xcm::latest::AssetId::try_from(asset_id).ok()?;
+					Some((xcm::latest::MultiAsset::from((asset_id, amount)).into(), locker))
+				})
+				.collect()
+		}
+	}
+
+	impl assets_common::runtime_api::TrappedAssetsApi<Block> for Runtime {
+		fn query_trapped_assets(origin: xcm::latest::MultiLocation) -> Vec<(xcm::latest::MultiAssets, u32)> {
+			TrappedAssets::trapped_assets(&origin)
+		}
+
+		fn claim_trapped_assets_program(
+			assets: xcm::latest::MultiAssets,
+			beneficiary: xcm::latest::MultiLocation,
+		) -> xcm::latest::Xcm<()> {
+			TrappedAssets::claim_program(assets, beneficiary)
+		}
+	}
+
+	impl assets_common::runtime_api::DryRunApi<Block, RuntimeCall, RuntimeEvent, OriginCaller> for Runtime {
+		fn dry_run_call(
+			origin: OriginCaller,
+			call: RuntimeCall,
+		) -> Result<assets_common::runtime_api::CallDryRunEffects<RuntimeEvent>, assets_common::runtime_api::DryRunError> {
+			Ok(assets_common::dry_run::dry_run_call::<Runtime, XcmRouter>(origin.into(), call))
+		}
+
+		fn dry_run_xcm(
+			origin_location: xcm::VersionedMultiLocation,
+			xcm: xcm::VersionedXcm<RuntimeCall>,
+		) -> Result<assets_common::runtime_api::XcmDryRunEffects<RuntimeEvent>, assets_common::runtime_api::DryRunError> {
+			assets_common::dry_run::dry_run_xcm::<
+				Runtime,
+				XcmRouter,
+				XcmConfig,
+				xcm_config::MessageQueueWeightLimit,
+			>(origin_location, xcm)
+		}
+	}
+
 	impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
 		fn collect_collation_info(header: &<Block as BlockT>::Header) -> cumulus_primitives_core::CollationInfo {
 			ParachainSystem::collect_collation_info(header)
 		}
 	}
 
+	impl cumulus_primitives_core::CollatorEquivocationApi<Block> for Runtime {
+		fn equivocation_report_extrinsic(
+			first_header: <Block as BlockT>::Header,
+			second_header: <Block as BlockT>::Header,
+		) -> Option<<Block as BlockT>::Extrinsic> {
+			let call = CollatorEquivocation::report_equivocation_call(first_header, second_header)?;
+			Some(UncheckedExtrinsic::new_unsigned(call.into()))
+		}
+	}
+
+	impl cumulus_primitives_core::XcmpVersionsApi<Block> for Runtime {
+		fn xcmp_versions() -> Vec<(cumulus_primitives_core::ParaId, cumulus_primitives_core::XcmVersionReport)> {
+			XcmpQueue::version_reports()
+		}
+	}
+
 	#[cfg(feature = "try-runtime")]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
@@ -1018,7 +1299,42 @@ impl_runtime_apis! {
 				}
 
 				fn worst_case_asset_exchange() -> Result<(MultiAssets, MultiAssets), BenchmarkError> {
-					Err(BenchmarkError::Skip)
+					use frame_support::traits::tokens::fungibles::Mutate;
+					use pallet_asset_swap::BenchmarkHelper;
+					use xcm_config::TrustBackedAssetsPalletLocation;
+
+					// Exchange through a pool of two trust backed assets.
+					let (give, want) = (
+						AssetSwapBenchmarkHelper::create_asset(1),
+						AssetSwapBenchmarkHelper::create_asset(2),
+					);
+					let provider: AccountId = frame_benchmarking::account("provider", 0, 0);
+					for asset in [give, want] {
+						Assets::mint_into(asset, &provider, 1_000 * UNITS)
+							.map_err(|_| BenchmarkError::Stop("failed to mint the pool assets"))?;
+					}
+					AssetSwap::create_pool(RuntimeOrigin::signed(provider.clone()), give, want)
+						.map_err(|_| BenchmarkError::Stop("failed to create the pool"))?;
+					AssetSwap::add_liquidity(
+						RuntimeOrigin::signed(provider),
+						give,
+						want,
+						1_000 * UNITS,
+						1_000 * UNITS,
+						0,
+						0,
+					)
+					.map_err(|_| BenchmarkError::Stop("failed to add liquidity"))?;
+
+					let location = |asset: AssetIdForTrustBackedAssets| {
+						let mut location = TrustBackedAssetsPalletLocation::get();
+						location.push_interior(GeneralIndex(asset.into())).map(|_| location)
+					};
+					let (give, want) = match (location(give), location(want)) {
+						(Ok(give), Ok(want)) => (give, want),
+						_ => return Err(BenchmarkError::Stop("failed to locate the pool assets")),
+					};
+					Ok(((give, UNITS).into(), (want, UNITS / 2).into()))
 				}
 
 				fn universal_alias() -> Result<Junction, BenchmarkError> {
@@ -1041,7 +1357,26 @@ impl_runtime_apis! {
 				}
 
 				fn unlockable_asset() -> Result<(MultiLocation, MultiLocation, MultiAsset), BenchmarkError> {
-					Err(BenchmarkError::Skip)
+					use frame_support::traits::tokens::fungibles::{Create, Mutate};
+					use xcm_config::TrustBackedAssetsPalletLocation;
+
+					// The relay chain may unlock a trust backed asset of the owner.
+					let asset_id: AssetIdForTrustBackedAssets = 1;
+					let admin: AccountId = frame_benchmarking::account("asset_admin", 0, 0);
+					let owner: AccountId = frame_benchmarking::account("owner", 0, 0);
+					<Assets as Create<AccountId>>::create(asset_id, admin, true, 1)
+						.map_err(|_| BenchmarkError::Stop("failed to create the asset"))?;
+					Assets::mint_into(asset_id, &owner, 1_000 * UNITS)
+						.map_err(|_| BenchmarkError::Stop("failed to mint the asset"))?;
+
+					let mut asset_location = TrustBackedAssetsPalletLocation::get();
+					asset_location
+						.push_interior(GeneralIndex(asset_id.into()))
+						.map_err(|_| BenchmarkError::Stop("failed to locate the asset"))?;
+					let owner_location = AccountId32 { network: None, id: owner.into() }.into();
+					pallet_asset_locker::TrustedUnlockers::<Runtime>::insert(MultiLocation::parent(), ());
+					pallet_asset_locker::TrustedLockers::<Runtime>::insert(MultiLocation::parent(), ());
+					Ok((Parent.into(), owner_location, (asset_location, 100 * UNITS).into()))
 				}
 			}
 
@@ -14,28 +14,46 @@
 // limitations under the License.
 
 use super::{
-	AccountId, AllPalletsWithSystem, AssetLink, Assets, Authorship, Balance, Balances,
-	InfrablockspaceXcm, ParachainInfo, ParachainSystem, Runtime, RuntimeCall, RuntimeEvent,
-	RuntimeOrigin, WeightToFee, XcmpQueue,
+	AccountId, AllPalletsWithSystem, AssetLink, AssetLocker, Assets, AssetsForceOrigin, Authorship,
+	Balance, Balances, BlockNumber, DmpQueue, InfrablockspaceXcm, ParachainInfo, ParachainSystem,
+	Runtime, RuntimeCall, RuntimeEvent, RuntimeOrigin, WeightToFee, XcmpQueue, CENTS, MILLICENTS,
+};
+use assets_common::{
+	bridging::{
+		Bridge, BridgedNetworks, BridgedUniversalAliases, ForwardExportToBridgeHub,
+		IsBridgedReserve, ToBridgeHubRouter,
+	},
+	matching::{
+		StartsWith, StartsWithExplicitGlobalConsensus, StartsWithExplicitGlobalConsensusExcept,
+	},
 };
-use assets_common::matching::{StartsWith, StartsWithExplicitGlobalConsensus};
 use frame_support::{
 	match_types, parameter_types,
 	traits::{ConstU32, Contains, Everything, Nothing, PalletInfoAccess},
 };
 
 use infrablockspace_parachain::primitives::Sibling;
+use pallet_trapped_assets::IndexTrappedAssets;
 use pallet_xcm::XcmPassthrough;
-use parachains_common::{xcm_config::AssetFeeAsExistentialDepositMultiplier, AssetId};
-use sp_runtime::traits::ConvertInto;
+use pallet_xcm_rate_limiter::RateLimitedRouter;
+use parachains_common::{
+	impls::ToStakingPot,
+	xcm_config::{
+		AllowGovernedUnpaidExecutionFrom, AssetFeeAsExistentialDepositMultiplier,
+		DenyGovernedInstructions, DenyThenTry,
+	},
+	AssetId, AssetIdForTrustBackedAssets,
+};
+use sp_runtime::traits::{Convert, ConvertInto};
+use sp_std::prelude::*;
 use xcm::latest::prelude::*;
 use xcm_builder::{
-	AccountId32Aliases, AllowExplicitUnpaidExecutionFrom, AllowSubscriptionsFrom,
-	AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, CurrencyAdapter, EnsureXcmOrigin,
-	FungiblesAdapter, IsConcrete, LocalMint, NativeAsset, NonLocalMint, ParentAsSuperuser,
-	ParentIsPreset, RelayChainAsNative, SiblingParachainAsNative, SiblingParachainConvertsVia,
-	SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation, TakeWeightCredit,
-	WeightInfoBounds,
+	AccountId32Aliases, AllowSubscriptionsFrom, AllowTopLevelPaidExecutionFrom, CurrencyAdapter,
+	EnsureXcmOrigin, FungiblesAdapter, IsConcrete, LocalMint, NativeAsset, NonLocalMint,
+	ParentAsSuperuser, ParentIsPreset, RelayChainAsNative, SiblingParachainAsNative,
+	SiblingParachainConvertsVia, SignedAccountId32AsNative, SignedToAccountId32,
+	SovereignSignedViaLocation, TakeWeightCredit, UsingComponents, WeightInfoBounds,
+	WithComputedOrigin,
 };
 use xcm_executor::{traits::WithOriginFilter, XcmExecutor};
 use xcm_primitives::TrappistDropAssets;
@@ -56,6 +74,26 @@ parameter_types! {
 	pub CheckingAccount: AccountId = InfrablockspaceXcm::check_account();
 }
 
+parameter_types! {
+	/// The networks bridged through a sibling bridge hub, with the location of the bridge hub and
+	/// the fee it is paid by the sovereign account of the chain. Set by governance.
+	pub storage BridgeTable: Vec<Bridge> = Vec::new();
+	/// Whether the foreign assets of the networks which are not in `BridgeTable` are ignored. Set
+	/// by governance once the balances of these assets have been moved out, as they can't be
+	/// transacted anymore afterwards.
+	pub storage ForeignAssetsOfBridgedNetworksOnly: bool = false;
+}
+
+/// The networks whose foreign assets are transacted: all of them, or only the bridged ones once
+/// `ForeignAssetsOfBridgedNetworksOnly` is set.
+pub struct AcceptedForeignNetworks;
+impl Contains<NetworkId> for AcceptedForeignNetworks {
+	fn contains(network: &NetworkId) -> bool {
+		!ForeignAssetsOfBridgedNetworksOnly::get() ||
+			BridgedNetworks::<BridgeTable>::contains(network)
+	}
+}
+
 /// Type for specifying how a `MultiLocation` can be converted into an `AccountId`. This is used
 /// when determining ownership of accounts for asset transacting and when attempting to use XCM
 /// `Transact` in order to determine the dispatch Origin.
@@ -112,11 +150,19 @@ pub type ForeignAssetsConvertedConcreteId = assets_common::ForeignAssetsConverte
 		// - foreign assets from our consensus should be: `MultiLocation {parent: 1, X*(Parachain(xyz))}
 		// - foreign assets outside our consensus with the same `GlobalConsensus(NetworkId)` wont be accepted here
 		StartsWithExplicitGlobalConsensus<UniversalLocationNetworkId>,
+		// Ignore asset which starts explicitly with the `GlobalConsensus(NetworkId)` of a network
+		// which is not accepted
+		StartsWithExplicitGlobalConsensusExcept<AcceptedForeignNetworks>,
 	),
 	AssetLink,
 	Balance,
 >;
 
+/// Locally issued system tokens and the linked foreign ones, which may pay fees and be swapped
+/// through the pools of `AssetSwap`.
+pub type PoolAssetsConvertedConcreteId =
+	(TrustBackedAssetsConvertedConcreteId, ForeignAssetsConvertedConcreteId);
+
 /// Means for transacting foreign assets from different global consensus.
 pub type ForeignFungiblesTransactor = FungiblesAdapter<
 	// Use this fungibles implementation:
@@ -164,6 +210,9 @@ parameter_types! {
 	pub const MaxInstructions: u32 = 100;
 	pub const MaxAssetsIntoHolding: u32 = 64;
 	pub XcmAssetFeesReceiver: Option<AccountId> = Authorship::author();
+	/// Assets tried first to pay for XCM execution, in this order. The other assets in the fee are
+	/// tried afterwards.
+	pub XcmFeeAssetPreference: Vec<MultiLocation> = vec![DotLocation::get()];
 }
 
 match_types! {
@@ -283,16 +332,26 @@ impl Contains<RuntimeCall> for SafeCallFilter {
 	}
 }
 
-pub type Barrier = (
-	// Weight that is paid for may be consumed.
-	TakeWeightCredit,
-	AllowUnpaidExecutionFrom<ParentOrSiblings>,
-	AllowTopLevelPaidExecutionFrom<Everything>,
-	// Messages coming from system parachains need not pay for execution.
-	AllowExplicitUnpaidExecutionFrom<Everything>,
-	// Subscriptions for version tracking are OK.
-	AllowSubscriptionsFrom<Everything>,
-);
+pub type Barrier = DenyThenTry<
+	// Instruction patterns denied by governance.
+	DenyGovernedInstructions<Runtime>,
+	(
+		// Weight that is paid for may be consumed.
+		TakeWeightCredit,
+		// The messages of the bridged networks start with `UniversalOrigin` and `DescendOrigin`.
+		WithComputedOrigin<
+			(
+				AllowTopLevelPaidExecutionFrom<Everything>,
+				// Origins allowed by governance need not pay for execution, up to their weight cap.
+				AllowGovernedUnpaidExecutionFrom<Runtime>,
+				// Subscriptions for version tracking are OK.
+				AllowSubscriptionsFrom<Everything>,
+			),
+			UniversalLocation,
+			ConstU32<8>,
+		>,
+	),
+>;
 
 pub type AssetFeeAsExistentialDepositMultiplierFeeCharger = AssetFeeAsExistentialDepositMultiplier<
 	Runtime,
@@ -319,10 +378,10 @@ impl xcm_executor::Config for XcmConfig {
 	type XcmSender = XcmRouter;
 	type AssetTransactor = AssetTransactors;
 	type OriginConverter = XcmOriginToTransactDispatchOrigin;
-	// Statemint does not recognize a reserve location for any asset. This does not prevent
-	// Statemint acting _as_ a reserve location for DOT and assets created under `pallet-assets`.
-	// For DOT, users must use teleport where allowed (e.g. with the Relay Chain).
-	type IsReserve = ();
+	// Statemint only recognizes the bridged networks as reserve locations of their assets. This
+	// does not prevent Statemint acting _as_ a reserve location for DOT and assets created under
+	// `pallet-assets`. For DOT, users must use teleport where allowed (e.g. with the Relay Chain).
+	type IsReserve = IsBridgedReserve<BridgeTable>;
 	type IsTeleporter = ();
 	type UniversalLocation = UniversalLocation;
 	type Barrier = Barrier;
@@ -331,48 +390,76 @@ impl xcm_executor::Config for XcmConfig {
 		RuntimeCall,
 		MaxInstructions,
 	>;
-	type Trader = (
-		cumulus_primitives_utility::TakeFirstAssetTrader<
+	type Trader = cumulus_primitives_utility::TakePreferredAssetTrader<
+		AccountId,
+		XcmFeeAssetPreference,
+		DotLocation,
+		UsingComponents<WeightToFee, DotLocation, AccountId, Balances, ToStakingPot<Runtime>>,
+		AssetFeeAsExistentialDepositMultiplierFeeCharger,
+		PoolAssetsConvertedConcreteId,
+		Assets,
+		cumulus_primitives_utility::XcmFeesTo32ByteAccount<
+			AssetTransactors,
 			AccountId,
-			AssetFeeAsExistentialDepositMultiplierFeeCharger,
-			ForeignAssetsConvertedConcreteId,
-			Assets,
-			cumulus_primitives_utility::XcmFeesTo32ByteAccount<
-				LocalIssuedFungiblesTransactor,
-				AccountId,
-				XcmAssetFeesReceiver,
-			>,
+			XcmAssetFeesReceiver,
 		>,
-	);
+	>;
 	type ResponseHandler = InfrablockspaceXcm;
-	type AssetTrap =
-		TrappistDropAssets<AssetId, AssetLink, Assets, Balances, InfrablockspaceXcm, AccountId>;
-	type AssetClaims = InfrablockspaceXcm;
+	type AssetTrap = TrappistDropAssets<
+		AssetId,
+		AssetLink,
+		Assets,
+		Balances,
+		IndexTrappedAssets<Runtime, InfrablockspaceXcm>,
+		AccountId,
+	>;
+	type AssetClaims = IndexTrappedAssets<Runtime, InfrablockspaceXcm>;
 	type SubscriptionService = InfrablockspaceXcm;
 	type PalletInstancesInfo = AllPalletsWithSystem;
 	type MaxAssetsIntoHolding = MaxAssetsIntoHolding;
-	type AssetLocker = ();
-	type AssetExchanger = ();
+	type AssetLocker = AssetLocker;
+	type AssetExchanger =
+		assets_common::exchange::PoolAssetExchanger<Runtime, PoolAssetsConvertedConcreteId>;
 	type FeeManager = ();
-	type MessageExporter = ();
-	type UniversalAliases = Nothing;
+	type MessageExporter = ForwardExportToBridgeHub<BridgeTable, XcmpQueue, UniversalLocation>;
+	type UniversalAliases = BridgedUniversalAliases<BridgeTable>;
 	type CallDispatcher = WithOriginFilter<SafeCallFilter>;
 	type SafeCallFilter = SafeCallFilter;
 }
 
+/// The weight the message queue of `origin` lets a single message use: the DMP queue for the
+/// Relay Chain, the XCMP queue for the other locations.
+pub struct MessageQueueWeightLimit;
+impl Convert<MultiLocation, Weight> for MessageQueueWeightLimit {
+	fn convert(origin: MultiLocation) -> Weight {
+		if origin == MultiLocation::parent() {
+			DmpQueue::max_individual_weight()
+		} else {
+			XcmpQueue::max_individual_weight()
+		}
+	}
+}
+
 /// Converts a local signed origin into an XCM multilocation.
 /// Forms the basis for local origins sending/executing XCMs.
 pub type LocalOriginToLocation = SignedToAccountId32<RuntimeOrigin, AccountId, RelayNetwork>;
 
-/// The means for routing XCM messages which are not for local execution into the right message
-/// queues.
-pub type XcmRouter = (
+/// The means for routing XCM messages to the relay chain and the sibling chains.
+pub type LocalXcmRouter = (
 	// Two routers - use UMP to communicate with the relay chain:
 	cumulus_primitives_utility::ParentAsUmp<ParachainSystem, InfrablockspaceXcm, ()>,
 	// ..and XCMP to communicate with the sibling chains.
 	XcmpQueue,
 );
 
+/// The means for routing XCM messages which are not for local execution into the right message
+/// queues.
+pub type XcmRouter = (
+	LocalXcmRouter,
+	// ..and the sibling bridge hubs to communicate with the bridged networks.
+	ToBridgeHubRouter<BridgeTable, XcmpQueue, UniversalLocation>,
+);
+
 #[cfg(feature = "runtime-benchmarks")]
 parameter_types! {
 	pub ReachableDest: Option<MultiLocation> = Some(Parent.into());
@@ -380,9 +467,11 @@ parameter_types! {
 
 impl pallet_xcm::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	// We want to disallow users sending (arbitrary) XCMs from this chain.
-	type SendXcmOrigin = EnsureXcmOrigin<RuntimeOrigin, ()>;
-	type XcmRouter = XcmRouter;
+	// Users may send their own XCMs, which are rate limited and charged.
+	type SendXcmOrigin = EnsureXcmOrigin<RuntimeOrigin, LocalOriginToLocation>;
+	// The bridges are not reachable by the users, as the bridge hubs are paid by the sovereign
+	// account of the chain, not by the sender.
+	type XcmRouter = RateLimitedRouter<Runtime, LocalXcmRouter>;
 	// We support local origins dispatching XCM executions in principle...
 	type ExecuteXcmOrigin = EnsureXcmOrigin<RuntimeOrigin, LocalOriginToLocation>;
 	// ... but disallow generic XCM execution. As a result only teleports and reserve transfers are allowed.
@@ -410,6 +499,43 @@ impl pallet_xcm::Config for Runtime {
 	type ReachableDest = ReachableDest;
 }
 
+impl pallet_asset_locker::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AssetId = AssetIdForTrustBackedAssets;
+	type Balance = Balance;
+	type Assets = Assets;
+	type AssetMatcher = TrustBackedAssetsConvertedConcreteId;
+	type AccountIdConverter = LocationToAccountId;
+	// Governance allows the locations which may hold locks on our assets, and lock theirs for us
+	// to unlock.
+	type AdminOrigin = AssetsForceOrigin;
+	type MaxLocks = ConstU32<8>;
+	type MaxTrustedLocations = ConstU32<100>;
+	type WeightInfo = pallet_asset_locker::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_trapped_assets::Config for Runtime {}
+
+parameter_types! {
+	pub const XcmMessageDeposit: Balance = CENTS;
+	pub const XcmByteDeposit: Balance = MILLICENTS;
+	pub const XcmRateLimitWindow: BlockNumber = 10;
+}
+
+impl pallet_xcm_rate_limiter::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AccountIdConverter = LocationToAccountId;
+	type Currency = Balances;
+	type MessageDeposit = XcmMessageDeposit;
+	type ByteDeposit = XcmByteDeposit;
+	type Window = XcmRateLimitWindow;
+	type MaxMessagesPerAccount = ConstU32<5>;
+	type MaxBytesPerAccount = ConstU32<{ 16 * 1024 }>;
+	type MaxMessagesPerDestination = ConstU32<50>;
+	type MaxBytesPerDestination = ConstU32<{ 64 * 1024 }>;
+	type WeightInfo = pallet_xcm_rate_limiter::weights::SubstrateWeight<Runtime>;
+}
+
 impl cumulus_pallet_xcm::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type XcmExecutor = XcmExecutor<XcmConfig>;
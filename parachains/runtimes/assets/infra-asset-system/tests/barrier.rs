@@ -0,0 +1,64 @@
+use asset_test_utils::ExtBuilder;
+use frame_support::weights::Weight;
+use infra_asset_system_runtime::{xcm_config::Barrier, Runtime, RuntimeCall};
+use xcm::latest::prelude::*;
+use xcm_executor::traits::ShouldExecute;
+
+const BRIDGED: NetworkId = NetworkId::ByGenesis([1; 32]);
+const BOB: [u8; 32] = [2u8; 32];
+
+fn bridge_hub() -> MultiLocation {
+	MultiLocation::new(1, X1(Parachain(1002)))
+}
+
+fn bridged_prefix() -> Vec<Instruction<RuntimeCall>> {
+	vec![UniversalOrigin(GlobalConsensus(BRIDGED)), DescendOrigin(X1(Parachain(1000)))]
+}
+
+fn paid_deposit() -> Vec<Instruction<RuntimeCall>> {
+	let fees: MultiAsset = (MultiLocation::parent(), 1_000_000_000u128).into();
+	vec![
+		WithdrawAsset(fees.clone().into()),
+		BuyExecution { fees, weight_limit: Unlimited },
+		DepositAsset {
+			assets: Wild(AllCounted(1)),
+			beneficiary: AccountId32 { network: None, id: BOB }.into(),
+		},
+	]
+}
+
+fn should_execute(origin: MultiLocation, mut message: Vec<Instruction<RuntimeCall>>) -> bool {
+	Barrier::should_execute(
+		&origin,
+		&mut message,
+		Weight::from_parts(1_000_000_000, 64 * 1024),
+		&mut Weight::zero(),
+	)
+	.is_ok()
+}
+
+#[test]
+fn paid_messages_are_allowed_after_the_computed_origin() {
+	ExtBuilder::<Runtime>::default().build().execute_with(|| {
+		let message = bridged_prefix().into_iter().chain(paid_deposit()).collect();
+
+		assert!(should_execute(bridge_hub(), message));
+	})
+}
+
+#[test]
+fn unpaid_messages_are_denied_after_the_computed_origin() {
+	ExtBuilder::<Runtime>::default().build().execute_with(|| {
+		let message =
+			bridged_prefix().into_iter().chain(paid_deposit().into_iter().skip(2)).collect();
+
+		assert!(!should_execute(bridge_hub(), message));
+	})
+}
+
+#[test]
+fn paid_messages_without_a_computed_origin_are_still_allowed() {
+	ExtBuilder::<Runtime>::default().build().execute_with(|| {
+		assert!(should_execute(MultiLocation::new(1, X1(Parachain(2000))), paid_deposit()));
+	})
+}
@@ -0,0 +1,112 @@
+use asset_test_utils::{ExtBuilder, RuntimeHelper};
+use frame_support::{assert_ok, weights::Weight};
+use infra_asset_system_runtime::{
+	common::AccountId,
+	constants::currency::UNITS,
+	xcm_config::{MessageQueueWeightLimit, XcmConfig, XcmRouter},
+	Balances, Runtime, RuntimeCall, RuntimeEvent, XcmpQueue,
+};
+use infrablockspace_parachain::primitives::Sibling;
+use sp_runtime::traits::AccountIdConversion;
+use xcm::{latest::prelude::*, VersionedMultiLocation, VersionedXcm};
+
+const ALICE: [u8; 32] = [1u8; 32];
+const BOB: [u8; 32] = [2u8; 32];
+const SIBLING: u32 = 2000;
+
+fn sibling() -> MultiLocation {
+	MultiLocation::new(1, X1(Parachain(SIBLING)))
+}
+
+fn sibling_account() -> AccountId {
+	Sibling::from(SIBLING).into_account_truncating()
+}
+
+fn paid_transfer(amount: u128) -> VersionedXcm<RuntimeCall> {
+	VersionedXcm::from(Xcm(vec![
+		WithdrawAsset((Here, amount).into()),
+		BuyExecution { fees: (Here, amount).into(), weight_limit: Unlimited },
+		DepositAsset {
+			assets: Wild(AllCounted(1)),
+			beneficiary: AccountId32 { network: None, id: BOB }.into(),
+		},
+	]))
+}
+
+#[test]
+fn dry_run_call_reports_the_events() {
+	ExtBuilder::<Runtime>::default()
+		.with_balances(vec![(AccountId::from(ALICE), 10 * UNITS)])
+		.build()
+		.execute_with(|| {
+			let call = RuntimeCall::Balances(pallet_balances::Call::transfer {
+				dest: AccountId::from(BOB).into(),
+				value: UNITS,
+			});
+
+			let effects = assets_common::dry_run::dry_run_call::<Runtime, XcmRouter>(
+				RuntimeHelper::<Runtime>::origin_of(AccountId::from(ALICE)),
+				call,
+			);
+
+			assert_ok!(effects.execution_result);
+			assert!(effects.emitted_events.contains(&RuntimeEvent::Balances(
+				pallet_balances::Event::Transfer {
+					from: AccountId::from(ALICE),
+					to: AccountId::from(BOB),
+					amount: UNITS,
+				}
+			)));
+			assert!(effects.forwarded_xcms.is_empty());
+			assert_eq!(Balances::free_balance(AccountId::from(BOB)), UNITS);
+		})
+}
+
+#[test]
+fn dry_run_xcm_reports_the_charged_fees() {
+	ExtBuilder::<Runtime>::default()
+		.with_balances(vec![(sibling_account(), 10 * UNITS)])
+		.build()
+		.execute_with(|| {
+			let effects = assets_common::dry_run::dry_run_xcm::<
+				Runtime,
+				XcmRouter,
+				XcmConfig,
+				MessageQueueWeightLimit,
+			>(VersionedMultiLocation::from(sibling()), paid_transfer(UNITS))
+			.unwrap();
+
+			assert!(matches!(effects.execution_result, Outcome::Complete(_)));
+			let fees = effects.charged_fees.inner();
+			assert_eq!(fees.len(), 1);
+			assert!(matches!(&fees[0], MultiAsset { id: Concrete(id), fun: Fungible(amount) }
+				if *id == Here.into_location() && *amount > 0 && *amount < UNITS));
+		})
+}
+
+#[test]
+fn dry_run_xcm_is_limited_by_the_message_queue_weight() {
+	ExtBuilder::<Runtime>::default()
+		.with_balances(vec![(sibling_account(), 10 * UNITS)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(XcmpQueue::update_xcmp_max_individual_weight(
+				RuntimeHelper::<Runtime>::root_origin(),
+				Weight::from_parts(1, 1),
+			));
+
+			let effects = assets_common::dry_run::dry_run_xcm::<
+				Runtime,
+				XcmRouter,
+				XcmConfig,
+				MessageQueueWeightLimit,
+			>(VersionedMultiLocation::from(sibling()), paid_transfer(UNITS))
+			.unwrap();
+
+			assert!(matches!(
+				effects.execution_result,
+				Outcome::Error(XcmError::WeightLimitReached(_))
+			));
+			assert!(effects.charged_fees.is_none());
+		})
+}
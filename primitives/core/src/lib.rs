@@ -20,6 +20,7 @@
 
 use codec::{Decode, Encode};
 use infrablockspace_parachain::primitives::HeadData;
+use scale_info::TypeInfo;
 use sp_runtime::{traits::Block as BlockT, types::PotVotesResult, RuntimeDebug};
 use sp_std::prelude::*;
 
@@ -33,6 +34,7 @@ pub use infrablockspace_primitives::{
 };
 
 pub use xcm::latest::prelude::*;
+use xcm::{Version as XcmVersion, VersionedMultiLocation, VersionedXcm};
 
 /// A module that re-exports relevant relay chain definitions.
 pub mod relay_chain {
@@ -48,7 +50,7 @@ pub type InboundHrmpMessage =
 pub type OutboundHrmpMessage = infrablockspace_primitives::OutboundHrmpMessage<ParaId>;
 
 /// Error description of a message send failure.
-#[derive(Eq, PartialEq, Copy, Clone, RuntimeDebug, Encode, Decode)]
+#[derive(Eq, PartialEq, Copy, Clone, RuntimeDebug, Encode, Decode, TypeInfo)]
 pub enum MessageSendError {
 	/// The dispatch queue is full.
 	QueueFull,
@@ -72,6 +74,21 @@ impl From<MessageSendError> for &'static str {
 	}
 }
 
+/// The XCM version used to send messages to a recipient.
+#[derive(Eq, PartialEq, Clone, Default, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct XcmVersionReport {
+	/// The version of the last message sent to the recipient.
+	pub version: Option<XcmVersion>,
+	/// The version all messages to the recipient are converted to, overriding the negotiated
+	/// one.
+	pub pinned: Option<XcmVersion>,
+	/// Why the last message to the recipient couldn't be queued, if no message was queued since.
+	///
+	/// The failures of the messages sent by a failing extrinsic are rolled back with it, so are
+	/// not recorded.
+	pub last_failure: Option<MessageSendError>,
+}
+
 /// Information about an XCMP channel.
 pub struct ChannelInfo {
 	/// The maximum number of messages that can be pending in the channel at once.
@@ -131,6 +148,28 @@ impl XcmpMessageSource for () {
 	}
 }
 
+/// Something that queues XCM messages for sending and can inspect them, e.g. to report the
+/// messages sent when dry-running a program.
+pub trait InspectMessageQueues {
+	/// Clear the queued messages.
+	fn clear_messages();
+	/// Return the queued messages, grouped by destination.
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl InspectMessageQueues for Tuple {
+	fn clear_messages() {
+		for_tuples!( #( Tuple::clear_messages(); )* );
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		let mut messages = Vec::new();
+		for_tuples!( #( messages.append(&mut Tuple::get_messages()); )* );
+		messages
+	}
+}
+
 /// The "quality of service" considerations for message sending.
 #[derive(Eq, PartialEq, Clone, Copy, Encode, Decode, RuntimeDebug)]
 pub enum ServiceQuality {
@@ -264,4 +303,23 @@ sp_api::decl_runtime_apis! {
 		/// we are collecting the collation info for.
 		fn collect_collation_info(header: &Block::Header) -> CollationInfo;
 	}
+
+	/// Runtime api to query the XCM versions used to send messages to sibling parachains.
+	pub trait XcmpVersionsApi {
+		/// The XCM version report of each sibling parachain messages were sent to.
+		fn xcmp_versions() -> Vec<(ParaId, XcmVersionReport)>;
+	}
+
+	/// Runtime api to report the block authors sealing different blocks for the same slot.
+	pub trait CollatorEquivocationApi {
+		/// Build the unsigned extrinsic reporting that the author of the slot of both headers
+		/// sealed them both.
+		///
+		/// The headers are given with their seal. Returns `None` if they don't prove an
+		/// equivocation.
+		fn equivocation_report_extrinsic(
+			first_header: Block::Header,
+			second_header: Block::Header,
+		) -> Option<Block::Extrinsic>;
+	}
 }
@@ -20,7 +20,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Encode;
-use cumulus_primitives_core::{MessageSendError, UpwardMessageSender};
+use cumulus_primitives_core::{InspectMessageQueues, MessageSendError, UpwardMessageSender};
 use frame_support::{
 	traits::{
 		tokens::{fungibles, fungibles::Inspect},
@@ -31,7 +31,7 @@ use frame_support::{
 use infrablockspace_runtime_common::xcm_sender::ConstantPrice;
 use sp_runtime::{traits::Saturating, SaturatedConversion};
 use sp_std::{marker::PhantomData, prelude::*};
-use xcm::{latest::prelude::*, WrapVersion};
+use xcm::{latest::prelude::*, VersionedMultiLocation, VersionedXcm, WrapVersion};
 use xcm_builder::TakeRevenue;
 use xcm_executor::traits::{MatchesFungibles, TransactAsset, WeightTrader};
 
@@ -100,6 +100,19 @@ where
 	}
 }
 
+impl<T, W, P> InspectMessageQueues for ParentAsUmp<T, W, P>
+where
+	T: UpwardMessageSender + InspectMessageQueues,
+{
+	fn clear_messages() {
+		T::clear_messages()
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		T::get_messages()
+	}
+}
+
 /// Contains information to handle refund/payment for xcm-execution
 #[derive(Clone, Eq, PartialEq, Debug)]
 struct AssetTraderRefunder {
@@ -109,6 +122,101 @@ struct AssetTraderRefunder {
 	outstanding_concrete_asset: MultiAsset,
 }
 
+/// Returns `asset` with the balance charged by `FeeCharger` for `weight`, at least the minimum
+/// balance of the matching local asset.
+fn charge_weight_in_asset<AccountId, FeeCharger, Matcher, ConcreteAssets>(
+	asset: &MultiAsset,
+	weight: Weight,
+) -> Result<MultiAsset, XcmError>
+where
+	FeeCharger: ChargeWeightInFungibles<AccountId, ConcreteAssets>,
+	Matcher: MatchesFungibles<ConcreteAssets::AssetId, ConcreteAssets::Balance>,
+	ConcreteAssets: fungibles::Inspect<AccountId>,
+{
+	// Get the local asset id in which we can pay for fees
+	let (local_asset_id, _) =
+		Matcher::matches_fungibles(asset).map_err(|_| XcmError::AssetNotFound)?;
+
+	// Calculate how much we should charge in the asset_id for such amount of weight
+	// Require at least a payment of minimum_balance
+	// Necessary for fully collateral-backed assets
+	let asset_balance: u128 = FeeCharger::charge_weight_in_fungibles(local_asset_id, weight)
+		.map(|amount| {
+			let minimum_balance = ConcreteAssets::minimum_balance(local_asset_id);
+			if amount < minimum_balance {
+				minimum_balance
+			} else {
+				amount
+			}
+		})?
+		.try_into()
+		.map_err(|_| XcmError::Overflow)?;
+
+	// Convert to the same kind of multiasset, with the required fungible balance
+	let required = asset.id.clone().into_multiasset(asset_balance.into());
+
+	log::trace!(target: "xcm::weight",
+		"charge_weight_in_fungibles asset: local_asset_id: {:?}, weight: {:?}, asset_balance: {:?}, required: {:?}",
+		local_asset_id, weight, asset_balance, required);
+
+	Ok(required)
+}
+
+/// Refunds the balance charged for `weight` out of the outstanding payment of `refunder`.
+fn refund_weight_in_asset<AccountId, FeeCharger, Matcher, ConcreteAssets>(
+	refunder: &mut AssetTraderRefunder,
+	weight: Weight,
+) -> Option<MultiAsset>
+where
+	FeeCharger: ChargeWeightInFungibles<AccountId, ConcreteAssets>,
+	Matcher: MatchesFungibles<ConcreteAssets::AssetId, ConcreteAssets::Balance>,
+	ConcreteAssets: fungibles::Inspect<AccountId>,
+{
+	let MultiAsset { id, fun } = refunder.outstanding_concrete_asset.clone();
+
+	// Get the local asset id in which we can refund fees
+	let (local_asset_id, outstanding_balance) =
+		Matcher::matches_fungibles(&(id.clone(), fun).into()).ok()?;
+
+	let minimum_balance = ConcreteAssets::minimum_balance(local_asset_id);
+
+	// Calculate asset_balance
+	// This read should have already be cached in buy_weight
+	let (asset_balance, outstanding_minus_substracted) =
+		FeeCharger::charge_weight_in_fungibles(local_asset_id, weight).ok().map(
+			|asset_balance| {
+				// Require at least a drop of minimum_balance
+				// Necessary for fully collateral-backed assets
+				if outstanding_balance.saturating_sub(asset_balance) > minimum_balance {
+					(asset_balance, outstanding_balance.saturating_sub(asset_balance))
+				}
+				// If the amount to be refunded leaves the remaining balance below ED,
+				// we just refund the exact amount that guarantees at least ED will be
+				// dropped
+				else {
+					(outstanding_balance.saturating_sub(minimum_balance), minimum_balance)
+				}
+			},
+		)?;
+
+	// Convert balances into u128
+	let outstanding_minus_substracted: u128 = outstanding_minus_substracted.saturated_into();
+	let asset_balance: u128 = asset_balance.saturated_into();
+
+	// Substract from existing weight and balance
+	*refunder = AssetTraderRefunder {
+		weight_outstanding: refunder.weight_outstanding.saturating_sub(weight),
+		outstanding_concrete_asset: (id.clone(), outstanding_minus_substracted).into(),
+	};
+
+	// Only refund if positive
+	if asset_balance > 0 {
+		Some((id, asset_balance).into())
+	} else {
+		None
+	}
+}
+
 /// Charges for execution in the first multiasset of those selected for fee payment
 /// Only succeeds for Concrete Fungible Assets
 /// First tries to convert the this MultiAsset into a local assetId
@@ -163,31 +271,10 @@ impl<
 		// Take the first multiasset from the selected MultiAssets
 		let first = multiassets.get(0).ok_or(XcmError::AssetNotFound)?;
 
-		// Get the local asset id in which we can pay for fees
-		let (local_asset_id, _) =
-			Matcher::matches_fungibles(&first).map_err(|_| XcmError::AssetNotFound)?;
-
-		// Calculate how much we should charge in the asset_id for such amount of weight
-		// Require at least a payment of minimum_balance
-		// Necessary for fully collateral-backed assets
-		let asset_balance: u128 = FeeCharger::charge_weight_in_fungibles(local_asset_id, weight)
-			.map(|amount| {
-				let minimum_balance = ConcreteAssets::minimum_balance(local_asset_id);
-				if amount < minimum_balance {
-					minimum_balance
-				} else {
-					amount
-				}
-			})?
-			.try_into()
-			.map_err(|_| XcmError::Overflow)?;
-
-		// Convert to the same kind of multiasset, with the required fungible balance
-		let required = first.id.clone().into_multiasset(asset_balance.into());
-
-		log::trace!(target: "xcm::buy_weight******************* ",
-			"charge_weight_in_fungibles asset: local_asset_id: {:?}, weight: {:?}, asset_balance: {:?}, required: {:?}",
-			local_asset_id, weight, asset_balance, required);
+		// Calculate how much we should charge in this multiasset for such amount of weight
+		let required = charge_weight_in_asset::<AccountId, FeeCharger, Matcher, ConcreteAssets>(
+			first, weight,
+		)?;
 
 		// Substract payment
 		let unused = payment.checked_sub(required.clone()).map_err(|_| XcmError::TooExpensive)?;
@@ -203,60 +290,8 @@ impl<
 
 	fn refund_weight(&mut self, weight: Weight) -> Option<MultiAsset> {
 		log::trace!(target: "xcm::weight", "TakeFirstAssetTrader::refund_weight weight: {:?}", weight);
-		if let Some(AssetTraderRefunder {
-			mut weight_outstanding,
-			outstanding_concrete_asset: MultiAsset { id, fun },
-		}) = self.0.clone()
-		{
-			// Get the local asset id in which we can refund fees
-			let (local_asset_id, outstanding_balance) =
-				Matcher::matches_fungibles(&(id.clone(), fun).into()).ok()?;
-
-			let minimum_balance = ConcreteAssets::minimum_balance(local_asset_id);
-
-			// Calculate asset_balance
-			// This read should have already be cached in buy_weight
-			let (asset_balance, outstanding_minus_substracted) =
-				FeeCharger::charge_weight_in_fungibles(local_asset_id, weight).ok().map(
-					|asset_balance| {
-						// Require at least a drop of minimum_balance
-						// Necessary for fully collateral-backed assets
-						if outstanding_balance.saturating_sub(asset_balance) > minimum_balance {
-							(asset_balance, outstanding_balance.saturating_sub(asset_balance))
-						}
-						// If the amount to be refunded leaves the remaining balance below ED,
-						// we just refund the exact amount that guarantees at least ED will be
-						// dropped
-						else {
-							(outstanding_balance.saturating_sub(minimum_balance), minimum_balance)
-						}
-					},
-				)?;
-
-			// Convert balances into u128
-			let outstanding_minus_substracted: u128 =
-				outstanding_minus_substracted.saturated_into();
-			let asset_balance: u128 = asset_balance.saturated_into();
-
-			// Construct outstanding_concrete_asset with the same location id and substracted balance
-			let outstanding_concrete_asset: MultiAsset =
-				(id.clone(), outstanding_minus_substracted).into();
-
-			// Substract from existing weight and balance
-			weight_outstanding = weight_outstanding.saturating_sub(weight);
-
-			// Override AssetTraderRefunder
-			self.0 = Some(AssetTraderRefunder { weight_outstanding, outstanding_concrete_asset });
-
-			// Only refund if positive
-			if asset_balance > 0 {
-				Some((id, asset_balance).into())
-			} else {
-				None
-			}
-		} else {
-			None
-		}
+		let refunder = self.0.as_mut()?;
+		refund_weight_in_asset::<AccountId, FeeCharger, Matcher, ConcreteAssets>(refunder, weight)
 	}
 }
 
@@ -277,6 +312,198 @@ impl<
 	}
 }
 
+/// Payment charged by [`TakePreferredAssetTrader`].
+enum PreferredAssetPayment {
+	/// The native currency, charged by the native trader.
+	Native,
+	/// A fungible asset, charged in the given outstanding asset.
+	Fungibles(AssetTraderRefunder),
+}
+
+/// Charges for execution in any of the multiassets selected for fee payment
+/// The fungible multiassets are tried in the order given by `Preference`, the multiassets not
+/// listed there are tried afterwards in holding order
+/// The native currency, at `NativeLocation`, is charged by `NativeTrader`, the other multiassets
+/// are charged like with [`TakeFirstAssetTrader`]
+/// The unused weight is refunded in the multiasset that was charged
+/// Important: Errors if the Trader is being called twice by 2 BuyExecution instructions
+pub struct TakePreferredAssetTrader<
+	AccountId,
+	Preference: Get<Vec<MultiLocation>>,
+	NativeLocation: Get<MultiLocation>,
+	NativeTrader: WeightTrader,
+	FeeCharger: ChargeWeightInFungibles<AccountId, ConcreteAssets>,
+	Matcher: MatchesFungibles<ConcreteAssets::AssetId, ConcreteAssets::Balance>,
+	ConcreteAssets: fungibles::Inspect<AccountId>,
+	HandleRefund: TakeRevenue,
+>(
+	Option<PreferredAssetPayment>,
+	NativeTrader,
+	PhantomData<(
+		AccountId,
+		Preference,
+		NativeLocation,
+		FeeCharger,
+		Matcher,
+		ConcreteAssets,
+		HandleRefund,
+	)>,
+);
+impl<
+		AccountId,
+		Preference: Get<Vec<MultiLocation>>,
+		NativeLocation: Get<MultiLocation>,
+		NativeTrader: WeightTrader,
+		FeeCharger: ChargeWeightInFungibles<AccountId, ConcreteAssets>,
+		Matcher: MatchesFungibles<ConcreteAssets::AssetId, ConcreteAssets::Balance>,
+		ConcreteAssets: fungibles::Inspect<AccountId>,
+		HandleRefund: TakeRevenue,
+	>
+	TakePreferredAssetTrader<
+		AccountId,
+		Preference,
+		NativeLocation,
+		NativeTrader,
+		FeeCharger,
+		Matcher,
+		ConcreteAssets,
+		HandleRefund,
+	>
+{
+	/// Buy `weight` with `asset` out of `payment`.
+	fn buy_weight_in(
+		&mut self,
+		asset: &MultiAsset,
+		weight: Weight,
+		payment: &xcm_executor::Assets,
+	) -> Result<xcm_executor::Assets, XcmError> {
+		if matches!(asset.id, Concrete(ref location) if *location == NativeLocation::get()) {
+			let unused = self.1.buy_weight(weight, payment.clone())?;
+			self.0 = Some(PreferredAssetPayment::Native);
+			return Ok(unused)
+		}
+
+		let required = charge_weight_in_asset::<AccountId, FeeCharger, Matcher, ConcreteAssets>(
+			asset, weight,
+		)?;
+		let unused = payment
+			.clone()
+			.checked_sub(required.clone())
+			.map_err(|_| XcmError::TooExpensive)?;
+		self.0 = Some(PreferredAssetPayment::Fungibles(AssetTraderRefunder {
+			weight_outstanding: weight,
+			outstanding_concrete_asset: required,
+		}));
+		Ok(unused)
+	}
+}
+impl<
+		AccountId,
+		Preference: Get<Vec<MultiLocation>>,
+		NativeLocation: Get<MultiLocation>,
+		NativeTrader: WeightTrader,
+		FeeCharger: ChargeWeightInFungibles<AccountId, ConcreteAssets>,
+		Matcher: MatchesFungibles<ConcreteAssets::AssetId, ConcreteAssets::Balance>,
+		ConcreteAssets: fungibles::Inspect<AccountId>,
+		HandleRefund: TakeRevenue,
+	> WeightTrader
+	for TakePreferredAssetTrader<
+		AccountId,
+		Preference,
+		NativeLocation,
+		NativeTrader,
+		FeeCharger,
+		Matcher,
+		ConcreteAssets,
+		HandleRefund,
+	>
+{
+	fn new() -> Self {
+		Self(None, NativeTrader::new(), PhantomData)
+	}
+
+	fn buy_weight(
+		&mut self,
+		weight: Weight,
+		payment: xcm_executor::Assets,
+	) -> Result<xcm_executor::Assets, XcmError> {
+		log::trace!(target: "xcm::weight", "TakePreferredAssetTrader::buy_weight weight: {:?}, payment: {:?}", weight, payment);
+
+		// Make sure we dont enter twice
+		if self.0.is_some() {
+			return Err(XcmError::NotWithdrawable)
+		}
+
+		// Sort the multiassets by preference, keeping the holding order otherwise
+		let preference = Preference::get();
+		let mut candidates = payment.fungible_assets_iter().collect::<Vec<_>>();
+		candidates.sort_by_key(|asset| {
+			match asset.id {
+				Concrete(ref location) =>
+					preference.iter().position(|preferred| preferred == location),
+				_ => None,
+			}
+			.unwrap_or(preference.len())
+		});
+
+		// Report why the most preferred matching multiasset could not pay
+		let mut error = XcmError::AssetNotFound;
+		for asset in candidates {
+			match self.buy_weight_in(&asset, weight, &payment) {
+				Ok(unused) => return Ok(unused),
+				Err(e) => {
+					log::trace!(target: "xcm::weight", "TakePreferredAssetTrader::buy_weight asset: {:?}, error: {:?}", asset, e);
+					if error == XcmError::AssetNotFound {
+						error = e;
+					}
+				},
+			}
+		}
+
+		Err(error)
+	}
+
+	fn refund_weight(&mut self, weight: Weight) -> Option<MultiAsset> {
+		log::trace!(target: "xcm::weight", "TakePreferredAssetTrader::refund_weight weight: {:?}", weight);
+		match self.0.as_mut()? {
+			PreferredAssetPayment::Native => self.1.refund_weight(weight),
+			PreferredAssetPayment::Fungibles(refunder) =>
+				refund_weight_in_asset::<AccountId, FeeCharger, Matcher, ConcreteAssets>(
+					refunder, weight,
+				),
+		}
+	}
+}
+
+impl<
+		AccountId,
+		Preference: Get<Vec<MultiLocation>>,
+		NativeLocation: Get<MultiLocation>,
+		NativeTrader: WeightTrader,
+		FeeCharger: ChargeWeightInFungibles<AccountId, ConcreteAssets>,
+		Matcher: MatchesFungibles<ConcreteAssets::AssetId, ConcreteAssets::Balance>,
+		ConcreteAssets: fungibles::Inspect<AccountId>,
+		HandleRefund: TakeRevenue,
+	> Drop
+	for TakePreferredAssetTrader<
+		AccountId,
+		Preference,
+		NativeLocation,
+		NativeTrader,
+		FeeCharger,
+		Matcher,
+		ConcreteAssets,
+		HandleRefund,
+	>
+{
+	fn drop(&mut self) {
+		// The native trader takes its own revenue when dropped
+		if let Some(PreferredAssetPayment::Fungibles(asset_trader)) = self.0.take() {
+			HandleRefund::take_revenue(asset_trader.outstanding_concrete_asset);
+		}
+	}
+}
+
 /// XCM fee depositor to which we implement the TakeRevenue trait
 /// It receives a Transact implemented argument, a 32 byte convertible acocuntId, and the fee receiver account
 /// FungiblesMutateAdapter should be identical to that implemented by WithdrawAsset
@@ -555,4 +782,153 @@ mod tests {
 			Err(XcmError::NotWithdrawable)
 		);
 	}
+
+	#[test]
+	fn take_preferred_asset_trader_charges_and_refunds_in_the_preferred_asset() {
+		type TestAccountId = u32;
+		type TestAssetId = u32;
+		type TestBalance = u128;
+
+		fn asset_location(id: TestAssetId) -> MultiLocation {
+			(PalletInstance(50), GeneralIndex(id.into())).into()
+		}
+
+		struct TestAssets;
+		impl MatchesFungibles<TestAssetId, TestBalance> for TestAssets {
+			fn matches_fungibles(a: &MultiAsset) -> Result<(TestAssetId, TestBalance), Error> {
+				match a {
+					MultiAsset {
+						fun: Fungible(amount),
+						id:
+							Concrete(MultiLocation {
+								parents: 0,
+								interior: X2(PalletInstance(50), GeneralIndex(id)),
+							}),
+					} => Ok((*id as TestAssetId, *amount)),
+					_ => Err(Error::AssetNotHandled),
+				}
+			}
+		}
+		impl fungibles::Inspect<TestAccountId> for TestAssets {
+			type AssetId = TestAssetId;
+			type Balance = TestBalance;
+
+			fn total_issuance(_: Self::AssetId) -> Self::Balance {
+				todo!()
+			}
+
+			fn minimum_balance(_: Self::AssetId) -> Self::Balance {
+				10
+			}
+
+			fn balance(_: Self::AssetId, _: &TestAccountId) -> Self::Balance {
+				todo!()
+			}
+
+			fn reducible_balance(_: Self::AssetId, _: &TestAccountId, _: bool) -> Self::Balance {
+				todo!()
+			}
+
+			fn can_deposit(
+				_: Self::AssetId,
+				_: &TestAccountId,
+				_: Self::Balance,
+				_: bool,
+			) -> DepositConsequence {
+				todo!()
+			}
+
+			fn can_withdraw(
+				_: Self::AssetId,
+				_: &TestAccountId,
+				_: Self::Balance,
+			) -> WithdrawConsequence<Self::Balance> {
+				todo!()
+			}
+
+			fn asset_exists(_: Self::AssetId) -> bool {
+				todo!()
+			}
+		}
+
+		// Charges the ref time of the weight, asset 2 has no conversion rate
+		struct FeeChargerAssetsHandleRefund;
+		impl ChargeWeightInFungibles<TestAccountId, TestAssets> for FeeChargerAssetsHandleRefund {
+			fn charge_weight_in_fungibles(
+				asset_id: <TestAssets as Inspect<TestAccountId>>::AssetId,
+				weight: Weight,
+			) -> Result<<TestAssets as Inspect<TestAccountId>>::Balance, XcmError> {
+				match asset_id {
+					2 => Err(XcmError::TooExpensive),
+					_ => Ok(weight.ref_time().into()),
+				}
+			}
+		}
+		impl TakeRevenue for FeeChargerAssetsHandleRefund {
+			fn take_revenue(_: MultiAsset) {}
+		}
+
+		// Never able to pay, to check that the native currency is skipped
+		struct NativeTrader;
+		impl WeightTrader for NativeTrader {
+			fn new() -> Self {
+				NativeTrader
+			}
+
+			fn buy_weight(&mut self, _: Weight, _: Assets) -> Result<Assets, XcmError> {
+				Err(XcmError::TooExpensive)
+			}
+		}
+
+		frame_support::parameter_types! {
+			pub Preference: Vec<MultiLocation> =
+				vec![Here.into(), asset_location(2), asset_location(3)];
+			pub NativeLocation: MultiLocation = Here.into();
+		}
+
+		type Trader = TakePreferredAssetTrader<
+			TestAccountId,
+			Preference,
+			NativeLocation,
+			NativeTrader,
+			FeeChargerAssetsHandleRefund,
+			TestAssets,
+			TestAssets,
+			FeeChargerAssetsHandleRefund,
+		>;
+		let mut trader = <Trader as WeightTrader>::new();
+
+		// The native currency and asset 2 can't pay, asset 3 is preferred over asset 1
+		let mut payment = Assets::new();
+		payment.subsume((Here, 1_000u128).into());
+		payment.subsume((asset_location(1), 1_000u128).into());
+		payment.subsume((asset_location(2), 1_000u128).into());
+		payment.subsume((asset_location(3), 1_000u128).into());
+		let unused = trader.buy_weight(Weight::from_parts(100, 0), payment).unwrap();
+
+		let mut expected = Assets::new();
+		expected.subsume((Here, 1_000u128).into());
+		expected.subsume((asset_location(1), 1_000u128).into());
+		expected.subsume((asset_location(2), 1_000u128).into());
+		expected.subsume((asset_location(3), 900u128).into());
+		assert_eq!(unused, expected);
+
+		// The unused weight is refunded in asset 3, keeping at least the minimum balance
+		assert_eq!(
+			trader.refund_weight(Weight::from_parts(60, 0)),
+			Some((asset_location(3), 60u128).into())
+		);
+		assert_eq!(
+			trader.refund_weight(Weight::from_parts(60, 0)),
+			Some((asset_location(3), 30u128).into())
+		);
+
+		// Only assets which can't pay
+		let mut trader = <Trader as WeightTrader>::new();
+		let payment = Assets::from(MultiAsset::from((asset_location(2), 1_000u128)));
+		assert_eq!(
+			trader.buy_weight(Weight::from_parts(100, 0), payment),
+			Err(XcmError::TooExpensive)
+		);
+	}
 }
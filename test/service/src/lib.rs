@@ -327,6 +327,7 @@ where
 			spawn_handle: task_manager.spawn_handle(),
 			relay_chain_interface: relay_chain_interface.clone(),
 			import_queue: params.import_queue,
+			author_verification: None,
 		})
 		.await?;
 
@@ -428,6 +429,11 @@ where
 			import_queue: import_queue_service,
 			relay_chain_slot_duration: Duration::from_secs(6),
 			recovery_handle,
+			block_data_requester: None,
+			recovery_control: None,
+			collation_stats: None,
+			collation_archive: None,
+			leader_lease: None,
 		};
 
 		start_collator(params).await?;
@@ -441,6 +447,8 @@ where
 			import_queue: import_queue_service,
 			relay_chain_slot_duration: Duration::from_secs(6),
 			recovery_handle,
+			block_data_requester: None,
+			recovery_control: None,
 		};
 
 		start_full_node(params)?;